@@ -5,8 +5,8 @@ fn main() {
     let v = IrValue::Array(vec![IrValue::Integer(42), IrValue::String("ok".into())]);
     let j = osc_codec_json::to_json(&v);
     let v2 = osc_codec_json::from_json(&j);
-    let mp = osc_codec_msgpack::to_msgpack(&v);
-    let v3 = osc_codec_msgpack::from_msgpack(&mp);
+    let mp = osc_codec_msgpack::try_to_msgpack(&v).expect("encode");
+    let v3 = osc_codec_msgpack::try_from_msgpack(&mp).expect("decode");
     println!("Array - JSON: {}\nArray - MP: {} bytes\n", j, mp.len());
     assert_eq!(v, v2);
     assert_eq!(v, v3);
@@ -29,8 +29,8 @@ fn main() {
     let bundle_v2 = osc_codec_json::from_json(&bundle_json);
     
     // Test Bundle MessagePack roundtrip
-    let bundle_mp = osc_codec_msgpack::to_msgpack(&bundle_value);
-    let bundle_v3 = osc_codec_msgpack::from_msgpack(&bundle_mp);
+    let bundle_mp = osc_codec_msgpack::try_to_msgpack(&bundle_value).expect("encode");
+    let bundle_v3 = osc_codec_msgpack::try_from_msgpack(&bundle_mp).expect("decode");
 
     println!("Bundle - JSON: {}\nBundle - MP: {} bytes\n", bundle_json, bundle_mp.len());
     assert_eq!(bundle_value, bundle_v2);
@@ -11,6 +11,8 @@
 //! - **Bidirectional Conversion**: Convert between `IrValue` and OSC types from `rust-osc-types`
 //! - **OSC Version Support**: Support for both OSC 1.0 and OSC 1.1 via feature flags
 //! - **Message Conversion**: Convert OSC messages to/from IR representation
+//! - **Bundle Conversion**: Convert OSC bundles and packets, including nested bundles and timetags
+//! - **Owned Conversion**: `into_message` consumes IR into a self-contained `OwnedMessage`
 //! - **Type Preservation**: Maintain type information during conversion
 //! - **no_std Compatible**: Works in no_std environments with `alloc`
 //!
@@ -38,7 +40,7 @@ extern crate alloc;
 #[cfg(any(feature = "osc10", feature = "osc11"))]
 use alloc::{string::String, vec, vec::Vec};
 #[cfg(any(feature = "osc10", feature = "osc11"))]
-use osc_ir::IrValue;
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag, IrValue};
 
 #[cfg(any(feature = "osc10", feature = "osc11"))]
 const MESSAGE_TYPE_TAG: &str = "osc.message";
@@ -87,6 +89,139 @@ fn try_extract_message<'a>(value: &'a IrValue) -> Option<(&'a str, &'a [IrValue]
     Some((address, args))
 }
 
+#[cfg(any(feature = "osc10", feature = "osc11"))]
+fn try_take_message(value: IrValue) -> Option<(String, Vec<IrValue>)> {
+    let map = match value {
+        IrValue::Map(map) => map,
+        _ => return None,
+    };
+    let mut address: Option<String> = None;
+    let mut args: Option<Vec<IrValue>> = None;
+
+    for (key, entry) in map {
+        match key.as_str() {
+            "$type" => {
+                let tag = entry.as_str()?;
+                if tag != MESSAGE_TYPE_TAG {
+                    return None;
+                }
+            }
+            "address" => {
+                address = match entry {
+                    IrValue::String(s) => Some(s.into_string()),
+                    _ => None,
+                };
+            }
+            "args" => {
+                args = match entry {
+                    IrValue::Array(values) => Some(values),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    Some((address?, args.unwrap_or_default()))
+}
+
+/// Defines the owned message types and `into_message` of a version module, built on
+/// the module's `osc` alias.
+#[cfg(any(feature = "osc10", feature = "osc11"))]
+macro_rules! owned_message_api {
+    () => {
+        /// Owned counterpart of `osc::OscType`.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum OwnedOscType {
+            /// 32-bit integer (i)
+            Int(i32),
+            /// 32-bit IEEE 754 float (f)
+            Float(f32),
+            /// String (s)
+            String(String),
+            /// Binary blob (b)
+            Blob(Vec<u8>),
+        }
+
+        impl OwnedOscType {
+            /// Borrows this argument as an `osc::OscType`.
+            pub fn as_osc(&self) -> osc::OscType<'_> {
+                match self {
+                    OwnedOscType::Int(v) => osc::OscType::Int(*v),
+                    OwnedOscType::Float(v) => osc::OscType::Float(*v),
+                    OwnedOscType::String(s) => osc::OscType::String(s.as_str()),
+                    OwnedOscType::Blob(bytes) => osc::OscType::Blob(bytes.as_slice()),
+                }
+            }
+        }
+
+        impl From<&osc::OscType<'_>> for OwnedOscType {
+            fn from(arg: &osc::OscType<'_>) -> Self {
+                match arg {
+                    osc::OscType::Int(v) => OwnedOscType::Int(*v),
+                    osc::OscType::Float(v) => OwnedOscType::Float(*v),
+                    osc::OscType::String(s) => OwnedOscType::String(String::from(*s)),
+                    osc::OscType::Blob(bytes) => OwnedOscType::Blob(bytes.to_vec()),
+                }
+            }
+        }
+
+        /// Self-contained OSC message that owns its address and arguments, so it can
+        /// outlive the IR it was converted from and be sent across threads.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct OwnedMessage {
+            /// OSC address pattern
+            pub address: String,
+            /// Arguments of the message
+            pub args: Vec<OwnedOscType>,
+        }
+
+        impl OwnedMessage {
+            /// Borrows this message as an `osc::Message`.
+            pub fn as_message(&self) -> osc::Message<'_> {
+                osc::Message {
+                    address: self.address.as_str(),
+                    args: self.args.iter().map(OwnedOscType::as_osc).collect(),
+                }
+            }
+        }
+
+        impl From<&osc::Message<'_>> for OwnedMessage {
+            fn from(message: &osc::Message<'_>) -> Self {
+                OwnedMessage {
+                    address: String::from(message.address),
+                    args: message.args.iter().map(OwnedOscType::from).collect(),
+                }
+            }
+        }
+
+        fn into_arg(value: IrValue) -> Option<OwnedOscType> {
+            match value {
+                IrValue::Integer(i) => i32::try_from(i).ok().map(OwnedOscType::Int),
+                IrValue::Float32(f) => Some(OwnedOscType::Float(f)),
+                IrValue::Float(f) => Some(OwnedOscType::Float(f as f32)),
+                IrValue::String(s) => Some(OwnedOscType::String(s.into_string())),
+                IrValue::Binary(bytes) => Some(OwnedOscType::Blob(bytes)),
+                _ => None,
+            }
+        }
+
+        /// Consumes an IR message map and converts it into an [`OwnedMessage`],
+        /// moving strings and blobs out of the IR instead of borrowing them.
+        pub fn into_message(value: IrValue) -> Option<OwnedMessage> {
+            let (address, args) = try_take_message(value)?;
+            let mut osc_args = Vec::with_capacity(args.len());
+            for arg in args {
+                osc_args.push(into_arg(arg)?);
+            }
+            Some(OwnedMessage {
+                address,
+                args: osc_args,
+            })
+        }
+    };
+}
+
 #[cfg(feature = "osc10")]
 pub mod v10 {
     use super::*;
@@ -95,7 +230,7 @@ pub mod v10 {
     fn arg_to_ir(arg: &osc::OscType) -> IrValue {
         match arg {
             osc::OscType::Int(v) => IrValue::Integer(*v as i64),
-            osc::OscType::Float(v) => IrValue::Float(*v as f64),
+            osc::OscType::Float(v) => IrValue::Float32(*v),
             osc::OscType::String(s) => IrValue::from(*s),
             osc::OscType::Blob(bytes) => IrValue::Binary(bytes.to_vec()),
         }
@@ -104,6 +239,7 @@ pub mod v10 {
     fn ir_to_arg(value: &IrValue) -> Option<osc::OscType<'_>> {
         match value {
             IrValue::Integer(i) => i32::try_from(*i).ok().map(osc::OscType::Int),
+            IrValue::Float32(f) => Some(osc::OscType::Float(*f)),
             IrValue::Float(f) => Some(osc::OscType::Float(*f as f32)),
             IrValue::String(s) => Some(osc::OscType::String(s.as_ref())),
             IrValue::Binary(bytes) => Some(osc::OscType::Blob(bytes.as_slice())),
@@ -130,6 +266,62 @@ pub mod v10 {
             args: osc_args,
         })
     }
+
+    owned_message_api!();
+
+    /// Converts an OSC bundle, including any nested bundles, into an [`IrBundle`].
+    pub fn bundle_to_ir(bundle: &osc::Bundle) -> IrBundle {
+        let elements = bundle
+            .packets
+            .iter()
+            .map(|packet| match packet {
+                osc::OscPacket::Message(message) => {
+                    IrBundleElement::Message(message_to_ir(message))
+                }
+                osc::OscPacket::Bundle(nested) => IrBundleElement::Bundle(bundle_to_ir(nested)),
+            })
+            .collect();
+        IrBundle {
+            timetag: IrTimetag::from_ntp(bundle.timetag),
+            elements,
+        }
+    }
+
+    /// Converts an [`IrBundle`] back into an OSC bundle.
+    ///
+    /// Returns `None` if any contained message cannot be represented in OSC.
+    pub fn ir_to_bundle(bundle: &IrBundle) -> Option<osc::Bundle<'_>> {
+        let mut packets = Vec::with_capacity(bundle.elements.len());
+        for element in &bundle.elements {
+            packets.push(match element {
+                IrBundleElement::Message(message) => {
+                    osc::OscPacket::Message(ir_to_message(message)?)
+                }
+                IrBundleElement::Bundle(nested) => osc::OscPacket::Bundle(ir_to_bundle(nested)?),
+            });
+        }
+        Some(osc::Bundle {
+            timetag: bundle.timetag.value,
+            packets,
+        })
+    }
+
+    /// Converts an OSC packet into IR: messages become message maps and bundles
+    /// become `IrValue::Bundle`.
+    pub fn packet_to_ir(packet: &osc::OscPacket) -> IrValue {
+        match packet {
+            osc::OscPacket::Message(message) => message_to_ir(message),
+            osc::OscPacket::Bundle(bundle) => IrValue::Bundle(bundle_to_ir(bundle)),
+        }
+    }
+
+    /// Converts IR into an OSC packet, dispatching on bundle vs. message map.
+    pub fn ir_to_packet(value: &IrValue) -> Option<osc::OscPacket<'_>> {
+        match value {
+            IrValue::Bundle(bundle) => ir_to_bundle(bundle).map(osc::OscPacket::Bundle),
+            _ => ir_to_message(value).map(osc::OscPacket::Message),
+        }
+    }
 }
 
 #[cfg(feature = "osc11")]
@@ -140,7 +332,7 @@ pub mod v11 {
     fn arg_to_ir(arg: &osc::OscType) -> IrValue {
         match arg {
             osc::OscType::Int(v) => IrValue::Integer(*v as i64),
-            osc::OscType::Float(v) => IrValue::Float(*v as f64),
+            osc::OscType::Float(v) => IrValue::Float32(*v),
             osc::OscType::String(s) => IrValue::from(*s),
             osc::OscType::Blob(bytes) => IrValue::Binary(bytes.to_vec()),
         }
@@ -149,9 +341,11 @@ pub mod v11 {
     fn ir_to_arg(value: &IrValue) -> Option<osc::OscType<'_>> {
         match value {
             IrValue::Integer(i) => i32::try_from(*i).ok().map(osc::OscType::Int),
+            IrValue::Float32(f) => Some(osc::OscType::Float(*f)),
             IrValue::Float(f) => Some(osc::OscType::Float(*f as f32)),
             IrValue::String(s) => Some(osc::OscType::String(s.as_ref())),
             IrValue::Binary(bytes) => Some(osc::OscType::Blob(bytes.as_slice())),
+            // osc-types has no `r`/`m` arguments; see `ExtOscType`
             IrValue::Color { .. } | IrValue::Midi { .. } => None,
             _ => None,
         }
@@ -173,6 +367,439 @@ pub mod v11 {
             args: osc_args,
         })
     }
+
+    owned_message_api!();
+
+    /// Converts an OSC bundle, including any nested bundles, into an [`IrBundle`].
+    pub fn bundle_to_ir(bundle: &osc::Bundle) -> IrBundle {
+        let elements = bundle
+            .packets
+            .iter()
+            .map(|packet| match packet {
+                osc::OscPacket::Message(message) => {
+                    IrBundleElement::Message(message_to_ir(message))
+                }
+                osc::OscPacket::Bundle(nested) => IrBundleElement::Bundle(bundle_to_ir(nested)),
+            })
+            .collect();
+        IrBundle {
+            timetag: IrTimetag::from_ntp(bundle.timetag),
+            elements,
+        }
+    }
+
+    /// Converts an [`IrBundle`] back into an OSC bundle.
+    ///
+    /// Returns `None` if any contained message cannot be represented in OSC.
+    pub fn ir_to_bundle(bundle: &IrBundle) -> Option<osc::Bundle<'_>> {
+        let mut packets = Vec::with_capacity(bundle.elements.len());
+        for element in &bundle.elements {
+            packets.push(match element {
+                IrBundleElement::Message(message) => {
+                    osc::OscPacket::Message(ir_to_message(message)?)
+                }
+                IrBundleElement::Bundle(nested) => osc::OscPacket::Bundle(ir_to_bundle(nested)?),
+            });
+        }
+        Some(osc::Bundle {
+            timetag: bundle.timetag.value,
+            packets,
+        })
+    }
+
+    /// Converts an OSC packet into IR: messages become message maps and bundles
+    /// become `IrValue::Bundle`.
+    pub fn packet_to_ir(packet: &osc::OscPacket) -> IrValue {
+        match packet {
+            osc::OscPacket::Message(message) => message_to_ir(message),
+            osc::OscPacket::Bundle(bundle) => IrValue::Bundle(bundle_to_ir(bundle)),
+        }
+    }
+
+    /// Converts IR into an OSC packet, dispatching on bundle vs. message map.
+    pub fn ir_to_packet(value: &IrValue) -> Option<osc::OscPacket<'_>> {
+        match value {
+            IrValue::Bundle(bundle) => ir_to_bundle(bundle).map(osc::OscPacket::Bundle),
+            _ => ir_to_message(value).map(osc::OscPacket::Message),
+        }
+    }
+
+    /// OSC 1.1 argument, extending `osc_types11::OscType` with the `r` (RGBA color)
+    /// and `m` (MIDI message) argument types that osc-types does not model.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ExtOscType<'a> {
+        /// One of the argument types provided by osc-types
+        Standard(osc::OscType<'a>),
+        /// 32-bit RGBA color (r)
+        Color { r: u8, g: u8, b: u8, a: u8 },
+        /// 4-byte MIDI message: port id, status byte, data1, data2 (m)
+        Midi {
+            port: u8,
+            status: u8,
+            data1: u8,
+            data2: u8,
+        },
+    }
+
+    impl ExtOscType<'_> {
+        /// Returns the OSC type tag character for this argument.
+        pub fn type_tag(&self) -> char {
+            match self {
+                ExtOscType::Standard(osc::OscType::Int(_)) => 'i',
+                ExtOscType::Standard(osc::OscType::Float(_)) => 'f',
+                ExtOscType::Standard(osc::OscType::String(_)) => 's',
+                ExtOscType::Standard(osc::OscType::Blob(_)) => 'b',
+                ExtOscType::Color { .. } => 'r',
+                ExtOscType::Midi { .. } => 'm',
+            }
+        }
+    }
+
+    impl<'a> From<osc::OscType<'a>> for ExtOscType<'a> {
+        fn from(arg: osc::OscType<'a>) -> Self {
+            ExtOscType::Standard(arg)
+        }
+    }
+
+    /// OSC 1.1 message whose arguments may include color and MIDI values.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ExtMessage<'a> {
+        /// OSC address pattern
+        pub address: &'a str,
+        /// Arguments of the message
+        pub args: Vec<ExtOscType<'a>>,
+    }
+
+    impl<'a> ExtMessage<'a> {
+        /// Narrows this message to a plain osc-types message.
+        ///
+        /// Returns `None` if any argument is a color or MIDI value.
+        pub fn into_standard(self) -> Option<osc::Message<'a>> {
+            let mut args = Vec::with_capacity(self.args.len());
+            for arg in self.args {
+                match arg {
+                    ExtOscType::Standard(arg) => args.push(arg),
+                    _ => return None,
+                }
+            }
+            Some(osc::Message {
+                address: self.address,
+                args,
+            })
+        }
+    }
+
+    impl<'a> From<osc::Message<'a>> for ExtMessage<'a> {
+        fn from(message: osc::Message<'a>) -> Self {
+            ExtMessage {
+                address: message.address,
+                args: message.args.into_iter().map(ExtOscType::Standard).collect(),
+            }
+        }
+    }
+
+    fn ext_arg_to_ir(arg: &ExtOscType) -> IrValue {
+        match arg {
+            ExtOscType::Standard(arg) => arg_to_ir(arg),
+            ExtOscType::Color { r, g, b, a } => IrValue::color(*r, *g, *b, *a),
+            ExtOscType::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => IrValue::midi(*port, *status, *data1, *data2),
+        }
+    }
+
+    fn ir_to_ext_arg(value: &IrValue) -> Option<ExtOscType<'_>> {
+        match value {
+            IrValue::Color { r, g, b, a } => Some(ExtOscType::Color {
+                r: *r,
+                g: *g,
+                b: *b,
+                a: *a,
+            }),
+            IrValue::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => Some(ExtOscType::Midi {
+                port: *port,
+                status: *status,
+                data1: *data1,
+                data2: *data2,
+            }),
+            _ => ir_to_arg(value).map(ExtOscType::Standard),
+        }
+    }
+
+    /// Converts an OSC 1.1 message, including color and MIDI arguments, into IR.
+    pub fn ext_message_to_ir(message: &ExtMessage) -> IrValue {
+        let args = message.args.iter().map(ext_arg_to_ir).collect::<Vec<_>>();
+        message_to_ir_map(message.address, args)
+    }
+
+    /// Converts IR into an OSC 1.1 message, mapping `IrValue::Color` and
+    /// `IrValue::Midi` to the `r` and `m` argument types.
+    pub fn ir_to_ext_message(value: &IrValue) -> Option<ExtMessage<'_>> {
+        let (address, args) = try_extract_message(value)?;
+        let mut osc_args = Vec::with_capacity(args.len());
+        for arg in args {
+            osc_args.push(ir_to_ext_arg(arg)?);
+        }
+        Some(ExtMessage {
+            address,
+            args: osc_args,
+        })
+    }
+
+    /// OSC 1.1 bundle whose messages may include color and MIDI arguments.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ExtBundle<'a> {
+        /// NTP timetag of the bundle
+        pub timetag: u64,
+        /// Messages and nested bundles of the bundle
+        pub packets: Vec<ExtPacket<'a>>,
+    }
+
+    /// OSC 1.1 packet whose messages may include color and MIDI arguments.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ExtPacket<'a> {
+        /// A single message
+        Message(ExtMessage<'a>),
+        /// A bundle of packets
+        Bundle(ExtBundle<'a>),
+    }
+
+    impl<'a> ExtBundle<'a> {
+        /// Narrows this bundle to a plain osc-types bundle.
+        ///
+        /// Returns `None` if any message, nested ones included, has a color or MIDI
+        /// argument.
+        pub fn into_standard(self) -> Option<osc::Bundle<'a>> {
+            let mut packets = Vec::with_capacity(self.packets.len());
+            for packet in self.packets {
+                packets.push(packet.into_standard()?);
+            }
+            Some(osc::Bundle {
+                timetag: self.timetag,
+                packets,
+            })
+        }
+    }
+
+    impl<'a> ExtPacket<'a> {
+        /// Narrows this packet to a plain osc-types packet.
+        ///
+        /// Returns `None` if any message has a color or MIDI argument.
+        pub fn into_standard(self) -> Option<osc::OscPacket<'a>> {
+            match self {
+                ExtPacket::Message(message) => message.into_standard().map(osc::OscPacket::Message),
+                ExtPacket::Bundle(bundle) => bundle.into_standard().map(osc::OscPacket::Bundle),
+            }
+        }
+    }
+
+    impl<'a> From<osc::Bundle<'a>> for ExtBundle<'a> {
+        fn from(bundle: osc::Bundle<'a>) -> Self {
+            ExtBundle {
+                timetag: bundle.timetag,
+                packets: bundle.packets.into_iter().map(ExtPacket::from).collect(),
+            }
+        }
+    }
+
+    impl<'a> From<osc::OscPacket<'a>> for ExtPacket<'a> {
+        fn from(packet: osc::OscPacket<'a>) -> Self {
+            match packet {
+                osc::OscPacket::Message(message) => ExtPacket::Message(message.into()),
+                osc::OscPacket::Bundle(bundle) => ExtPacket::Bundle(bundle.into()),
+            }
+        }
+    }
+
+    /// Converts an OSC 1.1 bundle, including color and MIDI arguments and nested
+    /// bundles, into an [`IrBundle`].
+    pub fn ext_bundle_to_ir(bundle: &ExtBundle) -> IrBundle {
+        let elements = bundle
+            .packets
+            .iter()
+            .map(|packet| match packet {
+                ExtPacket::Message(message) => IrBundleElement::Message(ext_message_to_ir(message)),
+                ExtPacket::Bundle(nested) => IrBundleElement::Bundle(ext_bundle_to_ir(nested)),
+            })
+            .collect();
+        IrBundle {
+            timetag: IrTimetag::from_ntp(bundle.timetag),
+            elements,
+        }
+    }
+
+    /// Converts an [`IrBundle`] into an OSC 1.1 bundle, mapping `IrValue::Color` and
+    /// `IrValue::Midi` to the `r` and `m` argument types.
+    ///
+    /// Returns `None` if any contained message cannot be represented in OSC.
+    pub fn ir_to_ext_bundle(bundle: &IrBundle) -> Option<ExtBundle<'_>> {
+        let mut packets = Vec::with_capacity(bundle.elements.len());
+        for element in &bundle.elements {
+            packets.push(match element {
+                IrBundleElement::Message(message) => {
+                    ExtPacket::Message(ir_to_ext_message(message)?)
+                }
+                IrBundleElement::Bundle(nested) => ExtPacket::Bundle(ir_to_ext_bundle(nested)?),
+            });
+        }
+        Some(ExtBundle {
+            timetag: bundle.timetag.value,
+            packets,
+        })
+    }
+
+    /// Converts an OSC 1.1 packet, including color and MIDI arguments, into IR.
+    pub fn ext_packet_to_ir(packet: &ExtPacket) -> IrValue {
+        match packet {
+            ExtPacket::Message(message) => ext_message_to_ir(message),
+            ExtPacket::Bundle(bundle) => IrValue::Bundle(ext_bundle_to_ir(bundle)),
+        }
+    }
+
+    /// Converts IR into an OSC 1.1 packet, dispatching on bundle vs. message map and
+    /// mapping `IrValue::Color` and `IrValue::Midi` to the `r` and `m` argument types.
+    pub fn ir_to_ext_packet(value: &IrValue) -> Option<ExtPacket<'_>> {
+        match value {
+            IrValue::Bundle(bundle) => ir_to_ext_bundle(bundle).map(ExtPacket::Bundle),
+            _ => ir_to_ext_message(value).map(ExtPacket::Message),
+        }
+    }
+
+    /// Owned counterpart of [`ExtOscType`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum OwnedExtOscType {
+        /// One of the argument types provided by osc-types
+        Standard(OwnedOscType),
+        /// 32-bit RGBA color (r)
+        Color { r: u8, g: u8, b: u8, a: u8 },
+        /// 4-byte MIDI message: port id, status byte, data1, data2 (m)
+        Midi {
+            port: u8,
+            status: u8,
+            data1: u8,
+            data2: u8,
+        },
+    }
+
+    impl OwnedExtOscType {
+        /// Borrows this argument as an [`ExtOscType`].
+        pub fn as_ext(&self) -> ExtOscType<'_> {
+            match self {
+                OwnedExtOscType::Standard(arg) => ExtOscType::Standard(arg.as_osc()),
+                OwnedExtOscType::Color { r, g, b, a } => ExtOscType::Color {
+                    r: *r,
+                    g: *g,
+                    b: *b,
+                    a: *a,
+                },
+                OwnedExtOscType::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                } => ExtOscType::Midi {
+                    port: *port,
+                    status: *status,
+                    data1: *data1,
+                    data2: *data2,
+                },
+            }
+        }
+    }
+
+    impl From<&ExtOscType<'_>> for OwnedExtOscType {
+        fn from(arg: &ExtOscType<'_>) -> Self {
+            match arg {
+                ExtOscType::Standard(arg) => OwnedExtOscType::Standard(arg.into()),
+                ExtOscType::Color { r, g, b, a } => OwnedExtOscType::Color {
+                    r: *r,
+                    g: *g,
+                    b: *b,
+                    a: *a,
+                },
+                ExtOscType::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                } => OwnedExtOscType::Midi {
+                    port: *port,
+                    status: *status,
+                    data1: *data1,
+                    data2: *data2,
+                },
+            }
+        }
+    }
+
+    /// Self-contained OSC 1.1 message whose arguments may include color and MIDI
+    /// values.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct OwnedExtMessage {
+        /// OSC address pattern
+        pub address: String,
+        /// Arguments of the message
+        pub args: Vec<OwnedExtOscType>,
+    }
+
+    impl OwnedExtMessage {
+        /// Borrows this message as an [`ExtMessage`].
+        pub fn as_ext_message(&self) -> ExtMessage<'_> {
+            ExtMessage {
+                address: self.address.as_str(),
+                args: self.args.iter().map(OwnedExtOscType::as_ext).collect(),
+            }
+        }
+    }
+
+    impl From<&ExtMessage<'_>> for OwnedExtMessage {
+        fn from(message: &ExtMessage<'_>) -> Self {
+            OwnedExtMessage {
+                address: String::from(message.address),
+                args: message.args.iter().map(OwnedExtOscType::from).collect(),
+            }
+        }
+    }
+
+    fn into_ext_arg(value: IrValue) -> Option<OwnedExtOscType> {
+        match value {
+            IrValue::Color { r, g, b, a } => Some(OwnedExtOscType::Color { r, g, b, a }),
+            IrValue::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => Some(OwnedExtOscType::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            }),
+            _ => into_arg(value).map(OwnedExtOscType::Standard),
+        }
+    }
+
+    /// Consumes an IR message map and converts it into an [`OwnedExtMessage`], mapping
+    /// `IrValue::Color` and `IrValue::Midi` to the `r` and `m` argument types.
+    pub fn into_ext_message(value: IrValue) -> Option<OwnedExtMessage> {
+        let (address, args) = try_take_message(value)?;
+        let mut osc_args = Vec::with_capacity(args.len());
+        for arg in args {
+            osc_args.push(into_ext_arg(arg)?);
+        }
+        Some(OwnedExtMessage {
+            address,
+            args: osc_args,
+        })
+    }
 }
 
 #[cfg(all(test, any(feature = "osc10", feature = "osc11")))]
@@ -220,7 +847,7 @@ mod tests {
 
             let expected = vec![
                 IrValue::Integer(42),
-                IrValue::Float(0.5),
+                IrValue::Float32(0.5),
                 IrValue::from("text"),
                 IrValue::Binary(vec![1, 2, 3]),
             ];
@@ -264,6 +891,146 @@ mod tests {
 
             assert!(v10::ir_to_message(&ir).is_none());
         }
+
+        #[test]
+        fn into_message_outlives_ir_and_crosses_threads() {
+            use osc_types10 as osc;
+
+            let ir = IrValue::Map(vec![
+                ("$type".to_owned(), IrValue::from(MESSAGE_TYPE_TAG)),
+                ("address".to_owned(), IrValue::from("/owned")),
+                (
+                    "args".to_owned(),
+                    IrValue::Array(vec![
+                        IrValue::Integer(3),
+                        IrValue::Float(0.5),
+                        IrValue::from("text"),
+                        IrValue::Binary(vec![1, 2]),
+                    ]),
+                ),
+            ]);
+            let borrowed = v10::ir_to_message(&ir).map(|m| v10::OwnedMessage::from(&m));
+
+            // `ir` is consumed here; the owned message no longer borrows from it.
+            let owned = v10::into_message(ir).expect("expected successful conversion");
+            assert_eq!(Some(&owned), borrowed.as_ref());
+
+            let handle = std::thread::spawn(move || owned);
+            let owned = handle.join().unwrap();
+
+            let message = owned.as_message();
+            assert_eq!(message.address, "/owned");
+            assert_eq!(
+                message.args,
+                vec![
+                    osc::OscType::Int(3),
+                    osc::OscType::Float(0.5),
+                    osc::OscType::String("text"),
+                    osc::OscType::Blob(&[1, 2]),
+                ]
+            );
+        }
+
+        #[test]
+        fn into_message_rejects_what_ir_to_message_rejects() {
+            let unknown_arg = IrValue::Map(vec![
+                ("address".to_owned(), IrValue::from("/invalid")),
+                ("args".to_owned(), IrValue::Array(vec![IrValue::Bool(true)])),
+            ]);
+            assert!(v10::into_message(unknown_arg).is_none());
+
+            let wrong_tag = IrValue::Map(vec![
+                ("$type".to_owned(), IrValue::from("osc.bundle")),
+                ("address".to_owned(), IrValue::from("/bad")),
+            ]);
+            assert!(v10::into_message(wrong_tag).is_none());
+            assert!(v10::into_message(IrValue::from("not a map")).is_none());
+        }
+
+        #[test]
+        fn bundle_roundtrips_with_nested_timetags() {
+            use osc_types10 as osc;
+
+            let mut inner = osc::Bundle::empty(2000);
+            inner.add_message(osc::Message::new("/inner", vec![osc::OscType::Int(1)]));
+
+            let mut outer = osc::Bundle::empty(1000);
+            outer.add_message(osc::Message::new(
+                "/outer",
+                vec![osc::OscType::String("a"), osc::OscType::Blob(&[4, 5])],
+            ));
+            outer.add_bundle(inner);
+
+            let ir = v10::bundle_to_ir(&outer);
+            assert_eq!(ir.timetag, IrTimetag::from_ntp(1000));
+            assert_eq!(ir.len(), 2);
+            assert!(ir.elements[0].is_message());
+            let nested = ir.elements[1].as_bundle().expect("expected nested bundle");
+            assert_eq!(nested.timetag.value, 2000);
+
+            let restored = v10::ir_to_bundle(&ir).expect("expected successful conversion");
+            assert_eq!(restored, outer);
+        }
+
+        #[test]
+        fn packet_conversion_dispatches_on_kind() {
+            use osc_types10 as osc;
+
+            let message = osc::OscPacket::Message(osc::Message::new("/m", vec![]));
+            let ir = v10::packet_to_ir(&message);
+            assert!(ir.as_map().is_some());
+            assert_eq!(v10::ir_to_packet(&ir), Some(message));
+
+            let bundle = osc::OscPacket::Bundle(osc::Bundle::with_messages(
+                1,
+                vec![osc::Message::new("/b", vec![osc::OscType::Float(0.25)])],
+            ));
+            let ir = v10::packet_to_ir(&bundle);
+            assert!(ir.as_bundle().expect("expected bundle").is_immediate());
+            assert_eq!(v10::ir_to_packet(&ir), Some(bundle));
+        }
+
+        #[test]
+        fn integer_boundaries_and_large_timetags() {
+            use osc_types10 as osc;
+
+            let message = |arg: IrValue| {
+                IrValue::Map(vec![
+                    ("$type".to_owned(), IrValue::from(MESSAGE_TYPE_TAG)),
+                    ("address".to_owned(), IrValue::from("/n")),
+                    ("args".to_owned(), IrValue::Array(vec![arg])),
+                ])
+            };
+            for i in [i32::MIN, i32::MAX] {
+                let ir = message(IrValue::from(i));
+                let converted = v10::ir_to_message(&ir).expect("i32 converts");
+                assert_eq!(converted.args, vec![osc::OscType::Int(i)]);
+                assert_eq!(v10::message_to_ir(&converted), ir);
+            }
+            // OSC 1.0 only has 32-bit integers.
+            assert!(v10::ir_to_message(&message(IrValue::from(i32::MAX as i64 + 1))).is_none());
+            assert!(v10::ir_to_message(&message(IrValue::from(u64::MAX))).is_none());
+
+            let mut bundle = osc::Bundle::empty(u64::MAX);
+            bundle.add_message(osc::Message::new("/late", vec![]));
+            let ir = v10::bundle_to_ir(&bundle);
+            assert_eq!(ir.timetag.value, u64::MAX);
+            assert_eq!(v10::ir_to_bundle(&ir), Some(bundle));
+        }
+
+        #[test]
+        fn ir_to_bundle_rejects_unconvertible_messages() {
+            let mut nested = IrBundle::immediate();
+            nested.add_message(IrValue::Map(vec![
+                ("address".to_owned(), IrValue::from("/invalid")),
+                ("args".to_owned(), IrValue::Array(vec![IrValue::Null])),
+            ]));
+            let mut bundle = IrBundle::new(IrTimetag::from_ntp(5));
+            bundle.add_bundle(nested);
+
+            assert!(v10::ir_to_bundle(&bundle).is_none());
+            assert!(v10::ir_to_packet(&IrValue::Bundle(bundle)).is_none());
+        }
     }
 
     #[test]
@@ -293,30 +1060,196 @@ mod tests {
     mod osc11 {
         use super::*;
 
-        #[test]
-        fn ir_to_message_rejects_color_and_midi() {
-            let ir = IrValue::Map(vec![
-                ("address".into(), IrValue::from("/unsupported")),
+        fn color_and_midi_message() -> IrValue {
+            IrValue::Map(vec![
+                ("$type".into(), IrValue::from(MESSAGE_TYPE_TAG)),
+                ("address".into(), IrValue::from("/extended")),
                 (
                     "args".into(),
                     IrValue::Array(vec![
-                        IrValue::Color {
-                            r: 0,
-                            g: 1,
-                            b: 2,
-                            a: 3,
-                        },
-                        IrValue::Midi {
-                            port: 1,
-                            status: 2,
-                            data1: 3,
-                            data2: 4,
-                        },
+                        IrValue::color(0, 1, 2, 3),
+                        IrValue::Integer(9),
+                        IrValue::midi(1, 0x90, 60, 127),
                     ]),
                 ),
-            ]);
+            ])
+        }
+
+        #[test]
+        fn color_and_midi_roundtrip_through_ext_message() {
+            use osc_types11 as osc;
+
+            let ir = color_and_midi_message();
+            let message = v11::ir_to_ext_message(&ir).expect("expected successful conversion");
+            assert_eq!(message.address, "/extended");
+            assert_eq!(
+                message.args,
+                vec![
+                    v11::ExtOscType::Color {
+                        r: 0,
+                        g: 1,
+                        b: 2,
+                        a: 3
+                    },
+                    v11::ExtOscType::Standard(osc::OscType::Int(9)),
+                    v11::ExtOscType::Midi {
+                        port: 1,
+                        status: 0x90,
+                        data1: 60,
+                        data2: 127
+                    },
+                ]
+            );
+            let tags: alloc::string::String = message.args.iter().map(|a| a.type_tag()).collect();
+            assert_eq!(tags, "rim");
+
+            assert_eq!(v11::ext_message_to_ir(&message), ir);
+        }
+
+        #[test]
+        fn ext_message_roundtrip_from_osc_side() {
+            use osc_types11 as osc;
+
+            let message = v11::ExtMessage {
+                address: "/light",
+                args: vec![
+                    v11::ExtOscType::Color {
+                        r: 255,
+                        g: 128,
+                        b: 0,
+                        a: 64,
+                    },
+                    v11::ExtOscType::Midi {
+                        port: 0,
+                        status: 0xB0,
+                        data1: 7,
+                        data2: 100,
+                    },
+                    osc::OscType::String("label").into(),
+                ],
+            };
+
+            let ir = v11::ext_message_to_ir(&message);
+            let restored = v11::ir_to_ext_message(&ir).expect("expected successful conversion");
+            assert_eq!(restored, message);
+        }
 
+        #[test]
+        fn standard_message_conversion_still_rejects_color_and_midi() {
+            let ir = color_and_midi_message();
             assert!(v11::ir_to_message(&ir).is_none());
+
+            let ext = v11::ir_to_ext_message(&ir).expect("expected successful conversion");
+            assert!(ext.into_standard().is_none());
+        }
+
+        #[test]
+        fn ext_message_wraps_standard_message() {
+            use osc_types11 as osc;
+
+            let message = osc::Message::new("/plain", vec![osc::OscType::Float(1.5)]);
+            let ext = v11::ExtMessage::from(message.clone());
+            assert_eq!(v11::ext_message_to_ir(&ext), v11::message_to_ir(&message));
+            assert_eq!(ext.into_standard(), Some(message));
+        }
+
+        #[test]
+        fn into_message_is_self_contained() {
+            use osc_types11 as osc;
+
+            let message = osc::Message::new("/owned", vec![osc::OscType::String("x")]);
+            let owned = v11::into_message(v11::message_to_ir(&message))
+                .expect("expected successful conversion");
+            assert_eq!(owned.as_message(), message);
+            assert!(v11::into_message(color_and_midi_message()).is_none());
+        }
+
+        #[test]
+        fn color_and_midi_roundtrip_through_ext_bundle() {
+            use osc_types11 as osc;
+
+            let mut inner = IrBundle::new(IrTimetag::from_ntp(2000));
+            inner.add_message(color_and_midi_message());
+            let mut outer = IrBundle::new(IrTimetag::from_ntp(1000));
+            outer.add_message(v11::message_to_ir(&osc::Message::new(
+                "/plain",
+                vec![osc::OscType::Float(0.5)],
+            )));
+            outer.add_bundle(inner);
+            let ir = IrValue::Bundle(outer);
+
+            // The standard conversions cannot carry `r`/`m`.
+            assert!(v11::ir_to_packet(&ir).is_none());
+
+            let packet = v11::ir_to_ext_packet(&ir).expect("expected successful conversion");
+            let bundle = match &packet {
+                v11::ExtPacket::Bundle(bundle) => bundle,
+                other => panic!("expected bundle, got {:?}", other),
+            };
+            assert_eq!(bundle.timetag, 1000);
+            let nested = match &bundle.packets[1] {
+                v11::ExtPacket::Bundle(nested) => nested,
+                other => panic!("expected nested bundle, got {:?}", other),
+            };
+            assert_eq!(nested.timetag, 2000);
+            match &nested.packets[0] {
+                v11::ExtPacket::Message(message) => {
+                    let tags: alloc::string::String =
+                        message.args.iter().map(|a| a.type_tag()).collect();
+                    assert_eq!(tags, "rim");
+                }
+                other => panic!("expected message, got {:?}", other),
+            }
+
+            assert_eq!(v11::ext_packet_to_ir(&packet), ir);
+            assert!(packet.into_standard().is_none());
+        }
+
+        #[test]
+        fn ext_bundle_wraps_standard_bundle() {
+            use osc_types11 as osc;
+
+            let mut bundle = osc::Bundle::empty(7);
+            bundle.add_message(osc::Message::new("/a", vec![osc::OscType::Int(1)]));
+            bundle.add_bundle(osc::Bundle::empty(8));
+            let ext = v11::ExtBundle::from(bundle.clone());
+            assert_eq!(v11::ext_bundle_to_ir(&ext), v11::bundle_to_ir(&bundle));
+            assert_eq!(
+                v11::ir_to_ext_bundle(&v11::bundle_to_ir(&bundle)),
+                Some(ext.clone())
+            );
+            assert_eq!(ext.into_standard(), Some(bundle));
+        }
+
+        #[test]
+        fn into_ext_message_keeps_color_and_midi() {
+            let ir = color_and_midi_message();
+            let borrowed = v11::ir_to_ext_message(&ir).map(|m| v11::OwnedExtMessage::from(&m));
+
+            let owned = v11::into_ext_message(ir.clone()).expect("expected successful conversion");
+            assert_eq!(Some(&owned), borrowed.as_ref());
+            let owned = std::thread::spawn(move || owned).join().unwrap();
+            assert_eq!(v11::ext_message_to_ir(&owned.as_ext_message()), ir);
+
+            assert!(v11::into_ext_message(IrValue::from("not a map")).is_none());
+        }
+
+        #[test]
+        fn bundle_packet_roundtrip() {
+            use osc_types11 as osc;
+
+            let mut inner = osc::Bundle::empty(42);
+            inner.add_message(osc::Message::with_strings("/inner", vec!["x"]));
+            let mut outer = osc::Bundle::empty(1);
+            outer.add_bundle(inner);
+            outer.add_message(osc::Message::new("/outer", vec![osc::OscType::Int(-3)]));
+            let packet = osc::OscPacket::Bundle(outer);
+
+            let ir = v11::packet_to_ir(&packet);
+            let bundle = ir.as_bundle().expect("expected bundle");
+            assert!(bundle.is_immediate());
+            assert_eq!(bundle.elements[0].as_bundle().unwrap().timetag.value, 42);
+            assert_eq!(v11::ir_to_packet(&ir), Some(packet));
         }
     }
 }
@@ -21,8 +21,8 @@ fn message_ir_msgpack_roundtrip() {
     let ir = adapter::v10::message_to_ir(&message);
 
     // IrValue -> MsgPack -> IrValue
-    let bytes = msgpack::to_msgpack(&ir);
-    let ir2 = msgpack::from_msgpack(&bytes);
+    let bytes = msgpack::try_to_msgpack(&ir).expect("encode");
+    let ir2 = msgpack::try_from_msgpack(&bytes).expect("decode");
 
     // IrValue -> OSC
     let message2 = adapter::v10::ir_to_message(&ir2).expect("expected successful conversion");
@@ -38,3 +38,23 @@ fn message_ir_msgpack_roundtrip() {
     assert!(matches!(message2.args[2], osc::OscType::String("value")));
     assert!(matches!(message2.args[3], osc::OscType::Blob(slice) if slice == [9, 8, 7]));
 }
+
+#[test]
+fn bundle_ir_msgpack_roundtrip() {
+    let mut nested = osc::Bundle::empty(2000);
+    nested.add_message(osc::Message::new("/nested", vec![osc::OscType::Blob(&[1, 2])]));
+
+    let mut bundle = osc::Bundle::empty(1000);
+    bundle.add_message(osc::Message::new("/top", vec![osc::OscType::Int(5)]));
+    bundle.add_bundle(nested);
+    let packet = osc::OscPacket::Bundle(bundle);
+
+    // OSC -> IrValue -> MsgPack -> IrValue -> OSC
+    let ir = adapter::v10::packet_to_ir(&packet);
+    let bytes = msgpack::try_to_msgpack(&ir).expect("encode");
+    let ir2 = msgpack::try_from_msgpack(&bytes).expect("decode");
+    assert_eq!(ir, ir2);
+
+    let packet2 = adapter::v10::ir_to_packet(&ir2).expect("expected successful conversion");
+    assert_eq!(packet2, packet);
+}
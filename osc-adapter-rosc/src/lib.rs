@@ -0,0 +1,484 @@
+//! # osc-adapter-rosc
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Bidirectional adapter between `osc-ir` intermediate representation and the
+//! [`rosc`](https://docs.rs/rosc) crate.
+//!
+//! ## Features
+//!
+//! - **Packet Conversion**: Convert `rosc` packets, messages and nested bundles to/from IR
+//! - **Timetags**: `OscTime` maps losslessly onto `IrTimetag`
+//! - **Full Type Coverage**: Every `OscType`, including color, MIDI, char, nil, infinitum,
+//!   int64, double, time and arrays
+//! - **Lossless Round-Trips**: `rosc -> IR -> rosc` and `IR -> rosc -> IR` both preserve values
+//! - **no_std Compatible**: Works in no_std environments with `alloc`
+//! - **Codec Trait**: [`OscCodec`] implements `osc_ir::Codec` for the OSC binary format
+//!
+//! ## Type Mapping
+//!
+//! Messages use the same `{"$type": "osc.message", "address", "args"}` map as
+//! `osc-adapter-osc-types`, and bundles become `IrValue::Bundle`.
+//!
+//! | `OscType` | `IrValue` |
+//! |-----------|-----------|
+//! | `Int`, `Long` | `Integer` |
+//! | `Float` | `Float32` |
+//! | `Double` | `Float` |
+//! | `String` | `String` |
+//! | `Blob` | `Binary` |
+//! | `Bool` | `Bool` |
+//! | `Nil` | `Null` |
+//! | `Array` | `Array` |
+//! | `Color` | `Color` |
+//! | `Midi` | `Midi` |
+//! | `Char`, `Inf`, `Time` | `Ext` |
+//!
+//! OSC types with no IR counterpart are carried as `IrValue::Ext` whose `type_id` is the
+//! OSC type tag character (see [`EXT_CHAR`] and friends). `Long` values also use `Ext`
+//! when the narrower `Int` would otherwise be chosen on the way back, so that both
+//! directions round-trip exactly. Floats keep their precision class: `Float32` is `f`
+//! and `Float` is `d`.
+//!
+//! `IrValue::Map`, `Timestamp`, `Bundle` and `Unsigned` (integers above `i64::MAX`) have
+//! no OSC argument type, so messages containing them are rejected by [`ir_to_message`].
+//!
+//! ```rust
+//! use osc_adapter_rosc::{ir_to_packet, packet_to_ir};
+//! use rosc::{OscMessage, OscPacket, OscType};
+//!
+//! let packet = OscPacket::Message(OscMessage {
+//!     addr: "/synth/freq".into(),
+//!     args: vec![OscType::Float(440.0), OscType::Long(1 << 40)],
+//! });
+//!
+//! let ir = packet_to_ir(&packet);
+//! assert_eq!(ir_to_packet(&ir), Some(packet));
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::{String, ToString}, vec::Vec};
+use osc_ir::{Codec, CodecError, IrBundle, IrBundleElement, IrTimetag, IrValue};
+use rosc::{OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType};
+
+/// `Ext` type id for `OscType::Long` values that fit in 32 bits (`h`).
+/// Payload: 8-byte big-endian `i64`.
+pub const EXT_INT64: i8 = b'h' as i8;
+/// `Ext` type id for `OscType::Char` (`c`). Payload: 4-byte big-endian code point.
+pub const EXT_CHAR: i8 = b'c' as i8;
+/// `Ext` type id for `OscType::Inf` (`I`). Payload: empty.
+pub const EXT_INFINITUM: i8 = b'I' as i8;
+/// `Ext` type id for `OscType::Time` (`t`). Payload: 8-byte big-endian NTP timetag.
+pub const EXT_TIME: i8 = b't' as i8;
+
+fn fits_i32(v: i64) -> bool {
+    i32::try_from(v).is_ok()
+}
+
+fn time_to_ntp(time: &OscTime) -> u64 {
+    ((time.seconds as u64) << 32) | time.fractional as u64
+}
+
+fn ntp_to_time(ntp: u64) -> OscTime {
+    OscTime {
+        seconds: (ntp >> 32) as u32,
+        fractional: ntp as u32,
+    }
+}
+
+fn ext(type_id: i8, data: &[u8]) -> IrValue {
+    IrValue::Ext {
+        type_id,
+        data: data.to_vec(),
+    }
+}
+
+fn ext_arg(type_id: i8, data: &[u8]) -> Option<OscType> {
+    match type_id {
+        EXT_INT64 => Some(OscType::Long(i64::from_be_bytes(data.try_into().ok()?))),
+        EXT_CHAR => char::from_u32(u32::from_be_bytes(data.try_into().ok()?)).map(OscType::Char),
+        EXT_INFINITUM if data.is_empty() => Some(OscType::Inf),
+        EXT_TIME => Some(OscType::Time(ntp_to_time(u64::from_be_bytes(
+            data.try_into().ok()?,
+        )))),
+        _ => None,
+    }
+}
+
+fn arg_to_ir(arg: &OscType) -> IrValue {
+    match arg {
+        OscType::Int(v) => IrValue::Integer(*v as i64),
+        OscType::Long(v) if fits_i32(*v) => ext(EXT_INT64, &v.to_be_bytes()),
+        OscType::Long(v) => IrValue::Integer(*v),
+        OscType::Float(v) => IrValue::Float32(*v),
+        OscType::Double(v) => IrValue::Float(*v),
+        OscType::String(s) => IrValue::from(s.as_str()),
+        OscType::Blob(bytes) => IrValue::Binary(bytes.clone()),
+        OscType::Time(time) => ext(EXT_TIME, &time_to_ntp(time).to_be_bytes()),
+        OscType::Char(c) => ext(EXT_CHAR, &(*c as u32).to_be_bytes()),
+        OscType::Color(color) => IrValue::color(color.red, color.green, color.blue, color.alpha),
+        OscType::Midi(midi) => IrValue::midi(midi.port, midi.status, midi.data1, midi.data2),
+        OscType::Bool(b) => IrValue::Bool(*b),
+        OscType::Array(array) => IrValue::Array(array.content.iter().map(arg_to_ir).collect()),
+        OscType::Nil => IrValue::Null,
+        OscType::Inf => ext(EXT_INFINITUM, &[]),
+    }
+}
+
+fn ir_to_arg(value: &IrValue) -> Option<OscType> {
+    match value {
+        IrValue::Null => Some(OscType::Nil),
+        IrValue::Bool(b) => Some(OscType::Bool(*b)),
+        IrValue::Integer(i) if fits_i32(*i) => Some(OscType::Int(*i as i32)),
+        IrValue::Integer(i) => Some(OscType::Long(*i)),
+        IrValue::Float32(f) => Some(OscType::Float(*f)),
+        IrValue::Float(f) => Some(OscType::Double(*f)),
+        IrValue::String(s) => Some(OscType::String(String::from(s.as_ref()))),
+        IrValue::Binary(bytes) => Some(OscType::Blob(bytes.clone())),
+        IrValue::Array(values) => {
+            let mut content = Vec::with_capacity(values.len());
+            for value in values {
+                content.push(ir_to_arg(value)?);
+            }
+            Some(OscType::Array(OscArray { content }))
+        }
+        IrValue::Ext { type_id, data } => ext_arg(*type_id, data),
+        IrValue::Color { r, g, b, a } => Some(OscType::Color(OscColor {
+            red: *r,
+            green: *g,
+            blue: *b,
+            alpha: *a,
+        })),
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => Some(OscType::Midi(OscMidiMessage {
+            port: *port,
+            status: *status,
+            data1: *data1,
+            data2: *data2,
+        })),
+        IrValue::Map(_) | IrValue::Timestamp(_) | IrValue::Bundle(_) | IrValue::Unsigned(_) => None,
+    }
+}
+
+/// Converts a `rosc` message into an IR message map.
+pub fn message_to_ir(message: &OscMessage) -> IrValue {
+    let args = message.args.iter().map(arg_to_ir).collect::<Vec<_>>();
+    IrValue::message(message.addr.as_str(), args)
+}
+
+/// Converts an IR message map into a `rosc` message.
+///
+/// Returns `None` if the value is not a message map or an argument has no OSC equivalent.
+pub fn ir_to_message(value: &IrValue) -> Option<OscMessage> {
+    let (address, args) = value.as_message()?;
+    let mut osc_args = Vec::with_capacity(args.len());
+    for arg in args {
+        osc_args.push(ir_to_arg(arg)?);
+    }
+    Some(OscMessage {
+        addr: String::from(address),
+        args: osc_args,
+    })
+}
+
+/// Converts a `rosc` bundle, including any nested bundles, into an [`IrBundle`].
+pub fn bundle_to_ir(bundle: &OscBundle) -> IrBundle {
+    let elements = bundle
+        .content
+        .iter()
+        .map(|packet| match packet {
+            OscPacket::Message(message) => IrBundleElement::Message(message_to_ir(message)),
+            OscPacket::Bundle(nested) => IrBundleElement::Bundle(bundle_to_ir(nested)),
+        })
+        .collect();
+    IrBundle {
+        timetag: IrTimetag::from_ntp(time_to_ntp(&bundle.timetag)),
+        elements,
+    }
+}
+
+/// Converts an [`IrBundle`] back into a `rosc` bundle.
+///
+/// Returns `None` if any contained message cannot be represented in OSC.
+pub fn ir_to_bundle(bundle: &IrBundle) -> Option<OscBundle> {
+    let mut content = Vec::with_capacity(bundle.elements.len());
+    for element in &bundle.elements {
+        content.push(match element {
+            IrBundleElement::Message(message) => OscPacket::Message(ir_to_message(message)?),
+            IrBundleElement::Bundle(nested) => OscPacket::Bundle(ir_to_bundle(nested)?),
+        });
+    }
+    Some(OscBundle {
+        timetag: ntp_to_time(bundle.timetag.value),
+        content,
+    })
+}
+
+/// Converts a `rosc` packet into IR: messages become message maps and bundles
+/// become `IrValue::Bundle`.
+pub fn packet_to_ir(packet: &OscPacket) -> IrValue {
+    match packet {
+        OscPacket::Message(message) => message_to_ir(message),
+        OscPacket::Bundle(bundle) => IrValue::Bundle(bundle_to_ir(bundle)),
+    }
+}
+
+/// Converts IR into a `rosc` packet, dispatching on bundle vs. message map.
+pub fn ir_to_packet(value: &IrValue) -> Option<OscPacket> {
+    match value {
+        IrValue::Bundle(bundle) => ir_to_bundle(bundle).map(OscPacket::Bundle),
+        _ => ir_to_message(value).map(OscPacket::Message),
+    }
+}
+
+/// [`Codec`] implementation for the OSC 1.0 binary packet format, as sent in UDP
+/// datagrams.
+///
+/// Encoding fails for values [`ir_to_packet`] rejects; decoding fails for malformed
+/// packets and for trailing bytes after the packet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OscCodec;
+
+impl Codec for OscCodec {
+    fn name(&self) -> &'static str {
+        "osc"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/osc"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        let packet = ir_to_packet(value)
+            .ok_or_else(|| CodecError::encode("value has no OSC packet representation"))?;
+        rosc::encoder::encode(&packet).map_err(|e| CodecError::encode(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        match rosc::decoder::decode_udp(bytes) {
+            Ok(([], packet)) => Ok(packet_to_ir(&packet)),
+            Ok((rest, _)) => Err(CodecError::decode(alloc::format!(
+                "{} trailing bytes after the OSC packet",
+                rest.len()
+            ))),
+            Err(e) => Err(CodecError::decode(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(args: Vec<OscType>) -> OscMessage {
+        OscMessage {
+            addr: "/test".into(),
+            args,
+        }
+    }
+
+    fn ir_args(message: &OscMessage) -> Vec<IrValue> {
+        match message_to_ir(message) {
+            IrValue::Map(entries) => entries
+                .into_iter()
+                .find(|(k, _)| k == "args")
+                .and_then(|(_, v)| v.as_array().map(|a| a.to_vec()))
+                .expect("expected args array"),
+            _ => panic!("expected map"),
+        }
+    }
+
+    #[test]
+    fn message_map_matches_osc_types_adapter_layout() {
+        let ir = message_to_ir(&message(vec![OscType::Int(1)]));
+        let entries = ir.as_map().expect("expected map");
+        assert_eq!(entries[0], ("$type".into(), IrValue::from("osc.message")));
+        assert_eq!(entries[1], ("address".into(), IrValue::from("/test")));
+        assert_eq!(
+            entries[2],
+            ("args".into(), IrValue::Array(vec![IrValue::Integer(1)]))
+        );
+    }
+
+    #[test]
+    fn native_types_map_to_plain_ir_values() {
+        let msg = message(vec![
+            OscType::Int(-7),
+            OscType::Long(i64::MAX),
+            OscType::Float(0.5),
+            OscType::Double(0.1),
+            OscType::String("s".into()),
+            OscType::Blob(vec![1, 2]),
+            OscType::Bool(true),
+            OscType::Nil,
+            OscType::Color(OscColor {
+                red: 1,
+                green: 2,
+                blue: 3,
+                alpha: 4,
+            }),
+            OscType::Midi(OscMidiMessage {
+                port: 0,
+                status: 0x90,
+                data1: 60,
+                data2: 100,
+            }),
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::String("x".into())],
+            }),
+        ]);
+
+        assert_eq!(
+            ir_args(&msg),
+            vec![
+                IrValue::Integer(-7),
+                IrValue::Integer(i64::MAX),
+                IrValue::Float32(0.5),
+                IrValue::Float(0.1),
+                IrValue::from("s"),
+                IrValue::Binary(vec![1, 2]),
+                IrValue::Bool(true),
+                IrValue::Null,
+                IrValue::color(1, 2, 3, 4),
+                IrValue::midi(0, 0x90, 60, 100),
+                IrValue::Array(vec![IrValue::Integer(1), IrValue::from("x")]),
+            ]
+        );
+        assert_eq!(ir_to_message(&message_to_ir(&msg)), Some(msg));
+    }
+
+    #[test]
+    fn osc_only_types_use_tagged_ext_values() {
+        let msg = message(vec![
+            OscType::Char('é'),
+            OscType::Inf,
+            OscType::Time(OscTime {
+                seconds: 3,
+                fractional: 9,
+            }),
+            OscType::Long(5),
+        ]);
+
+        assert_eq!(
+            ir_args(&msg),
+            vec![
+                ext(EXT_CHAR, &('é' as u32).to_be_bytes()),
+                ext(EXT_INFINITUM, &[]),
+                ext(EXT_TIME, &((3_u64 << 32) | 9).to_be_bytes()),
+                ext(EXT_INT64, &5_i64.to_be_bytes()),
+            ]
+        );
+        assert_eq!(ir_to_message(&message_to_ir(&msg)), Some(msg));
+    }
+
+    #[test]
+    fn ir_integers_choose_narrowest_osc_type() {
+        assert_eq!(ir_to_arg(&IrValue::Integer(1)), Some(OscType::Int(1)));
+        assert_eq!(
+            ir_to_arg(&IrValue::Integer(1 << 40)),
+            Some(OscType::Long(1 << 40))
+        );
+    }
+
+    #[test]
+    fn float_precision_class_is_preserved() {
+        assert_eq!(ir_to_arg(&IrValue::Float32(0.25)), Some(OscType::Float(0.25)));
+        assert_eq!(ir_to_arg(&IrValue::Float(0.25)), Some(OscType::Double(0.25)));
+        assert_eq!(ir_to_arg(&IrValue::Float(0.1)), Some(OscType::Double(0.1)));
+        assert_eq!(arg_to_ir(&OscType::Float(1.5)), IrValue::Float32(1.5));
+        assert_eq!(arg_to_ir(&OscType::Double(1.5)), IrValue::Float(1.5));
+    }
+
+    #[test]
+    fn integer_boundaries_roundtrip() {
+        let boundaries = [
+            i64::MIN,
+            i32::MIN as i64 - 1,
+            i32::MIN as i64,
+            i32::MAX as i64,
+            i32::MAX as i64 + 1,
+            i64::MAX,
+        ];
+        for i in boundaries {
+            let arg = ir_to_arg(&IrValue::Integer(i)).expect("integer converts");
+            assert_eq!(matches!(arg, OscType::Int(_)), fits_i32(i));
+            assert_eq!(arg_to_ir(&arg), IrValue::Integer(i));
+        }
+        for long in [i64::MIN, 0, i64::MAX] {
+            let arg = OscType::Long(long);
+            assert_eq!(ir_to_arg(&arg_to_ir(&arg)), Some(arg));
+        }
+
+        // OSC has no unsigned 64-bit argument type.
+        assert!(ir_to_arg(&IrValue::from(u64::MAX)).is_none());
+        assert!(ir_to_arg(&IrValue::from(i64::MAX as u64)).is_some());
+    }
+
+    #[test]
+    fn unsupported_values_are_rejected() {
+        assert!(ir_to_arg(&IrValue::Map(vec![])).is_none());
+        assert!(ir_to_arg(&IrValue::Unsigned(u64::MAX)).is_none());
+        assert!(ir_to_arg(&ext(42, &[])).is_none());
+        assert!(ir_to_arg(&ext(EXT_INT64, &[1, 2])).is_none());
+        assert!(ir_to_arg(&ext(EXT_CHAR, &0xD800_u32.to_be_bytes())).is_none());
+
+        let not_a_message = IrValue::Map(vec![("$type".into(), IrValue::from("other"))]);
+        assert!(ir_to_packet(&not_a_message).is_none());
+    }
+
+    #[test]
+    fn bundle_timetags_are_preserved() {
+        let packet = OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 0xDEAD_BEEF,
+                fractional: 0x1234_5678,
+            },
+            content: vec![
+                OscPacket::Message(message(vec![OscType::Int(1)])),
+                OscPacket::Bundle(OscBundle {
+                    timetag: OscTime {
+                        seconds: 0,
+                        fractional: 1,
+                    },
+                    content: vec![],
+                }),
+            ],
+        });
+
+        let ir = packet_to_ir(&packet);
+        let bundle = ir.as_bundle().expect("expected bundle");
+        assert_eq!(bundle.timetag.value, 0xDEAD_BEEF_1234_5678);
+        assert!(bundle.timetag.value > i64::MAX as u64);
+        assert!(bundle.elements[1].as_bundle().unwrap().is_immediate());
+        assert_eq!(ir_to_packet(&ir), Some(packet));
+    }
+
+    #[test]
+    fn codec_encodes_osc_binary_packets() {
+        let ir = message_to_ir(&message(vec![
+            OscType::Float(0.5),
+            OscType::String("hi".into()),
+        ]));
+        let bytes = OscCodec.encode(&ir).unwrap();
+        assert_eq!(&bytes[..8], b"/test\0\0\0");
+        assert_eq!(&bytes[8..12], b",fs\0");
+        assert_eq!(OscCodec.decode(&bytes).unwrap(), ir);
+
+        assert!(OscCodec.encode(&IrValue::Map(vec![])).is_err());
+        assert!(OscCodec.decode(&bytes[..10]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.extend_from_slice(&[0; 4]);
+        assert_eq!(
+            OscCodec.decode(&trailing).unwrap_err().message(),
+            "4 trailing bytes after the OSC packet"
+        );
+    }
+}
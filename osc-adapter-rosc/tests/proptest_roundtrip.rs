@@ -0,0 +1,132 @@
+use osc_adapter_rosc as adapter;
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag, IrValue};
+use proptest::prelude::*;
+use rosc::{
+    OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType,
+};
+
+fn osc_time() -> impl Strategy<Value = OscTime> {
+    (any::<u32>(), any::<u32>()).prop_map(|(seconds, fractional)| OscTime {
+        seconds,
+        fractional,
+    })
+}
+
+fn osc_arg() -> impl Strategy<Value = OscType> {
+    let leaf = prop_oneof![
+        any::<i32>().prop_map(OscType::Int),
+        any::<i64>().prop_map(OscType::Long),
+        any::<f32>()
+            .prop_filter("NaN is not comparable", |f| !f.is_nan())
+            .prop_map(OscType::Float),
+        any::<f64>()
+            .prop_filter("NaN is not comparable", |f| !f.is_nan())
+            .prop_map(OscType::Double),
+        ".*".prop_map(OscType::String),
+        prop::collection::vec(any::<u8>(), 0..16).prop_map(OscType::Blob),
+        osc_time().prop_map(OscType::Time),
+        any::<char>().prop_map(OscType::Char),
+        any::<[u8; 4]>().prop_map(|[red, green, blue, alpha]| OscType::Color(OscColor {
+            red,
+            green,
+            blue,
+            alpha
+        })),
+        any::<[u8; 4]>().prop_map(|[port, status, data1, data2]| OscType::Midi(
+            OscMidiMessage {
+                port,
+                status,
+                data1,
+                data2
+            }
+        )),
+        any::<bool>().prop_map(OscType::Bool),
+        Just(OscType::Nil),
+        Just(OscType::Inf),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop::collection::vec(inner, 0..4).prop_map(|content| OscType::Array(OscArray { content }))
+    })
+}
+
+fn osc_message() -> impl Strategy<Value = OscMessage> {
+    ("/[a-z/]{0,12}", prop::collection::vec(osc_arg(), 0..6))
+        .prop_map(|(addr, args)| OscMessage { addr, args })
+}
+
+fn osc_packet() -> impl Strategy<Value = OscPacket> {
+    osc_message()
+        .prop_map(OscPacket::Message)
+        .prop_recursive(3, 12, 4, |inner| {
+            (osc_time(), prop::collection::vec(inner, 0..4))
+                .prop_map(|(timetag, content)| OscPacket::Bundle(OscBundle { timetag, content }))
+        })
+}
+
+/// IR argument values that have an OSC representation.
+fn ir_arg() -> impl Strategy<Value = IrValue> {
+    let leaf = prop_oneof![
+        Just(IrValue::Null),
+        any::<bool>().prop_map(IrValue::Bool),
+        any::<i64>().prop_map(IrValue::Integer),
+        any::<f64>()
+            .prop_filter("NaN is not comparable", |f| !f.is_nan())
+            .prop_map(IrValue::Float),
+        any::<f32>()
+            .prop_filter("NaN is not comparable", |f| !f.is_nan())
+            .prop_map(IrValue::Float32),
+        ".*".prop_map(IrValue::from),
+        prop::collection::vec(any::<u8>(), 0..16).prop_map(IrValue::Binary),
+        any::<[u8; 4]>().prop_map(|[r, g, b, a]| IrValue::color(r, g, b, a)),
+        any::<[u8; 4]>().prop_map(|[p, s, d1, d2]| IrValue::midi(p, s, d1, d2)),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop::collection::vec(inner, 0..4).prop_map(IrValue::Array)
+    })
+}
+
+fn ir_message() -> impl Strategy<Value = IrValue> {
+    ("/[a-z/]{0,12}", prop::collection::vec(ir_arg(), 0..6)).prop_map(|(address, args)| {
+        IrValue::message(address, args)
+    })
+}
+
+fn ir_packet() -> impl Strategy<Value = IrValue> {
+    ir_message().prop_recursive(3, 12, 4, |inner| {
+        (any::<u64>(), prop::collection::vec(inner, 0..4)).prop_map(|(timetag, values)| {
+            let elements = values
+                .into_iter()
+                .map(|value| match value {
+                    IrValue::Bundle(bundle) => IrBundleElement::Bundle(bundle),
+                    message => IrBundleElement::Message(message),
+                })
+                .collect();
+            IrValue::Bundle(IrBundle {
+                timetag: IrTimetag::from_ntp(timetag),
+                elements,
+            })
+        })
+    })
+}
+
+proptest! {
+    #[test]
+    fn rosc_ir_rosc_is_identity(packet in osc_packet()) {
+        let ir = adapter::packet_to_ir(&packet);
+        prop_assert_eq!(adapter::ir_to_packet(&ir), Some(packet));
+    }
+
+    #[test]
+    fn ir_rosc_ir_is_identity(value in ir_packet()) {
+        let packet = adapter::ir_to_packet(&value).expect("representable IR must convert");
+        prop_assert_eq!(adapter::packet_to_ir(&packet), value);
+    }
+
+    #[test]
+    fn rosc_ir_msgpack_rosc_is_identity(packet in osc_packet()) {
+        let ir = adapter::packet_to_ir(&packet);
+        let bytes = osc_codec_msgpack::try_to_msgpack(&ir).expect("encode");
+        let decoded = osc_codec_msgpack::try_from_msgpack(&bytes).expect("decode");
+        prop_assert_eq!(adapter::ir_to_packet(&decoded), Some(packet));
+    }
+}
@@ -0,0 +1,241 @@
+//! Typed argument columns and their Arrow conversion.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array, Int64Array, NullArray,
+    StringArray, TimestampNanosecondArray, UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+use osc_ir::{IrTimestamp, IrValue};
+
+/// Time zone of every timestamp column.
+pub(crate) const UTC: &str = "UTC";
+
+/// Column type of an argument position, widened as messages arrive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// Only nil arguments so far
+    Null,
+    Bool,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    Utf8,
+    Binary,
+    Timestamp,
+    /// OSC 1.1 color or MIDI message, as 4 bytes
+    #[cfg(feature = "osc11")]
+    Bytes4,
+}
+
+impl Kind {
+    /// Returns the kind a column must have to hold both kinds, if any.
+    ///
+    /// Nil fits anything; numbers widen to `Float64` when they differ.
+    pub(crate) fn unify(self, other: Kind) -> Option<Kind> {
+        use Kind::*;
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (Null, k) | (k, Null) => Some(k),
+            (Int64 | UInt64 | Float32 | Float64, Int64 | UInt64 | Float32 | Float64) => {
+                Some(Float64)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn data_type(self) -> DataType {
+        match self {
+            Kind::Null => DataType::Null,
+            Kind::Bool => DataType::Boolean,
+            Kind::Int64 => DataType::Int64,
+            Kind::UInt64 => DataType::UInt64,
+            Kind::Float32 => DataType::Float32,
+            Kind::Float64 => DataType::Float64,
+            Kind::Utf8 => DataType::Utf8,
+            Kind::Binary => DataType::Binary,
+            Kind::Timestamp => timestamp_type(),
+            #[cfg(feature = "osc11")]
+            Kind::Bytes4 => DataType::FixedSizeBinary(4),
+        }
+    }
+}
+
+pub(crate) fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some(UTC.into()))
+}
+
+/// Nanoseconds since the Unix epoch, if they fit an `i64` (years 1677-2262).
+pub(crate) fn epoch_nanos(ts: &IrTimestamp) -> Option<i64> {
+    ts.seconds
+        .checked_mul(1_000_000_000)?
+        .checked_add(i64::from(ts.nanos))
+}
+
+/// One argument value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Cell {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    F32(f32),
+    F64(f64),
+    Str(Box<str>),
+    Bin(Vec<u8>),
+    Time(i64),
+    #[cfg(feature = "osc11")]
+    Bytes4([u8; 4]),
+}
+
+impl Cell {
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Cell::Int(i) => Some(i as f64),
+            Cell::UInt(u) => Some(u as f64),
+            Cell::F32(x) => Some(x.into()),
+            Cell::F64(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// Why an argument cannot be stored in a column.
+pub(crate) enum Unsupported {
+    /// Arrays, maps, ext values and bundles have no column type
+    Type,
+    /// A timestamp outside the range of nanosecond timestamps
+    Time(IrTimestamp),
+}
+
+/// Converts an argument to its OSC type tag, column kind and value (`None` for nil).
+pub(crate) fn cell(arg: &IrValue) -> Result<(char, Kind, Option<Cell>), Unsupported> {
+    Ok(match arg {
+        IrValue::Null => ('N', Kind::Null, None),
+        IrValue::Bool(b) => (if *b { 'T' } else { 'F' }, Kind::Bool, Some(Cell::Bool(*b))),
+        IrValue::Integer(i) => {
+            let tag = if i32::try_from(*i).is_ok() { 'i' } else { 'h' };
+            (tag, Kind::Int64, Some(Cell::Int(*i)))
+        }
+        IrValue::Unsigned(u) => ('h', Kind::UInt64, Some(Cell::UInt(*u))),
+        IrValue::Float32(x) => ('f', Kind::Float32, Some(Cell::F32(*x))),
+        IrValue::Float(x) => ('d', Kind::Float64, Some(Cell::F64(*x))),
+        IrValue::String(s) => ('s', Kind::Utf8, Some(Cell::Str(s.clone()))),
+        IrValue::Binary(data) => ('b', Kind::Binary, Some(Cell::Bin(data.clone()))),
+        IrValue::Timestamp(ts) => {
+            let nanos = epoch_nanos(ts).ok_or(Unsupported::Time(*ts))?;
+            ('t', Kind::Timestamp, Some(Cell::Time(nanos)))
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => ('r', Kind::Bytes4, Some(Cell::Bytes4([*r, *g, *b, *a]))),
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => (
+            'm',
+            Kind::Bytes4,
+            Some(Cell::Bytes4([*port, *status, *data1, *data2])),
+        ),
+        _ => return Err(Unsupported::Type),
+    })
+}
+
+/// The values of one argument position, one per row.
+pub(crate) struct Column {
+    pub(crate) kind: Kind,
+    pub(crate) cells: Vec<Option<Cell>>,
+}
+
+impl Column {
+    /// Builds the Arrow array, converting numbers to the widened kind.
+    pub(crate) fn finish(self) -> Result<ArrayRef, ArrowError> {
+        let cells = self.cells;
+        Ok(match self.kind {
+            Kind::Null => Arc::new(NullArray::new(cells.len())),
+            Kind::Bool => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::Bool(b)) => Some(*b),
+                        _ => None,
+                    })
+                    .collect::<BooleanArray>(),
+            ),
+            Kind::Int64 => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::Int(i)) => Some(*i),
+                        _ => None,
+                    })
+                    .collect::<Int64Array>(),
+            ),
+            Kind::UInt64 => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::UInt(u)) => Some(*u),
+                        _ => None,
+                    })
+                    .collect::<UInt64Array>(),
+            ),
+            Kind::Float32 => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::F32(x)) => Some(*x),
+                        _ => None,
+                    })
+                    .collect::<Float32Array>(),
+            ),
+            Kind::Float64 => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| c.as_ref().and_then(Cell::as_f64))
+                    .collect::<Float64Array>(),
+            ),
+            Kind::Utf8 => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::Str(s)) => Some(s.as_ref()),
+                        _ => None,
+                    })
+                    .collect::<StringArray>(),
+            ),
+            Kind::Binary => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::Bin(data)) => Some(data.as_slice()),
+                        _ => None,
+                    })
+                    .collect::<BinaryArray>(),
+            ),
+            Kind::Timestamp => Arc::new(
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Some(Cell::Time(nanos)) => Some(*nanos),
+                        _ => None,
+                    })
+                    .collect::<TimestampNanosecondArray>()
+                    .with_timezone(UTC),
+            ),
+            #[cfg(feature = "osc11")]
+            Kind::Bytes4 => Arc::new(
+                arrow_array::FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    cells.iter().map(|c| match c {
+                        Some(Cell::Bytes4(bytes)) => Some(bytes.as_slice()),
+                        _ => None,
+                    }),
+                    4,
+                )?,
+            ),
+        })
+    }
+}
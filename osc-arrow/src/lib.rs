@@ -0,0 +1,615 @@
+//! # osc-arrow
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Apache Arrow export of recorded OSC traffic for offline analysis.
+//!
+//! A recording is a sequence of `osc-ir` messages, each with the time it was received.
+//! [`BatchBuilder`] groups them by address and builds one Arrow `RecordBatch` per
+//! address, with the arguments exploded into typed columns inferred from the messages
+//! sent to that address. With the `parquet` feature the batches can be written to
+//! Parquet files for pandas, Polars, DuckDB and friends.
+//!
+//! ## Features
+//!
+//! - **Per-Address Tables**: Every address gets its own schema, so `/imu ffff` and
+//!   `/note isi` do not share sparse columns
+//! - **Type Inference**: Argument columns take the type of the values sent; numbers of
+//!   different types widen to `Float64` and nil arguments become nulls
+//! - **Bundles**: Messages inside recorded bundles share the receive time of the bundle
+//! - **Parquet Files**: [`write_parquet`] and [`write_parquet_dir`] (feature `parquet`,
+//!   enabled by default)
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_arrow::BatchBuilder;
+//! use osc_ir::{IrTimestamp, IrValue};
+//!
+//! let message = IrValue::message("/fader/1", vec![IrValue::Float32(0.5)]);
+//!
+//! let mut builder = BatchBuilder::new();
+//! builder.push(&IrTimestamp { seconds: 1_760_000_000, nanos: 0 }, &message).unwrap();
+//!
+//! let tables = builder.finish().unwrap();
+//! assert_eq!(tables[0].address, "/fader/1");
+//! assert_eq!(tables[0].batch.num_rows(), 1);
+//! ```
+//!
+//! ## Schema
+//!
+//! | Column | Arrow type | Contents |
+//! |--------|------------|----------|
+//! | `time` | `Timestamp(Nanosecond, "UTC")` | Receive time |
+//! | `address` | `Utf8` | OSC address |
+//! | `type_tags` | `Utf8` | Type tags of the message, without the leading `,` |
+//! | `arg0`, `arg1`, ... | see below | One column per argument position |
+//!
+//! | IR value | Arrow type |
+//! |----------|------------|
+//! | `Null` | null in any column; `Null` if the position is always nil |
+//! | `Bool` | `Boolean` |
+//! | `Integer` | `Int64` |
+//! | `Unsigned` | `UInt64` |
+//! | `Float32` | `Float32` |
+//! | `Float` | `Float64` |
+//! | `String` | `Utf8` |
+//! | `Binary` | `Binary` |
+//! | `Timestamp` | `Timestamp(Nanosecond, "UTC")` |
+//! | `Color`, `Midi` | `FixedSizeBinary(4)` |
+//!
+//! Arrays, maps and ext values have no column type and are rejected.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch, StringArray, TimestampNanosecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+#[cfg(feature = "osc10")]
+use osc_ir::IrBundle;
+use osc_ir::{IrTimestamp, IrValue};
+
+mod column;
+#[cfg(feature = "parquet")]
+mod parquet;
+
+use column::{Column, Kind, Unsupported};
+#[cfg(feature = "parquet")]
+pub use parquet::{write_parquet, write_parquet_dir};
+
+/// Schema metadata key holding the address of a batch.
+pub const ADDRESS_METADATA_KEY: &str = "osc.address";
+
+/// Error produced while building or writing batches.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value pushed is not an OSC message (or bundle)
+    NotAMessage,
+    /// The receive time is outside the range of nanosecond timestamps (1677-2262)
+    TimeOutOfRange(IrTimestamp),
+    /// An argument has no column type, such as an array or a map
+    UnsupportedArgument {
+        /// Address of the message
+        address: String,
+        /// Position of the argument
+        index: usize,
+    },
+    /// An argument does not fit the column built from earlier messages
+    IncompatibleArgument {
+        /// Address of the message
+        address: String,
+        /// Position of the argument
+        index: usize,
+        /// Type of the column so far
+        column: DataType,
+        /// Type of the argument
+        found: DataType,
+    },
+    /// Error from Arrow
+    Arrow(ArrowError),
+    /// Error from the Parquet writer
+    #[cfg(feature = "parquet")]
+    Parquet(::parquet::errors::ParquetError),
+    /// I/O error while writing files
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotAMessage => f.write_str("value is not an OSC message or bundle"),
+            Error::TimeOutOfRange(ts) => write!(
+                f,
+                "time {}.{:09} is outside the range of nanosecond timestamps",
+                ts.seconds, ts.nanos
+            ),
+            Error::UnsupportedArgument { address, index } => {
+                write!(f, "argument {} of `{}` has no column type", index, address)
+            }
+            Error::IncompatibleArgument {
+                address,
+                index,
+                column,
+                found,
+            } => write!(
+                f,
+                "argument {} of `{}` is {} but earlier messages made the column {}",
+                index, address, found, column
+            ),
+            Error::Arrow(e) => write!(f, "arrow: {}", e),
+            #[cfg(feature = "parquet")]
+            Error::Parquet(e) => write!(f, "parquet: {}", e),
+            Error::Io(e) => write!(f, "io: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Arrow(e) => Some(e),
+            #[cfg(feature = "parquet")]
+            Error::Parquet(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ArrowError> for Error {
+    fn from(e: ArrowError) -> Self {
+        Error::Arrow(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<::parquet::errors::ParquetError> for Error {
+    fn from(e: ::parquet::errors::ParquetError) -> Self {
+        Error::Parquet(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// The record batch of one address.
+#[derive(Clone, Debug)]
+pub struct AddressBatch {
+    /// OSC address of every row
+    pub address: String,
+    /// Rows in the order they were pushed
+    pub batch: RecordBatch,
+}
+
+/// Rows recorded for one address.
+#[derive(Default)]
+struct Table {
+    times: Vec<i64>,
+    type_tags: Vec<String>,
+    args: Vec<Column>,
+}
+
+impl Table {
+    fn push(&mut self, time: i64, address: &str, args: &[IrValue]) -> Result<(), Error> {
+        // Check every argument before touching the columns so a rejected message
+        // leaves the table unchanged.
+        let mut tags = String::with_capacity(args.len());
+        let mut cells = Vec::with_capacity(args.len());
+        for (index, arg) in args.iter().enumerate() {
+            let (tag, kind, cell) = column::cell(arg).map_err(|e| match e {
+                Unsupported::Type => Error::UnsupportedArgument {
+                    address: address.to_string(),
+                    index,
+                },
+                Unsupported::Time(ts) => Error::TimeOutOfRange(ts),
+            })?;
+            if let Some(column) = self.args.get(index) {
+                if column.kind.unify(kind).is_none() {
+                    return Err(Error::IncompatibleArgument {
+                        address: address.to_string(),
+                        index,
+                        column: column.kind.data_type(),
+                        found: kind.data_type(),
+                    });
+                }
+            }
+            tags.push(tag);
+            cells.push((kind, cell));
+        }
+
+        let rows = self.times.len();
+        for (index, (kind, cell)) in cells.into_iter().enumerate() {
+            if index == self.args.len() {
+                self.args.push(Column {
+                    kind: Kind::Null,
+                    cells: vec![None; rows],
+                });
+            }
+            let column = &mut self.args[index];
+            column.kind = column.kind.unify(kind).unwrap_or(kind);
+            column.cells.push(cell);
+        }
+        // Messages shorter than earlier ones leave their trailing columns null
+        for column in self.args.iter_mut().skip(args.len()) {
+            column.cells.push(None);
+        }
+        self.times.push(time);
+        self.type_tags.push(tags);
+        Ok(())
+    }
+
+    fn finish(self, address: String) -> Result<AddressBatch, Error> {
+        let mut fields = vec![
+            Field::new("time", column::timestamp_type(), false),
+            Field::new("address", DataType::Utf8, false),
+            Field::new("type_tags", DataType::Utf8, false),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampNanosecondArray::from(self.times).with_timezone(column::UTC)),
+            Arc::new(StringArray::from(vec![
+                address.as_str();
+                self.type_tags.len()
+            ])),
+            Arc::new(StringArray::from(self.type_tags)),
+        ];
+        for (index, column) in self.args.into_iter().enumerate() {
+            fields.push(Field::new(
+                format!("arg{}", index),
+                column.kind.data_type(),
+                true,
+            ));
+            columns.push(column.finish()?);
+        }
+        let metadata = [(ADDRESS_METADATA_KEY.to_string(), address.clone())].into();
+        let schema = Schema::new(fields).with_metadata(metadata);
+        let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+        Ok(AddressBatch { address, batch })
+    }
+}
+
+/// Accumulates timestamped messages and builds one record batch per address.
+#[derive(Default)]
+pub struct BatchBuilder {
+    tables: BTreeMap<String, Table>,
+    rows: usize,
+}
+
+impl BatchBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a message received at `time`.
+    ///
+    /// Bundles are flattened and every message inside gets `time`; their timetags are
+    /// not recorded. A rejected message leaves the builder unchanged, except that
+    /// messages of a rejected bundle before the offending one are kept.
+    pub fn push(&mut self, time: &IrTimestamp, value: &IrValue) -> Result<(), Error> {
+        let nanos = column::epoch_nanos(time).ok_or(Error::TimeOutOfRange(*time))?;
+        self.push_value(nanos, value)
+    }
+
+    fn push_value(&mut self, time: i64, value: &IrValue) -> Result<(), Error> {
+        #[cfg(feature = "osc10")]
+        if let IrValue::Bundle(bundle) = value {
+            return self.push_bundle(time, bundle);
+        }
+        let (address, args) = value.as_message().ok_or(Error::NotAMessage)?;
+        match self.tables.get_mut(address) {
+            Some(table) => table.push(time, address, args)?,
+            None => {
+                let mut table = Table::default();
+                table.push(time, address, args)?;
+                self.tables.insert(address.to_string(), table);
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    #[cfg(feature = "osc10")]
+    fn push_bundle(&mut self, time: i64, bundle: &IrBundle) -> Result<(), Error> {
+        for element in &bundle.elements {
+            match element {
+                osc_ir::IrBundleElement::Message(message) => self.push_value(time, message)?,
+                osc_ir::IrBundleElement::Bundle(nested) => self.push_bundle(time, nested)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of messages recorded.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns true if no message has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Returns the addresses seen so far, in sorted order.
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Builds the record batches, one per address in sorted address order.
+    pub fn finish(self) -> Result<Vec<AddressBatch>, Error> {
+        self.tables
+            .into_iter()
+            .map(|(address, table)| table.finish(address))
+            .collect()
+    }
+}
+
+/// Builds the record batches of a whole recording.
+pub fn record_batches<'a, I>(recording: I) -> Result<Vec<AddressBatch>, Error>
+where
+    I: IntoIterator<Item = (IrTimestamp, &'a IrValue)>,
+{
+    let mut builder = BatchBuilder::new();
+    for (time, value) in recording {
+        builder.push(&time, value)?;
+    }
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, BooleanArray, Float32Array, Float64Array, Int64Array, NullArray};
+
+    fn at(seconds: i64) -> IrTimestamp {
+        IrTimestamp { seconds, nanos: 0 }
+    }
+
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> &'a T {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<T>()
+            .unwrap()
+    }
+
+    #[test]
+    fn groups_messages_by_address() {
+        let a = IrValue::message("/a", vec![IrValue::from(1)]);
+        let b = IrValue::message("/b", vec![IrValue::from("x"), IrValue::Bool(true)]);
+        let tables = record_batches([(at(2), &b), (at(1), &a), (at(3), &a)]).unwrap();
+
+        let addresses: Vec<_> = tables.iter().map(|t| t.address.as_str()).collect();
+        assert_eq!(addresses, ["/a", "/b"]);
+
+        let a = &tables[0].batch;
+        assert_eq!(a.num_rows(), 2);
+        assert_eq!(a.num_columns(), 4);
+        let times = column::<TimestampNanosecondArray>(a, "time");
+        assert_eq!(times.values(), &[1_000_000_000, 3_000_000_000]);
+        assert_eq!(column::<StringArray>(a, "address").value(1), "/a");
+        assert_eq!(column::<StringArray>(a, "type_tags").value(0), "i");
+        assert_eq!(column::<Int64Array>(a, "arg0").values(), &[1, 1]);
+        assert_eq!(
+            a.schema()
+                .metadata()
+                .get(ADDRESS_METADATA_KEY)
+                .map(String::as_str),
+            Some("/a")
+        );
+
+        let b = &tables[1].batch;
+        assert_eq!(column::<StringArray>(b, "type_tags").value(0), "sT");
+        assert_eq!(column::<StringArray>(b, "arg0").value(0), "x");
+        assert!(column::<BooleanArray>(b, "arg1").value(0));
+    }
+
+    #[test]
+    fn timestamps_are_utc_nanoseconds() {
+        let msg = IrValue::message(
+            "/t",
+            vec![IrValue::Timestamp(IrTimestamp {
+                seconds: 5,
+                nanos: 7,
+            })],
+        );
+        let tables = record_batches([(
+            IrTimestamp {
+                seconds: 1,
+                nanos: 2,
+            },
+            &msg,
+        )])
+        .unwrap();
+        let batch = &tables[0].batch;
+        let schema = batch.schema();
+        assert_eq!(schema.field(0).data_type(), &column::timestamp_type());
+        assert_eq!(schema.field(3).data_type(), &column::timestamp_type());
+        assert_eq!(
+            column::<TimestampNanosecondArray>(batch, "time").value(0),
+            1_000_000_002
+        );
+        assert_eq!(
+            column::<TimestampNanosecondArray>(batch, "arg0").value(0),
+            5_000_000_007
+        );
+    }
+
+    #[test]
+    fn mixed_numbers_widen_to_float64() {
+        let tables = record_batches([
+            (at(0), &IrValue::message("/x", vec![IrValue::from(2)])),
+            (at(1), &IrValue::message("/x", vec![IrValue::Float32(0.5)])),
+            (
+                at(2),
+                &IrValue::message("/x", vec![IrValue::Unsigned(u64::MAX)]),
+            ),
+        ])
+        .unwrap();
+        let batch = &tables[0].batch;
+        let types = column::<StringArray>(batch, "type_tags");
+        assert_eq!(types.iter().flatten().collect::<Vec<_>>(), ["i", "f", "h"]);
+        let values = column::<Float64Array>(batch, "arg0");
+        assert_eq!(values.values(), &[2.0, 0.5, u64::MAX as f64]);
+    }
+
+    #[test]
+    fn float32_stays_single_precision() {
+        let tables =
+            record_batches([(at(0), &IrValue::message("/f", vec![IrValue::Float32(0.25)]))])
+                .unwrap();
+        assert_eq!(
+            column::<Float32Array>(&tables[0].batch, "arg0").value(0),
+            0.25
+        );
+    }
+
+    #[test]
+    fn nil_and_missing_arguments_are_null() {
+        let tables = record_batches([
+            (
+                at(0),
+                &IrValue::message("/n", vec![IrValue::Null, IrValue::Null]),
+            ),
+            (
+                at(1),
+                &IrValue::message("/n", vec![IrValue::Null, IrValue::from(4)]),
+            ),
+            (at(2), &IrValue::message("/n", vec![])),
+            (
+                at(3),
+                &IrValue::message(
+                    "/n",
+                    vec![IrValue::Null, IrValue::from(5), IrValue::from("late")],
+                ),
+            ),
+        ])
+        .unwrap();
+        let batch = &tables[0].batch;
+        assert_eq!(batch.num_rows(), 4);
+        assert_eq!(column::<NullArray>(batch, "arg0").len(), 4);
+        let arg1 = column::<Int64Array>(batch, "arg1");
+        assert_eq!(
+            arg1.iter().collect::<Vec<_>>(),
+            [None, Some(4), None, Some(5)]
+        );
+        let arg2 = column::<StringArray>(batch, "arg2");
+        assert_eq!(
+            arg2.iter().collect::<Vec<_>>(),
+            [None, None, None, Some("late")]
+        );
+        let types = column::<StringArray>(batch, "type_tags");
+        assert_eq!(
+            types.iter().flatten().collect::<Vec<_>>(),
+            ["NN", "Ni", "", "Nis"]
+        );
+    }
+
+    #[test]
+    fn conflicting_argument_types_are_rejected() {
+        let mut builder = BatchBuilder::new();
+        builder
+            .push(&at(0), &IrValue::message("/c", vec![IrValue::from(1)]))
+            .unwrap();
+        let err = builder
+            .push(&at(1), &IrValue::message("/c", vec![IrValue::from("one")]))
+            .unwrap_err();
+        match err {
+            Error::IncompatibleArgument {
+                address,
+                index,
+                column,
+                found,
+            } => {
+                assert_eq!(address, "/c");
+                assert_eq!(index, 0);
+                assert_eq!(column, DataType::Int64);
+                assert_eq!(found, DataType::Utf8);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        // The rejected message was not recorded
+        assert_eq!(builder.len(), 1);
+        assert_eq!(builder.finish().unwrap()[0].batch.num_rows(), 1);
+    }
+
+    #[test]
+    fn rejects_unsupported_values() {
+        let mut builder = BatchBuilder::new();
+        let nested = IrValue::message("/arr", vec![IrValue::from(1), IrValue::Array(vec![])]);
+        assert!(matches!(
+            builder.push(&at(0), &nested),
+            Err(Error::UnsupportedArgument { index: 1, .. })
+        ));
+        assert!(matches!(
+            builder.push(&at(0), &IrValue::from(3)),
+            Err(Error::NotAMessage)
+        ));
+        assert!(matches!(
+            builder.push(&at(i64::MAX), &IrValue::message("/late", vec![])),
+            Err(Error::TimeOutOfRange(_))
+        ));
+        assert!(builder.is_empty());
+        assert_eq!(builder.addresses().count(), 0);
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn bundles_share_the_receive_time() {
+        let mut inner = IrBundle::immediate();
+        inner.add_message(IrValue::message("/b", vec![IrValue::from(2)]));
+        let mut outer = IrBundle::immediate();
+        outer.add_message(IrValue::message("/a", vec![IrValue::from(1)]));
+        outer.add_bundle(inner);
+
+        let tables = record_batches([(at(9), &IrValue::Bundle(outer))]).unwrap();
+        assert_eq!(tables.len(), 2);
+        for table in &tables {
+            assert_eq!(
+                column::<TimestampNanosecondArray>(&table.batch, "time").value(0),
+                9_000_000_000
+            );
+        }
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn colors_and_midi_are_four_bytes() {
+        use arrow_array::FixedSizeBinaryArray;
+
+        let msg = IrValue::message(
+            "/rgb",
+            vec![
+                IrValue::Color {
+                    r: 1,
+                    g: 2,
+                    b: 3,
+                    a: 4,
+                },
+                IrValue::Midi {
+                    port: 0,
+                    status: 0x90,
+                    data1: 60,
+                    data2: 100,
+                },
+            ],
+        );
+        let tables = record_batches([(at(0), &msg)]).unwrap();
+        let batch = &tables[0].batch;
+        assert_eq!(column::<StringArray>(batch, "type_tags").value(0), "rm");
+        assert_eq!(
+            column::<FixedSizeBinaryArray>(batch, "arg0").value(0),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            column::<FixedSizeBinaryArray>(batch, "arg1").value(0),
+            [0, 0x90, 60, 100]
+        );
+    }
+}
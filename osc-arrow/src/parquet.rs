@@ -0,0 +1,143 @@
+//! Parquet output of record batches.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use arrow_array::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::{AddressBatch, Error};
+
+/// Writes one batch as a Snappy-compressed Parquet file.
+pub fn write_parquet<W: Write + Send>(writer: W, batch: &RecordBatch) -> Result<(), Error> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Writes every batch to its own file in `dir` and returns the paths, in batch order.
+///
+/// File names come from the address with separators and other unsafe characters
+/// replaced by `_` (`/mixer/fader/1` becomes `mixer_fader_1.parquet`); a numeric
+/// suffix is added when two addresses map to the same name. `dir` is created if
+/// missing and existing files are overwritten.
+pub fn write_parquet_dir(
+    dir: impl AsRef<Path>,
+    batches: &[AddressBatch],
+) -> Result<Vec<PathBuf>, Error> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut used = BTreeSet::new();
+    let mut paths = Vec::with_capacity(batches.len());
+    for table in batches {
+        let stem = file_stem(&table.address);
+        let mut name = format!("{}.parquet", stem);
+        let mut n = 2;
+        while !used.insert(name.clone()) {
+            name = format!("{}-{}.parquet", stem, n);
+            n += 1;
+        }
+        let path = dir.join(name);
+        write_parquet(File::create(&path)?, &table.batch)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn file_stem(address: &str) -> String {
+    let stem: String = address
+        .trim_start_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "root".to_string()
+    } else {
+        stem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record_batches;
+    use arrow_array::{Array, Float32Array, StringArray};
+    use osc_ir::{IrTimestamp, IrValue};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("osc-arrow-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn file_stems_are_safe() {
+        assert_eq!(file_stem("/mixer/fader/1"), "mixer_fader_1");
+        assert_eq!(file_stem("/a b/c*"), "a_b_c_");
+        assert_eq!(file_stem("/"), "root");
+    }
+
+    #[test]
+    fn parquet_files_read_back() {
+        let time = IrTimestamp {
+            seconds: 1_760_000_000,
+            nanos: 0,
+        };
+        let fader = IrValue::message("/fader/1", vec![IrValue::Float32(0.75), IrValue::Null]);
+        let label = IrValue::message("/label", vec![IrValue::from("intro")]);
+        let same_stem = IrValue::message("/fader_1", vec![]);
+        let tables = record_batches([(time, &fader), (time, &label), (time, &same_stem)]).unwrap();
+
+        let dir = scratch_dir("read-back");
+        let paths = write_parquet_dir(&dir, &tables).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["fader_1.parquet", "fader_1-2.parquet", "label.parquet"]
+        );
+
+        let file = File::open(&paths[0]).unwrap();
+        let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema().fields(), tables[0].batch.schema().fields());
+        let arg0 = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(arg0.value(0), 0.75);
+        assert_eq!(batch.column(4).logical_nulls().unwrap().null_count(), 1);
+        let tags = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(tags.value(0), "fN");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+//! Decoding of arbitrary CBOR (RFC 8949) into `IrValue`.
+
+use core::fmt;
+
+use osc_ir::{IrBundle, IrBundleElement, IrTimestamp, IrTimetag, IrValue};
+
+use crate::ser::{MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_NEGATIVE, MAJOR_TAG, MAJOR_TEXT, MAJOR_UNSIGNED};
+use crate::{MAX_DEPTH, TAG_BUNDLE, TAG_EXT};
+#[cfg(feature = "osc11")]
+use crate::{TAG_COLOR, TAG_MIDI};
+
+const BREAK: u8 = 0xFF;
+
+/// Error produced while decoding CBOR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of an item
+    UnexpectedEof {
+        /// Length of the input
+        offset: usize,
+    },
+    /// An item is malformed or cannot be represented as an `IrValue`
+    Invalid {
+        /// Offset of the offending item
+        offset: usize,
+        /// What was wrong with it
+        reason: &'static str,
+    },
+    /// Arrays, maps and tags are nested deeper than [`MAX_DEPTH`]
+    DepthLimitExceeded {
+        /// Offset of the item that exceeded the limit
+        offset: usize,
+    },
+    /// Bytes remain after the top-level item
+    TrailingData {
+        /// Offset of the first unread byte
+        offset: usize,
+    },
+}
+
+impl DecodeError {
+    /// Returns the byte offset the error refers to.
+    pub fn offset(&self) -> usize {
+        match self {
+            DecodeError::UnexpectedEof { offset }
+            | DecodeError::Invalid { offset, .. }
+            | DecodeError::DepthLimitExceeded { offset }
+            | DecodeError::TrailingData { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof { offset } => {
+                write!(f, "unexpected end of input at byte {}", offset)
+            }
+            DecodeError::Invalid { offset, reason } => write!(f, "{} at byte {}", reason, offset),
+            DecodeError::DepthLimitExceeded { offset } => {
+                write!(f, "nesting deeper than {} levels at byte {}", MAX_DEPTH, offset)
+            }
+            DecodeError::TrailingData { offset } => write!(f, "trailing data at byte {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+type Result<T> = core::result::Result<T, DecodeError>;
+
+fn invalid<T>(offset: usize, reason: &'static str) -> Result<T> {
+    Err(DecodeError::Invalid { offset, reason })
+}
+
+/// Reads CBOR items from a byte slice.
+pub(crate) struct Decoder<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Decoder {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn eof(&self) -> DecodeError {
+        DecodeError::UnexpectedEof {
+            offset: self.input.len(),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.input.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.remaining() {
+            return Err(self.eof());
+        }
+        let bytes = &self.input[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    /// Consumes a break stop code if one comes next.
+    fn at_break(&mut self) -> Result<bool> {
+        match self.input.get(self.pos) {
+            Some(&BREAK) => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(self.eof()),
+        }
+    }
+
+    fn enter(&mut self, offset: usize) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(DecodeError::DepthLimitExceeded { offset });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Reads the argument encoded in the additional information, or `None` for an
+    /// indefinite length.
+    fn argument(&mut self, info: u8, start: usize) -> Result<Option<u64>> {
+        Ok(Some(match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.byte()?),
+            25 => u64::from(u16::from_be_bytes(self.array()?)),
+            26 => u64::from(u32::from_be_bytes(self.array()?)),
+            27 => u64::from_be_bytes(self.array()?),
+            31 => return Ok(None),
+            _ => return invalid(start, "reserved additional information"),
+        }))
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn definite(&mut self, info: u8, start: usize) -> Result<u64> {
+        match self.argument(info, start)? {
+            Some(value) => Ok(value),
+            None => invalid(start, "unexpected indefinite length"),
+        }
+    }
+
+    /// Fails early when `count` items of at least `min_size` bytes cannot fit in
+    /// the rest of the input, so declared lengths never drive large allocations.
+    fn check_len(&self, count: u64, min_size: u64) -> Result<usize> {
+        match count.checked_mul(min_size) {
+            Some(size) if size <= self.remaining() as u64 => Ok(count as usize),
+            _ => Err(self.eof()),
+        }
+    }
+
+    /// Decodes the next complete item.
+    pub(crate) fn value(&mut self) -> Result<IrValue> {
+        let start = self.pos;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1F);
+        match major {
+            MAJOR_UNSIGNED => Ok(IrValue::from(self.definite(info, start)?)),
+            MAJOR_NEGATIVE => Ok(negative(self.definite(info, start)?)),
+            MAJOR_BYTES => self.string(major, info, start).map(IrValue::Binary),
+            MAJOR_TEXT => match String::from_utf8(self.string(major, info, start)?) {
+                Ok(s) => Ok(IrValue::from(s)),
+                Err(_) => invalid(start, "invalid UTF-8 in text string"),
+            },
+            MAJOR_ARRAY => {
+                self.enter(start)?;
+                let items = self.array_items(info, start)?;
+                self.depth -= 1;
+                Ok(IrValue::Array(items))
+            }
+            MAJOR_MAP => {
+                self.enter(start)?;
+                let entries = self.map_entries(info, start)?;
+                self.depth -= 1;
+                Ok(IrValue::Map(entries))
+            }
+            MAJOR_TAG => {
+                let tag = self.definite(info, start)?;
+                self.enter(start)?;
+                let value = self.tagged(tag)?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => self.simple(info, start),
+        }
+    }
+
+    /// Reads a byte or text string, joining the chunks of an indefinite-length one.
+    fn string(&mut self, major: u8, info: u8, start: usize) -> Result<Vec<u8>> {
+        if let Some(len) = self.argument(info, start)? {
+            let len = self.check_len(len, 1)?;
+            return Ok(self.take(len)?.to_vec());
+        }
+        let mut out = Vec::new();
+        while !self.at_break()? {
+            let chunk_start = self.pos;
+            let initial = self.byte()?;
+            if initial >> 5 != major || initial & 0x1F == 31 {
+                return invalid(chunk_start, "invalid chunk in indefinite-length string");
+            }
+            let len = self.definite(initial & 0x1F, chunk_start)?;
+            let len = self.check_len(len, 1)?;
+            out.extend_from_slice(self.take(len)?);
+        }
+        Ok(out)
+    }
+
+    fn array_items(&mut self, info: u8, start: usize) -> Result<Vec<IrValue>> {
+        match self.argument(info, start)? {
+            Some(len) => {
+                let len = self.check_len(len, 1)?;
+                (0..len).map(|_| self.value()).collect()
+            }
+            None => {
+                let mut items = Vec::new();
+                while !self.at_break()? {
+                    items.push(self.value()?);
+                }
+                Ok(items)
+            }
+        }
+    }
+
+    fn map_entries(&mut self, info: u8, start: usize) -> Result<Vec<(String, IrValue)>> {
+        match self.argument(info, start)? {
+            Some(len) => {
+                let len = self.check_len(len, 2)?;
+                (0..len).map(|_| self.entry()).collect()
+            }
+            None => {
+                let mut entries = Vec::new();
+                while !self.at_break()? {
+                    entries.push(self.entry()?);
+                }
+                Ok(entries)
+            }
+        }
+    }
+
+    /// Reads a key/value pair. Text keys are kept; integer keys become their decimal
+    /// string, since `IrValue` maps are keyed by strings.
+    fn entry(&mut self) -> Result<(String, IrValue)> {
+        let start = self.pos;
+        let key = match self.value()? {
+            IrValue::String(s) => s.into(),
+            IrValue::Integer(i) => i.to_string(),
+            IrValue::Unsigned(u) => u.to_string(),
+            _ => return invalid(start, "unsupported map key"),
+        };
+        Ok((key, self.value()?))
+    }
+
+    /// Decodes tag content. Unknown tags are ignored and yield their content.
+    fn tagged(&mut self, tag: u64) -> Result<IrValue> {
+        let start = self.pos;
+        let content = self.value()?;
+        match (tag, content) {
+            (0, IrValue::String(s)) => match IrTimestamp::parse_rfc3339(&s) {
+                Some(ts) => Ok(IrValue::Timestamp(ts)),
+                None => invalid(start, "invalid date/time string"),
+            },
+            (0, _) => invalid(start, "invalid date/time string"),
+            (1, content) => epoch_time(&content)
+                .map(IrValue::Timestamp)
+                .map_or_else(|| invalid(start, "invalid epoch time"), Ok),
+            (2, IrValue::Binary(bytes)) => Ok(match bignum(&bytes) {
+                Some(n) => IrValue::from(n),
+                None => IrValue::Float(bignum_to_f64(&bytes)),
+            }),
+            (3, IrValue::Binary(bytes)) => Ok(match bignum(&bytes) {
+                Some(n) => negative(n),
+                None => IrValue::Float(-1.0 - bignum_to_f64(&bytes)),
+            }),
+            (TAG_BUNDLE, IrValue::Array(items)) => match bundle(items) {
+                Some(bundle) => Ok(IrValue::Bundle(bundle)),
+                None => invalid(start, "invalid bundle"),
+            },
+            (TAG_BUNDLE, _) => invalid(start, "invalid bundle"),
+            (TAG_EXT, IrValue::Array(items)) => match items.as_slice() {
+                [type_id, IrValue::Binary(data)] => match type_id.as_integer().map(i8::try_from) {
+                    Some(Ok(type_id)) => Ok(IrValue::Ext {
+                        type_id,
+                        data: data.clone(),
+                    }),
+                    _ => invalid(start, "invalid ext value"),
+                },
+                _ => invalid(start, "invalid ext value"),
+            },
+            (TAG_EXT, _) => invalid(start, "invalid ext value"),
+            #[cfg(feature = "osc11")]
+            (TAG_COLOR, IrValue::Binary(bytes)) => match bytes.as_slice() {
+                &[r, g, b, a] => Ok(IrValue::Color { r, g, b, a }),
+                _ => invalid(start, "invalid color"),
+            },
+            #[cfg(feature = "osc11")]
+            (TAG_COLOR, _) => invalid(start, "invalid color"),
+            #[cfg(feature = "osc11")]
+            (TAG_MIDI, IrValue::Binary(bytes)) => match bytes.as_slice() {
+                &[port, status, data1, data2] => Ok(IrValue::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                }),
+                _ => invalid(start, "invalid MIDI message"),
+            },
+            #[cfg(feature = "osc11")]
+            (TAG_MIDI, _) => invalid(start, "invalid MIDI message"),
+            (_, content) => Ok(content),
+        }
+    }
+
+    /// Major type 7: simple values and floats. Unassigned simple values and
+    /// `undefined` decode as `Null`.
+    fn simple(&mut self, info: u8, start: usize) -> Result<IrValue> {
+        Ok(match info {
+            20 => IrValue::Bool(false),
+            21 => IrValue::Bool(true),
+            0..=19 | 22 | 23 => IrValue::Null,
+            24 => match self.byte()? {
+                32.. => IrValue::Null,
+                _ => return invalid(start, "invalid simple value encoding"),
+            },
+            25 => IrValue::Float32(f16_to_f32(u16::from_be_bytes(self.array()?))),
+            26 => IrValue::Float32(f32::from_be_bytes(self.array()?)),
+            27 => IrValue::Float(f64::from_be_bytes(self.array()?)),
+            31 => return invalid(start, "unexpected break"),
+            _ => return invalid(start, "reserved additional information"),
+        })
+    }
+}
+
+/// Converts a CBOR negative integer argument `n` (value `-1 - n`), falling back to
+/// a float below `i64::MIN`.
+fn negative(n: u64) -> IrValue {
+    match i64::try_from(n) {
+        Ok(n) => IrValue::Integer(-1 - n),
+        Err(_) => IrValue::Float(-1.0 - n as f64),
+    }
+}
+
+/// Reads a bignum that fits in `u64`.
+fn bignum(bytes: &[u8]) -> Option<u64> {
+    let significant = &bytes[bytes.iter().take_while(|b| **b == 0).count()..];
+    if significant.len() > 8 {
+        return None;
+    }
+    Some(significant.iter().fold(0, |acc, b| acc << 8 | u64::from(*b)))
+}
+
+fn bignum_to_f64(bytes: &[u8]) -> f64 {
+    bytes.iter().fold(0.0, |acc, b| acc * 256.0 + f64::from(*b))
+}
+
+fn epoch_time(content: &IrValue) -> Option<IrTimestamp> {
+    match content {
+        IrValue::Integer(seconds) => Some(IrTimestamp {
+            seconds: *seconds,
+            nanos: 0,
+        }),
+        IrValue::Float(_) | IrValue::Float32(_) => {
+            let x = content.as_float()?;
+            if !x.is_finite() || x < i64::MIN as f64 || x >= i64::MAX as f64 {
+                return None;
+            }
+            let seconds = x.floor();
+            let nanos = ((x - seconds) * 1e9).round() as u32;
+            Some(if nanos >= 1_000_000_000 {
+                IrTimestamp {
+                    seconds: seconds as i64 + 1,
+                    nanos: 0,
+                }
+            } else {
+                IrTimestamp {
+                    seconds: seconds as i64,
+                    nanos,
+                }
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Builds a bundle from `[timetag, element...]`; nested bundles become bundle
+/// elements and everything else a message.
+fn bundle(items: Vec<IrValue>) -> Option<IrBundle> {
+    let mut items = items.into_iter();
+    let timetag = match items.next()? {
+        IrValue::Integer(value) => u64::try_from(value).ok()?,
+        IrValue::Unsigned(value) => value,
+        _ => return None,
+    };
+    let elements = items
+        .map(|item| match item {
+            IrValue::Bundle(bundle) => IrBundleElement::Bundle(bundle),
+            other => IrBundleElement::Message(other),
+        })
+        .collect();
+    Some(IrBundle {
+        timetag: IrTimetag { value: timetag },
+        elements,
+    })
+}
+
+/// Widens an IEEE 754 half-precision float.
+fn f16_to_f32(half: u16) -> f32 {
+    let exponent = i32::from((half >> 10) & 0x1F);
+    let mantissa = f32::from(half & 0x3FF);
+    let magnitude = match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (mantissa + 1024.0) * 2f32.powi(exponent - 25),
+    };
+    if half & 0x8000 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
@@ -0,0 +1,326 @@
+//! # osc-codec-cbor
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! CBOR (RFC 8949) codec for the `osc-ir` intermediate representation, for constrained
+//! devices and web clients that speak CBOR natively.
+//!
+//! ## Features
+//!
+//! - **Native Types**: Binary data as byte strings, integers and floats in their own
+//!   major types, single and double precision kept apart
+//! - **Standard Timestamps**: Tag 1 (epoch seconds) for whole seconds, tag 0 (RFC 3339)
+//!   when nanoseconds are present
+//! - **OSC Tags**: Private tags for bundles, timetags, ext values and OSC 1.1 color/MIDI
+//! - **Arbitrary Input**: Decodes any well-formed CBOR, including indefinite lengths,
+//!   half floats and bignums, with a nesting limit and byte offsets in errors
+//! - **Codec Trait**: [`CborCodec`] implements `osc_ir::Codec` for format-agnostic tooling
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_ir::{IrValue, IrBundle, IrTimetag};
+//! use osc_codec_cbor::{to_cbor, from_cbor};
+//!
+//! # #[cfg(feature = "osc10")]
+//! # {
+//! let mut bundle = IrBundle::new(IrTimetag::from_ntp(12345));
+//! bundle.add_message(IrValue::from("hello"));
+//! bundle.add_message(IrValue::from(vec![1u8, 2, 3]));
+//! let value = IrValue::Bundle(bundle);
+//!
+//! let bytes = to_cbor(&value);
+//! assert_eq!(from_cbor(&bytes).unwrap(), value);
+//! # }
+//! ```
+//!
+//! ## Mapping
+//!
+//! | `IrValue` | CBOR |
+//! |-----------|------|
+//! | `Null`, `Bool` | simple values 22, 20/21 |
+//! | `Integer`, `Unsigned` | major types 0/1 |
+//! | `Float` / `Float32` | float64 / float32 |
+//! | `String` / `Binary` | text / byte string |
+//! | `Array` / `Map` | array / map with text keys |
+//! | `Timestamp` | tag 1 integer, or tag 0 RFC 3339 string with a fraction |
+//! | `Ext` | tag [`TAG_EXT`] `[type_id, bytes]` |
+//! | `Bundle` | tag [`TAG_BUNDLE`] `[tag TAG_TIMETAG uint, element...]` |
+//! | `Color` / `Midi` | tag [`TAG_COLOR`] / [`TAG_MIDI`] 4-byte byte string |
+//!
+//! Decoding other CBOR is best-effort: half floats become `Float32`, integer map keys
+//! their decimal string, bignums an integer when they fit and a float otherwise,
+//! `undefined` and unassigned simple values `Null`, and unknown tags their content.
+
+use osc_ir::{Codec, CodecError, IrValue};
+
+mod de;
+mod ser;
+
+pub use de::DecodeError;
+
+/// Tag of an OSC bundle: `[timetag, element...]`.
+///
+/// The OSC tags are in the first-come-first-served range and spell "OSC" followed
+/// by a letter.
+pub const TAG_BUNDLE: u64 = 0x4F53_4342;
+/// Tag of a bundle's NTP timetag (an unsigned integer).
+pub const TAG_TIMETAG: u64 = 0x4F53_4354;
+/// Tag of an ext value: `[type_id, bytes]`.
+pub const TAG_EXT: u64 = 0x4F53_4345;
+/// Tag of an OSC 1.1 color: a byte string `[r, g, b, a]`.
+pub const TAG_COLOR: u64 = 0x4F53_4372;
+/// Tag of an OSC 1.1 MIDI message: a byte string `[port, status, data1, data2]`.
+pub const TAG_MIDI: u64 = 0x4F53_436D;
+
+/// Maximum nesting of arrays, maps and tags accepted by the decoder.
+pub const MAX_DEPTH: usize = 128;
+
+/// [`Codec`] implementation producing CBOR bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CborCodec;
+
+impl Codec for CborCodec {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/cbor"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        Ok(to_cbor(value))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        from_cbor(bytes).map_err(|e| CodecError::decode(e.to_string()))
+    }
+}
+
+/// Convert IR -> CBOR bytes.
+pub fn to_cbor(v: &IrValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ser::encode(&mut out, v);
+    out
+}
+
+/// Write the CBOR encoding of IR into a writer.
+pub fn to_cbor_writer<W: std::io::Write>(mut writer: W, v: &IrValue) -> std::io::Result<()> {
+    writer.write_all(&to_cbor(v))
+}
+
+/// Convert CBOR bytes -> IR. The input must hold exactly one item.
+pub fn from_cbor(bytes: &[u8]) -> Result<IrValue, DecodeError> {
+    let mut decoder = de::Decoder::new(bytes);
+    let value = decoder.value()?;
+    if decoder.position() != bytes.len() {
+        return Err(DecodeError::TrailingData {
+            offset: decoder.position(),
+        });
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::{IrBundle, IrTimestamp, IrTimetag};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn roundtrip_all_value_kinds() {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        nested.add_message(IrValue::message("/n", vec![IrValue::from(1)]));
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(IrValue::message("/a", vec![IrValue::Float32(0.5), IrValue::from("x")]));
+        bundle.add_bundle(nested);
+        bundle.add_message(IrValue::Null);
+
+        let values = [
+            IrValue::Null,
+            IrValue::Bool(true),
+            IrValue::Integer(i64::MIN),
+            IrValue::Integer(i64::MAX),
+            IrValue::Integer(-24),
+            IrValue::Unsigned(u64::MAX),
+            IrValue::Float(0.1),
+            IrValue::Float32(0.1),
+            IrValue::Float(f64::INFINITY),
+            IrValue::from("héllo"),
+            IrValue::Binary(vec![0, 255]),
+            IrValue::Array(vec![]),
+            IrValue::Map(vec![("b".into(), IrValue::from(1)), ("a".into(), IrValue::from(2)), ("b".into(), IrValue::Null)]),
+            IrValue::Timestamp(IrTimestamp { seconds: -1, nanos: 0 }),
+            IrValue::Timestamp(IrTimestamp { seconds: 1_700_000_000, nanos: 1 }),
+            IrValue::Ext { type_id: -128, data: vec![1, 2, 3] },
+            IrValue::Bundle(bundle),
+        ];
+        for value in values {
+            let bytes = to_cbor(&value);
+            assert_eq!(from_cbor(&bytes).unwrap(), value, "bytes: {:02x?}", bytes);
+        }
+
+        let nan = from_cbor(&to_cbor(&IrValue::Float32(f32::NAN))).unwrap();
+        assert!(matches!(nan, IrValue::Float32(x) if x.is_nan()));
+    }
+
+    #[test]
+    fn encodes_native_cbor() {
+        let cases = [
+            (IrValue::Integer(-1000), "3903e7"),
+            (IrValue::Unsigned(u64::MAX), "1bffffffffffffffff"),
+            (IrValue::Float32(1.5), "fa3fc00000"),
+            (IrValue::Float(1.5), "fb3ff8000000000000"),
+            (IrValue::Binary(vec![1, 2, 3]), "43010203"),
+            (IrValue::Map(vec![("a".into(), IrValue::from(1))]), "a1616101"),
+            (IrValue::Timestamp(IrTimestamp { seconds: 1_363_896_240, nanos: 0 }), "c11a514b67b0"),
+            (
+                IrValue::Timestamp(IrTimestamp { seconds: 1_363_896_240, nanos: 500_000_000 }),
+                "c076323031332d30332d32315432303a30343a30302e355a",
+            ),
+            (IrValue::Ext { type_id: 5, data: vec![9] }, "da4f5343458205 4109"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(to_cbor(&value), hex(&expected.replace(' ', "")), "{:?}", value);
+        }
+
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(2));
+        bundle.add_message(IrValue::from(true));
+        assert_eq!(to_cbor(&IrValue::Bundle(bundle)), hex("da4f534342 82 da4f534354 02 f5".replace(' ', "").as_str()));
+    }
+
+    #[test]
+    fn timestamps_outside_rfc3339_years_fall_back_to_epoch_floats() {
+        let ts = IrTimestamp { seconds: -100_000_000_000, nanos: 500_000_000 };
+        let bytes = to_cbor(&IrValue::Timestamp(ts));
+        assert_eq!(&bytes[..2], &[0xC1, 0xFB]);
+        assert_eq!(from_cbor(&bytes).unwrap(), IrValue::Timestamp(ts));
+    }
+
+    #[test]
+    fn decodes_rfc8949_examples() {
+        let cases = [
+            ("00", IrValue::from(0)),
+            ("1864", IrValue::from(100)),
+            ("1b000000e8d4a51000", IrValue::from(1_000_000_000_000i64)),
+            ("c249010000000000000000", IrValue::Float(18446744073709551616.0)),
+            ("3bffffffffffffffff", IrValue::Float(-18446744073709551616.0)),
+            ("c348ffffffffffffffff", IrValue::Float(-18446744073709551616.0)),
+            ("c249000000000000000001", IrValue::Integer(1)),
+            ("3863", IrValue::from(-100)),
+            ("f93c00", IrValue::Float32(1.0)),
+            ("f90001", IrValue::Float32(5.9604645e-8)),
+            ("f9c400", IrValue::Float32(-4.0)),
+            ("f97c00", IrValue::Float32(f32::INFINITY)),
+            ("fa47c35000", IrValue::Float32(100000.0)),
+            ("fb3ff199999999999a", IrValue::Float(1.1)),
+            ("f7", IrValue::Null),
+            ("f0", IrValue::Null),
+            ("f8ff", IrValue::Null),
+            ("c074323031332d30332d32315432303a30343a30305a", IrValue::Timestamp(IrTimestamp { seconds: 1_363_896_240, nanos: 0 })),
+            ("c1fb41d452d9ec200000", IrValue::Timestamp(IrTimestamp { seconds: 1_363_896_240, nanos: 500_000_000 })),
+            ("d74401020304", IrValue::Binary(vec![1, 2, 3, 4])),
+            ("d818456449455446", IrValue::Binary(b"dIETF".to_vec())),
+            ("6449455446", IrValue::from("IETF")),
+            ("62c3bc", IrValue::from("ü")),
+            ("a201020304", IrValue::Map(vec![("1".into(), IrValue::from(2)), ("3".into(), IrValue::from(4))])),
+            ("5f42010243030405ff", IrValue::Binary(vec![1, 2, 3, 4, 5])),
+            ("7f657374726561646d696e67ff", IrValue::from("streaming")),
+            (
+                "9f018202039f0405ffff",
+                IrValue::Array(vec![
+                    IrValue::from(1),
+                    IrValue::Array(vec![IrValue::from(2), IrValue::from(3)]),
+                    IrValue::Array(vec![IrValue::from(4), IrValue::from(5)]),
+                ]),
+            ),
+            (
+                "bf61610161629f0203ffff",
+                IrValue::Map(vec![
+                    ("a".into(), IrValue::from(1)),
+                    ("b".into(), IrValue::Array(vec![IrValue::from(2), IrValue::from(3)])),
+                ]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(from_cbor(&hex(input)).unwrap(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn reports_errors_with_offsets() {
+        let err = |input: &str| from_cbor(&hex(input)).unwrap_err();
+        assert_eq!(err(""), DecodeError::UnexpectedEof { offset: 0 });
+        assert_eq!(err("8301"), DecodeError::UnexpectedEof { offset: 2 });
+        assert_eq!(err("0000"), DecodeError::TrailingData { offset: 1 });
+        assert_eq!(err("8162c328"), DecodeError::Invalid { offset: 1, reason: "invalid UTF-8 in text string" });
+        assert_eq!(err("ff"), DecodeError::Invalid { offset: 0, reason: "unexpected break" });
+        assert_eq!(err("1c"), DecodeError::Invalid { offset: 0, reason: "reserved additional information" });
+        assert_eq!(err("1f"), DecodeError::Invalid { offset: 0, reason: "unexpected indefinite length" });
+        assert_eq!(err("5f01ff"), DecodeError::Invalid { offset: 1, reason: "invalid chunk in indefinite-length string" });
+        assert_eq!(err("a1f601"), DecodeError::Invalid { offset: 1, reason: "unsupported map key" });
+        assert_eq!(err("f801"), DecodeError::Invalid { offset: 0, reason: "invalid simple value encoding" });
+        assert_eq!(err("c06161"), DecodeError::Invalid { offset: 1, reason: "invalid date/time string" });
+        assert_eq!(err("da4f53434280"), DecodeError::Invalid { offset: 5, reason: "invalid bundle" });
+        assert_eq!(err("da4f5343458218ff4100"), DecodeError::Invalid { offset: 5, reason: "invalid ext value" });
+        assert_eq!(err("81da4f53434280").to_string(), "invalid bundle at byte 6");
+
+        // Declared lengths larger than the input fail before allocating.
+        assert_eq!(err("9bffffffffffffffff"), DecodeError::UnexpectedEof { offset: 9 });
+        assert_eq!(err("5b00000000ffffffff00"), DecodeError::UnexpectedEof { offset: 10 });
+    }
+
+    #[test]
+    fn enforces_depth_limit() {
+        let nested = |depth: usize| {
+            let mut bytes = vec![0x81; depth];
+            bytes.push(0x00);
+            bytes
+        };
+        assert!(from_cbor(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            from_cbor(&nested(MAX_DEPTH + 1)).unwrap_err(),
+            DecodeError::DepthLimitExceeded { offset: MAX_DEPTH }
+        );
+        let mut tags = vec![0xC6; MAX_DEPTH + 1];
+        tags.push(0x00);
+        assert!(matches!(from_cbor(&tags), Err(DecodeError::DepthLimitExceeded { .. })));
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn roundtrip_color_and_midi() {
+        let value = IrValue::message("/c", vec![IrValue::color(255, 0, 16, 128), IrValue::midi(1, 0x90, 60, 127)]);
+        let bytes = to_cbor(&value);
+        assert_eq!(from_cbor(&bytes).unwrap(), value);
+        assert_eq!(to_cbor(&IrValue::color(1, 2, 3, 4)), hex("da4f53437244 01020304".replace(' ', "").as_str()));
+        assert!(from_cbor(&hex("da4f53437243010203")).is_err());
+    }
+
+    #[test]
+    fn codec_trait() {
+        let codec = CborCodec;
+        assert_eq!(codec.name(), "cbor");
+        assert_eq!(codec.mime_type(), "application/cbor");
+
+        let value = IrValue::Array(vec![IrValue::from("a"), IrValue::Binary(vec![1, 2])]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+
+        let mut out = Vec::new();
+        to_cbor_writer(&mut out, &value).unwrap();
+        assert_eq!(out, bytes);
+
+        let err = codec.decode(&bytes[..3]).unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Decode);
+        assert!(err.message().contains("byte"));
+    }
+}
@@ -0,0 +1,147 @@
+//! Encoding of `IrValue` into CBOR (RFC 8949).
+
+use osc_ir::{IrBundle, IrBundleElement, IrTimestamp, IrValue};
+
+use crate::{TAG_BUNDLE, TAG_EXT, TAG_TIMETAG};
+#[cfg(feature = "osc11")]
+use crate::{TAG_COLOR, TAG_MIDI};
+
+pub(crate) const MAJOR_UNSIGNED: u8 = 0;
+pub(crate) const MAJOR_NEGATIVE: u8 = 1;
+pub(crate) const MAJOR_BYTES: u8 = 2;
+pub(crate) const MAJOR_TEXT: u8 = 3;
+pub(crate) const MAJOR_ARRAY: u8 = 4;
+pub(crate) const MAJOR_MAP: u8 = 5;
+pub(crate) const MAJOR_TAG: u8 = 6;
+
+const FALSE: u8 = 0xF4;
+const TRUE: u8 = 0xF5;
+const NULL: u8 = 0xF6;
+const FLOAT32: u8 = 0xFA;
+const FLOAT64: u8 = 0xFB;
+
+/// Appends the CBOR encoding of `v` to `out`.
+pub(crate) fn encode(out: &mut Vec<u8>, v: &IrValue) {
+    match v {
+        IrValue::Null => out.push(NULL),
+        IrValue::Bool(b) => out.push(if *b { TRUE } else { FALSE }),
+        IrValue::Integer(i) => integer(out, *i),
+        IrValue::Unsigned(u) => head(out, MAJOR_UNSIGNED, *u),
+        IrValue::Float(x) => {
+            out.push(FLOAT64);
+            out.extend_from_slice(&x.to_be_bytes());
+        }
+        IrValue::Float32(x) => {
+            out.push(FLOAT32);
+            out.extend_from_slice(&x.to_be_bytes());
+        }
+        IrValue::String(s) => text(out, s),
+        IrValue::Binary(data) => bytes(out, data),
+        IrValue::Array(xs) => {
+            head(out, MAJOR_ARRAY, xs.len() as u64);
+            for x in xs {
+                encode(out, x);
+            }
+        }
+        IrValue::Map(entries) => {
+            head(out, MAJOR_MAP, entries.len() as u64);
+            for (key, value) in entries {
+                text(out, key);
+                encode(out, value);
+            }
+        }
+        IrValue::Timestamp(ts) => timestamp(out, ts),
+        IrValue::Ext { type_id, data } => {
+            head(out, MAJOR_TAG, TAG_EXT);
+            head(out, MAJOR_ARRAY, 2);
+            integer(out, i64::from(*type_id));
+            bytes(out, data);
+        }
+        IrValue::Bundle(bundle) => self::bundle(out, bundle),
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => {
+            head(out, MAJOR_TAG, TAG_COLOR);
+            bytes(out, &[*r, *g, *b, *a]);
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => {
+            head(out, MAJOR_TAG, TAG_MIDI);
+            bytes(out, &[*port, *status, *data1, *data2]);
+        }
+        // OSC 1.1 Color and MIDI types when osc-ir enables them but this crate does not
+        #[allow(unreachable_patterns)]
+        _ => out.push(NULL),
+    }
+}
+
+/// Writes an initial byte and argument using the shortest form.
+pub(crate) fn head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        out.push(major | value as u8);
+    } else if let Ok(v) = u8::try_from(value) {
+        out.extend_from_slice(&[major | 24, v]);
+    } else if let Ok(v) = u16::try_from(value) {
+        out.push(major | 25);
+        out.extend_from_slice(&v.to_be_bytes());
+    } else if let Ok(v) = u32::try_from(value) {
+        out.push(major | 26);
+        out.extend_from_slice(&v.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn integer(out: &mut Vec<u8>, i: i64) {
+    if i >= 0 {
+        head(out, MAJOR_UNSIGNED, i as u64);
+    } else {
+        // CBOR stores -1 - n; for negative i that is the bitwise complement.
+        head(out, MAJOR_NEGATIVE, !i as u64);
+    }
+}
+
+fn text(out: &mut Vec<u8>, s: &str) {
+    head(out, MAJOR_TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn bytes(out: &mut Vec<u8>, data: &[u8]) {
+    head(out, MAJOR_BYTES, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+/// Whole seconds use tag 1 with an integer; sub-second timestamps use tag 0 with an
+/// RFC 3339 string, which keeps nanosecond precision. Years outside 0000-9999 cannot
+/// be written as RFC 3339 and fall back to a tag 1 float.
+fn timestamp(out: &mut Vec<u8>, ts: &IrTimestamp) {
+    if ts.nanos == 0 {
+        head(out, MAJOR_TAG, 1);
+        integer(out, ts.seconds);
+    } else if let Some(formatted) = ts.to_rfc3339() {
+        head(out, MAJOR_TAG, 0);
+        text(out, &formatted);
+    } else {
+        head(out, MAJOR_TAG, 1);
+        encode(out, &IrValue::Float(ts.seconds as f64 + f64::from(ts.nanos) / 1e9));
+    }
+}
+
+fn bundle(out: &mut Vec<u8>, bundle: &IrBundle) {
+    head(out, MAJOR_TAG, TAG_BUNDLE);
+    head(out, MAJOR_ARRAY, bundle.elements.len() as u64 + 1);
+    head(out, MAJOR_TAG, TAG_TIMETAG);
+    head(out, MAJOR_UNSIGNED, bundle.timetag.value);
+    for element in &bundle.elements {
+        match element {
+            IrBundleElement::Message(msg) => encode(out, msg),
+            IrBundleElement::Bundle(nested) => self::bundle(out, nested),
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Decoding of the compact format into `IrValue`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag};
+use osc_ir::{IrTimestamp, IrValue};
+
+use crate::{tag, MAX_DEPTH};
+
+/// Error produced while decoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value
+    UnexpectedEof {
+        /// Length of the input
+        offset: usize,
+    },
+    /// A value is malformed or uses a type this build does not support
+    Invalid {
+        /// Offset of the offending byte
+        offset: usize,
+        /// What was wrong with it
+        reason: &'static str,
+    },
+    /// Arrays, maps and bundles are nested deeper than [`MAX_DEPTH`]
+    DepthLimitExceeded {
+        /// Offset of the value that exceeded the limit
+        offset: usize,
+    },
+    /// Bytes remain after the value
+    TrailingData {
+        /// Offset of the first unread byte
+        offset: usize,
+    },
+}
+
+impl DecodeError {
+    /// Returns the byte offset the error refers to.
+    pub fn offset(&self) -> usize {
+        match self {
+            DecodeError::UnexpectedEof { offset }
+            | DecodeError::Invalid { offset, .. }
+            | DecodeError::DepthLimitExceeded { offset }
+            | DecodeError::TrailingData { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof { offset } => {
+                write!(f, "unexpected end of input at byte {}", offset)
+            }
+            DecodeError::Invalid { offset, reason } => write!(f, "{} at byte {}", reason, offset),
+            DecodeError::DepthLimitExceeded { offset } => {
+                write!(f, "nesting deeper than {} levels at byte {}", MAX_DEPTH, offset)
+            }
+            DecodeError::TrailingData { offset } => write!(f, "trailing data at byte {}", offset),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+type Result<T> = core::result::Result<T, DecodeError>;
+
+fn invalid<T>(offset: usize, reason: &'static str) -> Result<T> {
+    Err(DecodeError::Invalid { offset, reason })
+}
+
+/// Reads values from a byte slice.
+pub(crate) struct Decoder<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Decoder {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn eof(&self) -> DecodeError {
+        DecodeError::UnexpectedEof {
+            offset: self.input.len(),
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.input.len() - self.pos {
+            return Err(self.eof());
+        }
+        let bytes = &self.input[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let start = self.pos;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u64::from(byte & 0x7F);
+            if shift == 63 && bits > 1 {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        invalid(start, "varint overflow")
+    }
+
+    fn zigzag(&mut self) -> Result<i64> {
+        let n = self.varint()?;
+        Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+    }
+
+    /// Reads a length or count, failing early when that many items of at least one
+    /// byte cannot fit in the rest of the input.
+    fn len(&mut self) -> Result<usize> {
+        let len = self.varint()?;
+        if len > (self.input.len() - self.pos) as u64 {
+            return Err(self.eof());
+        }
+        Ok(len as usize)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.len()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        match core::str::from_utf8(self.bytes()?) {
+            Ok(s) => Ok(String::from(s)),
+            Err(_) => invalid(start, "invalid UTF-8 in string"),
+        }
+    }
+
+    fn enter(&mut self, offset: usize) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(DecodeError::DepthLimitExceeded { offset });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Decodes the next value.
+    pub(crate) fn value(&mut self) -> Result<IrValue> {
+        let start = self.pos;
+        Ok(match self.byte()? {
+            tag::NULL => IrValue::Null,
+            tag::FALSE => IrValue::Bool(false),
+            tag::TRUE => IrValue::Bool(true),
+            tag::INTEGER => IrValue::Integer(self.zigzag()?),
+            tag::UNSIGNED => IrValue::Unsigned(self.varint()?),
+            tag::FLOAT => IrValue::Float(f64::from_le_bytes(self.array()?)),
+            tag::FLOAT32 => IrValue::Float32(f32::from_le_bytes(self.array()?)),
+            tag::STRING => IrValue::from(self.string()?),
+            tag::BINARY => IrValue::Binary(self.bytes()?.to_vec()),
+            tag::ARRAY => {
+                self.enter(start)?;
+                let len = self.len()?;
+                let items = (0..len).map(|_| self.value()).collect::<Result<Vec<_>>>()?;
+                self.depth -= 1;
+                IrValue::Array(items)
+            }
+            tag::MAP => {
+                self.enter(start)?;
+                let len = self.len()?;
+                let entries = (0..len)
+                    .map(|_| Ok((self.string()?, self.value()?)))
+                    .collect::<Result<Vec<_>>>()?;
+                self.depth -= 1;
+                IrValue::Map(entries)
+            }
+            tag::TIMESTAMP => {
+                let seconds = self.zigzag()?;
+                let nanos_start = self.pos;
+                let nanos = match u32::try_from(self.varint()?) {
+                    Ok(nanos) => nanos,
+                    Err(_) => return invalid(nanos_start, "nanoseconds out of range"),
+                };
+                IrValue::Timestamp(IrTimestamp { seconds, nanos })
+            }
+            tag::EXT => {
+                let type_id = self.byte()? as i8;
+                IrValue::Ext {
+                    type_id,
+                    data: self.bytes()?.to_vec(),
+                }
+            }
+            #[cfg(feature = "osc10")]
+            tag::BUNDLE => IrValue::Bundle(self.bundle(start)?),
+            #[cfg(feature = "osc11")]
+            tag::COLOR => {
+                let [r, g, b, a] = self.array()?;
+                IrValue::Color { r, g, b, a }
+            }
+            #[cfg(feature = "osc11")]
+            tag::MIDI => {
+                let [port, status, data1, data2] = self.array()?;
+                IrValue::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                }
+            }
+            _ => return invalid(start, "unknown type tag"),
+        })
+    }
+
+    #[cfg(feature = "osc10")]
+    fn bundle(&mut self, start: usize) -> Result<IrBundle> {
+        self.enter(start)?;
+        let timetag = IrTimetag {
+            value: self.varint()?,
+        };
+        let len = self.len()?;
+        let mut elements = Vec::with_capacity(len);
+        for _ in 0..len {
+            let element_start = self.pos;
+            elements.push(match self.byte()? {
+                tag::ELEMENT_MESSAGE => IrBundleElement::Message(self.value()?),
+                tag::ELEMENT_BUNDLE => IrBundleElement::Bundle(self.bundle(element_start)?),
+                _ => return invalid(element_start, "invalid bundle element"),
+            });
+        }
+        self.depth -= 1;
+        Ok(IrBundle { timetag, elements })
+    }
+}
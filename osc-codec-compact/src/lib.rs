@@ -0,0 +1,333 @@
+//! # osc-codec-compact
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Compact binary codec for the `osc-ir` intermediate representation that works in
+//! `no_std` + `alloc` environments, for storing and transferring IR on microcontrollers.
+//!
+//! ## Features
+//!
+//! - **no_std**: Only needs `alloc`; the `std` feature (on by default) adds
+//!   `std::error::Error` impls
+//! - **Fixed-Capacity Encoding**: [`to_compact_slice`] writes into a caller-provided buffer
+//!   and never allocates; [`encoded_len`] sizes the buffer up front
+//! - **Compact**: Varint lengths and integers, single-byte type tags, no field names
+//! - **Stable Format**: Explicit type tags, so the bytes do not depend on which OSC
+//!   feature flags are enabled
+//! - **Streams**: [`take_from_compact`] decodes one value and returns the rest of the input
+//! - **Codec Trait**: [`CompactCodec`] implements `osc_ir::Codec` for format-agnostic tooling
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_ir::IrValue;
+//! use osc_codec_compact::{encoded_len, from_compact, to_compact_slice};
+//!
+//! let value = IrValue::from(vec![IrValue::from("/led"), IrValue::from(255)]);
+//!
+//! let mut buf = [0u8; 32];
+//! assert!(encoded_len(&value) <= buf.len());
+//! let bytes = to_compact_slice(&value, &mut buf).unwrap();
+//! assert_eq!(from_compact(bytes).unwrap(), value);
+//! ```
+//!
+//! ## Format
+//!
+//! Every value is a one-byte type tag followed by its payload. `varint` is unsigned
+//! LEB128; `zigzag` maps signed integers onto varints (0, -1, 1, -2, ...); floats
+//! are little-endian.
+//!
+//! | Tag | Value | Payload |
+//! |-----|-------|---------|
+//! | `0x00` | `Null` | - |
+//! | `0x01` / `0x02` | `Bool` false / true | - |
+//! | `0x03` | `Integer` | zigzag |
+//! | `0x04` | `Unsigned` | varint |
+//! | `0x05` | `Float` | 8 bytes |
+//! | `0x06` | `Float32` | 4 bytes |
+//! | `0x07` | `String` | varint length, UTF-8 bytes |
+//! | `0x08` | `Binary` | varint length, bytes |
+//! | `0x09` | `Array` | varint count, values |
+//! | `0x0A` | `Map` | varint count, (varint key length, key, value) pairs |
+//! | `0x0B` | `Timestamp` | zigzag seconds, varint nanos |
+//! | `0x0C` | `Ext` | type id byte, varint length, bytes |
+//! | `0x0D` | `Bundle` | varint timetag, varint count, elements |
+//! | `0x0E` | `Color` | r, g, b, a |
+//! | `0x0F` | `Midi` | port, status, data1, data2 |
+//!
+//! A bundle element is `0x00` followed by a value (message) or `0x01` followed by a
+//! nested bundle's timetag, count and elements.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::vec::Vec;
+
+use osc_ir::{Codec, CodecError, IrValue};
+
+mod de;
+mod ser;
+
+pub use de::DecodeError;
+pub use ser::EncodeError;
+
+/// Type tags of the format.
+pub(crate) mod tag {
+    pub const NULL: u8 = 0x00;
+    pub const FALSE: u8 = 0x01;
+    pub const TRUE: u8 = 0x02;
+    pub const INTEGER: u8 = 0x03;
+    pub const UNSIGNED: u8 = 0x04;
+    pub const FLOAT: u8 = 0x05;
+    pub const FLOAT32: u8 = 0x06;
+    pub const STRING: u8 = 0x07;
+    pub const BINARY: u8 = 0x08;
+    pub const ARRAY: u8 = 0x09;
+    pub const MAP: u8 = 0x0A;
+    pub const TIMESTAMP: u8 = 0x0B;
+    pub const EXT: u8 = 0x0C;
+    #[cfg(feature = "osc10")]
+    pub const BUNDLE: u8 = 0x0D;
+    #[cfg(feature = "osc11")]
+    pub const COLOR: u8 = 0x0E;
+    #[cfg(feature = "osc11")]
+    pub const MIDI: u8 = 0x0F;
+
+    #[cfg(feature = "osc10")]
+    pub const ELEMENT_MESSAGE: u8 = 0x00;
+    #[cfg(feature = "osc10")]
+    pub const ELEMENT_BUNDLE: u8 = 0x01;
+}
+
+/// Maximum nesting of arrays, maps and bundles accepted by the decoder.
+pub const MAX_DEPTH: usize = 128;
+
+/// [`Codec`] implementation producing the compact format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompactCodec;
+
+impl Codec for CompactCodec {
+    fn name(&self) -> &'static str {
+        "compact"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/x-osc-ir-compact"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        Ok(to_compact(value))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        from_compact(bytes).map_err(|e| CodecError::decode(alloc::format!("{}", e)))
+    }
+}
+
+/// Returns the number of bytes `v` encodes to.
+pub fn encoded_len(v: &IrValue) -> usize {
+    let mut counter = ser::Counter(0);
+    match ser::encode(&mut counter, v) {
+        Ok(()) => counter.0,
+        Err(never) => match never {},
+    }
+}
+
+/// Convert IR -> compact bytes.
+pub fn to_compact(v: &IrValue) -> Vec<u8> {
+    let mut out = Vec::with_capacity(encoded_len(v));
+    match ser::encode(&mut out, v) {
+        Ok(()) => out,
+        Err(never) => match never {},
+    }
+}
+
+/// Encode IR into `buf` without allocating, returning the written prefix.
+///
+/// Fails with [`EncodeError::BufferTooSmall`] if `buf` cannot hold the value; the
+/// contents of `buf` are unspecified in that case.
+pub fn to_compact_slice<'a>(v: &IrValue, buf: &'a mut [u8]) -> Result<&'a mut [u8], EncodeError> {
+    let mut sink = ser::SliceSink { buf, len: 0 };
+    match ser::encode(&mut sink, v) {
+        Ok(()) => {
+            let ser::SliceSink { buf, len } = sink;
+            Ok(&mut buf[..len])
+        }
+        Err(ser::Full) => Err(EncodeError::BufferTooSmall {
+            needed: encoded_len(v),
+            capacity: sink.buf.len(),
+        }),
+    }
+}
+
+/// Convert compact bytes -> IR. The input must hold exactly one value.
+pub fn from_compact(bytes: &[u8]) -> Result<IrValue, DecodeError> {
+    let (value, rest) = take_from_compact(bytes)?;
+    if !rest.is_empty() {
+        return Err(DecodeError::TrailingData {
+            offset: bytes.len() - rest.len(),
+        });
+    }
+    Ok(value)
+}
+
+/// Decode one value from the front of `bytes`, returning it with the remaining input.
+///
+/// Useful for logs of back-to-back values, e.g. in flash storage.
+pub fn take_from_compact(bytes: &[u8]) -> Result<(IrValue, &[u8]), DecodeError> {
+    let mut decoder = de::Decoder::new(bytes);
+    let value = decoder.value()?;
+    Ok((value, &bytes[decoder.position()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::{IrBundle, IrTimestamp, IrTimetag};
+
+    fn sample_values() -> Vec<IrValue> {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        nested.add_message(IrValue::message("/n", vec![IrValue::from(1)]));
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(IrValue::message("/a", vec![IrValue::Float32(0.5), IrValue::from("x")]));
+        bundle.add_bundle(nested);
+
+        vec![
+            IrValue::Null,
+            IrValue::Bool(false),
+            IrValue::Bool(true),
+            IrValue::Integer(i64::MIN),
+            IrValue::Integer(i64::MAX),
+            IrValue::Integer(-1),
+            IrValue::Unsigned(u64::MAX),
+            IrValue::Unsigned(7),
+            IrValue::Float(-0.1),
+            IrValue::Float32(0.1),
+            IrValue::from("héllo"),
+            IrValue::Binary(vec![0, 255]),
+            IrValue::Array(vec![]),
+            IrValue::Map(vec![("b".into(), IrValue::from(1)), ("b".into(), IrValue::Null)]),
+            IrValue::Timestamp(IrTimestamp { seconds: -5, nanos: 999_999_999 }),
+            IrValue::Ext { type_id: -128, data: vec![1, 2, 3] },
+            IrValue::Bundle(bundle),
+        ]
+    }
+
+    #[test]
+    fn roundtrip_all_value_kinds() {
+        for value in sample_values() {
+            let bytes = to_compact(&value);
+            assert_eq!(bytes.len(), encoded_len(&value));
+            assert_eq!(from_compact(&bytes).unwrap(), value, "bytes: {:02x?}", bytes);
+        }
+    }
+
+    #[test]
+    fn wire_format_is_compact() {
+        let cases: [(IrValue, &[u8]); 7] = [
+            (IrValue::Integer(-1), &[0x03, 0x01]),
+            (IrValue::Integer(300), &[0x03, 0xD8, 0x04]),
+            (IrValue::Unsigned(u64::MAX), &[0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]),
+            (IrValue::Float32(1.0), &[0x06, 0x00, 0x00, 0x80, 0x3F]),
+            (IrValue::from("hi"), &[0x07, 0x02, b'h', b'i']),
+            (IrValue::Map(vec![("a".into(), IrValue::Null)]), &[0x0A, 0x01, 0x01, b'a', 0x00]),
+            (IrValue::Ext { type_id: -1, data: vec![9] }, &[0x0C, 0xFF, 0x01, 0x09]),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(to_compact(&value), expected, "{:?}", value);
+        }
+
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(1));
+        bundle.add_message(IrValue::Bool(true));
+        bundle.add_bundle(IrBundle::immediate());
+        assert_eq!(
+            to_compact(&IrValue::Bundle(bundle)),
+            [0x0D, 0x01, 0x02, 0x00, 0x02, 0x01, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn encodes_into_fixed_buffers() {
+        let value = IrValue::message("/sensor/temp", vec![IrValue::Float32(21.5), IrValue::from(3)]);
+        let needed = encoded_len(&value);
+
+        let mut buf = [0xAAu8; 64];
+        let written = to_compact_slice(&value, &mut buf).unwrap();
+        assert_eq!(written.len(), needed);
+        assert_eq!(from_compact(written).unwrap(), value);
+        assert_eq!(buf[needed], 0xAA);
+
+        let mut exact = vec![0u8; needed];
+        assert!(to_compact_slice(&value, &mut exact).is_ok());
+        let mut short = vec![0u8; needed - 1];
+        assert_eq!(
+            to_compact_slice(&value, &mut short).unwrap_err(),
+            EncodeError::BufferTooSmall { needed, capacity: needed - 1 }
+        );
+        assert!(to_compact_slice(&IrValue::Null, &mut []).is_err());
+    }
+
+    #[test]
+    fn takes_values_from_a_stream() {
+        let mut log = Vec::new();
+        for value in sample_values() {
+            log.extend(to_compact(&value));
+        }
+        let mut rest = log.as_slice();
+        let mut decoded = Vec::new();
+        while !rest.is_empty() {
+            let (value, tail) = take_from_compact(rest).unwrap();
+            decoded.push(value);
+            rest = tail;
+        }
+        assert_eq!(decoded, sample_values());
+    }
+
+    #[test]
+    fn reports_errors_with_offsets() {
+        let err = |input: &[u8]| from_compact(input).unwrap_err();
+        assert_eq!(err(&[]), DecodeError::UnexpectedEof { offset: 0 });
+        assert_eq!(err(&[0x09, 0x02, 0x00]), DecodeError::UnexpectedEof { offset: 3 });
+        assert_eq!(err(&[0x00, 0x00]), DecodeError::TrailingData { offset: 1 });
+        assert_eq!(err(&[0x10]), DecodeError::Invalid { offset: 0, reason: "unknown type tag" });
+        assert_eq!(err(&[0x09, 0x01, 0x07, 0x01, 0xFF]), DecodeError::Invalid { offset: 3, reason: "invalid UTF-8 in string" });
+        assert_eq!(err(&[0x0B, 0x00, 0x80, 0x80, 0x80, 0x80, 0x10]), DecodeError::Invalid { offset: 2, reason: "nanoseconds out of range" });
+        assert_eq!(err(&[0x0D, 0x01, 0x01, 0x05]), DecodeError::Invalid { offset: 3, reason: "invalid bundle element" });
+        assert_eq!(err(&[0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]).to_string(), "varint overflow at byte 1");
+
+        // Declared lengths larger than the input fail before allocating.
+        assert_eq!(err(&[0x08, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F]), DecodeError::UnexpectedEof { offset: 6 });
+        assert_eq!(err(&[0x09, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F]), DecodeError::UnexpectedEof { offset: 6 });
+
+        let mut deep = [0x09, 0x01].repeat(MAX_DEPTH + 1);
+        deep.push(0x00);
+        assert_eq!(err(&deep), DecodeError::DepthLimitExceeded { offset: 2 * MAX_DEPTH });
+        assert!(from_compact(&deep[2..]).is_ok());
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn roundtrip_color_and_midi() {
+        let value = IrValue::message("/c", vec![IrValue::color(255, 0, 16, 128), IrValue::midi(1, 0x90, 60, 127)]);
+        assert_eq!(from_compact(&to_compact(&value)).unwrap(), value);
+        assert_eq!(to_compact(&IrValue::color(1, 2, 3, 4)), [0x0E, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn codec_trait() {
+        let codec = CompactCodec;
+        assert_eq!(codec.name(), "compact");
+
+        let value = IrValue::Array(vec![IrValue::from("a"), IrValue::Binary(vec![1, 2])]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+
+        let err = codec.decode(&bytes[..3]).unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Decode);
+        assert_eq!(err.message(), "unexpected end of input at byte 3");
+    }
+}
@@ -0,0 +1,203 @@
+//! Encoding of `IrValue` into the compact format.
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement};
+use osc_ir::IrValue;
+
+use crate::tag;
+
+/// Error produced while encoding into a fixed-capacity buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The buffer cannot hold the encoded value
+    BufferTooSmall {
+        /// Bytes the encoded value needs
+        needed: usize,
+        /// Length of the buffer that was passed in
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall { needed, capacity } => write!(
+                f,
+                "buffer too small: need {} bytes, have {}",
+                needed, capacity
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+/// Destination of encoded bytes.
+pub(crate) trait Sink {
+    type Error;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl Sink for Vec<u8> {
+    type Error = Infallible;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Infallible> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Marker error: the slice is full.
+pub(crate) struct Full;
+
+/// Writes into a caller-provided slice without allocating.
+pub(crate) struct SliceSink<'a> {
+    pub(crate) buf: &'a mut [u8],
+    pub(crate) len: usize,
+}
+
+impl Sink for SliceSink<'_> {
+    type Error = Full;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Full> {
+        let end = self.len + bytes.len();
+        let dest = self.buf.get_mut(self.len..end).ok_or(Full)?;
+        dest.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Counts bytes instead of writing them.
+pub(crate) struct Counter(pub(crate) usize);
+
+impl Sink for Counter {
+    type Error = Infallible;
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Infallible> {
+        self.0 += bytes.len();
+        Ok(())
+    }
+}
+
+/// Writes `value` as an unsigned LEB128 varint.
+fn varint<S: Sink>(out: &mut S, mut value: u64) -> Result<(), S::Error> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    out.put(&buf[..len])
+}
+
+/// Writes a signed integer as a zigzag varint, so small magnitudes stay short.
+fn zigzag<S: Sink>(out: &mut S, value: i64) -> Result<(), S::Error> {
+    varint(out, ((value << 1) ^ (value >> 63)) as u64)
+}
+
+fn bytes<S: Sink>(out: &mut S, data: &[u8]) -> Result<(), S::Error> {
+    varint(out, data.len() as u64)?;
+    out.put(data)
+}
+
+/// Appends the compact encoding of `v` to `out`.
+pub(crate) fn encode<S: Sink>(out: &mut S, v: &IrValue) -> Result<(), S::Error> {
+    match v {
+        IrValue::Null => out.put(&[tag::NULL]),
+        IrValue::Bool(false) => out.put(&[tag::FALSE]),
+        IrValue::Bool(true) => out.put(&[tag::TRUE]),
+        IrValue::Integer(i) => {
+            out.put(&[tag::INTEGER])?;
+            zigzag(out, *i)
+        }
+        IrValue::Unsigned(u) => {
+            out.put(&[tag::UNSIGNED])?;
+            varint(out, *u)
+        }
+        IrValue::Float(x) => {
+            out.put(&[tag::FLOAT])?;
+            out.put(&x.to_le_bytes())
+        }
+        IrValue::Float32(x) => {
+            out.put(&[tag::FLOAT32])?;
+            out.put(&x.to_le_bytes())
+        }
+        IrValue::String(s) => {
+            out.put(&[tag::STRING])?;
+            bytes(out, s.as_bytes())
+        }
+        IrValue::Binary(data) => {
+            out.put(&[tag::BINARY])?;
+            bytes(out, data)
+        }
+        IrValue::Array(xs) => {
+            out.put(&[tag::ARRAY])?;
+            varint(out, xs.len() as u64)?;
+            xs.iter().try_for_each(|x| encode(out, x))
+        }
+        IrValue::Map(entries) => {
+            out.put(&[tag::MAP])?;
+            varint(out, entries.len() as u64)?;
+            entries.iter().try_for_each(|(key, value)| {
+                bytes(out, key.as_bytes())?;
+                encode(out, value)
+            })
+        }
+        IrValue::Timestamp(ts) => {
+            out.put(&[tag::TIMESTAMP])?;
+            zigzag(out, ts.seconds)?;
+            varint(out, u64::from(ts.nanos))
+        }
+        IrValue::Ext { type_id, data } => {
+            out.put(&[tag::EXT, *type_id as u8])?;
+            bytes(out, data)
+        }
+        #[cfg(feature = "osc10")]
+        IrValue::Bundle(b) => {
+            out.put(&[tag::BUNDLE])?;
+            bundle(out, b)
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => out.put(&[tag::COLOR, *r, *g, *b, *a]),
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => out.put(&[tag::MIDI, *port, *status, *data1, *data2]),
+        // Variants osc-ir enables through features this crate does not
+        #[allow(unreachable_patterns)]
+        _ => out.put(&[tag::NULL]),
+    }
+}
+
+#[cfg(feature = "osc10")]
+fn bundle<S: Sink>(out: &mut S, bundle: &IrBundle) -> Result<(), S::Error> {
+    varint(out, bundle.timetag.value)?;
+    varint(out, bundle.elements.len() as u64)?;
+    bundle.elements.iter().try_for_each(|element| match element {
+        IrBundleElement::Message(msg) => {
+            out.put(&[tag::ELEMENT_MESSAGE])?;
+            encode(out, msg)
+        }
+        IrBundleElement::Bundle(nested) => {
+            out.put(&[tag::ELEMENT_BUNDLE])?;
+            self::bundle(out, nested)
+        }
+    })
+}
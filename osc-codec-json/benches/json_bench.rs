@@ -0,0 +1,54 @@
+//! Compares the `serde_json::Value` path (`to_json`/`from_json`) with direct
+//! serialization (`to_json_writer`/`from_json_slice`) on bundles carrying large blobs.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use osc_codec_json::{from_json, from_json_slice, to_json, to_json_writer};
+use osc_ir::{IrBundle, IrTimetag, IrValue};
+
+fn blob_bundle(blob_size: usize, count: usize) -> IrValue {
+    let mut bundle = IrBundle::new(IrTimetag::from_ntp(0xE000_0000_0000_0000));
+    for i in 0..count {
+        bundle.add_message(IrValue::Map(vec![
+            ("address".into(), IrValue::from(format!("/blob/{}", i))),
+            ("args".into(), IrValue::Array(vec![IrValue::Binary(vec![i as u8; blob_size])])),
+        ]));
+    }
+    IrValue::Bundle(bundle)
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+    for blob_size in [1 << 10, 64 << 10, 1 << 20] {
+        let value = blob_bundle(blob_size, 8);
+        group.throughput(Throughput::Bytes((blob_size * 8) as u64));
+        group.bench_with_input(BenchmarkId::new("value", blob_size), &value, |b, v| {
+            b.iter(|| serde_json::to_vec(&to_json(black_box(v))).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("direct", blob_size), &value, |b, v| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                to_json_writer(&mut out, black_box(v)).unwrap();
+                out
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    for blob_size in [1 << 10, 64 << 10, 1 << 20] {
+        let bytes = serde_json::to_vec(&to_json(&blob_bundle(blob_size, 8))).unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::new("value", blob_size), &bytes, |b, bytes| {
+            b.iter(|| from_json(&serde_json::from_slice(black_box(bytes)).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("direct", blob_size), &bytes, |b, bytes| {
+            b.iter(|| from_json_slice(black_box(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,298 @@
+//! Direct serde deserialization of the `$type` JSON representation into `IrValue`,
+//! without building an intermediate `serde_json::Value`.
+//!
+//! Decoding follows the same best-effort rules as `from_json`.
+
+use core::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use osc_ir::{IrBundle, IrBundleElement, IrTimestamp, IrTimetag, IrValue};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+/// A decoded JSON value.
+///
+/// Objects are kept as raw entries until their parent consumes them, so that a
+/// `map` wrapper can take its contents without `$type` markers being interpreted.
+pub(crate) struct Node {
+    value: IrValue,
+    /// Set when `value` holds a base64 `data` field decoded eagerly in `visit_map`.
+    fast_binary: bool,
+    /// Sorted, deduplicated entries of an object not yet interpreted.
+    object: Option<Vec<(String, Node)>>,
+}
+
+impl Node {
+    fn new(value: IrValue) -> Self {
+        Node {
+            value,
+            fast_binary: false,
+            object: None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        self.value.as_integer()
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        self.value.as_unsigned()
+    }
+
+    /// Returns the `$type` marker of an object node.
+    pub(crate) fn type_tag(&self) -> Option<&str> {
+        let entries = self.object.as_deref()?;
+        find(entries, "$type").and_then(|i| entries[i].1.value.as_str())
+    }
+
+    /// Converts the node into IR, interpreting `$type` markers of objects.
+    pub(crate) fn into_value(self) -> IrValue {
+        match self.object {
+            Some(entries) => finish_object(entries),
+            None => self.value,
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::new(IrValue::Null))
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Node, E> {
+        Ok(Node::new(IrValue::Bool(v)))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Node, E> {
+        Ok(Node::new(IrValue::Integer(v)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Node, E> {
+        Ok(Node::new(IrValue::from(v)))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Node, E> {
+        Ok(Node::new(IrValue::Float(v)))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Node, E> {
+        Ok(Node::new(IrValue::from(v)))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Node, E> {
+        Ok(Node::new(IrValue::from(v)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(node) = seq.next_element::<Node>()? {
+            values.push(node.into_value());
+        }
+        Ok(Node::new(IrValue::Array(values)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries: Vec<(String, Node)> = Vec::with_capacity(map.size_hint().unwrap_or(4));
+        let mut binary_tag = false;
+        while let Some(key) = map.next_key::<String>()? {
+            let node = if binary_tag && key == "data" {
+                // `$type` is written first, so binary payloads can usually be
+                // decoded straight from the input without an intermediate string.
+                map.next_value_seed(DataSeed)?
+            } else {
+                map.next_value::<Node>()?
+            };
+            if key == "$type" {
+                binary_tag = matches!(node.value.as_str(), Some("binary" | "ext"));
+            }
+            entries.push((key, node));
+        }
+        // Apply `serde_json::Map` semantics: sorted keys, last duplicate wins. The sort
+        // is stable, so moving each later duplicate into the retained earlier slot
+        // keeps the last value.
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|later, kept| {
+            let duplicate = later.0 == kept.0;
+            if duplicate {
+                core::mem::swap(&mut later.1, &mut kept.1);
+            }
+            duplicate
+        });
+        Ok(Node {
+            object: Some(entries),
+            ..Node::new(IrValue::Null)
+        })
+    }
+}
+
+/// Decodes a `data` field, turning valid base64 strings straight into bytes.
+///
+/// Anything else decodes like a regular [`Node`], so the field can still be read
+/// as a string or object if `$type` turns out to be something else.
+struct DataSeed;
+
+impl<'de> DeserializeSeed<'de> for DataSeed {
+    type Value = Node;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DataSeed {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a base64 string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Node, E> {
+        Ok(match STANDARD.decode(v) {
+            Ok(bytes) => Node {
+                fast_binary: true,
+                ..Node::new(IrValue::Binary(bytes))
+            },
+            Err(_) => Node::new(IrValue::from(v)),
+        })
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Node, E> {
+        NodeVisitor.visit_unit()
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Node, E> {
+        NodeVisitor.visit_bool(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Node, E> {
+        NodeVisitor.visit_i64(v)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Node, E> {
+        NodeVisitor.visit_u64(v)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Node, E> {
+        NodeVisitor.visit_f64(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Node, A::Error> {
+        NodeVisitor.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Node, A::Error> {
+        NodeVisitor.visit_map(map)
+    }
+}
+
+fn base64_field(node: Option<Node>) -> Vec<u8> {
+    match node.map(|n| n.value) {
+        Some(IrValue::Binary(bytes)) => bytes,
+        Some(IrValue::String(s)) => STANDARD.decode(s.as_bytes()).unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn find(entries: &[(String, Node)], name: &str) -> Option<usize> {
+    entries.binary_search_by(|(k, _)| k.as_str().cmp(name)).ok()
+}
+
+/// Interprets `$type` markers of an object's sorted, deduplicated entries the same
+/// way `from_json` does.
+fn finish_object(mut entries: Vec<(String, Node)>) -> IrValue {
+    let tag = find(&entries, "$type")
+        .and_then(|i| entries[i].1.value.as_str())
+        .map(String::from);
+    let mut take = |name: &str| {
+        find(&entries, name).map(|i| core::mem::replace(&mut entries[i].1, Node::new(IrValue::Null)))
+    };
+
+    match tag.as_deref() {
+        Some("timestamp") => {
+            let seconds = take("seconds").and_then(|n| n.as_i64()).unwrap_or(0);
+            let nanos = take("nanos").and_then(|n| n.as_u64()).unwrap_or(0) as u32;
+            IrValue::Timestamp(IrTimestamp { seconds, nanos })
+        }
+        Some("binary") => IrValue::Binary(base64_field(take("data"))),
+        Some(tag @ ("float" | "float32")) => {
+            let value = take("value").map(Node::into_value);
+            crate::float_from_marker(tag == "float32", value.as_ref())
+        }
+        Some("ext") => {
+            let type_id = take("ext").and_then(|n| n.as_i64()).unwrap_or(0) as i8;
+            IrValue::Ext {
+                type_id,
+                data: base64_field(take("data")),
+            }
+        }
+        Some("bundle") => {
+            let timetag = IrTimetag {
+                value: take("timetag").and_then(|n| n.as_u64()).unwrap_or(1),
+            };
+            let elements = match take("elements").map(Node::into_value) {
+                Some(IrValue::Array(items)) => items.into_iter().map(bundle_element).collect(),
+                _ => Vec::new(),
+            };
+            IrValue::Bundle(IrBundle { timetag, elements })
+        }
+        Some("map") => match take("data").and_then(|n| n.object) {
+            Some(inner) => IrValue::Map(map_entries(inner)),
+            None => IrValue::Map(Vec::new()),
+        },
+        _ => IrValue::Map(map_entries(entries)),
+    }
+}
+
+/// Converts object entries into plain map entries.
+fn map_entries(entries: Vec<(String, Node)>) -> Vec<(String, IrValue)> {
+    entries
+        .into_iter()
+        .map(|(key, node)| match node.value {
+            // Undo the fast path if `$type` turned out not to be binary or ext.
+            IrValue::Binary(ref bytes) if node.fast_binary => {
+                (key, IrValue::from(STANDARD.encode(bytes)))
+            }
+            _ => (key, node.into_value()),
+        })
+        .collect()
+}
+
+/// Mirrors `bundle_element_from_json` on an already-decoded element object.
+fn bundle_element(value: IrValue) -> IrBundleElement {
+    let mut entries = match value {
+        IrValue::Map(entries) => entries,
+        other => return IrBundleElement::Message(other),
+    };
+    let is_kind = |kind: &str| {
+        entries
+            .iter()
+            .any(|(k, v)| k == "type" && v.as_str() == Some(kind))
+    };
+    let (is_message, is_bundle) = (is_kind("message"), is_kind("bundle"));
+    if let Some(i) = entries.iter().position(|(k, _)| k == "data") {
+        if is_message {
+            return IrBundleElement::Message(entries.swap_remove(i).1);
+        }
+        if is_bundle {
+            match core::mem::take(&mut entries[i].1) {
+                IrValue::Bundle(bundle) => return IrBundleElement::Bundle(bundle),
+                other => entries[i].1 = other,
+            }
+        }
+    }
+    IrBundleElement::Message(IrValue::Map(entries))
+}
@@ -12,6 +12,15 @@
 //! - **Bundle Support**: Full support for OSC bundles with nested structures
 //! - **Type Preservation**: Special handling for binary data, timestamps, and extended types
 //! - **OSC Compatibility**: Support for OSC 1.0 and 1.1 features via feature flags
+//! - **Codec Trait**: [`JsonCodec`] implements `osc_ir::Codec` for format-agnostic tooling
+//! - **Streaming**: [`to_json_writer`] / [`from_json_reader`] and friends skip the intermediate
+//!   `serde_json::Value`
+//! - **Representation Styles**: [`JsonOptions`] selects the tagged, plain or OSCQuery-compatible
+//!   form via [`to_json_with`] / [`from_json_with`], with configurable marker key names
+//! - **Non-finite Floats**: NaN and ±infinity are written as `{"$type": "float", "value": "NaN"}`
+//!   markers by default; [`NonFiniteFloats`] can turn them into `null` or an error instead
+//! - **NDJSON Logs**: [`ndjson`] reads and writes one value per line, with optional
+//!   receive time and source metadata
 //!
 //! ## Usage
 //!
@@ -38,72 +47,261 @@
 //! # }
 //! ```
 
-use osc_ir::{IrValue, IrTimestamp, IrBundle, IrBundleElement, IrTimetag};
+use osc_ir::{Codec, CodecError, CodecErrorKind, IrValue, IrTimestamp, IrBundle, IrBundleElement, IrTimetag};
 use serde_json::Value as J;
 use base64::Engine;
 
+mod de;
+pub mod ndjson;
+mod options;
+mod ser;
+
+pub use options::{JsonOptions, JsonStyle, NonFiniteFloats};
+
+/// [`Codec`] implementation producing UTF-8 JSON bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(&ser::JsonRepr(value)).map_err(|e| CodecError::encode(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        from_json_slice(bytes).map_err(|e| CodecError::decode(e.to_string()))
+    }
+
+    fn encode_to_writer(&self, value: &IrValue, writer: &mut dyn std::io::Write) -> Result<(), CodecError> {
+        to_json_writer(writer, value).map_err(|e| {
+            if e.is_io() {
+                CodecError::new(CodecErrorKind::Io, e.to_string())
+            } else {
+                CodecError::encode(e.to_string())
+            }
+        })
+    }
+}
+
+/// Serialize IR as JSON text directly into a writer, without building a `serde_json::Value`.
+///
+/// The output is identical to `to_json(v).to_string()`.
+pub fn to_json_writer<W: std::io::Write>(writer: W, v: &IrValue) -> serde_json::Result<()> {
+    serde_json::to_writer(writer, &ser::JsonRepr(v))
+}
+
+/// Serialize IR as a JSON string, without building a `serde_json::Value`.
+pub fn to_json_string(v: &IrValue) -> serde_json::Result<String> {
+    serde_json::to_string(&ser::JsonRepr(v))
+}
+
+/// Parse JSON bytes directly into IR, without building a `serde_json::Value`.
+///
+/// Decodes the same way as `from_json` applied to the parsed document.
+pub fn from_json_slice(bytes: &[u8]) -> serde_json::Result<IrValue> {
+    serde_json::from_slice::<de::Node>(bytes).map(de::Node::into_value)
+}
+
+/// Parse JSON from a reader directly into IR, without building a `serde_json::Value`.
+pub fn from_json_reader<R: std::io::Read>(reader: R) -> serde_json::Result<IrValue> {
+    serde_json::from_reader::<_, de::Node>(reader).map(de::Node::into_value)
+}
+
+/// Converts a single-precision float into a JSON number using its shortest round-trip
+/// decimal form (e.g. `0.1` rather than `0.10000000149011612`).
+pub(crate) fn f32_to_json(x: f32) -> J {
+    x.to_string().parse::<f64>().map(J::from).unwrap_or(J::Null)
+}
+
+/// Returns the JavaScript spelling of a non-finite float: `NaN`, `Infinity` or `-Infinity`.
+pub(crate) fn non_finite_name(x: f64) -> &'static str {
+    if x.is_nan() {
+        "NaN"
+    } else if x > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    }
+}
+
+/// Parses the names produced by [`non_finite_name`].
+pub(crate) fn parse_non_finite(s: &str) -> Option<f64> {
+    match s {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+/// Decodes a `float` / `float32` marker from its already-decoded `value` field.
+///
+/// The value may be a non-finite name or a plain number; anything else gives `Null`.
+pub(crate) fn float_from_marker(single: bool, value: Option<&IrValue>) -> IrValue {
+    let x = match value {
+        Some(IrValue::String(s)) => parse_non_finite(s),
+        Some(IrValue::Integer(i)) => Some(*i as f64),
+        Some(IrValue::Unsigned(u)) => Some(*u as f64),
+        Some(v) => v.as_float(),
+        None => None,
+    };
+    match x {
+        Some(x) if single => IrValue::Float32(x as f32),
+        Some(x) => IrValue::Float(x),
+        None => IrValue::Null,
+    }
+}
+
+/// Returns the first NaN or infinite float found in `v`, if any.
+fn find_non_finite(v: &IrValue) -> Option<f64> {
+    match v {
+        IrValue::Float(x) if !x.is_finite() => Some(*x),
+        IrValue::Float32(x) if !x.is_finite() => Some((*x).into()),
+        IrValue::Array(xs) => xs.iter().find_map(find_non_finite),
+        IrValue::Map(entries) => entries.iter().find_map(|(_, v)| find_non_finite(v)),
+        IrValue::Bundle(bundle) => bundle_non_finite(bundle),
+        _ => None,
+    }
+}
+
+/// Returns the first NaN or infinite float found in the messages of `bundle`.
+fn bundle_non_finite(bundle: &IrBundle) -> Option<f64> {
+    bundle.elements.iter().find_map(|element| match element {
+        IrBundleElement::Message(msg) => find_non_finite(msg),
+        IrBundleElement::Bundle(b) => bundle_non_finite(b),
+    })
+}
+
+/// `$type` values with a special meaning. User maps carrying one of them under the
+/// type key are wrapped as `{"$type": "map", "data": {...}}` so they decode unchanged.
+const RESERVED_TYPES: [&str; 8] = [
+    "binary", "bundle", "ext", "float", "float32", "map", "record", "timestamp",
+];
+
+/// Returns true if a user map would be mistaken for a marker object.
+pub(crate) fn collides_with_marker(entries: &[(String, IrValue)], type_key: &str) -> bool {
+    entries.iter().any(|(k, v)| {
+        k == type_key && v.as_str().is_some_and(|tag| RESERVED_TYPES.contains(&tag))
+    })
+}
+
 /// Convert IrBundleElement -> serde_json::Value.
-fn bundle_element_to_json(element: &IrBundleElement) -> J {
+fn bundle_element_to_json(element: &IrBundleElement, opts: &JsonOptions) -> J {
     match element {
         IrBundleElement::Message(msg) => J::Object([
-            ("type".to_string(), J::from("message")),
-            ("data".to_string(), to_json(msg)),
+            (opts.element_type_key.to_string(), J::from("message")),
+            (opts.data_key.to_string(), tagged_to_json(msg, opts)),
         ].into_iter().collect()),
         IrBundleElement::Bundle(bundle) => J::Object([
-            ("type".to_string(), J::from("bundle")),
-            ("data".to_string(), to_json(&IrValue::Bundle(bundle.clone()))),
+            (opts.element_type_key.to_string(), J::from("bundle")),
+            (opts.data_key.to_string(), tagged_to_json(&IrValue::Bundle(bundle.clone()), opts)),
         ].into_iter().collect()),
     }
 }
 
 /// Convert IR -> serde_json::Value.
 pub fn to_json(v: &IrValue) -> J {
+    tagged_to_json(v, &JsonOptions::TAGGED)
+}
+
+/// Convert IR -> serde_json::Value using the given representation style.
+///
+/// Fails only when `v` contains NaN or an infinity and `opts.non_finite` is
+/// [`NonFiniteFloats::Error`].
+pub fn to_json_with(v: &IrValue, opts: &JsonOptions) -> serde_json::Result<J> {
+    if opts.non_finite == NonFiniteFloats::Error {
+        if let Some(x) = find_non_finite(v) {
+            return Err(serde::ser::Error::custom(format_args!(
+                "non-finite float {} cannot be represented in JSON",
+                non_finite_name(x)
+            )));
+        }
+    }
+    Ok(match opts.style {
+        JsonStyle::Tagged => tagged_to_json(v, opts),
+        JsonStyle::Plain => options::plain_to_json(v, opts.non_finite),
+        JsonStyle::OscQuery => options::oscquery_to_json(v, opts.non_finite),
+    })
+}
+
+/// Convert IR -> tagged JSON, using the marker key names from `opts`.
+fn tagged_to_json(v: &IrValue, opts: &JsonOptions) -> J {
+    let type_key = || opts.type_key.to_string();
+    let data_key = || opts.data_key.to_string();
     match v {
         IrValue::Null => J::Null,
         IrValue::Bool(b) => J::Bool(*b),
         IrValue::Integer(i) => J::from(*i),
+        IrValue::Unsigned(u) => J::from(*u),
+        IrValue::Float(x) if !x.is_finite() && opts.non_finite == NonFiniteFloats::Encode => J::Object([
+            (type_key(), J::from("float")),
+            ("value".to_string(), J::from(non_finite_name(*x))),
+        ].into_iter().collect()),
+        IrValue::Float32(x) if !x.is_finite() && opts.non_finite == NonFiniteFloats::Encode => J::Object([
+            (type_key(), J::from("float32")),
+            ("value".to_string(), J::from(non_finite_name((*x).into()))),
+        ].into_iter().collect()),
         IrValue::Float(x) => J::from(*x),
+        IrValue::Float32(x) => f32_to_json(*x),
         IrValue::String(s) => J::from(s.as_ref()),
         IrValue::Binary(bytes) => J::Object([
-            ("$type".to_string(), J::from("binary")),
-            ("data".to_string(), J::from(base64::engine::general_purpose::STANDARD.encode(bytes))),
+            (type_key(), J::from("binary")),
+            (data_key(), J::from(base64::engine::general_purpose::STANDARD.encode(bytes))),
         ].into_iter().collect()),
-        IrValue::Array(xs) => J::Array(xs.iter().map(to_json).collect()),
-        IrValue::Map(entries) => J::Object(entries.iter().map(|(k, v)| (k.clone(), to_json(v))).collect()),
+        IrValue::Array(xs) => J::Array(xs.iter().map(|x| tagged_to_json(x, opts)).collect()),
+        IrValue::Map(entries) => {
+            let object = J::Object(entries.iter().map(|(k, v)| (k.clone(), tagged_to_json(v, opts))).collect());
+            if collides_with_marker(entries, &opts.type_key) {
+                J::Object([
+                    (type_key(), J::from("map")),
+                    (data_key(), object),
+                ].into_iter().collect())
+            } else {
+                object
+            }
+        }
         IrValue::Timestamp(IrTimestamp{seconds, nanos}) => J::Object([
-            ("$type".to_string(), J::from("timestamp")),
+            (type_key(), J::from("timestamp")),
             ("seconds".to_string(), J::from(*seconds)),
             ("nanos".to_string(), J::from(*nanos as u64)),
         ].into_iter().collect()),
         IrValue::Ext{ type_id, data } => J::Object([
-            ("$type".to_string(), J::from("ext")),
+            (type_key(), J::from("ext")),
             ("ext".to_string(), J::from(*type_id as i64)),
-            ("data".to_string(), J::from(base64::engine::general_purpose::STANDARD.encode(data))),
+            (data_key(), J::from(base64::engine::general_purpose::STANDARD.encode(data))),
         ].into_iter().collect()),
         IrValue::Bundle(bundle) => J::Object([
-            ("$type".to_string(), J::from("bundle")),
+            (type_key(), J::from("bundle")),
             ("timetag".to_string(), J::from(bundle.timetag.value)),
-            ("elements".to_string(), J::Array(bundle.elements.iter().map(bundle_element_to_json).collect())),
+            ("elements".to_string(), J::Array(bundle.elements.iter().map(|e| bundle_element_to_json(e, opts)).collect())),
         ].into_iter().collect()),
         // OSC 1.1 Color and MIDI types - currently serialized as null
         // This handles any additional variants when osc11 is enabled
+        #[allow(unreachable_patterns)]
         _ => J::Null,
     }
 }
 
 /// Convert serde_json::Value -> IrBundleElement.
-fn bundle_element_from_json(j: &J) -> IrBundleElement {
+fn bundle_element_from_json(j: &J, opts: &JsonOptions) -> IrBundleElement {
     if let J::Object(map) = j {
-        if let Some(J::String(element_type)) = map.get("type") {
+        if let Some(J::String(element_type)) = map.get(opts.element_type_key.as_ref()) {
             match element_type.as_str() {
                 "message" => {
-                    if let Some(data) = map.get("data") {
-                        return IrBundleElement::Message(from_json(data));
+                    if let Some(data) = map.get(opts.data_key.as_ref()) {
+                        return IrBundleElement::Message(tagged_from_json(data, opts));
                     }
                 }
                 "bundle" => {
-                    if let Some(data) = map.get("data") {
-                        if let IrValue::Bundle(bundle) = from_json(data) {
+                    if let Some(data) = map.get(opts.data_key.as_ref()) {
+                        if let IrValue::Bundle(bundle) = tagged_from_json(data, opts) {
                             return IrBundleElement::Bundle(bundle);
                         }
                     }
@@ -113,50 +311,70 @@ fn bundle_element_from_json(j: &J) -> IrBundleElement {
         }
     }
     // Fallback: treat as message
-    IrBundleElement::Message(from_json(j))
+    IrBundleElement::Message(tagged_from_json(j, opts))
 }
 
 /// Convert serde_json::Value -> IR (best-effort; special objects recognized by $type markers).
 pub fn from_json(j: &J) -> IrValue {
+    tagged_from_json(j, &JsonOptions::TAGGED)
+}
+
+/// Convert serde_json::Value -> IR, accepting the representation style produced by
+/// [`to_json_with`] with the same options.
+pub fn from_json_with(j: &J, opts: &JsonOptions) -> IrValue {
+    match opts.style {
+        JsonStyle::Tagged => tagged_from_json(j, opts),
+        JsonStyle::Plain => options::plain_from_json(j),
+        JsonStyle::OscQuery => options::oscquery_from_json(j),
+    }
+}
+
+/// Convert tagged JSON -> IR, recognizing markers under the key names from `opts`.
+fn tagged_from_json(j: &J, opts: &JsonOptions) -> IrValue {
     match j {
         J::Null => IrValue::Null,
         J::Bool(b) => IrValue::Bool(*b),
         J::Number(n) => n.as_i64().map(IrValue::Integer)
+            .or_else(|| n.as_u64().map(IrValue::Unsigned))
             .or_else(|| n.as_f64().map(IrValue::Float))
             .unwrap_or(IrValue::Null),
         J::String(s) => IrValue::String(s.clone().into_boxed_str()),
-        J::Array(xs) => IrValue::Array(xs.iter().map(from_json).collect()),
+        J::Array(xs) => IrValue::Array(xs.iter().map(|x| tagged_from_json(x, opts)).collect()),
         J::Object(map) => {
-            if let Some(J::String(tag)) = map.get("$type") {
+            let data = || map.get(opts.data_key.as_ref()).and_then(|v| v.as_str()).map(|s|
+                base64::engine::general_purpose::STANDARD.decode(s).unwrap_or_default()).unwrap_or_default();
+            if let Some(J::String(tag)) = map.get(opts.type_key.as_ref()) {
                 match tag.as_str() {
                     "timestamp" => {
                         let sec = map.get("seconds").and_then(|v| v.as_i64()).unwrap_or(0);
                         let ns = map.get("nanos").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
                         IrValue::Timestamp(IrTimestamp{ seconds: sec, nanos: ns })
                     }
-                    "binary" => {
-                        let data = map.get("data").and_then(|v| v.as_str()).map(|s| 
-                            base64::engine::general_purpose::STANDARD.decode(s).unwrap_or_default()).unwrap_or_default();
-                        IrValue::Binary(data)
+                    "binary" => IrValue::Binary(data()),
+                    "float" | "float32" => {
+                        let value = map.get("value").map(|v| tagged_from_json(v, opts));
+                        float_from_marker(tag == "float32", value.as_ref())
                     }
                     "ext" => {
                         let ext = map.get("ext").and_then(|v| v.as_i64()).unwrap_or(0) as i8;
-                        let data = map.get("data").and_then(|v| v.as_str()).map(|s| 
-                            base64::engine::general_purpose::STANDARD.decode(s).unwrap_or_default()).unwrap_or_default();
-                        IrValue::Ext{ type_id: ext, data }
+                        IrValue::Ext{ type_id: ext, data: data() }
                     }
                     "bundle" => {
                         let timetag_value = map.get("timetag").and_then(|v| v.as_u64()).unwrap_or(1);
                         let timetag = IrTimetag { value: timetag_value };
                         let elements = map.get("elements").and_then(|v| v.as_array())
-                            .map(|arr| arr.iter().map(bundle_element_from_json).collect())
+                            .map(|arr| arr.iter().map(|e| bundle_element_from_json(e, opts)).collect())
                             .unwrap_or_default();
                         IrValue::Bundle(IrBundle { timetag, elements })
                     }
-                    _ => IrValue::Map(map.iter().map(|(k,v)| (k.clone(), from_json(v))).collect())
+                    "map" => match map.get(opts.data_key.as_ref()) {
+                        Some(J::Object(inner)) => IrValue::Map(inner.iter().map(|(k,v)| (k.clone(), tagged_from_json(v, opts))).collect()),
+                        _ => IrValue::Map(Vec::new()),
+                    },
+                    _ => IrValue::Map(map.iter().map(|(k,v)| (k.clone(), tagged_from_json(v, opts))).collect())
                 }
             } else {
-                IrValue::Map(map.iter().map(|(k,v)| (k.clone(), from_json(v))).collect())
+                IrValue::Map(map.iter().map(|(k,v)| (k.clone(), tagged_from_json(v, opts))).collect())
             }
         }
     }
@@ -239,4 +457,339 @@ mod tests {
         let decoded = from_json(&json);
         assert_eq!(value, decoded);
     }
+
+    fn sample_values() -> Vec<IrValue> {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX - 7));
+        nested.add_message(IrValue::Binary(vec![0xFF; 33]));
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(IrValue::from("msg"));
+        bundle.add_bundle(nested);
+
+        vec![
+            IrValue::Null,
+            IrValue::Bool(false),
+            IrValue::Integer(i64::MIN),
+            IrValue::Float(-0.5),
+            IrValue::Float(f64::NAN),
+            IrValue::Float32(0.1),
+            IrValue::Float32(f32::MIN_POSITIVE / 8.0),
+            IrValue::from("quote \" and \u{1F600}"),
+            IrValue::Binary(vec![]),
+            IrValue::Timestamp(IrTimestamp { seconds: -5, nanos: 999 }),
+            IrValue::Ext { type_id: -3, data: vec![1, 2, 3] },
+            IrValue::Map(vec![
+                ("z".into(), IrValue::Integer(1)),
+                (" first".into(), IrValue::Array(vec![IrValue::Null])),
+                ("z".into(), IrValue::Integer(2)),
+            ]),
+            IrValue::Bundle(bundle),
+        ]
+    }
+
+    #[test]
+    fn test_direct_serialization_matches_value_path() {
+        for value in sample_values() {
+            let expected = to_json(&value).to_string();
+            assert_eq!(to_json_string(&value).unwrap(), expected);
+
+            let mut out = Vec::new();
+            to_json_writer(&mut out, &value).unwrap();
+            assert_eq!(out, expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_direct_deserialization_matches_value_path() {
+        let mut inputs: Vec<String> = sample_values()
+            .iter()
+            .map(|v| to_json(v).to_string())
+            .collect();
+        inputs.extend(
+            [
+                r#"18446744073709551615"#,
+                r#"{"data":"AQI=","$type":"binary"}"#,
+                r#"{"$type":"binary","data":"not base64!"}"#,
+                r#"{"$type":"binary","data":[1,2]}"#,
+                r#"{"$type":"ext","ext":300,"data":"AA=="}"#,
+                r#"{"$type":"bundle","timetag":18446744073709551615,"elements":[{"type":"bundle","data":5},{"type":"message"},7]}"#,
+                r#"{"$type":"timestamp","seconds":1.5,"nanos":-1}"#,
+                r#"{"$type":"unknown","data":"AQI="}"#,
+                r#"{"$type":"binary","data":"AQI=","$type":"other"}"#,
+                r#"{"a":{"$type":"binary","data":"AQI="},"a":1,"b":{"$type":3}}"#,
+                r#"{"$type":"map","data":{"$type":"binary","data":"AQI="}}"#,
+                r#"{"data":{"$type":"ext","ext":1,"data":"AQI="},"$type":"map"}"#,
+                r#"{"$type":"map","data":[{"$type":"binary","data":"AQI="}]}"#,
+                r#"{"$type":"map"}"#,
+            ]
+            .map(String::from),
+        );
+
+        for input in inputs {
+            let expected = from_json(&serde_json::from_str::<J>(&input).unwrap());
+            let direct = from_json_slice(input.as_bytes()).unwrap();
+            let from_reader = from_json_reader(input.as_bytes()).unwrap();
+            // NaN never compares equal; compare the debug representation instead.
+            assert_eq!(format!("{:?}", direct), format!("{:?}", expected), "input: {}", input);
+            assert_eq!(format!("{:?}", from_reader), format!("{:?}", expected), "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_direct_roundtrip_and_errors() {
+        let value = sample_values().pop().unwrap();
+        let text = to_json_string(&value).unwrap();
+        assert_eq!(from_json_slice(text.as_bytes()).unwrap(), value);
+
+        assert!(from_json_slice(b"[1, 2").is_err());
+        assert!(from_json_reader(&b"{\"a\" 1}"[..]).is_err());
+    }
+
+    /// Maps whose entries would read as marker objects. Entries are listed in sorted
+    /// key order, since JSON objects do not preserve insertion order.
+    fn adversarial_maps() -> Vec<IrValue> {
+        let map = |entries: &[(&str, IrValue)]| {
+            IrValue::Map(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+        };
+        let mut maps: Vec<IrValue> = RESERVED_TYPES
+            .iter()
+            .map(|tag| map(&[("$type", IrValue::from(*tag))]))
+            .collect();
+        maps.extend([
+            map(&[("$type", IrValue::from("binary")), ("data", IrValue::from("AQI="))]),
+            map(&[("$type", IrValue::from("binary")), ("data", IrValue::from("not base64!"))]),
+            map(&[("$type", IrValue::from("ext")), ("data", IrValue::from("AA==")), ("ext", IrValue::from(1))]),
+            map(&[("$type", IrValue::from("timestamp")), ("nanos", IrValue::from(2)), ("seconds", IrValue::from(1))]),
+            map(&[("$type", IrValue::from("bundle")), ("elements", IrValue::Array(vec![])), ("timetag", IrValue::from(1))]),
+            map(&[("$type", IrValue::from("map")), ("data", map(&[("$type", IrValue::from("binary"))]))]),
+            map(&[("$type", IrValue::from("float")), ("value", IrValue::from("NaN"))]),
+            map(&[("$$type", IrValue::from("binary")), ("$type", IrValue::from("osc.message"))]),
+            map(&[("$type", IrValue::from(3)), ("data", IrValue::Binary(vec![1]))]),
+            IrValue::Array(vec![map(&[("$type", IrValue::from("binary")), ("data", IrValue::Binary(vec![7]))])]),
+        ]);
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(map(&[("$type", IrValue::from("bundle"))]));
+        maps.push(IrValue::Bundle(bundle));
+        maps
+    }
+
+    #[test]
+    fn test_maps_colliding_with_markers_roundtrip() {
+        let custom = JsonOptions::tagged().with_type_key("@t").with_data_key("payload");
+        for value in adversarial_maps() {
+            let json = to_json(&value);
+            assert_eq!(from_json(&json), value, "json: {}", json);
+            assert_eq!(from_json_slice(json.to_string().as_bytes()).unwrap(), value);
+            assert_eq!(to_json_string(&value).unwrap(), json.to_string());
+
+            let renamed = to_json_with(&value, &custom).unwrap();
+            assert_eq!(from_json_with(&renamed, &custom), value, "json: {}", renamed);
+        }
+
+        // Only colliding maps are wrapped; messages keep their shape.
+        let message = IrValue::Map(vec![
+            ("$type".into(), IrValue::from("osc.message")),
+            ("address".into(), IrValue::from("/a")),
+        ]);
+        assert_eq!(to_json(&message)["$type"], "osc.message");
+        let colliding = IrValue::Map(vec![("$type".into(), IrValue::from("binary"))]);
+        assert_eq!(
+            to_json(&colliding),
+            serde_json::json!({"$type": "map", "data": {"$type": "binary"}})
+        );
+    }
+
+    #[test]
+    fn test_ndjson_does_not_mistake_escaped_maps_for_records() {
+        use ndjson::{NdjsonReader, NdjsonWriter, Record};
+
+        let fake = IrValue::Map(vec![
+            ("$type".into(), IrValue::from("record")),
+            ("source".into(), IrValue::from("spoofed")),
+            ("value".into(), IrValue::from(1)),
+        ]);
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer.write_value(&fake).unwrap();
+        let log = writer.into_inner();
+
+        let records: Vec<Record> = NdjsonReader::new(log.as_slice())
+            .records()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records, vec![Record::new(fake)]);
+    }
+
+    #[test]
+    fn test_integer_boundaries_are_lossless() {
+        let values = [
+            IrValue::Integer(i64::MIN),
+            IrValue::Integer(-1),
+            IrValue::Integer(i64::MAX),
+            IrValue::from(i64::MAX as u64 + 1),
+            IrValue::from(u64::MAX),
+        ];
+        for value in values {
+            let text = to_json_string(&value).unwrap();
+            assert_eq!(text, to_json(&value).to_string());
+            assert_eq!(from_json(&serde_json::from_str(&text).unwrap()), value, "{}", text);
+            assert_eq!(from_json_slice(text.as_bytes()).unwrap(), value, "{}", text);
+            for opts in [JsonOptions::plain(), JsonOptions::oscquery()] {
+                assert_eq!(from_json_with(&to_json_with(&value, &opts).unwrap(), &opts), value);
+            }
+        }
+        assert_eq!(to_json_string(&IrValue::from(u64::MAX)).unwrap(), "18446744073709551615");
+
+        let message = IrValue::message("/big", vec![IrValue::from(u64::MAX), IrValue::Integer(i64::MIN)]);
+        let opts = JsonOptions::oscquery();
+        let json = to_json_with(&message, &opts).unwrap();
+        assert_eq!(json["TYPE"], "hh");
+        assert_eq!(from_json_with(&json, &opts), message);
+        assert_eq!(
+            from_json_slice(b"18446744073709551616").unwrap(),
+            IrValue::Float(18446744073709551616.0)
+        );
+
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        bundle.add_message(IrValue::from(u64::MAX));
+        let value = IrValue::Bundle(bundle);
+        assert_eq!(from_json(&to_json(&value)), value);
+        assert_eq!(from_json_slice(to_json_string(&value).unwrap().as_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_float32_uses_shortest_representation() {
+        let cases: [(f32, &str); 6] = [
+            (0.1, "0.1"),
+            (1.0, "1.0"),
+            (-2.5e-8, "-2.5e-8"),
+            (16_777_216.0, "16777216.0"),
+            (f32::MAX, "3.4028235e38"),
+            (1e-45, "1e-45"),
+        ];
+        for (x, expected) in cases {
+            let value = IrValue::Float32(x);
+            assert_eq!(to_json(&value).to_string(), expected);
+            assert_eq!(to_json_string(&value).unwrap(), expected);
+
+            // JSON numbers decode as double precision; narrowing recovers the exact f32.
+            let decoded = from_json_slice(expected.as_bytes()).unwrap();
+            assert_eq!(decoded.as_float().map(|f| f as f32), Some(x));
+        }
+        assert_eq!(to_json(&IrValue::Float(0.1_f32 as f64)).to_string(), "0.10000000149011612");
+    }
+
+    /// Compares floats by bit pattern so NaN equals itself.
+    fn same_float(a: &IrValue, b: &IrValue) -> bool {
+        match (a, b) {
+            (IrValue::Float(x), IrValue::Float(y)) => x.to_bits() == y.to_bits(),
+            (IrValue::Float32(x), IrValue::Float32(y)) => x.to_bits() == y.to_bits(),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_non_finite_floats_are_encoded_by_default() {
+        let values = [
+            IrValue::Float(f64::NAN),
+            IrValue::Float(f64::INFINITY),
+            IrValue::Float(f64::NEG_INFINITY),
+            IrValue::Float32(f32::NAN),
+            IrValue::Float32(f32::INFINITY),
+            IrValue::Float32(f32::NEG_INFINITY),
+        ];
+        for value in &values {
+            let json = to_json(value);
+            assert_eq!(to_json_string(value).unwrap(), json.to_string());
+            assert!(same_float(&from_json(&json), value), "json: {}", json);
+            assert!(same_float(&from_json_slice(json.to_string().as_bytes()).unwrap(), value));
+        }
+        assert_eq!(to_json(&values[0]), serde_json::json!({"$type": "float", "value": "NaN"}));
+        assert_eq!(to_json(&values[5]), serde_json::json!({"$type": "float32", "value": "-Infinity"}));
+
+        // Finite floats stay plain numbers, and markers also accept them.
+        assert_eq!(to_json(&IrValue::Float(1.5)), serde_json::json!(1.5));
+        for json in [r#"{"$type":"float","value":2}"#, r#"{"value":2,"$type":"float"}"#] {
+            assert_eq!(from_json_slice(json.as_bytes()).unwrap(), IrValue::Float(2.0));
+        }
+        let bogus = serde_json::json!({"$type": "float32", "value": "nan"});
+        assert_eq!(from_json(&bogus), IrValue::Null);
+        assert_eq!(from_json_slice(bogus.to_string().as_bytes()).unwrap(), IrValue::Null);
+    }
+
+    #[test]
+    fn test_non_finite_float_policies() {
+        let message = IrValue::message("/sensor", vec![
+            IrValue::Float32(f32::NAN),
+            IrValue::Float(f64::INFINITY),
+            IrValue::Float(0.5),
+        ]);
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(message.clone());
+        let mut outer = IrBundle::immediate();
+        outer.add_message(IrValue::from("fine"));
+        outer.add_bundle(bundle.clone());
+        let bundle = IrValue::Bundle(bundle);
+        let nested = IrValue::Bundle(outer);
+        let args = |value: &IrValue| match value.as_map().and_then(|m| m.iter().find(|(k, _)| k == "args")) {
+            Some((_, IrValue::Array(args))) => args.clone(),
+            _ => panic!("not a message: {:?}", value),
+        };
+
+        // Plain style writes strings, which only the OSCQuery type tags turn back into floats.
+        let plain = to_json_with(&message, &JsonOptions::plain()).unwrap();
+        assert_eq!(plain["args"], serde_json::json!(["NaN", "Infinity", 0.5]));
+        let oscquery = JsonOptions::oscquery();
+        let json = to_json_with(&message, &oscquery).unwrap();
+        assert_eq!(json["VALUE"], serde_json::json!(["NaN", "Infinity", 0.5]));
+        let decoded = args(&from_json_with(&json, &oscquery));
+        for (decoded, original) in decoded.iter().zip(args(&message).iter()) {
+            assert!(same_float(decoded, original), "{:?} != {:?}", decoded, original);
+        }
+
+        for style in [JsonStyle::Tagged, JsonStyle::Plain, JsonStyle::OscQuery] {
+            let null = JsonOptions::tagged().with_style(style).with_non_finite(NonFiniteFloats::Null);
+            let json = to_json_with(&message, &null).unwrap();
+            assert!(!json.to_string().contains("NaN"), "json: {}", json);
+            if style != JsonStyle::OscQuery {
+                assert_eq!(args(&from_json_with(&json, &null))[..2], [IrValue::Null, IrValue::Null]);
+            }
+
+            let error = null.with_non_finite(NonFiniteFloats::Error);
+            for value in [&message, &bundle, &nested] {
+                let err = to_json_with(value, &error).unwrap_err();
+                assert_eq!(err.to_string(), "non-finite float NaN cannot be represented in JSON");
+            }
+            assert!(to_json_with(&IrValue::Float(0.5), &error).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_json_codec_trait() {
+        let codec = JsonCodec;
+        assert_eq!(codec.name(), "json");
+        assert_eq!(codec.mime_type(), "application/json");
+
+        let value = IrValue::Array(vec![IrValue::from("a"), IrValue::Binary(vec![1, 2])]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(bytes, serde_json::to_vec(&to_json(&value)).unwrap());
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+
+        let mut out = Vec::new();
+        codec.encode_to_writer(&value, &mut out).unwrap();
+        assert_eq!(out, bytes);
+
+        let err = codec.decode(b"{not json").unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Decode);
+
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "closed"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = codec.encode_to_writer(&value, &mut Closed).unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Io);
+    }
 }
@@ -0,0 +1,375 @@
+//! Newline-delimited JSON (NDJSON) streams of `IrValue`s, one document per line.
+//!
+//! Each line holds either a bare value in the `$type` JSON representation or a
+//! record envelope carrying per-record metadata:
+//!
+//! ```json
+//! {"$type":"record","received":{"$type":"timestamp","nanos":0,"seconds":1700000000},"source":"127.0.0.1:9000","value":...}
+//! ```
+//!
+//! [`NdjsonWriter`] appends values or [`Record`]s; [`NdjsonReader`] iterates them back,
+//! reporting the 1-based line number of any line that fails to parse. Blank lines
+//! are skipped.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use osc_ir::{IrTimestamp, IrValue};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::de::Node;
+use crate::ser::JsonRepr;
+
+/// `$type` marker of a record envelope.
+const RECORD_TYPE: &str = "record";
+
+/// A logged value together with optional metadata about how it was received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// The logged value
+    pub value: IrValue,
+    /// When the value was received
+    pub received: Option<IrTimestamp>,
+    /// Where the value came from (e.g. a socket address)
+    pub source: Option<String>,
+}
+
+impl Record {
+    /// Creates a record without metadata.
+    pub fn new(value: IrValue) -> Self {
+        Self {
+            value,
+            received: None,
+            source: None,
+        }
+    }
+
+    /// Sets the receive time.
+    pub fn with_received(mut self, received: IrTimestamp) -> Self {
+        self.received = Some(received);
+        self
+    }
+
+    /// Sets the source.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Returns true if the record carries any metadata.
+    pub fn has_metadata(&self) -> bool {
+        self.received.is_some() || self.source.is_some()
+    }
+
+    /// Interprets a decoded line, unwrapping a record envelope if present.
+    ///
+    /// The envelope is recognized before `$type` markers are interpreted, so a user
+    /// map that was escaped because it carries `"$type": "record"` stays a value.
+    fn from_line(node: Node) -> Self {
+        if node.type_tag() != Some(RECORD_TYPE) {
+            return Self::new(node.into_value());
+        }
+        let entries = match node.into_value() {
+            IrValue::Map(entries) => entries,
+            other => return Self::new(other),
+        };
+        let mut record = Self::new(IrValue::Null);
+        for (key, value) in entries {
+            match (key.as_str(), value) {
+                ("value", value) => record.value = value,
+                ("received", IrValue::Timestamp(ts)) => record.received = Some(ts),
+                ("source", IrValue::String(source)) => record.source = Some(source.into()),
+                _ => {}
+            }
+        }
+        record
+    }
+}
+
+impl From<IrValue> for Record {
+    fn from(value: IrValue) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Serializes a record envelope with keys in the same sorted order as `to_json`.
+struct RecordRepr<'a>(&'a Record);
+
+impl Serialize for RecordRepr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let record = self.0;
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("$type", RECORD_TYPE)?;
+        if let Some(received) = &record.received {
+            map.serialize_entry("received", &JsonRepr(&IrValue::Timestamp(*received)))?;
+        }
+        if let Some(source) = &record.source {
+            map.serialize_entry("source", source)?;
+        }
+        map.serialize_entry("value", &JsonRepr(&record.value))?;
+        map.end()
+    }
+}
+
+/// Error produced while reading an NDJSON stream.
+#[derive(Debug)]
+pub enum NdjsonError {
+    /// Reading from the underlying stream failed
+    Io {
+        /// Line being read when the error occurred
+        line: usize,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// A line was not valid JSON
+    Json {
+        /// Line that failed to parse
+        line: usize,
+        /// The underlying error
+        source: serde_json::Error,
+    },
+}
+
+impl NdjsonError {
+    /// Returns the 1-based line number the error refers to.
+    pub fn line(&self) -> usize {
+        match self {
+            NdjsonError::Io { line, .. } | NdjsonError::Json { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for NdjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NdjsonError::Io { line, source } => write!(f, "line {}: {}", line, source),
+            NdjsonError::Json { line, source } => write!(f, "line {}: {}", line, source),
+        }
+    }
+}
+
+impl std::error::Error for NdjsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NdjsonError::Io { source, .. } => Some(source),
+            NdjsonError::Json { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Reads one `IrValue` per line from a buffered reader.
+///
+/// Iterating the reader yields the values only; use [`NdjsonReader::records`] to
+/// keep the per-record metadata.
+#[derive(Debug)]
+pub struct NdjsonReader<R> {
+    reader: R,
+    line: usize,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    /// Creates a reader over a buffered input.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 0,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Returns the number of lines consumed so far.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Reads the next non-blank line as a [`Record`], or `None` at end of input.
+    ///
+    /// After an I/O error the reader is exhausted; after a JSON error it continues
+    /// with the following line.
+    pub fn next_record(&mut self) -> Option<Result<Record, NdjsonError>> {
+        while !self.done {
+            self.buf.clear();
+            self.line += 1;
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    self.done = true;
+                    self.line -= 1;
+                }
+                Ok(_) => {
+                    let text = trim_line(&self.buf);
+                    if text.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    return Some(
+                        serde_json::from_slice::<Node>(text)
+                            .map(Record::from_line)
+                            .map_err(|source| NdjsonError::Json {
+                                line: self.line,
+                                source,
+                            }),
+                    );
+                }
+                Err(source) if source.kind() == io::ErrorKind::Interrupted => {
+                    self.line -= 1;
+                }
+                Err(source) => {
+                    self.done = true;
+                    return Some(Err(NdjsonError::Io {
+                        line: self.line,
+                        source,
+                    }));
+                }
+            }
+        }
+        None
+    }
+
+    /// Converts this reader into an iterator over [`Record`]s.
+    pub fn records(self) -> Records<R> {
+        Records(self)
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<IrValue, NdjsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().map(|r| r.map(|record| record.value))
+    }
+}
+
+/// Iterator over the [`Record`]s of an NDJSON stream, created by [`NdjsonReader::records`].
+#[derive(Debug)]
+pub struct Records<R>(NdjsonReader<R>);
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<Record, NdjsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_record()
+    }
+}
+
+fn trim_line(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Appends one JSON document per line to a writer.
+///
+/// Each call writes a complete line; wrap the writer in a `BufWriter` when logging
+/// high-rate traffic.
+#[derive(Debug)]
+pub struct NdjsonWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Creates a writer appending to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Appends a bare value.
+    pub fn write_value(&mut self, value: &IrValue) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &JsonRepr(value))?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Appends a record, writing a bare value when it carries no metadata.
+    pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        if !record.has_metadata() {
+            return self.write_value(&record.value);
+        }
+        serde_json::to_writer(&mut self.writer, &RecordRepr(record))?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::{IrBundle, IrTimetag};
+
+    fn message(address: &str, arg: i64) -> IrValue {
+        IrValue::message(address, vec![IrValue::from(arg)])
+    }
+
+    #[test]
+    fn values_and_records_roundtrip() {
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(u64::MAX - 1));
+        bundle.add_message(message("/a", 1));
+        let received = IrTimestamp {
+            seconds: 1_700_000_000,
+            nanos: 250_000_000,
+        };
+
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer.write_value(&message("/plain", 0)).unwrap();
+        writer
+            .write_record(
+                &Record::new(IrValue::Bundle(bundle.clone()))
+                    .with_received(received)
+                    .with_source("127.0.0.1:9000"),
+            )
+            .unwrap();
+        writer.write_record(&Record::new(IrValue::Binary(vec![1, 2, 3]))).unwrap();
+        let bytes = writer.into_inner();
+
+        let text = std::str::from_utf8(&bytes).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.lines().nth(1).unwrap().starts_with(r#"{"$type":"record","received":"#));
+
+        let records: Vec<Record> = NdjsonReader::new(bytes.as_slice())
+            .records()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records[0], Record::new(message("/plain", 0)));
+        assert_eq!(records[1].value, IrValue::Bundle(bundle));
+        assert_eq!(records[1].received, Some(received));
+        assert_eq!(records[1].source.as_deref(), Some("127.0.0.1:9000"));
+        assert_eq!(records[2], Record::new(IrValue::Binary(vec![1, 2, 3])));
+
+        let values: Vec<IrValue> = NdjsonReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0], message("/plain", 0));
+    }
+
+    #[test]
+    fn errors_report_line_numbers_and_reading_continues() {
+        let input = "1\r\n\n  \n{\"broken\": \ntrue\n[1,2]";
+        let mut reader = NdjsonReader::new(input.as_bytes());
+
+        assert_eq!(reader.next().unwrap().unwrap(), IrValue::Integer(1));
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.line(), 4);
+        assert!(err.to_string().starts_with("line 4: "));
+        assert_eq!(reader.next().unwrap().unwrap(), IrValue::Bool(true));
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            IrValue::Array(vec![IrValue::Integer(1), IrValue::Integer(2)])
+        );
+        assert!(reader.next().is_none());
+        assert_eq!(reader.line(), 6);
+    }
+}
@@ -0,0 +1,632 @@
+//! Selectable JSON representation styles.
+//!
+//! - [`JsonStyle::Tagged`]: the default `$type` marker objects produced by `to_json`,
+//!   with configurable marker key names.
+//! - [`JsonStyle::Plain`]: binary as base64 strings, timestamps as RFC 3339 strings and
+//!   bundles as `{timetag, elements}`. Decoding is lossy: binary data and timestamps
+//!   come back as strings.
+//! - [`JsonStyle::OscQuery`]: plain, except that OSC messages are written as OSCQuery
+//!   nodes (`{"FULL_PATH", "TYPE", "VALUE"}`) whose type tag string drives decoding.
+
+use std::borrow::Cow;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use osc_ir::{IrBundle, IrBundleElement, IrTimestamp, IrTimetag, IrValue};
+use serde_json::Value as J;
+
+/// JSON representation style, see [`JsonOptions`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// `$type` marker objects for binary, timestamp, ext and bundle values
+    #[default]
+    Tagged,
+    /// Base64 strings, RFC 3339 timestamps and `{timetag, elements}` bundles
+    Plain,
+    /// Plain, with OSC messages written as OSCQuery `FULL_PATH`/`TYPE`/`VALUE` nodes
+    OscQuery,
+}
+
+/// How NaN and ±infinity are written, see [`JsonOptions::non_finite`].
+///
+/// JSON numbers cannot express non-finite floats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFiniteFloats {
+    /// Tagged style writes `{"$type": "float", "value": "NaN"}` (`"float32"` for
+    /// single precision); plain and OSCQuery styles write the bare string `"NaN"`,
+    /// `"Infinity"` or `"-Infinity"`
+    #[default]
+    Encode,
+    /// Write `null`, which decodes as `IrValue::Null`
+    Null,
+    /// Fail the conversion
+    Error,
+}
+
+/// Options for [`to_json_with`](crate::to_json_with) and [`from_json_with`](crate::from_json_with).
+///
+/// The key names only apply to [`JsonStyle::Tagged`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Representation style
+    pub style: JsonStyle,
+    /// Key holding the type marker of special objects (default `"$type"`)
+    pub type_key: Cow<'static, str>,
+    /// Key holding base64 payloads and bundle element contents (default `"data"`)
+    pub data_key: Cow<'static, str>,
+    /// Key holding the kind of a bundle element (default `"type"`)
+    pub element_type_key: Cow<'static, str>,
+    /// Handling of NaN and ±infinity when encoding (default [`NonFiniteFloats::Encode`])
+    pub non_finite: NonFiniteFloats,
+}
+
+impl JsonOptions {
+    /// The tagged style with default key names, as used by `to_json`/`from_json`.
+    pub const TAGGED: JsonOptions = JsonOptions {
+        style: JsonStyle::Tagged,
+        type_key: Cow::Borrowed("$type"),
+        data_key: Cow::Borrowed("data"),
+        element_type_key: Cow::Borrowed("type"),
+        non_finite: NonFiniteFloats::Encode,
+    };
+
+    /// Tagged style with default key names.
+    pub fn tagged() -> Self {
+        Self::TAGGED
+    }
+
+    /// Plain style.
+    pub fn plain() -> Self {
+        Self::TAGGED.with_style(JsonStyle::Plain)
+    }
+
+    /// OSCQuery-compatible style.
+    pub fn oscquery() -> Self {
+        Self::TAGGED.with_style(JsonStyle::OscQuery)
+    }
+
+    /// Sets the representation style.
+    pub fn with_style(mut self, style: JsonStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the type marker key.
+    pub fn with_type_key(mut self, key: impl Into<Cow<'static, str>>) -> Self {
+        self.type_key = key.into();
+        self
+    }
+
+    /// Sets the payload key.
+    pub fn with_data_key(mut self, key: impl Into<Cow<'static, str>>) -> Self {
+        self.data_key = key.into();
+        self
+    }
+
+    /// Sets the bundle element kind key.
+    pub fn with_element_type_key(mut self, key: impl Into<Cow<'static, str>>) -> Self {
+        self.element_type_key = key.into();
+        self
+    }
+
+    /// Sets the handling of non-finite floats.
+    pub fn with_non_finite(mut self, non_finite: NonFiniteFloats) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self::TAGGED
+    }
+}
+
+pub(crate) fn plain_to_json(v: &IrValue, non_finite: NonFiniteFloats) -> J {
+    encode(v, false, non_finite)
+}
+
+pub(crate) fn oscquery_to_json(v: &IrValue, non_finite: NonFiniteFloats) -> J {
+    encode(v, true, non_finite)
+}
+
+pub(crate) fn plain_from_json(j: &J) -> IrValue {
+    decode(j, false)
+}
+
+pub(crate) fn oscquery_from_json(j: &J) -> IrValue {
+    decode(j, true)
+}
+
+fn encode(v: &IrValue, oscquery: bool, non_finite: NonFiniteFloats) -> J {
+    if oscquery {
+        if let Some((address, args)) = v.as_message() {
+            let mut tags = String::with_capacity(args.len());
+            let values = args.iter().map(|arg| encode_arg(arg, &mut tags, non_finite)).collect();
+            return J::Object(
+                [
+                    ("FULL_PATH".to_string(), J::from(address)),
+                    ("TYPE".to_string(), J::from(tags)),
+                    ("VALUE".to_string(), J::Array(values)),
+                ]
+                .into_iter()
+                .collect(),
+            );
+        }
+    }
+    match v {
+        IrValue::Null => J::Null,
+        IrValue::Bool(b) => J::Bool(*b),
+        IrValue::Integer(i) => J::from(*i),
+        IrValue::Unsigned(u) => J::from(*u),
+        IrValue::Float(x) => float_to_json(*x, non_finite),
+        IrValue::Float32(x) => float32_to_json(*x, non_finite),
+        IrValue::String(s) => J::from(s.as_ref()),
+        IrValue::Binary(bytes) => J::from(STANDARD.encode(bytes)),
+        IrValue::Array(xs) => J::Array(xs.iter().map(|x| encode(x, oscquery, non_finite)).collect()),
+        IrValue::Map(entries) => J::Object(
+            entries
+                .iter()
+                .map(|(k, v)| (k.clone(), encode(v, oscquery, non_finite)))
+                .collect(),
+        ),
+        IrValue::Timestamp(ts) => timestamp_to_json(ts),
+        IrValue::Ext { type_id, data } => J::Object(
+            [
+                ("ext".to_string(), J::from(*type_id as i64)),
+                ("data".to_string(), J::from(STANDARD.encode(data))),
+            ]
+            .into_iter()
+            .collect(),
+        ),
+        IrValue::Bundle(bundle) => J::Object(
+            [
+                ("timetag".to_string(), J::from(bundle.timetag.value)),
+                (
+                    "elements".to_string(),
+                    J::Array(
+                        bundle
+                            .elements
+                            .iter()
+                            .map(|element| match element {
+                                IrBundleElement::Message(msg) => encode(msg, oscquery, non_finite),
+                                IrBundleElement::Bundle(b) => {
+                                    encode(&IrValue::Bundle(b.clone()), oscquery, non_finite)
+                                }
+                            })
+                            .collect(),
+                    ),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        ),
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => J::from(format_color(*r, *g, *b, *a)),
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => J::from(vec![*port, *status, *data1, *data2]),
+        #[allow(unreachable_patterns)]
+        _ => J::Null,
+    }
+}
+
+/// Writes non-finite values as bare `"NaN"` / `"Infinity"` / `"-Infinity"` strings.
+fn float_to_json(x: f64, non_finite: NonFiniteFloats) -> J {
+    match non_finite {
+        NonFiniteFloats::Encode if !x.is_finite() => J::from(crate::non_finite_name(x)),
+        _ => J::from(x),
+    }
+}
+
+fn float32_to_json(x: f32, non_finite: NonFiniteFloats) -> J {
+    match non_finite {
+        NonFiniteFloats::Encode if !x.is_finite() => J::from(crate::non_finite_name(x.into())),
+        _ => crate::f32_to_json(x),
+    }
+}
+
+/// Reads an OSCQuery float argument, accepting the string forms of non-finite values.
+fn arg_as_f64(value: &J) -> Option<f64> {
+    match value {
+        J::String(s) => crate::parse_non_finite(s),
+        _ => value.as_f64(),
+    }
+}
+
+/// Encodes one OSCQuery argument, appending its OSC type tag(s) to `tags`.
+///
+/// Values without an OSC type tag (maps, ext values, bundles) are written as nil.
+fn encode_arg(arg: &IrValue, tags: &mut String, non_finite: NonFiniteFloats) -> J {
+    match arg {
+        IrValue::Null => {
+            tags.push('N');
+            J::Null
+        }
+        IrValue::Bool(b) => {
+            tags.push(if *b { 'T' } else { 'F' });
+            J::Bool(*b)
+        }
+        IrValue::Integer(i) => {
+            tags.push(if i32::try_from(*i).is_ok() { 'i' } else { 'h' });
+            J::from(*i)
+        }
+        IrValue::Unsigned(u) => {
+            // OSC has no unsigned 64-bit tag; `h` keeps the value in the 64-bit family
+            // and decoding falls back to the JSON number, which stays exact.
+            tags.push('h');
+            J::from(*u)
+        }
+        IrValue::Float(x) => {
+            tags.push('d');
+            float_to_json(*x, non_finite)
+        }
+        IrValue::Float32(x) => {
+            tags.push('f');
+            float32_to_json(*x, non_finite)
+        }
+        IrValue::String(s) => {
+            tags.push('s');
+            J::from(s.as_ref())
+        }
+        IrValue::Binary(bytes) => {
+            tags.push('b');
+            J::from(STANDARD.encode(bytes))
+        }
+        IrValue::Timestamp(ts) => {
+            tags.push('t');
+            match IrTimetag::from_timestamp(ts) {
+                Some(timetag) => J::from(timetag.value),
+                // Outside NTP era 0; `decode_arg` reads the string back
+                None => timestamp_to_json(ts),
+            }
+        }
+        IrValue::Array(xs) => {
+            tags.push('[');
+            let values = xs.iter().map(|x| encode_arg(x, tags, non_finite)).collect();
+            tags.push(']');
+            J::Array(values)
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => {
+            tags.push('r');
+            J::from(format_color(*r, *g, *b, *a))
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => {
+            tags.push('m');
+            J::from(vec![*port, *status, *data1, *data2])
+        }
+        _ => {
+            tags.push('N');
+            J::Null
+        }
+    }
+}
+
+fn decode(j: &J, oscquery: bool) -> IrValue {
+    match j {
+        J::Null => IrValue::Null,
+        J::Bool(b) => IrValue::Bool(*b),
+        J::Number(n) => n
+            .as_i64()
+            .map(IrValue::Integer)
+            .or_else(|| n.as_u64().map(IrValue::Unsigned))
+            .or_else(|| n.as_f64().map(IrValue::Float))
+            .unwrap_or(IrValue::Null),
+        J::String(s) => IrValue::from(s.as_str()),
+        J::Array(xs) => IrValue::Array(xs.iter().map(|x| decode(x, oscquery)).collect()),
+        J::Object(map) => {
+            if oscquery {
+                if let Some(address) = map.get("FULL_PATH").and_then(|v| v.as_str()) {
+                    let tags = map.get("TYPE").and_then(|v| v.as_str()).unwrap_or_default();
+                    let values = map
+                        .get("VALUE")
+                        .and_then(|v| v.as_array())
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    let args = decode_args(&mut tags.chars(), &mut values.iter());
+                    return IrValue::message(address, args);
+                }
+            }
+            if map.len() == 2 {
+                if let (Some(timetag), Some(elements)) = (
+                    map.get("timetag").and_then(|v| v.as_u64()),
+                    map.get("elements").and_then(|v| v.as_array()),
+                ) {
+                    let elements = elements
+                        .iter()
+                        .map(|e| match decode(e, oscquery) {
+                            IrValue::Bundle(bundle) => IrBundleElement::Bundle(bundle),
+                            other => IrBundleElement::Message(other),
+                        })
+                        .collect();
+                    return IrValue::Bundle(IrBundle {
+                        timetag: IrTimetag { value: timetag },
+                        elements,
+                    });
+                }
+                if let (Some(type_id), Some(Ok(data))) = (
+                    map.get("ext").and_then(|v| v.as_i64()),
+                    map.get("data").and_then(|v| v.as_str()).map(|s| STANDARD.decode(s)),
+                ) {
+                    return IrValue::Ext {
+                        type_id: type_id as i8,
+                        data,
+                    };
+                }
+            }
+            IrValue::Map(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), decode(v, oscquery)))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Decodes OSCQuery `VALUE` entries driven by their type tags, stopping at a
+/// closing `]` or the end of the tag string.
+fn decode_args(tags: &mut core::str::Chars<'_>, values: &mut core::slice::Iter<'_, J>) -> Vec<IrValue> {
+    let mut args = Vec::new();
+    while let Some(tag) = tags.next() {
+        match tag {
+            '[' => {
+                let inner = match values.next() {
+                    Some(J::Array(xs)) => xs.as_slice(),
+                    _ => &[],
+                };
+                args.push(IrValue::Array(decode_args(tags, &mut inner.iter())));
+            }
+            ']' => break,
+            tag => args.push(decode_arg(tag, values.next().unwrap_or(&J::Null))),
+        }
+    }
+    args
+}
+
+fn decode_arg(tag: char, value: &J) -> IrValue {
+    match tag {
+        'T' => IrValue::Bool(true),
+        'F' => IrValue::Bool(false),
+        'N' | 'I' => IrValue::Null,
+        'i' | 'h' if value.is_i64() => IrValue::Integer(value.as_i64().unwrap_or_default()),
+        'f' => IrValue::Float32(arg_as_f64(value).map_or(f32::NAN, |x| x as f32)),
+        'd' => IrValue::Float(arg_as_f64(value).unwrap_or(f64::NAN)),
+        'b' => match value.as_str().map(|s| STANDARD.decode(s)) {
+            Some(Ok(bytes)) => IrValue::Binary(bytes),
+            _ => decode(value, true),
+        },
+        't' => match value.as_u64() {
+            Some(ntp) => IrValue::Timestamp(IrTimetag::from_ntp(ntp).to_timestamp()),
+            None => match value.as_str().and_then(IrTimestamp::parse_rfc3339) {
+                Some(ts) => IrValue::Timestamp(ts),
+                None => decode(value, true),
+            },
+        },
+        #[cfg(feature = "osc11")]
+        'r' => match value.as_str().and_then(parse_color) {
+            Some((r, g, b, a)) => IrValue::Color { r, g, b, a },
+            None => decode(value, true),
+        },
+        #[cfg(feature = "osc11")]
+        'm' => match value.as_array().map(|xs| xs.iter().map(|x| x.as_u64()).collect::<Vec<_>>()) {
+            Some(bytes) if bytes.len() == 4 && bytes.iter().all(|b| matches!(b, Some(0..=255))) => {
+                let byte = |i: usize| bytes[i].unwrap_or_default() as u8;
+                IrValue::Midi {
+                    port: byte(0),
+                    status: byte(1),
+                    data1: byte(2),
+                    data2: byte(3),
+                }
+            }
+            _ => decode(value, true),
+        },
+        _ => decode(value, true),
+    }
+}
+
+#[cfg(feature = "osc11")]
+fn format_color(r: u8, g: u8, b: u8, a: u8) -> String {
+    format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+}
+
+#[cfg(feature = "osc11")]
+fn parse_color(s: &str) -> Option<(u8, u8, u8, u8)> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 8 || !hex.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((byte(0)?, byte(2)?, byte(4)?, byte(6)?))
+}
+
+/// Writes a timestamp as an RFC 3339 string, or as Unix seconds for years RFC 3339
+/// cannot express.
+fn timestamp_to_json(ts: &IrTimestamp) -> J {
+    match ts.to_rfc3339() {
+        Some(formatted) => J::from(formatted),
+        None => J::from(ts.seconds as f64 + f64::from(ts.nanos) / 1e9),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_json, from_json_with, to_json, to_json_with};
+
+    fn sample_bundle() -> IrValue {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX - 1));
+        nested.add_message(IrValue::message("/nested", vec![IrValue::from(1)]));
+        let mut bundle = IrBundle::immediate();
+        bundle.add_message(IrValue::message("/a", vec![IrValue::from("x")]));
+        bundle.add_bundle(nested);
+        IrValue::Bundle(bundle)
+    }
+
+    #[test]
+    fn tagged_default_matches_to_json() {
+        let values = [
+            sample_bundle(),
+            IrValue::Binary(vec![1, 2]),
+            IrValue::Ext { type_id: 5, data: vec![9] },
+        ];
+        for value in values {
+            assert_eq!(to_json_with(&value, &JsonOptions::default()).unwrap(), to_json(&value));
+            assert_eq!(from_json_with(&to_json(&value), &JsonOptions::tagged()), value);
+        }
+    }
+
+    #[test]
+    fn tagged_custom_keys_roundtrip() {
+        let opts = JsonOptions::tagged()
+            .with_type_key("@kind")
+            .with_data_key("payload")
+            .with_element_type_key(String::from("elementKind"));
+        let value = IrValue::Array(vec![
+            sample_bundle(),
+            IrValue::Binary(vec![0xAA, 0xBB]),
+            IrValue::Timestamp(IrTimestamp { seconds: 7, nanos: 8 }),
+            IrValue::Ext { type_id: -1, data: vec![1] },
+        ]);
+
+        let json = to_json_with(&value, &opts).unwrap();
+        assert_eq!(json[1], serde_json::json!({"@kind": "binary", "payload": "qrs="}));
+        assert_eq!(json[0]["elements"][0]["elementKind"], "message");
+        assert!(json[0]["elements"][1]["payload"].is_object());
+        assert_eq!(from_json_with(&json, &opts), value);
+
+        // Default markers are plain maps under custom keys, and vice versa.
+        let default_json = to_json(&IrValue::Binary(vec![1]));
+        assert!(matches!(from_json_with(&default_json, &opts), IrValue::Map(_)));
+        assert!(matches!(from_json(&json[1]), IrValue::Map(_)));
+    }
+
+    #[test]
+    fn plain_style_shapes_and_roundtrip() {
+        let opts = JsonOptions::plain();
+        let json = to_json_with(&sample_bundle(), &opts).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timetag": 1,
+                "elements": [
+                    {"$type": "osc.message", "address": "/a", "args": ["x"]},
+                    {"timetag": u64::MAX - 1, "elements": [
+                        {"$type": "osc.message", "address": "/nested", "args": [1]}
+                    ]}
+                ]
+            })
+        );
+        assert_eq!(from_json_with(&json, &opts), sample_bundle());
+
+        let ext = IrValue::Ext { type_id: 3, data: vec![1, 2, 3] };
+        assert_eq!(from_json_with(&to_json_with(&ext, &opts).unwrap(), &opts), ext);
+
+        // Binary and timestamps are written as strings and decode as strings.
+        let binary = to_json_with(&IrValue::Binary(vec![0xAA, 0xBB, 0xCC]), &opts).unwrap();
+        assert_eq!(binary, "qrvM");
+        assert_eq!(from_json_with(&binary, &opts), IrValue::from("qrvM"));
+        let ts = IrValue::Timestamp(IrTimestamp { seconds: 1_700_000_000, nanos: 250_000_000 });
+        assert_eq!(to_json_with(&ts, &opts).unwrap(), "2023-11-14T22:13:20.25Z");
+    }
+
+    #[test]
+    fn plain_timestamps_outside_rfc3339_are_seconds() {
+        let opts = JsonOptions::plain();
+        let ts = |seconds, nanos| IrValue::Timestamp(IrTimestamp { seconds, nanos });
+        assert_eq!(to_json_with(&ts(-1, 500), &opts).unwrap(), "1969-12-31T23:59:59.0000005Z");
+        assert_eq!(to_json_with(&ts(253_402_300_800, 0), &opts).unwrap(), 253_402_300_800.0);
+    }
+
+    #[test]
+    fn oscquery_messages_roundtrip() {
+        let opts = JsonOptions::oscquery();
+        let ts = IrTimestamp { seconds: 1_700_000_000, nanos: 123_456_789 };
+        let value = IrValue::message(
+            "/synth/1",
+            vec![
+                IrValue::from(1),
+                IrValue::from(1i64 << 40),
+                IrValue::from(0.5_f32),
+                IrValue::from(0.1),
+                IrValue::from("s"),
+                IrValue::Binary(vec![1, 2]),
+                IrValue::Bool(true),
+                IrValue::Null,
+                IrValue::Timestamp(ts),
+                IrValue::Array(vec![IrValue::from(2), IrValue::Array(vec![IrValue::from("in")])]),
+                IrValue::from(3),
+            ],
+        );
+
+        let json = to_json_with(&value, &opts).unwrap();
+        assert_eq!(json["FULL_PATH"], "/synth/1");
+        assert_eq!(json["TYPE"], "ihfdsbTNt[i[s]]i");
+        assert_eq!(json["VALUE"][5], "AQI=");
+        assert_eq!(from_json_with(&json, &opts), value);
+
+        // Messages nested in bundles use the node form too.
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(42));
+        bundle.add_message(value.clone());
+        let json = to_json_with(&IrValue::Bundle(bundle.clone()), &opts).unwrap();
+        assert_eq!(json["elements"][0]["TYPE"], "ihfdsbTNt[i[s]]i");
+        assert_eq!(from_json_with(&json, &opts), IrValue::Bundle(bundle));
+    }
+
+    #[test]
+    fn oscquery_decodes_nodes_leniently() {
+        let opts = JsonOptions::oscquery();
+        let json = serde_json::json!({"FULL_PATH": "/x", "TYPE": "ifT[", "VALUE": [2, 3, null]});
+        assert_eq!(
+            from_json_with(&json, &opts),
+            IrValue::message(
+                "/x",
+                vec![
+                    IrValue::from(2),
+                    IrValue::Float32(3.0),
+                    IrValue::Bool(true),
+                    IrValue::Array(vec![])
+                ]
+            )
+        );
+
+        let json = serde_json::json!({"FULL_PATH": "/y"});
+        assert_eq!(from_json_with(&json, &opts), IrValue::message("/y", vec![]));
+    }
+
+    #[test]
+    fn oscquery_timestamps_roundtrip() {
+        let opts = JsonOptions::oscquery();
+        for ts in [
+            IrTimestamp { seconds: 1_700_000_000, nanos: 0 },
+            IrTimestamp { seconds: 1_700_000_000, nanos: 1 },
+            IrTimestamp { seconds: 1_700_000_000, nanos: 999_999_999 },
+            // After NTP era 0, written as an RFC 3339 string
+            IrTimestamp { seconds: 4_000_000_000, nanos: 5 },
+        ] {
+            let value = IrValue::message("/t", vec![IrValue::Timestamp(ts)]);
+            assert_eq!(from_json_with(&to_json_with(&value, &opts).unwrap(), &opts), value);
+        }
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn oscquery_color_and_midi() {
+        let opts = JsonOptions::oscquery();
+        let value = IrValue::message("/c", vec![IrValue::color(255, 0, 16, 128), IrValue::midi(1, 0x90, 60, 127)]);
+        let json = to_json_with(&value, &opts).unwrap();
+        assert_eq!(json["TYPE"], "rm");
+        assert_eq!(json["VALUE"], serde_json::json!(["#FF001080", [1, 144, 60, 127]]));
+        assert_eq!(from_json_with(&json, &opts), value);
+    }
+}
@@ -0,0 +1,153 @@
+//! Direct serde serialization of `IrValue` into the `$type` JSON representation,
+//! without building an intermediate `serde_json::Value`.
+
+use base64::display::Base64Display;
+use base64::engine::general_purpose::STANDARD;
+use osc_ir::{IrBundleElement, IrValue};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Serializes an `IrValue` exactly as `to_json` would lay it out.
+pub(crate) struct JsonRepr<'a>(pub(crate) &'a IrValue);
+
+struct Base64<'a>(&'a [u8]);
+
+impl Serialize for Base64<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Base64Display::new(self.0, &STANDARD))
+    }
+}
+
+struct ElementRepr<'a>(&'a IrBundleElement);
+
+impl Serialize for ElementRepr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self.0 {
+            IrBundleElement::Message(msg) => {
+                map.serialize_entry("data", &JsonRepr(msg))?;
+                map.serialize_entry("type", "message")?;
+            }
+            IrBundleElement::Bundle(bundle) => {
+                map.serialize_entry("data", &BundleRepr(bundle))?;
+                map.serialize_entry("type", "bundle")?;
+            }
+        }
+        map.end()
+    }
+}
+
+struct BundleRepr<'a>(&'a osc_ir::IrBundle);
+
+impl Serialize for BundleRepr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("$type", "bundle")?;
+        map.serialize_entry("elements", &Elements(&self.0.elements))?;
+        map.serialize_entry("timetag", &self.0.timetag.value)?;
+        map.end()
+    }
+}
+
+struct Elements<'a>(&'a [IrBundleElement]);
+
+impl Serialize for Elements<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for element in self.0 {
+            seq.serialize_element(&ElementRepr(element))?;
+        }
+        seq.end()
+    }
+}
+
+/// Serializes a NaN or infinite float as a `float` / `float32` marker.
+struct NonFiniteRepr(&'static str, f64);
+
+impl Serialize for NonFiniteRepr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("$type", self.0)?;
+        map.serialize_entry("value", crate::non_finite_name(self.1))?;
+        map.end()
+    }
+}
+
+/// Serializes map entries as a plain JSON object.
+struct MapRepr<'a>(&'a [(String, IrValue)]);
+
+impl Serialize for MapRepr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Match `serde_json::Map` semantics: keys sorted, last duplicate wins.
+        let mut sorted: Vec<(&str, &IrValue)> =
+            self.0.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let mut unique: Vec<(&str, &IrValue)> = Vec::with_capacity(sorted.len());
+        for (k, v) in sorted {
+            match unique.last_mut() {
+                Some(last) if last.0 == k => last.1 = v,
+                _ => unique.push((k, v)),
+            }
+        }
+        let mut map = serializer.serialize_map(Some(unique.len()))?;
+        for (k, v) in unique {
+            map.serialize_entry(k, &JsonRepr(v))?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for JsonRepr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            IrValue::Null => serializer.serialize_unit(),
+            IrValue::Bool(b) => serializer.serialize_bool(*b),
+            IrValue::Integer(i) => serializer.serialize_i64(*i),
+            IrValue::Unsigned(u) => serializer.serialize_u64(*u),
+            IrValue::Float(x) if !x.is_finite() => NonFiniteRepr("float", *x).serialize(serializer),
+            IrValue::Float32(x) if !x.is_finite() => {
+                NonFiniteRepr("float32", (*x).into()).serialize(serializer)
+            }
+            IrValue::Float(x) => serializer.serialize_f64(*x),
+            IrValue::Float32(x) => serializer.serialize_f32(*x),
+            IrValue::String(s) => serializer.serialize_str(s),
+            IrValue::Binary(bytes) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("$type", "binary")?;
+                map.serialize_entry("data", &Base64(bytes))?;
+                map.end()
+            }
+            IrValue::Array(xs) => {
+                let mut seq = serializer.serialize_seq(Some(xs.len()))?;
+                for x in xs {
+                    seq.serialize_element(&JsonRepr(x))?;
+                }
+                seq.end()
+            }
+            IrValue::Map(entries) if crate::collides_with_marker(entries, "$type") => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("$type", "map")?;
+                map.serialize_entry("data", &MapRepr(entries))?;
+                map.end()
+            }
+            IrValue::Map(entries) => MapRepr(entries).serialize(serializer),
+            IrValue::Timestamp(ts) => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("$type", "timestamp")?;
+                map.serialize_entry("nanos", &ts.nanos)?;
+                map.serialize_entry("seconds", &ts.seconds)?;
+                map.end()
+            }
+            IrValue::Ext { type_id, data } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("$type", "ext")?;
+                map.serialize_entry("data", &Base64(data))?;
+                map.serialize_entry("ext", type_id)?;
+                map.end()
+            }
+            IrValue::Bundle(bundle) => BundleRepr(bundle).serialize(serializer),
+            // OSC 1.1 Color and MIDI types - currently serialized as null
+            #[allow(unreachable_patterns)]
+            _ => serializer.serialize_unit(),
+        }
+    }
+}
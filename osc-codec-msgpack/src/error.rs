@@ -0,0 +1,88 @@
+//! Error type of the crate.
+
+use std::fmt;
+
+/// Error produced while encoding or decoding MessagePack.
+#[derive(Debug)]
+pub enum Error {
+    /// The value could not be serialized
+    Encode(rmp_serde::encode::Error),
+    /// The bytes could not be decoded as an `IrValue`
+    Decode {
+        /// Offset at which decoding stopped; within a stream, the stream offset
+        offset: u64,
+        /// Innermost `IrValue` variant being decoded, if decoding got that far
+        variant: Option<&'static str>,
+        /// The underlying error
+        source: rmp_serde::decode::Error,
+    },
+    /// The never-used marker `0xC1` was found in a stream; decoding resumes at the
+    /// next byte
+    InvalidMarker {
+        /// Stream offset of the marker
+        offset: u64,
+    },
+    /// A stream value is longer than the limit; its bytes are discarded as they arrive
+    TooLarge {
+        /// Stream offset of the value
+        offset: u64,
+        /// Length of the value, or the length reached when the limit was hit
+        len: usize,
+        /// The limit
+        max: usize,
+    },
+}
+
+impl Error {
+    /// Returns the byte offset the error refers to, if any.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::Encode(_) => None,
+            Error::Decode { offset, .. }
+            | Error::InvalidMarker { offset }
+            | Error::TooLarge { offset, .. } => Some(*offset),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Encode(e) => write!(f, "MessagePack encoding failed: {}", e),
+            Error::Decode {
+                offset,
+                variant: Some(variant),
+                source,
+            } => write!(f, "offset {}: decoding `{}`: {}", offset, variant, source),
+            Error::Decode {
+                offset,
+                variant: None,
+                source,
+            } => write!(f, "offset {}: {}", offset, source),
+            Error::InvalidMarker { offset } => {
+                write!(f, "offset {}: invalid MessagePack marker 0xC1", offset)
+            }
+            Error::TooLarge { offset, len, max } => write!(
+                f,
+                "offset {}: value of {} bytes exceeds the limit of {} bytes",
+                offset, len, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Encode(e) => Some(e),
+            Error::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        Error::Encode(e)
+    }
+}
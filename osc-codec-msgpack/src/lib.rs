@@ -13,12 +13,13 @@
 //! - **Bundle Support**: Full support for OSC bundles with nested structures  
 //! - **Type Preservation**: Native support for binary data, timestamps, and all OSC types
 //! - **Cross-Format Compatibility**: Works seamlessly with JSON codec for the same data
+//! - **Streaming**: [`MsgpackStreamDecoder`] decodes concatenated values fed in arbitrary chunks
 //!
 //! ## Usage
 //!
 //! ```rust
 //! use osc_ir::{IrValue, IrBundle, IrTimetag};
-//! use osc_codec_msgpack::{to_msgpack, from_msgpack};
+//! use osc_codec_msgpack::{try_to_msgpack, try_from_msgpack};
 //!
 //! // Create some data
 //! # #[cfg(feature = "osc10")]
@@ -31,15 +32,38 @@
 //! let value = IrValue::Bundle(bundle);
 //!
 //! // Convert to MessagePack
-//! let msgpack_data = to_msgpack(&value);
+//! let msgpack_data = try_to_msgpack(&value).unwrap();
 //! println!("Serialized {} bytes", msgpack_data.len());
 //!
 //! // Convert back from MessagePack
-//! let restored = from_msgpack(&msgpack_data);
+//! let restored = try_from_msgpack(&msgpack_data).unwrap();
 //! assert_eq!(value, restored);
 //! # }
 //! ```
 //!
+//! ## Errors
+//!
+//! Every fallible function returns the crate [`Error`]. Decoding errors carry the
+//! byte offset where decoding stopped and the innermost `IrValue` variant being
+//! decoded:
+//!
+//! ```rust
+//! use osc_ir::IrValue;
+//! use osc_codec_msgpack::{try_from_msgpack, try_to_msgpack, Error};
+//!
+//! let bytes = try_to_msgpack(&IrValue::Array(vec![IrValue::from(1)])).unwrap();
+//! match try_from_msgpack(&bytes[..bytes.len() - 1]) {
+//!     Err(Error::Decode { offset, variant, .. }) => {
+//!         assert_eq!(offset, bytes.len() as u64 - 1);
+//!         assert_eq!(variant, Some("Integer"));
+//!     }
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//! ```
+//!
+//! The panicking helpers [`to_msgpack`] and [`from_msgpack`] are only available with
+//! the `panicking` feature.
+//!
 //! ## Performance
 //!
 //! MessagePack typically provides:
@@ -51,32 +75,102 @@
 //!
 //! ### Core Functions
 //!
-//! - [`to_msgpack`] - Convert IR to MessagePack binary
-//! - [`from_msgpack`] - Convert MessagePack binary to IR
-//! - [`try_to_msgpack`] - Fallible conversion to MessagePack
-//! - [`try_from_msgpack`] - Fallible conversion from MessagePack
+//! - [`try_to_msgpack`] - Convert IR to MessagePack binary
+//! - [`try_from_msgpack`] - Convert MessagePack binary to IR
+//! - `to_msgpack` / `from_msgpack` - Panicking variants, with the `panicking` feature
+//! - [`Error`] - Error type of every fallible function
+//!
+//! ### Codec Trait
+//!
+//! - [`MsgpackCodec`] - `osc_ir::Codec` implementation for format-agnostic tooling
+//!
+//! ### Streams
+//!
+//! - [`MsgpackStreamDecoder`] - Incremental decoder for a stream of concatenated values
+
+use osc_ir::{Codec, CodecError, CodecErrorKind, IrValue};
+use serde::Deserialize;
+
+mod error;
+mod scan;
+mod stream;
+
+pub use error::Error;
+pub use stream::{MsgpackStreamDecoder, DEFAULT_MAX_VALUE_LEN};
 
-use osc_ir::IrValue;
+/// [`Codec`] implementation producing MessagePack bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MsgpackCodec;
 
-pub type EncodeResult<T> = Result<T, rmp_serde::encode::Error>;
-pub type DecodeResult<T> = Result<T, rmp_serde::decode::Error>;
+impl Codec for MsgpackCodec {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        try_to_msgpack(value).map_err(|e| CodecError::encode(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        try_from_msgpack(bytes).map_err(|e| CodecError::decode(e.to_string()))
+    }
 
-pub fn try_to_msgpack(v: &IrValue) -> EncodeResult<Vec<u8>> {
-    rmp_serde::to_vec_named(v)
+    fn encode_to_writer(
+        &self,
+        value: &IrValue,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<(), CodecError> {
+        let mut serializer = rmp_serde::Serializer::new(writer).with_struct_map();
+        serde::Serialize::serialize(value, &mut serializer).map_err(|e| match e {
+            rmp_serde::encode::Error::InvalidValueWrite(e) => {
+                CodecError::new(CodecErrorKind::Io, e.to_string())
+            }
+            e => CodecError::encode(e.to_string()),
+        })
+    }
+}
+
+/// Encodes a value as MessagePack.
+pub fn try_to_msgpack(v: &IrValue) -> Result<Vec<u8>, Error> {
+    Ok(rmp_serde::to_vec_named(v)?)
 }
 
+/// Encodes a value as MessagePack, panicking on failure.
+#[cfg(feature = "panicking")]
 pub fn to_msgpack(v: &IrValue) -> Vec<u8> {
     try_to_msgpack(v).expect("serialize")
 }
 
-pub fn try_from_msgpack(bytes: &[u8]) -> DecodeResult<IrValue> {
-    rmp_serde::from_slice::<IrValue>(bytes)
+/// Decodes a MessagePack value.
+pub fn try_from_msgpack(bytes: &[u8]) -> Result<IrValue, Error> {
+    decode_at(bytes, 0)
 }
 
+/// Decodes a MessagePack value, panicking on failure.
+#[cfg(feature = "panicking")]
 pub fn from_msgpack(bytes: &[u8]) -> IrValue {
     try_from_msgpack(bytes).expect("deserialize")
 }
 
+/// Decodes `bytes`, found at offset `base` of a stream, reporting errors at their
+/// stream offset.
+pub(crate) fn decode_at(bytes: &[u8], base: u64) -> Result<IrValue, Error> {
+    let mut rest = bytes;
+    let result = IrValue::deserialize(&mut rmp_serde::Deserializer::new(&mut rest));
+    result.map_err(|source| {
+        let stop = bytes.len() - rest.len();
+        Error::Decode {
+            offset: base + stop as u64,
+            variant: scan::variant_at(bytes, stop),
+            source,
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,8 +201,8 @@ mod tests {
             ),
         ]);
 
-        let bytes = to_msgpack(&value);
-        let decoded = from_msgpack(&bytes);
+        let bytes = try_to_msgpack(&value).unwrap();
+        let decoded = try_from_msgpack(&bytes).unwrap();
         assert_eq!(decoded, value);
     }
 
@@ -124,8 +218,8 @@ mod tests {
         bundle.add_bundle(nested_bundle);
 
         let value = IrValue::Bundle(bundle);
-        let bytes = to_msgpack(&value);
-        let decoded = from_msgpack(&bytes);
+        let bytes = try_to_msgpack(&value).unwrap();
+        let decoded = try_from_msgpack(&bytes).unwrap();
         assert_eq!(decoded, value);
     }
 
@@ -147,8 +241,8 @@ mod tests {
         let value = IrValue::Bundle(root);
 
         // Test roundtrip
-        let bytes = to_msgpack(&value);
-        let decoded = from_msgpack(&bytes);
+        let bytes = try_to_msgpack(&value).unwrap();
+        let decoded = try_from_msgpack(&bytes).unwrap();
         assert_eq!(value, decoded);
     }
 
@@ -169,8 +263,8 @@ mod tests {
             ),
         ]);
 
-        let bytes = to_msgpack(&value);
-        let decoded = from_msgpack(&bytes);
+        let bytes = try_to_msgpack(&value).unwrap();
+        let decoded = try_from_msgpack(&bytes).unwrap();
 
         // Structure-preserving roundtrip
         assert_eq!(decoded, value);
@@ -195,6 +289,104 @@ mod tests {
         assert_eq!(args[3].as_binary(), Some(&[1_u8, 2, 3][..]));
     }
 
+    #[test]
+    fn roundtrip_integer_boundaries() {
+        let values = [
+            IrValue::Integer(i64::MIN),
+            IrValue::Integer(i64::MAX),
+            IrValue::from(i64::MAX as u64 + 1),
+            IrValue::from(u64::MAX),
+        ];
+        for value in values {
+            assert_eq!(try_from_msgpack(&try_to_msgpack(&value).unwrap()).unwrap(), value);
+        }
+
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        bundle.add_message(IrValue::from(u64::MAX));
+        let value = IrValue::Bundle(bundle);
+        assert_eq!(try_from_msgpack(&try_to_msgpack(&value).unwrap()).unwrap(), value);
+
+        // Unsigned payloads are native MessagePack uint64 values.
+        let bytes = try_to_msgpack(&IrValue::from(u64::MAX)).unwrap();
+        assert_eq!(&bytes[bytes.len() - 9..], &[0xCF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn float_precision_class_roundtrips() {
+        let single = try_to_msgpack(&IrValue::Float32(0.1)).unwrap();
+        let double = try_to_msgpack(&IrValue::Float(0.1)).unwrap();
+
+        // Single precision is written as a 5-byte float32, double as a 9-byte float64.
+        assert_eq!(&single[single.len() - 5..], &[&[0xCA][..], &0.1_f32.to_be_bytes()].concat()[..]);
+        assert_eq!(&double[double.len() - 9..], &[&[0xCB][..], &0.1_f64.to_be_bytes()].concat()[..]);
+
+        assert_eq!(try_from_msgpack(&single).unwrap(), IrValue::Float32(0.1));
+        assert_eq!(try_from_msgpack(&double).unwrap(), IrValue::Float(0.1));
+    }
+
+    #[test]
+    fn msgpack_codec_trait() {
+        let codec = MsgpackCodec;
+        assert_eq!(codec.name(), "msgpack");
+        assert_eq!(codec.mime_type(), "application/msgpack");
+
+        let value = IrValue::Array(vec![IrValue::from("a"), IrValue::Binary(vec![1, 2])]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(bytes, try_to_msgpack(&value).unwrap());
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+
+        let mut out = Vec::new();
+        codec.encode_to_writer(&value, &mut out).unwrap();
+        assert_eq!(out, bytes);
+
+        let err = codec.decode(&[0xC1]).unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Decode);
+
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "closed"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = codec.encode_to_writer(&value, &mut Closed).unwrap_err();
+        assert_eq!(err.kind(), osc_ir::CodecErrorKind::Io);
+    }
+
+    #[test]
+    fn decode_errors_report_offset_and_variant() {
+        let value = IrValue::Map(vec![(
+            "args".into(),
+            IrValue::Array(vec![IrValue::from(true), IrValue::from("text")]),
+        )]);
+        let bytes = try_to_msgpack(&value).unwrap();
+
+        // Truncated inside the final string
+        match try_from_msgpack(&bytes[..bytes.len() - 2]) {
+            Err(Error::Decode { offset, variant, .. }) => {
+                assert!(offset <= bytes.len() as u64 - 2);
+                assert_eq!(variant, Some("String"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // `Bool` holding an integer
+        let bytes = [0x81, 0xA4, b'B', b'o', b'o', b'l', 0x05];
+        match try_from_msgpack(&bytes) {
+            Err(err @ Error::Decode { .. }) => {
+                assert_eq!(err.offset(), Some(7));
+                assert!(matches!(err, Error::Decode { variant: Some("Bool"), .. }));
+                assert!(err.to_string().starts_with("offset 7: decoding `Bool`"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let err = MsgpackCodec.decode(&bytes).unwrap_err();
+        assert!(err.message().contains("offset 7"));
+    }
+
     #[test]
     fn msgpack_bytes_are_valid_and_match_contents() {
         use std::io::Cursor;
@@ -216,7 +408,7 @@ mod tests {
         ]);
 
         // Encode to MessagePack
-        let bytes = to_msgpack(&value);
+        let bytes = try_to_msgpack(&value).unwrap();
 
         // Ensure bytes are valid MessagePack by decoding with rmpv
         let mut cursor = Cursor::new(&bytes);
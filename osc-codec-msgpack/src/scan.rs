@@ -0,0 +1,170 @@
+//! Structural scanning of MessagePack bytes, without decoding values.
+
+/// Never-used MessagePack marker.
+pub(crate) const INVALID_MARKER: u8 = 0xC1;
+
+/// Names under which `IrValue` and `IrBundleElement` variants are encoded.
+const VARIANTS: &[&str] = &[
+    "Null",
+    "Bool",
+    "Integer",
+    "Unsigned",
+    "Float32",
+    "Float",
+    "String",
+    "Binary",
+    "Array",
+    "Map",
+    "Timestamp",
+    "Ext",
+    "Bundle",
+    "Color",
+    "Midi",
+    "Message",
+];
+
+/// The encoded extent of one MessagePack item, excluding nested items.
+pub(crate) struct Item {
+    /// Marker, length fields and payload
+    pub(crate) len: usize,
+    /// Nested items that follow: array items, or keys and values of map entries
+    pub(crate) children: u64,
+}
+
+/// Reads the item starting at `bytes[0]`.
+///
+/// Returns `None` if `bytes` is empty or ends inside the length fields, and an error
+/// for the never-used marker.
+pub(crate) fn item(bytes: &[u8]) -> Option<Result<Item, ()>> {
+    let marker = *bytes.first()?;
+    let size = |width: usize| -> Option<usize> {
+        let field = bytes.get(1..1 + width)?;
+        Some(field.iter().fold(0usize, |n, &b| n << 8 | usize::from(b)))
+    };
+    let fixed = |len: usize| Item { len, children: 0 };
+    let payload = |width: usize, extra: usize| -> Option<Item> {
+        Some(fixed((1 + width + extra).saturating_add(size(width)?)))
+    };
+    let container = |width: usize, per_entry: u64| -> Option<Item> {
+        Some(Item {
+            len: 1 + width,
+            children: size(width)? as u64 * per_entry,
+        })
+    };
+    Some(Ok(match marker {
+        0x00..=0x7F | 0xE0..=0xFF | 0xC0 | 0xC2 | 0xC3 => fixed(1),
+        0x80..=0x8F => Item {
+            len: 1,
+            children: u64::from(marker & 0x0F) * 2,
+        },
+        0x90..=0x9F => Item {
+            len: 1,
+            children: u64::from(marker & 0x0F),
+        },
+        0xA0..=0xBF => fixed(1 + usize::from(marker & 0x1F)),
+        INVALID_MARKER => return Some(Err(())),
+        0xC4 => payload(1, 0)?,
+        0xC5 => payload(2, 0)?,
+        0xC6 => payload(4, 0)?,
+        // Extensions carry a type byte after the length
+        0xC7 => payload(1, 1)?,
+        0xC8 => payload(2, 1)?,
+        0xC9 => payload(4, 1)?,
+        0xCA => fixed(5),
+        0xCB => fixed(9),
+        0xCC | 0xD0 => fixed(2),
+        0xCD | 0xD1 => fixed(3),
+        0xCE | 0xD2 => fixed(5),
+        0xCF | 0xD3 => fixed(9),
+        0xD4 => fixed(3),
+        0xD5 => fixed(4),
+        0xD6 => fixed(6),
+        0xD7 => fixed(10),
+        0xD8 => fixed(18),
+        0xD9 => payload(1, 0)?,
+        0xDA => payload(2, 0)?,
+        0xDB => payload(4, 0)?,
+        0xDC => container(2, 1)?,
+        0xDD => container(4, 1)?,
+        0xDE => container(2, 2)?,
+        0xDF => container(4, 2)?,
+    }))
+}
+
+/// Returns the innermost enum variant being decoded when decoding stopped at `offset`.
+///
+/// Serde encodes a variant with data as a one-entry map from the variant name to
+/// its contents; this walks the items that start before `offset` and keeps track of
+/// the open containers.
+pub(crate) fn variant_at(bytes: &[u8], offset: usize) -> Option<&'static str> {
+    // Open containers: items still expected, and the variant the container encodes
+    let mut open: Vec<(u64, Option<&'static str>)> = Vec::new();
+    let mut pos = 0;
+    while pos < offset {
+        let Some(Ok(item)) = bytes.get(pos..).and_then(item) else {
+            break;
+        };
+        let variant = match bytes[pos] {
+            0x81 => variant_name(&bytes[pos + 1..]),
+            _ => None,
+        };
+        pos = pos.saturating_add(item.len);
+        if item.children > 0 {
+            open.push((item.children, variant));
+            continue;
+        }
+        if pos >= offset {
+            break;
+        }
+        while let Some((remaining, _)) = open.last_mut() {
+            *remaining -= 1;
+            if *remaining > 0 {
+                break;
+            }
+            open.pop();
+        }
+    }
+    open.iter().rev().find_map(|(_, variant)| *variant)
+}
+
+/// Reads a string item and returns it if it is a variant name.
+fn variant_name(bytes: &[u8]) -> Option<&'static str> {
+    let (len, rest) = match *bytes.first()? {
+        marker @ 0xA0..=0xBF => (usize::from(marker & 0x1F), &bytes[1..]),
+        0xD9 => (usize::from(*bytes.get(1)?), &bytes[2..]),
+        _ => return None,
+    };
+    let name = rest.get(..len)?;
+    VARIANTS
+        .iter()
+        .copied()
+        .find(|variant| variant.as_bytes() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::try_to_msgpack;
+    use osc_ir::IrValue;
+
+    #[test]
+    fn items_report_length_and_children() {
+        let bytes = try_to_msgpack(&IrValue::from("abc")).unwrap();
+        // {"String": "abc"}
+        let map = item(&bytes).unwrap().unwrap();
+        assert_eq!((map.len, map.children), (1, 2));
+        assert!(item(&[0xC6, 0, 0, 1]).is_none());
+        let bin = item(&[0xC6, 0, 0, 1, 0]).unwrap().unwrap();
+        assert_eq!((bin.len, bin.children), (261, 0));
+        assert!(item(&[INVALID_MARKER]).unwrap().is_err());
+    }
+
+    #[test]
+    fn variant_of_a_nested_value() {
+        let value = IrValue::Array(vec![IrValue::from(1), IrValue::from("x")]);
+        let bytes = try_to_msgpack(&value).unwrap();
+        assert_eq!(variant_at(&bytes, 1), Some("Array"));
+        // The last byte belongs to the string "x"
+        assert_eq!(variant_at(&bytes, bytes.len()), Some("String"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Incremental decoding of MessagePack streams holding one value after another.
+//!
+//! [`MsgpackStreamDecoder`] is fed whatever chunks a socket or file read returns and
+//! yields every complete value. A value split across reads stays buffered until the
+//! rest arrives. Decoding resumes after a value that fails to decode.
+//!
+//! Value boundaries come from the MessagePack markers alone: the decoder scans each
+//! value's structure before decoding it. Values are decoded only once complete, so
+//! the stream stays in sync after a value that is valid MessagePack but not a valid
+//! `IrValue`.
+
+use osc_ir::IrValue;
+
+use crate::scan::item;
+use crate::{decode_at, Error};
+
+/// Default maximum encoded length of one value: 1 MiB.
+pub const DEFAULT_MAX_VALUE_LEN: usize = 1 << 20;
+
+/// Incremental decoder for a stream of concatenated MessagePack values.
+///
+/// ```rust
+/// use osc_ir::IrValue;
+/// use osc_codec_msgpack::{try_to_msgpack, MsgpackStreamDecoder};
+///
+/// let mut stream = try_to_msgpack(&IrValue::from("first")).unwrap();
+/// stream.extend(try_to_msgpack(&IrValue::from(2)).unwrap());
+///
+/// let mut decoder = MsgpackStreamDecoder::new();
+/// let (a, b) = stream.split_at(stream.len() - 1);
+/// decoder.feed(a);
+/// assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from("first"));
+/// assert!(decoder.next_value().is_none());
+/// decoder.feed(b);
+/// assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from(2));
+/// assert_eq!(decoder.consumed(), stream.len() as u64);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MsgpackStreamDecoder {
+    buf: Vec<u8>,
+    /// Start of the current value in `buf`
+    start: usize,
+    /// End of the scanned part of the current value; past the end of `buf` while a
+    /// string, binary or extension payload is still arriving
+    pos: usize,
+    /// Values still to be scanned, counting nested array items and map entries
+    pending: u64,
+    /// Discarding an oversized value up to its end
+    discarding: bool,
+    /// Stream offset of `buf[0]`
+    offset: u64,
+    max_len: usize,
+}
+
+impl Default for MsgpackStreamDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MsgpackStreamDecoder {
+    /// Creates a decoder accepting values up to [`DEFAULT_MAX_VALUE_LEN`] bytes.
+    pub fn new() -> Self {
+        Self::with_max_len(DEFAULT_MAX_VALUE_LEN)
+    }
+
+    /// Creates a decoder accepting values up to `max_len` encoded bytes.
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            pos: 0,
+            pending: 0,
+            discarding: false,
+            offset: 0,
+            max_len,
+        }
+    }
+
+    /// Appends bytes read from the stream.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        // Bytes of returned values, and scanned bytes of a discarded value, are dropped
+        let drop = if self.discarding {
+            self.pos.min(self.buf.len())
+        } else {
+            self.start
+        };
+        if drop > 0 {
+            self.buf.drain(..drop);
+            self.offset += drop as u64;
+            self.start = self.start.saturating_sub(drop);
+            self.pos -= drop;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Returns the next complete value, or `None` until more bytes are fed.
+    ///
+    /// An error reports a value that was skipped; later calls continue with the
+    /// values after it.
+    pub fn next_value(&mut self) -> Option<Result<IrValue, Error>> {
+        loop {
+            if self.pending == 0 {
+                if self.pos > self.buf.len() {
+                    return None;
+                }
+                if self.discarding {
+                    self.discarding = false;
+                    self.start = self.pos;
+                    continue;
+                }
+                if self.pos > self.start {
+                    let offset = self.offset + self.start as u64;
+                    let value = decode_at(&self.buf[self.start..self.pos], offset);
+                    self.start = self.pos;
+                    return Some(value);
+                }
+                if self.start == self.buf.len() {
+                    return None;
+                }
+                self.pending = 1;
+            }
+
+            let header = match self.buf.get(self.pos..).and_then(item) {
+                Some(Ok(header)) => header,
+                Some(Err(())) => {
+                    let offset = self.offset + self.pos as u64;
+                    self.pos += 1;
+                    self.start = self.pos;
+                    self.pending = 0;
+                    self.discarding = false;
+                    return Some(Err(Error::InvalidMarker { offset }));
+                }
+                None => return None,
+            };
+            self.pending = (self.pending - 1).saturating_add(header.children);
+            self.pos = self.pos.saturating_add(header.len);
+            let len = self.pos - self.start;
+            if !self.discarding && len > self.max_len {
+                self.discarding = true;
+                let offset = self.offset + self.start as u64;
+                self.start = self.pos.min(self.buf.len());
+                return Some(Err(Error::TooLarge {
+                    offset,
+                    len,
+                    max: self.max_len,
+                }));
+            }
+        }
+    }
+
+    /// Returns the number of stream bytes consumed so far: values returned, plus
+    /// bytes skipped after errors.
+    pub fn consumed(&self) -> u64 {
+        self.offset + self.start as u64
+    }
+
+    /// Returns the number of bytes fed but not yet consumed.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::try_to_msgpack;
+    use osc_ir::{IrBundle, IrTimetag};
+
+    fn values() -> Vec<IrValue> {
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(42));
+        bundle.add_message(IrValue::from("hello"));
+        bundle.add_message(IrValue::Binary(vec![0xC1; 300]));
+        vec![
+            IrValue::message("/a", vec![IrValue::from(1), IrValue::Float32(0.5)]),
+            IrValue::Bundle(bundle),
+            IrValue::Null,
+            IrValue::from("x".repeat(70_000)),
+            IrValue::Ext {
+                type_id: 7,
+                data: vec![1, 2, 3],
+            },
+        ]
+    }
+
+    fn stream(values: &[IrValue]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| try_to_msgpack(value).unwrap())
+            .collect()
+    }
+
+    fn drain(decoder: &mut MsgpackStreamDecoder) -> Vec<IrValue> {
+        let mut out = Vec::new();
+        while let Some(value) = decoder.next_value() {
+            out.push(value.unwrap());
+        }
+        out
+    }
+
+    #[test]
+    fn coalesced_reads_yield_every_value() {
+        let values = values();
+        let bytes = stream(&values);
+        let mut decoder = MsgpackStreamDecoder::new();
+        decoder.feed(&bytes);
+        assert_eq!(drain(&mut decoder), values);
+        assert_eq!(decoder.consumed(), bytes.len() as u64);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn split_reads_resume_after_partial_values() {
+        let values = values();
+        let bytes = stream(&values);
+        for chunk_len in [1, 2, 5, 64, 4096] {
+            let mut decoder = MsgpackStreamDecoder::new();
+            let mut decoded = Vec::new();
+            for chunk in bytes.chunks(chunk_len) {
+                decoder.feed(chunk);
+                decoded.extend(drain(&mut decoder));
+            }
+            assert_eq!(decoded, values, "chunks of {}", chunk_len);
+            assert_eq!(decoder.consumed(), bytes.len() as u64);
+        }
+    }
+
+    #[test]
+    fn consumed_counts_complete_values_only() {
+        let first = try_to_msgpack(&IrValue::from(1)).unwrap();
+        let second = try_to_msgpack(&IrValue::from("two")).unwrap();
+        let mut decoder = MsgpackStreamDecoder::new();
+        decoder.feed(&first);
+        decoder.feed(&second[..2]);
+        assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from(1));
+        assert!(decoder.next_value().is_none());
+        assert_eq!(decoder.consumed(), first.len() as u64);
+        assert_eq!(decoder.buffered(), 2);
+        decoder.feed(&second[2..]);
+        assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from("two"));
+        assert_eq!(decoder.consumed(), (first.len() + second.len()) as u64);
+    }
+
+    #[test]
+    fn errors_skip_one_value() {
+        let ok = try_to_msgpack(&IrValue::from(true)).unwrap();
+        let mut bytes = ok.clone();
+        // A plain MessagePack array is not an `IrValue` enum
+        bytes.extend([0x92, 0x01, 0x02]);
+        bytes.push(0xC1);
+        bytes.extend(&ok);
+
+        let mut decoder = MsgpackStreamDecoder::new();
+        decoder.feed(&bytes);
+        assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from(true));
+        // Offsets are stream offsets
+        let bad = ok.len() as u64;
+        match decoder.next_value() {
+            Some(Err(Error::Decode { offset, .. })) => assert!((bad..bad + 3).contains(&offset)),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(decoder.consumed(), bad + 3);
+        assert!(matches!(
+            decoder.next_value(),
+            Some(Err(Error::InvalidMarker { offset })) if offset == bad + 3
+        ));
+        assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from(true));
+        assert!(decoder.next_value().is_none());
+    }
+
+    #[test]
+    fn oversized_values_are_discarded() {
+        let big = try_to_msgpack(&IrValue::Binary(vec![0; 1000])).unwrap();
+        let ok = try_to_msgpack(&IrValue::from("ok")).unwrap();
+        let mut decoder = MsgpackStreamDecoder::with_max_len(100);
+
+        // The length field is enough to reject the value
+        decoder.feed(&big[..20]);
+        match decoder.next_value() {
+            Some(Err(Error::TooLarge {
+                offset: 0,
+                max: 100,
+                ..
+            })) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        for chunk in big[20..].chunks(64) {
+            decoder.feed(chunk);
+            assert!(decoder.next_value().is_none());
+            assert!(decoder.buffered() <= 64);
+        }
+        decoder.feed(&ok);
+        assert_eq!(decoder.next_value().unwrap().unwrap(), IrValue::from("ok"));
+        assert_eq!(decoder.consumed(), (big.len() + ok.len()) as u64);
+    }
+}
@@ -0,0 +1,280 @@
+//! Conversion of TOML values into `IrValue`.
+
+use std::fmt;
+
+use base64::Engine;
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag};
+use osc_ir::{IrTimestamp, IrValue};
+use toml::value::Datetime;
+use toml::{Table, Value};
+
+use crate::{Path, TYPE_KEY};
+
+/// Error produced while decoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The text is not valid TOML
+    Syntax {
+        /// Description from the TOML parser
+        message: String,
+        /// Byte offset of the problem, when known
+        offset: Option<usize>,
+    },
+    /// A `$type` table is malformed or a date/time cannot be converted
+    Invalid {
+        /// Location of the value, such as `elements[0].timetag`; empty for the root
+        path: String,
+        /// What was wrong with it
+        reason: String,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Syntax {
+                message,
+                offset: Some(offset),
+            } => write!(f, "{} at byte {}", message, offset),
+            DecodeError::Syntax { message, .. } => f.write_str(message),
+            DecodeError::Invalid { path, reason } if path.is_empty() => {
+                write!(f, "{} at the document root", reason)
+            }
+            DecodeError::Invalid { path, reason } => write!(f, "{} at `{}`", reason, path),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<toml::de::Error> for DecodeError {
+    fn from(e: toml::de::Error) -> Self {
+        DecodeError::Syntax {
+            message: e.message().trim_end().to_string(),
+            offset: e.span().map(|span| span.start),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, DecodeError>;
+
+fn invalid<T>(path: &Path<'_>, reason: impl Into<String>) -> Result<T> {
+    Err(DecodeError::Invalid {
+        path: path.to_string(),
+        reason: reason.into(),
+    })
+}
+
+pub(crate) fn document(table: Table) -> Result<IrValue> {
+    from_table(table, &Path::Root)
+}
+
+fn from_value(v: Value, path: &Path<'_>) -> Result<IrValue> {
+    Ok(match v {
+        Value::String(s) => IrValue::from(s),
+        Value::Integer(i) => IrValue::Integer(i),
+        Value::Float(x) => IrValue::Float(x),
+        Value::Boolean(b) => IrValue::Bool(b),
+        Value::Datetime(datetime) => IrValue::Timestamp(timestamp(&datetime, path)?),
+        Value::Array(xs) => IrValue::Array(
+            xs.into_iter()
+                .enumerate()
+                .map(|(i, x)| from_value(x, &Path::Index(path, i)))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Table(table) => from_table(table, path)?,
+    })
+}
+
+fn from_table(mut table: Table, path: &Path<'_>) -> Result<IrValue> {
+    let name = match table.get(TYPE_KEY) {
+        Some(Value::String(name)) if crate::RESERVED_TYPES.contains(&name.as_str()) => name.clone(),
+        _ => return plain_table(table, path),
+    };
+    table.remove(TYPE_KEY);
+    let mut fields = Fields { table, path };
+    let value = match name.as_str() {
+        "map" => match fields.take("data") {
+            Some(Value::Table(data)) => plain_table(data, &Path::Key(path, "data"))?,
+            _ => return fields.error("expects a table `data`"),
+        },
+        "unsigned" => match fields.take("value") {
+            Some(Value::String(s)) => match s.parse() {
+                Ok(u) => IrValue::Unsigned(u),
+                Err(_) => return fields.error("expects `value` to be a decimal string"),
+            },
+            Some(Value::Integer(i)) if i >= 0 => IrValue::Unsigned(i as u64),
+            _ => return fields.error("expects an unsigned `value`"),
+        },
+        "float32" => match fields.take("value") {
+            Some(Value::Float(x)) => IrValue::Float32(x as f32),
+            Some(Value::Integer(i)) => IrValue::Float32(i as f32),
+            _ => return fields.error("expects a float `value`"),
+        },
+        "binary" => IrValue::Binary(fields.base64("data")?),
+        "timestamp" => {
+            let seconds = match fields.take("seconds") {
+                Some(Value::Integer(seconds)) => seconds,
+                _ => return fields.error("expects integer `seconds`"),
+            };
+            let nanos = match fields.take("nanos") {
+                None => 0,
+                Some(Value::Integer(nanos)) if (0..1_000_000_000).contains(&nanos) => nanos as u32,
+                Some(_) => return fields.error("expects `nanos` from 0 to 999999999"),
+            };
+            IrValue::Timestamp(IrTimestamp { seconds, nanos })
+        }
+        "ext" => {
+            let type_id = match fields.take("type") {
+                Some(Value::Integer(i)) => i8::try_from(i).ok(),
+                _ => None,
+            };
+            let Some(type_id) = type_id else {
+                return fields.error("expects an integer `type` from -128 to 127");
+            };
+            IrValue::Ext {
+                type_id,
+                data: fields.base64("data")?,
+            }
+        }
+        #[cfg(feature = "osc10")]
+        "bundle" => IrValue::Bundle(bundle(&mut fields)?),
+        #[cfg(feature = "osc11")]
+        "color" => match fields.take("value") {
+            Some(Value::String(s)) => match parse_color(&s) {
+                Some([r, g, b, a]) => IrValue::Color { r, g, b, a },
+                None => return fields.error("expects `value` to be \"#RRGGBB\" or \"#RRGGBBAA\""),
+            },
+            _ => return fields.error("expects a string `value`"),
+        },
+        #[cfg(feature = "osc11")]
+        "midi" => {
+            let bytes = match fields.take("value") {
+                Some(Value::Array(xs)) => xs
+                    .iter()
+                    .map(|x| x.as_integer().and_then(|i| u8::try_from(i).ok()))
+                    .collect::<Option<Vec<u8>>>(),
+                _ => None,
+            };
+            match bytes.as_deref() {
+                Some(&[port, status, data1, data2]) => IrValue::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                },
+                _ => return fields.error("expects `value` = [port, status, data1, data2] with values 0-255"),
+            }
+        }
+        _ => return invalid(path, format!("`$type = \"{}\"` is not supported by this build", name)),
+    };
+    fields.finish()?;
+    Ok(value)
+}
+
+fn plain_table(table: Table, path: &Path<'_>) -> Result<IrValue> {
+    Ok(IrValue::Map(
+        table
+            .into_iter()
+            .map(|(k, v)| {
+                let value = from_value(v, &Path::Key(path, &k))?;
+                Ok((k, value))
+            })
+            .collect::<Result<_>>()?,
+    ))
+}
+
+#[cfg(feature = "osc10")]
+fn bundle(fields: &mut Fields<'_, '_>) -> Result<IrBundle> {
+    let timetag = match fields.take("timetag") {
+        None => Some(IrTimetag::immediate()),
+        Some(Value::String(s)) if s == "immediate" => Some(IrTimetag::immediate()),
+        Some(Value::String(s)) => s.parse().ok().map(IrTimetag::from_ntp),
+        Some(Value::Integer(i)) => u64::try_from(i).ok().map(IrTimetag::from_ntp),
+        Some(Value::Datetime(datetime)) => {
+            IrTimetag::from_timestamp(&timestamp(&datetime, &Path::Key(fields.path, "timetag"))?)
+        }
+        Some(_) => None,
+    };
+    let Some(timetag) = timetag else {
+        return fields.error(
+            "expects `timetag` to be \"immediate\", an NTP integer or a date-time in 1900-2036",
+        );
+    };
+    let elements_path = Path::Key(fields.path, "elements");
+    let elements = match fields.take("elements") {
+        None => Vec::new(),
+        Some(Value::Array(elements)) => elements
+            .into_iter()
+            .enumerate()
+            .map(|(i, element)| {
+                Ok(match from_value(element, &Path::Index(&elements_path, i))? {
+                    IrValue::Bundle(nested) => IrBundleElement::Bundle(nested),
+                    message => IrBundleElement::Message(message),
+                })
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => return fields.error("expects `elements` to be an array"),
+    };
+    Ok(IrBundle { timetag, elements })
+}
+
+/// Converts a TOML date-time; local date-times are taken as UTC and a local date as
+/// midnight UTC.
+fn timestamp(datetime: &Datetime, path: &Path<'_>) -> Result<IrTimestamp> {
+    let text = match (datetime.date, datetime.time, datetime.offset) {
+        (Some(_), Some(_), Some(_)) => datetime.to_string(),
+        (Some(_), Some(_), None) => format!("{}Z", datetime),
+        (Some(date), None, _) => format!("{}T00:00:00Z", date),
+        _ => return invalid(path, "a time without a date cannot be a timestamp"),
+    };
+    match IrTimestamp::parse_rfc3339(&text) {
+        Some(ts) => Ok(ts),
+        None => invalid(path, format!("invalid date-time {}", datetime)),
+    }
+}
+
+#[cfg(feature = "osc11")]
+fn parse_color(text: &str) -> Option<[u8; 4]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("ff"), 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?, byte(6)?])
+}
+
+/// The fields of a `$type` table, taken one by one; leftovers are an error so that
+/// typos in hand-written files do not go unnoticed.
+struct Fields<'a, 'p> {
+    table: Table,
+    path: &'a Path<'p>,
+}
+
+impl Fields<'_, '_> {
+    fn error<T>(&self, reason: &str) -> Result<T> {
+        invalid(self.path, reason)
+    }
+
+    fn take(&mut self, key: &str) -> Option<Value> {
+        self.table.remove(key)
+    }
+
+    fn base64(&mut self, key: &str) -> Result<Vec<u8>> {
+        match self.take(key) {
+            Some(Value::String(s)) => match base64::engine::general_purpose::STANDARD.decode(s) {
+                Ok(data) => Ok(data),
+                Err(_) => self.error(&format!("expects `{}` to be base64", key)),
+            },
+            _ => self.error(&format!("expects a base64 string `{}`", key)),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.table.keys().next() {
+            None => Ok(()),
+            Some(key) => self.error(&format!("unexpected key `{}`", key)),
+        }
+    }
+}
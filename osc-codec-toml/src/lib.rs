@@ -0,0 +1,360 @@
+//! # osc-codec-toml
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! TOML codec for the `osc-ir` intermediate representation, for configuration-style
+//! cue files written by hand.
+//!
+//! TOML cannot represent every value: documents are tables, there is no null, and
+//! keys cannot repeat. Encoding such values fails with an [`EncodeError`] naming the
+//! offending path; everything else round-trips.
+//!
+//! ## Features
+//!
+//! - **Native Types**: Strings, integers, floats (including `inf`/`nan`), booleans,
+//!   arrays, tables and date-times map directly
+//! - **Marker Tables**: Values TOML has no syntax for are `{"$type" = ...}` tables, as in
+//!   the tagged style of `osc-codec-json`
+//! - **Cue-Friendly Bundles**: `[[elements]]` arrays of tables, timetags written as
+//!   `"immediate"`, an NTP integer or a date-time
+//! - **Strict Markers**: Malformed or misspelled fields of marker tables are errors with
+//!   the path of the value
+//! - **Codec Trait**: [`TomlCodec`] implements `osc_ir::Codec` for format-agnostic tooling
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_codec_toml::{from_toml, to_toml};
+//!
+//! # #[cfg(feature = "osc10")]
+//! # {
+//! let cue = from_toml(
+//!     r#"
+//! name = "Act 1"
+//!
+//! [go]
+//! "$type" = "bundle"
+//! timetag = 2026-10-18T19:30:00Z
+//!
+//! [[go.elements]]
+//! "$type" = "osc.message"
+//! address = "/light/1/level"
+//! args = [0.75]
+//! "#,
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(from_toml(&to_toml(&cue).unwrap()).unwrap(), cue);
+//! # }
+//! ```
+//!
+//! ## Mapping
+//!
+//! | `IrValue` | TOML |
+//! |-----------|------|
+//! | `Bool`, `Integer`, `Float`, `String` | native |
+//! | `Array` / `Map` | array / table, order preserved |
+//! | `Timestamp` | offset date-time; `{"$type" = "timestamp", seconds, nanos}` outside years 0000-9999 |
+//! | `Unsigned` | `{"$type" = "unsigned", value = "<decimal>"}` |
+//! | `Float32` | `{"$type" = "float32", value}` |
+//! | `Binary` | `{"$type" = "binary", data = "<base64>"}` |
+//! | `Ext` | `{"$type" = "ext", type, data = "<base64>"}` |
+//! | `Bundle` | `{"$type" = "bundle", timetag, elements}` |
+//! | `Color` / `Midi` | `{"$type" = "color", value = "#RRGGBBAA"}` / `{"$type" = "midi", value = [port, status, data1, data2]}` |
+//! | `Null` | not representable |
+//!
+//! OSC messages are the usual `{"$type" = "osc.message", address, args}` tables. User
+//! tables whose `$type` names a marker are wrapped as `{"$type" = "map", data}`. Local
+//! date-times decode as UTC and local dates as midnight UTC.
+
+use std::fmt;
+
+use osc_ir::{Codec, CodecError, IrValue};
+
+mod de;
+mod ser;
+
+pub use de::DecodeError;
+pub use ser::EncodeError;
+
+/// Key naming the kind of a marker table.
+pub(crate) const TYPE_KEY: &str = "$type";
+
+/// `$type` values of marker tables.
+pub(crate) const RESERVED_TYPES: [&str; 9] = [
+    "binary", "bundle", "color", "ext", "float32", "map", "midi", "timestamp", "unsigned",
+];
+
+/// Returns true if a user map would be mistaken for a marker table.
+pub(crate) fn collides_with_marker(entries: &[(String, IrValue)]) -> bool {
+    entries.iter().any(|(k, v)| {
+        k == TYPE_KEY && v.as_str().is_some_and(|name| RESERVED_TYPES.contains(&name))
+    })
+}
+
+/// Location of a value inside the document, for error messages.
+pub(crate) enum Path<'a> {
+    Root,
+    Key(&'a Path<'a>, &'a str),
+    Index(&'a Path<'a>, usize),
+}
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Path::Root => Ok(()),
+            Path::Key(parent, key) => {
+                if !matches!(parent, Path::Root) {
+                    write!(f, "{}.", parent)?;
+                }
+                let bare = !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                if bare {
+                    f.write_str(key)
+                } else {
+                    write!(f, "{:?}", key)
+                }
+            }
+            Path::Index(parent, i) => write!(f, "{}[{}]", parent, i),
+        }
+    }
+}
+
+/// [`Codec`] implementation producing UTF-8 TOML text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TomlCodec;
+
+impl Codec for TomlCodec {
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/toml"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        to_toml(value)
+            .map(String::into_bytes)
+            .map_err(|e| CodecError::encode(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        let text = std::str::from_utf8(bytes).map_err(|e| CodecError::decode(e.to_string()))?;
+        from_toml(text).map_err(|e| CodecError::decode(e.to_string()))
+    }
+}
+
+/// Convert IR -> a TOML document. The value must be a map without nulls or repeated keys.
+pub fn to_toml(v: &IrValue) -> Result<String, EncodeError> {
+    ser::document(v)
+}
+
+/// Write the TOML document for IR into a writer.
+pub fn to_toml_writer<W: std::io::Write>(mut writer: W, v: &IrValue) -> std::io::Result<()> {
+    let text = to_toml(v).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    writer.write_all(text.as_bytes())
+}
+
+/// Convert a TOML document -> IR. The result is always a map.
+pub fn from_toml(text: &str) -> Result<IrValue, DecodeError> {
+    de::document(toml::from_str(text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::IrTimestamp;
+    #[cfg(feature = "osc10")]
+    use osc_ir::{IrBundle, IrTimetag};
+
+    fn map(entries: &[(&str, IrValue)]) -> IrValue {
+        IrValue::Map(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+    }
+
+    fn roundtrip(value: &IrValue) -> IrValue {
+        let text = to_toml(value).unwrap();
+        from_toml(&text).unwrap_or_else(|e| panic!("{}:\n{}", e, text))
+    }
+
+    #[test]
+    fn roundtrip_representable_values() {
+        let values = [
+            IrValue::Bool(true),
+            IrValue::Integer(i64::MIN),
+            IrValue::Unsigned(0),
+            IrValue::Unsigned(u64::MAX),
+            IrValue::Float(0.1),
+            IrValue::Float(f64::NEG_INFINITY),
+            IrValue::Float32(0.1),
+            IrValue::Float32(f32::MAX),
+            IrValue::from("multi\nline \"quoted\" ✓"),
+            IrValue::Binary(vec![]),
+            IrValue::Binary((0..=255).collect()),
+            IrValue::Array(vec![]),
+            IrValue::Array(vec![IrValue::from(1), IrValue::from("mixed"), map(&[("k", IrValue::Bool(false))])]),
+            IrValue::Map(vec![]),
+            IrValue::Timestamp(IrTimestamp { seconds: 1_700_000_000, nanos: 250_000_000 }),
+            IrValue::Timestamp(IrTimestamp { seconds: -1, nanos: 0 }),
+            IrValue::Timestamp(IrTimestamp { seconds: i64::MIN, nanos: 1 }),
+            IrValue::Ext { type_id: -128, data: vec![1, 2, 3] },
+            map(&[("b", IrValue::from(1)), ("a", IrValue::from(2)), ("a.b c", IrValue::from(3))]),
+            map(&[("$type", IrValue::from("binary")), ("data", IrValue::Binary(vec![7]))]),
+            IrValue::message("/a", vec![IrValue::from(1), IrValue::Float32(2.5), IrValue::Binary(vec![9])]),
+            IrValue::Array(vec![IrValue::message("/in-array", vec![]), IrValue::message("/b", vec![IrValue::Array(vec![])])]),
+        ];
+        for value in values {
+            let doc = map(&[("v", value.clone()), ("nested", map(&[("v", value)]))]);
+            assert_eq!(roundtrip(&doc), doc);
+        }
+
+        let doc = map(&[("nan", IrValue::Float32(f32::NAN))]);
+        assert!(matches!(roundtrip(&doc).as_map().unwrap()[0].1, IrValue::Float32(x) if x.is_nan()));
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn roundtrip_bundles() {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        nested.add_message(IrValue::message("/n", vec![IrValue::from(1)]));
+        nested.add_bundle(IrBundle::immediate());
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(2));
+        bundle.add_message(IrValue::message("/a", vec![IrValue::Float32(0.5), IrValue::from("x")]));
+        bundle.add_bundle(nested);
+        bundle.add_message(IrValue::from("loose"));
+
+        let doc = map(&[("cue", IrValue::Bundle(bundle)), ("empty", IrValue::Bundle(IrBundle::immediate()))]);
+        assert_eq!(roundtrip(&doc), doc);
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn cue_file_loads_into_bundle_and_converts_to_msgpack() {
+        let cue = r##"
+# Act 1, scene 2
+title = "Opening"
+
+[cue]
+"$type" = "bundle"
+timetag = 2026-10-18T19:30:00.5Z
+
+[[cue.elements]]
+"$type" = "osc.message"
+address = "/light/1/level"
+args = [0.75, 255, { "$type" = "float32", value = 0.5 }, "#1 house"]
+
+[[cue.elements]]
+"$type" = "bundle"
+timetag = "immediate"
+elements = [
+  { "$type" = "osc.message", address = "/video/clip", args = ["intro.mov", { "$type" = "binary", data = "AAEC" }, 2026-10-18] },
+]
+"##;
+        let value = from_toml(cue).unwrap();
+
+        let mut clip = IrBundle::immediate();
+        clip.add_message(IrValue::message(
+            "/video/clip",
+            vec![
+                IrValue::from("intro.mov"),
+                IrValue::Binary(vec![0, 1, 2]),
+                IrValue::Timestamp(IrTimestamp { seconds: 1_792_281_600, nanos: 0 }),
+            ],
+        ));
+        let start = IrTimestamp { seconds: 1_792_351_800, nanos: 500_000_000 };
+        let mut bundle = IrBundle::new(IrTimetag::from_timestamp(&start).unwrap());
+        bundle.add_message(IrValue::message(
+            "/light/1/level",
+            vec![IrValue::Float(0.75), IrValue::from(255), IrValue::Float32(0.5), IrValue::from("#1 house")],
+        ));
+        bundle.add_bundle(clip);
+        assert_eq!(value, map(&[("title", IrValue::from("Opening")), ("cue", IrValue::Bundle(bundle))]));
+
+        let bytes = osc_codec_msgpack::try_to_msgpack(&value).unwrap();
+        assert_eq!(osc_codec_msgpack::try_from_msgpack(&bytes).unwrap(), value);
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn color_and_midi_markers() {
+        let doc = map(&[("c", IrValue::color(255, 128, 0, 1)), ("m", IrValue::midi(0, 144, 60, 127))]);
+        assert_eq!(roundtrip(&doc), doc);
+        let value = from_toml(r##"c = { "$type" = "color", value = "#ff8000" }"##).unwrap();
+        assert_eq!(value, map(&[("c", IrValue::color(255, 128, 0, 255))]));
+        assert!(from_toml(r#"m = { "$type" = "midi", value = [0, 144, 60, 256] }"#).is_err());
+    }
+
+    #[test]
+    fn unrepresentable_values_name_their_path() {
+        let cases = [
+            (IrValue::from(1), "", "a TOML document must be a map at the document root"),
+            (map(&[("$type", IrValue::from("ext"))]), "", "a map with a reserved `$type` cannot be the root at the document root"),
+            (map(&[("a", IrValue::Array(vec![IrValue::from(1), IrValue::Null]))]), "a[1]", "TOML has no null at `a[1]`"),
+            (map(&[("x y", map(&[("k", IrValue::from(1)), ("k", IrValue::from(2))]))]), "\"x y\".k", "TOML tables cannot repeat a key at `\"x y\".k`"),
+        ];
+        for (value, path, message) in cases {
+            let err = to_toml(&value).unwrap_err();
+            assert_eq!(err.path(), path);
+            assert_eq!(err.to_string(), message);
+        }
+        assert!(TomlCodec.encode(&IrValue::Null).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_markers() {
+        let cases = [
+            (r#"a = [1"#, None),
+            (r#"a = { "$type" = "binary", data = "***" }"#, Some("a")),
+            (r#"a = { "$type" = "binary", data = "AQI=", extra = 1 }"#, Some("a")),
+            (r#"a = { "$type" = "ext", type = 300, data = "" }"#, Some("a")),
+            (r#"a = { "$type" = "unsigned", value = "-1" }"#, Some("a")),
+            (r#"a = { "$type" = "timestamp", seconds = 1, nanos = 1000000000 }"#, Some("a")),
+            (r#"a = { "$type" = "map", data = 1 }"#, Some("a")),
+            (r#"a = [{ b = 07:32:00 }]"#, Some("a[0].b")),
+            (r#"a = { "$type" = "bundle", timetag = -1 }"#, Some("a")),
+            (r#"a = { "$type" = "bundle", timetag = 2040-01-01T00:00:00Z }"#, Some("a")),
+            (r#"a = { "$type" = "bundle", elements = [{ "$type" = "binary" }] }"#, Some("a.elements[0]")),
+        ];
+        for (doc, path) in cases {
+            let err = from_toml(doc).unwrap_err();
+            match (&err, path) {
+                (DecodeError::Syntax { offset, .. }, None) => assert!(offset.is_some(), "{}", err),
+                (DecodeError::Invalid { path: actual, .. }, Some(path)) if cfg!(feature = "osc10") || !doc.contains("bundle") => {
+                    assert_eq!(actual, path, "{}: {}", doc, err)
+                }
+                (DecodeError::Invalid { .. }, Some(_)) => {}
+                _ => panic!("{}: unexpected {:?}", doc, err),
+            }
+        }
+
+        // Unknown `$type` names and the message type are ordinary tables
+        let value = from_toml(r#"a = { "$type" = "cue", n = 1 }"#).unwrap();
+        assert_eq!(value, map(&[("a", map(&[("$type", IrValue::from("cue")), ("n", IrValue::from(1))]))]));
+    }
+
+    #[test]
+    fn local_datetimes_are_utc() {
+        let value = from_toml("a = 2013-03-21T20:04:00\nb = 2013-03-21\nc = 2013-03-21T22:04:00+02:00").unwrap();
+        let ts = |seconds| IrValue::Timestamp(IrTimestamp { seconds, nanos: 0 });
+        assert_eq!(value, map(&[("a", ts(1_363_896_240)), ("b", ts(1_363_824_000)), ("c", ts(1_363_896_240))]));
+    }
+
+    #[test]
+    fn codec_trait() {
+        let codec = TomlCodec;
+        assert_eq!(codec.name(), "toml");
+        assert_eq!(codec.mime_type(), "application/toml");
+        let value = map(&[("a", IrValue::Array(vec![IrValue::from(1)]))]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+        assert!(codec.decode(&[0xFF]).is_err());
+
+        let mut out = Vec::new();
+        to_toml_writer(&mut out, &value).unwrap();
+        assert_eq!(out, bytes);
+        assert!(to_toml_writer(Vec::new(), &IrValue::Null).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Conversion of `IrValue` into TOML values.
+
+use std::fmt;
+
+use base64::Engine;
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement};
+use osc_ir::IrValue;
+use toml::value::Datetime;
+use toml::{Table, Value};
+
+use crate::{collides_with_marker, Path, TYPE_KEY};
+
+/// Error produced when a value has no TOML representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodeError {
+    path: String,
+    reason: &'static str,
+}
+
+impl EncodeError {
+    /// Returns the location of the value, such as `cue.args[2]`; empty for the root.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns why the value cannot be written.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{} at the document root", self.reason)
+        } else {
+            write!(f, "{} at `{}`", self.reason, self.path)
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+type Result<T> = std::result::Result<T, EncodeError>;
+
+fn unrepresentable<T>(path: &Path<'_>, reason: &'static str) -> Result<T> {
+    Err(EncodeError {
+        path: path.to_string(),
+        reason,
+    })
+}
+
+/// Writes the root value, which must be a map.
+pub(crate) fn document(v: &IrValue) -> Result<String> {
+    let path = Path::Root;
+    let table = match v {
+        IrValue::Map(entries) if !collides_with_marker(entries) => table(entries, &path)?,
+        IrValue::Map(_) => {
+            return unrepresentable(&path, "a map with a reserved `$type` cannot be the root")
+        }
+        _ => return unrepresentable(&path, "a TOML document must be a map"),
+    };
+    match toml::to_string(&table) {
+        Ok(text) => Ok(text),
+        Err(_) => unrepresentable(&path, "the TOML serializer rejected the document"),
+    }
+}
+
+fn table(entries: &[(String, IrValue)], path: &Path<'_>) -> Result<Table> {
+    let mut out = Table::new();
+    for (key, value) in entries {
+        let path = Path::Key(path, key);
+        let value = to_value(value, &path)?;
+        if out.insert(key.clone(), value).is_some() {
+            return unrepresentable(&path, "TOML tables cannot repeat a key");
+        }
+    }
+    Ok(out)
+}
+
+/// A `{"$type" = name, ...}` marker table.
+fn marker(name: &str, fields: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+    let mut out = Table::new();
+    out.insert(TYPE_KEY.into(), Value::from(name));
+    out.extend(fields.into_iter().map(|(k, v)| (k.to_string(), v)));
+    Value::Table(out)
+}
+
+fn base64(data: &[u8]) -> Value {
+    Value::from(base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+fn to_value(v: &IrValue, path: &Path<'_>) -> Result<Value> {
+    Ok(match v {
+        IrValue::Null => return unrepresentable(path, "TOML has no null"),
+        IrValue::Bool(b) => Value::Boolean(*b),
+        IrValue::Integer(i) => Value::Integer(*i),
+        IrValue::Unsigned(u) => marker("unsigned", [("value", Value::from(u.to_string()))]),
+        IrValue::Float(x) => Value::Float(*x),
+        IrValue::Float32(x) => marker("float32", [("value", Value::Float((*x).into()))]),
+        IrValue::String(s) => Value::from(s.as_ref()),
+        IrValue::Binary(data) => marker("binary", [("data", base64(data))]),
+        IrValue::Array(xs) => Value::Array(
+            xs.iter()
+                .enumerate()
+                .map(|(i, x)| to_value(x, &Path::Index(path, i)))
+                .collect::<Result<_>>()?,
+        ),
+        IrValue::Map(entries) if collides_with_marker(entries) => {
+            marker("map", [("data", Value::Table(table(entries, path)?))])
+        }
+        IrValue::Map(entries) => Value::Table(table(entries, path)?),
+        IrValue::Timestamp(ts) => match ts.to_rfc3339().and_then(|s| s.parse::<Datetime>().ok()) {
+            Some(datetime) if ts.nanos < 1_000_000_000 => Value::Datetime(datetime),
+            _ => marker(
+                "timestamp",
+                [
+                    ("seconds", Value::Integer(ts.seconds)),
+                    ("nanos", Value::Integer(ts.nanos.into())),
+                ],
+            ),
+        },
+        IrValue::Ext { type_id, data } => marker(
+            "ext",
+            [("type", Value::Integer((*type_id).into())), ("data", base64(data))],
+        ),
+        #[cfg(feature = "osc10")]
+        IrValue::Bundle(b) => bundle(b, path)?,
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => marker(
+            "color",
+            [("value", Value::from(format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)))],
+        ),
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => marker(
+            "midi",
+            [(
+                "value",
+                Value::Array([port, status, data1, data2].map(|b| Value::Integer((*b).into())).to_vec()),
+            )],
+        ),
+        // Variants osc-ir enables through features this crate does not
+        #[allow(unreachable_patterns)]
+        _ => return unrepresentable(path, "unsupported value"),
+    })
+}
+
+#[cfg(feature = "osc10")]
+fn bundle(b: &IrBundle, path: &Path<'_>) -> Result<Value> {
+    let timetag = match i64::try_from(b.timetag.value) {
+        _ if b.timetag.is_immediate() => Value::from("immediate"),
+        Ok(value) => Value::Integer(value),
+        // NTP timetags after 1968 do not fit a TOML integer
+        Err(_) => Value::from(b.timetag.value.to_string()),
+    };
+    let elements_path = Path::Key(path, "elements");
+    let elements = b
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(i, element)| {
+            let path = Path::Index(&elements_path, i);
+            match element {
+                IrBundleElement::Message(msg) => to_value(msg, &path),
+                IrBundleElement::Bundle(nested) => bundle(nested, &path),
+            }
+        })
+        .collect::<Result<_>>()?;
+    Ok(marker(
+        "bundle",
+        [("timetag", timetag), ("elements", Value::Array(elements))],
+    ))
+}
@@ -0,0 +1,580 @@
+//! Decoding of YAML documents into `IrValue`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::Chars;
+
+use base64::Engine;
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag};
+use osc_ir::{IrTimestamp, IrValue};
+use yaml_rust2::parser::{Event, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+use yaml_rust2::ScanError;
+
+use crate::MAX_DEPTH;
+
+/// Handle of the tags in the YAML core schema (`!!str`, `!!binary`, ...).
+const CORE_HANDLE: &str = "tag:yaml.org,2002:";
+
+/// Upper bound on the values produced by expanding aliases, so that nested aliases
+/// ("billion laughs") cannot exhaust memory.
+const ALIAS_EXPANSION_LIMIT: usize = 1 << 20;
+
+/// Error produced while decoding, with the position in the source text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl DecodeError {
+    fn new(mark: Marker, message: impl Into<String>) -> Self {
+        DecodeError {
+            line: mark.line(),
+            column: mark.col() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the 1-based line the error refers to.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column the error refers to.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the description of the error, without its position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {} column {}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<ScanError> for DecodeError {
+    fn from(e: ScanError) -> Self {
+        DecodeError::new(*e.marker(), e.info())
+    }
+}
+
+type Result<T> = std::result::Result<T, DecodeError>;
+
+/// A value together with the number of values it contains, for alias accounting.
+struct Anchored {
+    value: IrValue,
+    size: usize,
+}
+
+/// Builds `IrValue`s from the parser's event stream.
+pub(crate) struct Loader<'a> {
+    parser: Parser<Chars<'a>>,
+    anchors: HashMap<usize, Anchored>,
+    depth: usize,
+    expanded: usize,
+}
+
+impl<'a> Loader<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Loader {
+            parser: Parser::new_from_str(input),
+            anchors: HashMap::new(),
+            depth: 0,
+            expanded: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<(Event, Marker)> {
+        Ok(self.parser.next_token()?)
+    }
+
+    /// Reads the whole stream, which must hold at most one document. An empty stream
+    /// is `Null`.
+    pub(crate) fn document(&mut self) -> Result<IrValue> {
+        let (event, mark) = self.next()?;
+        if event != Event::StreamStart {
+            return Err(DecodeError::new(mark, "expected the start of the stream"));
+        }
+        let value = match self.next()? {
+            (Event::StreamEnd, _) => return Ok(IrValue::Null),
+            (Event::DocumentStart, _) => {
+                let (event, mark) = self.next()?;
+                self.node(event, mark)?
+            }
+            (_, mark) => return Err(DecodeError::new(mark, "expected a document")),
+        };
+        let (event, mark) = self.next()?;
+        if event != Event::DocumentEnd {
+            return Err(DecodeError::new(mark, "expected the end of the document"));
+        }
+        match self.next()? {
+            (Event::StreamEnd, _) => Ok(value),
+            (_, mark) => Err(DecodeError::new(mark, "more than one document")),
+        }
+    }
+
+    fn enter(&mut self, mark: Marker) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(DecodeError::new(
+                mark,
+                format!("nesting deeper than {} levels", MAX_DEPTH),
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Decodes the node starting with `event`.
+    fn node(&mut self, event: Event, mark: Marker) -> Result<IrValue> {
+        let (value, anchor) = match event {
+            Event::Alias(id) => return self.alias(id, mark),
+            Event::Scalar(text, style, anchor, tag) => {
+                (scalar(&text, style, tag.as_ref(), mark)?, anchor)
+            }
+            Event::SequenceStart(anchor, tag) => {
+                self.enter(mark)?;
+                let mut items = Vec::new();
+                loop {
+                    match self.next()? {
+                        (Event::SequenceEnd, _) => break,
+                        (event, mark) => items.push(self.node(event, mark)?),
+                    }
+                }
+                self.depth -= 1;
+                (tagged_sequence(items, tag.as_ref(), mark)?, anchor)
+            }
+            Event::MappingStart(anchor, tag) => {
+                self.enter(mark)?;
+                let mut entries = Vec::new();
+                loop {
+                    let key = match self.next()? {
+                        (Event::MappingEnd, _) => break,
+                        (event, mark) => self.key(event, mark)?,
+                    };
+                    let (event, mark) = self.next()?;
+                    entries.push((key, self.node(event, mark)?));
+                }
+                self.depth -= 1;
+                (tagged_mapping(entries, tag.as_ref(), mark)?, anchor)
+            }
+            _ => return Err(DecodeError::new(mark, "expected a value")),
+        };
+        if anchor != 0 {
+            let size = count(&value);
+            self.anchors.insert(
+                anchor,
+                Anchored {
+                    value: value.clone(),
+                    size,
+                },
+            );
+        }
+        Ok(value)
+    }
+
+    /// Decodes a mapping key, which must be an untagged scalar. The key is its source
+    /// text, so `1:` and `"1":` are the same key.
+    fn key(&mut self, event: Event, mark: Marker) -> Result<String> {
+        match event {
+            Event::Scalar(text, _, anchor, None) => {
+                if anchor != 0 {
+                    self.anchors.insert(
+                        anchor,
+                        Anchored {
+                            value: IrValue::from(text.as_str()),
+                            size: 1,
+                        },
+                    );
+                }
+                Ok(text)
+            }
+            Event::Alias(id) => match self.alias(id, mark)? {
+                IrValue::String(s) => Ok(s.into()),
+                _ => Err(DecodeError::new(mark, "map keys must be strings")),
+            },
+            Event::Scalar(..) => Err(DecodeError::new(mark, "map keys cannot be tagged")),
+            _ => Err(DecodeError::new(mark, "map keys must be scalars")),
+        }
+    }
+
+    fn alias(&mut self, id: usize, mark: Marker) -> Result<IrValue> {
+        let anchored = self
+            .anchors
+            .get(&id)
+            .ok_or_else(|| DecodeError::new(mark, "unknown alias"))?;
+        self.expanded += anchored.size;
+        if self.expanded > ALIAS_EXPANSION_LIMIT {
+            return Err(DecodeError::new(mark, "aliases expand to too many values"));
+        }
+        Ok(anchored.value.clone())
+    }
+}
+
+/// Counts the values in `v`, including itself.
+fn count(v: &IrValue) -> usize {
+    match v {
+        IrValue::Array(xs) => 1 + xs.iter().map(count).sum::<usize>(),
+        IrValue::Map(entries) => 1 + entries.iter().map(|(_, v)| count(v)).sum::<usize>(),
+        #[cfg(feature = "osc10")]
+        IrValue::Bundle(b) => 1 + bundle_count(b),
+        _ => 1,
+    }
+}
+
+#[cfg(feature = "osc10")]
+fn bundle_count(b: &IrBundle) -> usize {
+    b.elements
+        .iter()
+        .map(|e| match e {
+            IrBundleElement::Message(v) => count(v),
+            IrBundleElement::Bundle(b) => 1 + bundle_count(b),
+        })
+        .sum()
+}
+
+/// Returns the suffix of a local tag such as `!bundle`, or `None` for other tags.
+fn local(tag: &Tag) -> Option<&str> {
+    (tag.handle == "!").then_some(tag.suffix.as_str())
+}
+
+/// Returns the suffix of a core schema tag such as `!!str`, or `None` for other tags.
+fn core(tag: &Tag) -> Option<&str> {
+    (tag.handle == CORE_HANDLE).then_some(tag.suffix.as_str())
+}
+
+fn tag_name(tag: &Tag) -> String {
+    match tag.handle.as_str() {
+        "!" => format!("!{}", tag.suffix),
+        CORE_HANDLE => format!("!!{}", tag.suffix),
+        handle => format!("!<{}{}>", handle, tag.suffix),
+    }
+}
+
+fn unknown_tag<T>(tag: &Tag, mark: Marker, kind: &str) -> Result<T> {
+    Err(DecodeError::new(
+        mark,
+        format!("unsupported tag {} on a {}", tag_name(tag), kind),
+    ))
+}
+
+fn scalar(text: &str, style: TScalarStyle, tag: Option<&Tag>, mark: Marker) -> Result<IrValue> {
+    let Some(tag) = tag else {
+        return Ok(match style {
+            TScalarStyle::Plain => resolve_plain(text),
+            _ => IrValue::from(text),
+        });
+    };
+    let invalid = |what: &str| DecodeError::new(mark, format!("invalid {}: {:?}", what, text));
+    let name = local(tag).or_else(|| core(tag));
+    Ok(match name {
+        // The non-specific tag `!` forces a string
+        None if tag.handle.is_empty() && tag.suffix == "!" => IrValue::from(text),
+        Some("str") if core(tag).is_some() => IrValue::from(text),
+        Some("null") if core(tag).is_some() => match resolve_plain(text) {
+            IrValue::Null => IrValue::Null,
+            _ => return Err(invalid("null")),
+        },
+        Some("bool") if core(tag).is_some() => match resolve_plain(text) {
+            value @ IrValue::Bool(_) => value,
+            _ => return Err(invalid("boolean")),
+        },
+        Some("int") if core(tag).is_some() => match resolve_plain(text) {
+            value @ (IrValue::Integer(_) | IrValue::Unsigned(_)) => value,
+            _ => return Err(invalid("integer")),
+        },
+        Some("float") if core(tag).is_some() => {
+            IrValue::Float(parse_float(text).ok_or_else(|| invalid("float"))?)
+        }
+        Some("float32") if local(tag).is_some() => {
+            IrValue::Float32(parse_float(text).ok_or_else(|| invalid("float"))? as f32)
+        }
+        Some("unsigned") if local(tag).is_some() => {
+            IrValue::Unsigned(parse_int::<u64>(text).ok_or_else(|| invalid("unsigned integer"))?)
+        }
+        Some("binary") => IrValue::Binary(decode_base64(text).ok_or_else(|| invalid("base64"))?),
+        Some("timestamp") => {
+            IrValue::Timestamp(parse_timestamp(text).ok_or_else(|| invalid("timestamp"))?)
+        }
+        #[cfg(feature = "osc11")]
+        Some("color") if local(tag).is_some() => {
+            let [r, g, b, a] = parse_color(text).ok_or_else(|| {
+                DecodeError::new(
+                    mark,
+                    format!(
+                        "invalid color {:?}, expected \"#RRGGBB\" or \"#RRGGBBAA\" (quote the leading #)",
+                        text
+                    ),
+                )
+            })?;
+            IrValue::Color { r, g, b, a }
+        }
+        _ => return unknown_tag(tag, mark, "scalar"),
+    })
+}
+
+fn tagged_sequence(items: Vec<IrValue>, tag: Option<&Tag>, mark: Marker) -> Result<IrValue> {
+    let Some(tag) = tag else {
+        return Ok(IrValue::Array(items));
+    };
+    match (local(tag), core(tag)) {
+        (_, Some("seq")) => Ok(IrValue::Array(items)),
+        #[cfg(feature = "osc11")]
+        (Some("midi"), _) => {
+            let bytes = items
+                .iter()
+                .map(|item| item.as_integer().and_then(|i| u8::try_from(i).ok()))
+                .collect::<Option<Vec<u8>>>();
+            match bytes.as_deref() {
+                Some(&[port, status, data1, data2]) => Ok(IrValue::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                }),
+                _ => Err(DecodeError::new(
+                    mark,
+                    "!midi expects [port, status, data1, data2] with values 0-255",
+                )),
+            }
+        }
+        _ => unknown_tag(tag, mark, "sequence"),
+    }
+}
+
+fn tagged_mapping(
+    entries: Vec<(String, IrValue)>,
+    tag: Option<&Tag>,
+    mark: Marker,
+) -> Result<IrValue> {
+    let Some(tag) = tag else {
+        return Ok(IrValue::Map(entries));
+    };
+    match (local(tag), core(tag)) {
+        (_, Some("map")) => Ok(IrValue::Map(entries)),
+        (Some("message"), _) => {
+            let mut fields = Fields::new(entries, "!message", mark);
+            let address = match fields.take("address")? {
+                Some(IrValue::String(address)) => address,
+                _ => return Err(fields.error("expects a string `address`")),
+            };
+            let args = match fields.take("args")? {
+                None => Vec::new(),
+                Some(IrValue::Array(args)) => args,
+                Some(_) => return Err(fields.error("expects `args` to be a sequence")),
+            };
+            fields.finish()?;
+            Ok(IrValue::message(address, args))
+        }
+        #[cfg(feature = "osc10")]
+        (Some("bundle"), _) => {
+            let mut fields = Fields::new(entries, "!bundle", mark);
+            let timetag = match fields.take("timetag")? {
+                None => IrTimetag::immediate(),
+                Some(value) => timetag(&value).ok_or_else(|| {
+                    fields.error(
+                        "expects `timetag` to be `immediate`, an NTP integer or a !timestamp in 1900-2036",
+                    )
+                })?,
+            };
+            let elements = match fields.take("elements")? {
+                None => Vec::new(),
+                Some(IrValue::Array(elements)) => elements
+                    .into_iter()
+                    .map(|element| match element {
+                        IrValue::Bundle(bundle) => IrBundleElement::Bundle(bundle),
+                        message => IrBundleElement::Message(message),
+                    })
+                    .collect(),
+                Some(_) => return Err(fields.error("expects `elements` to be a sequence")),
+            };
+            fields.finish()?;
+            Ok(IrValue::Bundle(IrBundle { timetag, elements }))
+        }
+        (Some("ext"), _) => {
+            let mut fields = Fields::new(entries, "!ext", mark);
+            let type_id = match fields.take("type")? {
+                Some(IrValue::Integer(i)) => i8::try_from(i).ok(),
+                _ => None,
+            }
+            .ok_or_else(|| fields.error("expects an integer `type` from -128 to 127"))?;
+            let data = match fields.take("data")? {
+                Some(IrValue::Binary(data)) => data,
+                Some(IrValue::String(s)) => decode_base64(&s)
+                    .ok_or_else(|| fields.error("expects `data` to be base64"))?,
+                _ => return Err(fields.error("expects base64 `data`")),
+            };
+            fields.finish()?;
+            Ok(IrValue::Ext { type_id, data })
+        }
+        (Some("timestamp"), _) => {
+            let mut fields = Fields::new(entries, "!timestamp", mark);
+            let seconds = match fields.take("seconds")? {
+                Some(IrValue::Integer(seconds)) => seconds,
+                _ => return Err(fields.error("expects integer `seconds`")),
+            };
+            let nanos = match fields.take("nanos")? {
+                None => 0,
+                Some(IrValue::Integer(nanos)) if (0..1_000_000_000).contains(&nanos) => nanos as u32,
+                Some(_) => return Err(fields.error("expects `nanos` from 0 to 999999999")),
+            };
+            fields.finish()?;
+            Ok(IrValue::Timestamp(IrTimestamp { seconds, nanos }))
+        }
+        _ => unknown_tag(tag, mark, "mapping"),
+    }
+}
+
+/// Converts the `timetag` field of a `!bundle`.
+#[cfg(feature = "osc10")]
+fn timetag(value: &IrValue) -> Option<IrTimetag> {
+    match value {
+        IrValue::String(s) if s.as_ref() == "immediate" => Some(IrTimetag::immediate()),
+        IrValue::Integer(i) => u64::try_from(*i).ok().map(IrTimetag::from_ntp),
+        IrValue::Unsigned(u) => Some(IrTimetag::from_ntp(*u)),
+        IrValue::Timestamp(ts) => IrTimetag::from_timestamp(ts),
+        _ => None,
+    }
+}
+
+/// The entries of a tagged mapping, taken field by field; leftovers are an error so
+/// that typos in hand-written files do not go unnoticed.
+struct Fields {
+    entries: Vec<(String, IrValue)>,
+    tag: &'static str,
+    mark: Marker,
+}
+
+impl Fields {
+    fn new(entries: Vec<(String, IrValue)>, tag: &'static str, mark: Marker) -> Self {
+        Fields { entries, tag, mark }
+    }
+
+    fn error(&self, message: &str) -> DecodeError {
+        DecodeError::new(self.mark, format!("{} {}", self.tag, message))
+    }
+
+    fn take(&mut self, key: &str) -> Result<Option<IrValue>> {
+        let mut matches = self.entries.iter().filter(|(k, _)| k == key);
+        if matches.next().is_some() && matches.next().is_some() {
+            return Err(self.error(&format!("has more than one `{}`", key)));
+        }
+        Ok(self
+            .entries
+            .iter()
+            .position(|(k, _)| k == key)
+            .map(|i| self.entries.remove(i).1))
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.entries.first() {
+            None => Ok(()),
+            Some((key, _)) => Err(self.error(&format!("has unexpected key `{}`", key))),
+        }
+    }
+}
+
+/// Resolves an untagged plain scalar with the YAML 1.2 core schema: null, booleans,
+/// integers and floats; anything else is a string.
+pub(crate) fn resolve_plain(text: &str) -> IrValue {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return IrValue::Null,
+        "true" | "True" | "TRUE" => return IrValue::Bool(true),
+        "false" | "False" | "FALSE" => return IrValue::Bool(false),
+        _ => {}
+    }
+    if let Some(i) = parse_int::<i64>(text) {
+        return IrValue::Integer(i);
+    }
+    if let Some(u) = parse_int::<u64>(text) {
+        return IrValue::Unsigned(u);
+    }
+    match parse_float(text) {
+        Some(x) => IrValue::Float(x),
+        None => IrValue::from(text),
+    }
+}
+
+/// Parses a core schema integer: decimal with an optional sign, `0o` octal or `0x` hex.
+fn parse_int<T: TryFrom<i128>>(text: &str) -> Option<T> {
+    let (digits, radix) = if let Some(hex) = text.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(octal) = text.strip_prefix("0o") {
+        (octal, 8)
+    } else {
+        (text, 10)
+    };
+    let unsigned = if radix == 10 {
+        digits.strip_prefix(['-', '+']).unwrap_or(digits)
+    } else {
+        digits
+    };
+    if unsigned.is_empty() || !unsigned.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    i128::from_str_radix(digits, radix)
+        .ok()
+        .and_then(|i| T::try_from(i).ok())
+}
+
+/// Parses a core schema float, including `.inf`, `-.inf` and `.nan`.
+fn parse_float(text: &str) -> Option<f64> {
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let sign = if text.starts_with('-') { -1.0 } else { 1.0 };
+    match unsigned {
+        ".inf" | ".Inf" | ".INF" => return Some(sign * f64::INFINITY),
+        ".nan" | ".NaN" | ".NAN" if unsigned.len() == text.len() => return Some(f64::NAN),
+        _ => {}
+    }
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(i) => (&unsigned[..i], Some(&unsigned[i + 1..])),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let valid_mantissa = digits(whole)
+        && digits(fraction)
+        && (!whole.is_empty() || !fraction.is_empty());
+    let valid_exponent = exponent.map_or(true, |e| {
+        let e = e.strip_prefix(['-', '+']).unwrap_or(e);
+        !e.is_empty() && digits(e)
+    });
+    if !valid_mantissa || !valid_exponent {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Decodes base64, ignoring the whitespace of multi-line scalars.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD.decode(compact).ok()
+}
+
+/// Parses an RFC 3339 date/time, or a date alone as midnight UTC.
+fn parse_timestamp(text: &str) -> Option<IrTimestamp> {
+    let text = text.trim();
+    if text.len() == 10 {
+        return IrTimestamp::parse_rfc3339(&format!("{}T00:00:00Z", text));
+    }
+    IrTimestamp::parse_rfc3339(text)
+}
+
+#[cfg(feature = "osc11")]
+fn parse_color(text: &str) -> Option<[u8; 4]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("ff"), 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?, byte(6)?])
+}
@@ -0,0 +1,411 @@
+//! # osc-codec-yaml
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! YAML codec for the `osc-ir` intermediate representation, for cue lists and other
+//! files that people write and review by hand.
+//!
+//! ## Features
+//!
+//! - **Readable**: Block-style output, short argument lists on one line, no `$type`
+//!   wrapper objects
+//! - **Local Tags**: `!message`, `!bundle`, `!timestamp`, `!binary`, `!color` and friends
+//!   for the values plain YAML has no syntax for
+//! - **Cue-Friendly Bundles**: Timetags written as `immediate`, an NTP integer or a
+//!   `!timestamp`
+//! - **Strict Tags**: Unknown tags and unexpected keys in tagged mappings are errors
+//!   with a line and column, so typos do not go unnoticed
+//! - **Safe Decoding**: Nesting limit and a cap on alias expansion
+//! - **Codec Trait**: [`YamlCodec`] implements `osc_ir::Codec` for format-agnostic tooling
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_ir::IrValue;
+//! use osc_codec_yaml::{from_yaml, to_yaml};
+//!
+//! # #[cfg(feature = "osc10")]
+//! # {
+//! let cue = from_yaml(
+//!     "!bundle
+//! timetag: !timestamp 2026-10-18T19:30:00Z
+//! elements:
+//! - !message
+//!   address: /light/1/level
+//!   args: [0.75, !float32 1.5]
+//! - !message
+//!   address: /sound/go
+//! ",
+//! )
+//! .unwrap();
+//!
+//! let IrValue::Bundle(bundle) = &cue else { panic!() };
+//! assert_eq!(bundle.elements.len(), 2);
+//! assert_eq!(from_yaml(&to_yaml(&cue)).unwrap(), cue);
+//! # }
+//! ```
+//!
+//! ## Mapping
+//!
+//! | `IrValue` | YAML |
+//! |-----------|------|
+//! | `Null`, `Bool`, `Integer`, `Float` | core schema scalars; `.inf`, `-.inf`, `.nan` |
+//! | `Unsigned` | integer above `i64::MAX`, otherwise `!unsigned 5` |
+//! | `Float32` | `!float32 0.5` |
+//! | `String` | plain scalar, double-quoted when it would read back as something else |
+//! | `Binary` | `!binary` base64 |
+//! | `Array` / `Map` | sequence / mapping with string keys, order and duplicates preserved |
+//! | OSC message map | `!message {address, args}` |
+//! | `Timestamp` | `!timestamp` RFC 3339, or `{seconds, nanos}` outside years 0000-9999 |
+//! | `Ext` | `!ext {type, data}` |
+//! | `Bundle` | `!bundle {timetag, elements}` |
+//! | `Color` / `Midi` | `!color "#RRGGBBAA"` / `!midi [port, status, data1, data2]` |
+//!
+//! Bundle elements that are `!bundle` values become nested bundles. Plain scalars are
+//! resolved with the YAML 1.2 core schema, so `yes` and `2026-10-18` stay strings;
+//! mapping keys are always their source text.
+
+use osc_ir::{Codec, CodecError, IrValue};
+
+mod de;
+mod ser;
+
+pub use de::DecodeError;
+
+/// Maximum nesting of sequences and mappings accepted by the decoder.
+pub const MAX_DEPTH: usize = 128;
+
+/// [`Codec`] implementation producing UTF-8 YAML text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct YamlCodec;
+
+impl Codec for YamlCodec {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/yaml"
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+        Ok(to_yaml(value).into_bytes())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+        let text = std::str::from_utf8(bytes).map_err(|e| CodecError::decode(e.to_string()))?;
+        from_yaml(text).map_err(|e| CodecError::decode(e.to_string()))
+    }
+}
+
+/// Convert IR -> a YAML document.
+pub fn to_yaml(v: &IrValue) -> String {
+    ser::encode(v)
+}
+
+/// Write the YAML document for IR into a writer.
+pub fn to_yaml_writer<W: std::io::Write>(mut writer: W, v: &IrValue) -> std::io::Result<()> {
+    writer.write_all(to_yaml(v).as_bytes())
+}
+
+/// Convert a YAML document -> IR. The input must hold at most one document; an empty
+/// one is `Null`.
+pub fn from_yaml(text: &str) -> Result<IrValue, DecodeError> {
+    de::Loader::new(text).document()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::IrTimestamp;
+    #[cfg(feature = "osc10")]
+    use osc_ir::{IrBundle, IrTimetag};
+
+    fn map(entries: &[(&str, IrValue)]) -> IrValue {
+        IrValue::Map(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+    }
+
+    #[test]
+    fn roundtrip_all_value_kinds() {
+        let strings = [
+            "", " padded ", "null", "~", "true", "yes", "Off", "12", "-3.5e2", ".inf", "0x1F",
+            "- item", "key: value", "a # b", "#hash", "trailing:", "[1, 2]", "{}", "'quoted'",
+            "\"double\"", "!tag", "&anchor", "*alias", "|", "---", "...", "line\nbreak",
+            "tab\there", "bell\u{7}", "nel\u{85}", "héllo ✓", "/cue/1/go", "2026-10-18",
+        ];
+        let mut values: Vec<IrValue> = strings.iter().map(|s| IrValue::from(*s)).collect();
+        values.extend([
+            IrValue::Null,
+            IrValue::Bool(false),
+            IrValue::Integer(i64::MIN),
+            IrValue::Integer(0),
+            IrValue::Unsigned(7),
+            IrValue::Unsigned(u64::MAX),
+            IrValue::Float(0.1),
+            IrValue::Float(-0.0),
+            IrValue::Float(1e300),
+            IrValue::Float(5e-324),
+            IrValue::Float(f64::NEG_INFINITY),
+            IrValue::Float32(0.1),
+            IrValue::Float32(f32::MAX),
+            IrValue::Float32(f32::INFINITY),
+            IrValue::Binary(vec![]),
+            IrValue::Binary((0..=255).collect()),
+            IrValue::Array(vec![]),
+            IrValue::Map(vec![]),
+            IrValue::Timestamp(IrTimestamp { seconds: 1_700_000_000, nanos: 250_000_000 }),
+            IrValue::Timestamp(IrTimestamp { seconds: i64::MIN, nanos: 1 }),
+            IrValue::Ext { type_id: -128, data: vec![1, 2, 3] },
+            IrValue::Ext { type_id: 5, data: vec![] },
+            map(&[("b", IrValue::from(1)), ("a", IrValue::from(2)), ("b", IrValue::Null), ("1", IrValue::from("x")), ("", IrValue::from("y"))]),
+            IrValue::message("/a", vec![]),
+            IrValue::message("/b", vec![IrValue::from(1), IrValue::Float(2.0), IrValue::from("x, y"), IrValue::Binary(vec![9])]),
+            map(&[("$type", IrValue::from("osc.message")), ("address", IrValue::from("/not-a-message"))]),
+            IrValue::Array(vec![
+                IrValue::Array(vec![IrValue::Array(vec![IrValue::from(1)]), map(&[("k", IrValue::Array(vec![]))])]),
+                map(&[("nested", map(&[("deeper", IrValue::Array(vec![IrValue::from("a"); 40]))]))]),
+                IrValue::message("/in-seq", vec![IrValue::Null]),
+            ]),
+        ]);
+        for value in values {
+            let text = to_yaml(&value);
+            let decoded = from_yaml(&text).unwrap_or_else(|e| panic!("{}:\n{}", e, text));
+            let same = match (&decoded, &value) {
+                (IrValue::Float32(a), IrValue::Float32(b)) => a.to_bits() == b.to_bits(),
+                (IrValue::Float(a), IrValue::Float(b)) => a.to_bits() == b.to_bits(),
+                _ => decoded == value,
+            };
+            assert!(same, "{:?} was written as\n{}and read back as {:?}", value, text, decoded);
+        }
+
+        let nan = from_yaml(&to_yaml(&IrValue::Float32(f32::NAN))).unwrap();
+        assert!(matches!(nan, IrValue::Float32(x) if x.is_nan()));
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn roundtrip_bundles() {
+        let mut nested = IrBundle::new(IrTimetag::from_ntp(u64::MAX));
+        nested.add_message(IrValue::message("/n", vec![IrValue::from(1)]));
+        nested.add_bundle(IrBundle::immediate());
+        let mut bundle = IrBundle::new(IrTimetag::from_ntp(2));
+        bundle.add_message(IrValue::message("/a", vec![IrValue::Float32(0.5), IrValue::from("x")]));
+        bundle.add_bundle(nested);
+        bundle.add_message(IrValue::Null);
+        bundle.add_message(map(&[("key", IrValue::Array(vec![IrValue::from(1)]))]));
+
+        for value in [
+            IrValue::Bundle(bundle.clone()),
+            IrValue::Bundle(IrBundle::immediate()),
+            map(&[("cue", IrValue::Bundle(bundle.clone()))]),
+            IrValue::Array(vec![IrValue::Bundle(bundle)]),
+        ] {
+            let text = to_yaml(&value);
+            assert_eq!(from_yaml(&text).unwrap(), value, "{}", text);
+        }
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn cue_file_loads_into_bundle_and_converts_to_msgpack() {
+        let cue = r##"
+# Act 1, scene 2
+!bundle
+timetag: !timestamp 2026-10-18T19:30:00.5Z
+elements:
+  - !message
+    address: /light/1/level
+    args: [0.75, 255, !float32 0.5, "#1 house"]
+  - !message { address: /sound/go }
+  - !bundle
+    timetag: immediate
+    elements:
+      - !message
+        address: /video/clip
+        args:
+          - intro.mov
+          - !binary AAEC
+          - !timestamp 2026-10-18
+"##;
+        let value = from_yaml(cue).unwrap();
+
+        let mut fade = IrBundle::immediate();
+        fade.add_message(IrValue::message(
+            "/video/clip",
+            vec![
+                IrValue::from("intro.mov"),
+                IrValue::Binary(vec![0, 1, 2]),
+                IrValue::Timestamp(IrTimestamp { seconds: 1_792_281_600, nanos: 0 }),
+            ],
+        ));
+        let start = IrTimestamp { seconds: 1_792_351_800, nanos: 500_000_000 };
+        let mut expected = IrBundle::new(IrTimetag::from_timestamp(&start).unwrap());
+        expected.add_message(IrValue::message(
+            "/light/1/level",
+            vec![IrValue::Float(0.75), IrValue::from(255), IrValue::Float32(0.5), IrValue::from("#1 house")],
+        ));
+        expected.add_message(IrValue::message("/sound/go", vec![]));
+        expected.add_bundle(fade);
+        assert_eq!(value, IrValue::Bundle(expected));
+
+        let bytes = osc_codec_msgpack::try_to_msgpack(&value).unwrap();
+        assert_eq!(osc_codec_msgpack::try_from_msgpack(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn writes_readable_yaml() {
+        let value = map(&[
+            ("name", IrValue::from("Act 1")),
+            ("levels", IrValue::Array(vec![IrValue::from(1), IrValue::Float(0.5)])),
+            ("go", IrValue::message("/go", vec![IrValue::from("now")])),
+            ("notes", IrValue::Array(vec![map(&[("at", IrValue::from(1)), ("text", IrValue::from("yes"))])])),
+        ]);
+        assert_eq!(
+            to_yaml(&value),
+            "name: Act 1\n\
+             levels: [1, 0.5]\n\
+             go: !message\n  address: /go\n  args: [now]\n\
+             notes:\n  - at: 1\n    text: \"yes\"\n"
+        );
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn color_and_midi_tags() {
+        let value = from_yaml("[!color \"#ff8000\", !color 01020304, !midi [0, 144, 60, 127]]").unwrap();
+        assert_eq!(
+            value,
+            IrValue::Array(vec![
+                IrValue::color(255, 128, 0, 255),
+                IrValue::color(1, 2, 3, 4),
+                IrValue::midi(0, 144, 60, 127),
+            ])
+        );
+        assert_eq!(from_yaml(&to_yaml(&value)).unwrap(), value);
+
+        let err = from_yaml("a: !color #ff0000\n").unwrap_err();
+        assert!(err.message().contains("quote the leading #"), "{}", err);
+        assert!(from_yaml("!midi [0, 144, 60, 256]").is_err());
+        assert!(from_yaml("!midi [0, 144, 60]").is_err());
+    }
+
+    #[test]
+    fn resolves_core_schema_scalars() {
+        let doc = "[~, null, '', True, FALSE, 0o17, 0x1f, -12, +3, 18446744073709551615, -9223372036854775809, \
+                   1., .5, 1e3, -.INF, .NaN, !!str 12, ! true, !!float 3, !!binary AQI=, yes, 1_000, 0x, .]";
+        let IrValue::Array(items) = from_yaml(doc).unwrap() else { panic!() };
+        let expected = [
+            IrValue::Null,
+            IrValue::Null,
+            IrValue::from(""),
+            IrValue::Bool(true),
+            IrValue::Bool(false),
+            IrValue::Integer(15),
+            IrValue::Integer(31),
+            IrValue::Integer(-12),
+            IrValue::Integer(3),
+            IrValue::Unsigned(u64::MAX),
+            IrValue::Float(-9223372036854775809.0),
+            IrValue::Float(1.0),
+            IrValue::Float(0.5),
+            IrValue::Float(1000.0),
+            IrValue::Float(f64::NEG_INFINITY),
+        ];
+        assert_eq!(items[..expected.len()], expected);
+        assert!(matches!(items[15], IrValue::Float(x) if x.is_nan()));
+        assert_eq!(
+            items[16..],
+            [
+                IrValue::from("12"),
+                IrValue::from("true"),
+                IrValue::Float(3.0),
+                IrValue::Binary(vec![1, 2]),
+                IrValue::from("yes"),
+                IrValue::from("1_000"),
+                IrValue::from("0x"),
+                IrValue::from("."),
+            ]
+        );
+        assert_eq!(from_yaml("").unwrap(), IrValue::Null);
+        assert_eq!(from_yaml("# only a comment\n").unwrap(), IrValue::Null);
+    }
+
+    #[test]
+    fn aliases_are_expanded_within_limits() {
+        let value = from_yaml("base: &b {level: 1}\ncopy: *b\n&k key: *k\n").unwrap();
+        assert_eq!(
+            value,
+            map(&[
+                ("base", map(&[("level", IrValue::from(1))])),
+                ("copy", map(&[("level", IrValue::from(1))])),
+                ("key", IrValue::from("key")),
+            ])
+        );
+
+        let mut laughs = String::from("a: &a [x, x, x, x, x, x, x, x, x, x]\n");
+        for (name, prev) in ["b", "c", "d", "e", "f", "g", "h"].iter().zip(["a", "b", "c", "d", "e", "f", "g"]) {
+            laughs.push_str(&format!("{0}: &{0} [*{1}, *{1}, *{1}, *{1}, *{1}, *{1}, *{1}, *{1}, *{1}, *{1}]\n", name, prev));
+        }
+        let err = from_yaml(&laughs).unwrap_err();
+        assert!(err.message().contains("aliases"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_invalid_documents_with_positions() {
+        let cases = [
+            ("a: [1, 2\n", 2, 1),
+            ("a: 1\n---\nb: 2\n", 2, 1),
+            ("x: !colour red\n", 1, 12),
+            ("- !message {address: /a, arg: [1]}\n", 1, 12),
+            ("- !message {args: [1]}\n", 1, 12),
+            ("!ext {type: 300, data: AA==}", 1, 6),
+            ("!ext {type: 1, data: '***'}", 1, 6),
+            ("!binary '***'", 1, 9),
+            ("!timestamp 2026-13-01T00:00:00Z", 1, 12),
+            ("!timestamp {seconds: 1, nanos: 1000000000}", 1, 12),
+            ("!unsigned -1", 1, 11),
+            ("!float32 abc", 1, 10),
+            ("!!int 1.5", 1, 7),
+            ("? [a]\n: 1\n", 1, 3),
+            ("!!str key: 1\n", 1, 7),
+            ("*missing", 1, 1),
+        ];
+        for (doc, line, column) in cases {
+            let err = from_yaml(doc).unwrap_err();
+            assert_eq!((err.line(), err.column()), (line, column), "{:?}: {}", doc, err);
+        }
+        assert!(from_yaml("!bundle {timetag: 1}").is_ok() == cfg!(feature = "osc10"));
+        #[cfg(feature = "osc10")]
+        {
+            for doc in ["!bundle {timetag: -1}", "!bundle {timetag: !timestamp 2040-01-01}", "!bundle {elements: {}}"] {
+                assert!(from_yaml(doc).is_err(), "{}", doc);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        let deep = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(from_yaml(&deep).is_ok());
+        let deeper = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        let err = from_yaml(&deeper).unwrap_err();
+        assert!(err.message().contains("nesting"), "{}", err);
+    }
+
+    #[test]
+    fn codec_trait() {
+        let codec = YamlCodec;
+        assert_eq!(codec.name(), "yaml");
+        assert_eq!(codec.mime_type(), "application/yaml");
+        let value = map(&[("a", IrValue::Array(vec![IrValue::from(1)]))]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+        assert!(codec.decode(&[0xFF]).is_err());
+        assert!(codec.decode(b"a: [").is_err());
+
+        let mut out = Vec::new();
+        to_yaml_writer(&mut out, &value).unwrap();
+        assert_eq!(out, bytes);
+    }
+}
@@ -0,0 +1,303 @@
+//! Encoding of `IrValue` into block-style YAML.
+
+use base64::Engine;
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement};
+use osc_ir::IrValue;
+
+use crate::de::resolve_plain;
+
+/// Longest flow sequence (`[1, 2, 3]`) written on one line; longer ones use block style.
+const FLOW_WIDTH: usize = 60;
+
+/// A value laid out for writing.
+enum Node {
+    /// Fits on one line: scalars, empty collections, short sequences of scalars
+    Inline(String),
+    /// Needs lines of its own, optionally introduced by a tag such as `!bundle`
+    Block { tag: Option<&'static str>, body: Body },
+}
+
+enum Body {
+    Map(Vec<(String, Node)>),
+    Seq(Vec<Node>),
+}
+
+/// Writes `v` as a YAML document ending in a newline.
+pub(crate) fn encode(v: &IrValue) -> String {
+    let mut out = String::new();
+    match node(v) {
+        Node::Inline(text) => {
+            out.push_str(&text);
+            out.push('\n');
+        }
+        Node::Block { tag, body } => {
+            if let Some(tag) = tag {
+                out.push_str(tag);
+                out.push('\n');
+            }
+            write_body(&mut out, &body, 0, false);
+        }
+    }
+    out
+}
+
+/// Writes the lines of a block collection at `indent`. With `continued`, the first
+/// line goes right after a `- ` already written by the parent sequence.
+fn write_body(out: &mut String, body: &Body, indent: usize, mut continued: bool) {
+    let mut start_line = |out: &mut String| {
+        if !continued {
+            out.extend(std::iter::repeat(' ').take(indent));
+        }
+        continued = false;
+    };
+    match body {
+        Body::Map(entries) => {
+            for (key, value) in entries {
+                start_line(out);
+                out.push_str(key);
+                out.push(':');
+                match value {
+                    Node::Inline(text) => {
+                        out.push(' ');
+                        out.push_str(text);
+                        out.push('\n');
+                    }
+                    Node::Block { tag, body } => {
+                        if let Some(tag) = tag {
+                            out.push(' ');
+                            out.push_str(tag);
+                        }
+                        out.push('\n');
+                        write_body(out, body, indent + 2, false);
+                    }
+                }
+            }
+        }
+        Body::Seq(items) => {
+            for item in items {
+                start_line(out);
+                out.push_str("- ");
+                match item {
+                    Node::Inline(text) => {
+                        out.push_str(text);
+                        out.push('\n');
+                    }
+                    Node::Block { tag: Some(tag), body } => {
+                        out.push_str(tag);
+                        out.push('\n');
+                        write_body(out, body, indent + 2, false);
+                    }
+                    Node::Block { tag: None, body } => write_body(out, body, indent + 2, true),
+                }
+            }
+        }
+    }
+}
+
+fn node(v: &IrValue) -> Node {
+    match v {
+        IrValue::Array(xs) if xs.is_empty() => Node::Inline("[]".into()),
+        IrValue::Array(xs) => sequence(xs.iter().map(node).collect()),
+        IrValue::Map(entries) if entries.is_empty() => Node::Inline("{}".into()),
+        IrValue::Map(entries) => match as_message(entries) {
+            Some((address, args)) => Node::Block {
+                tag: Some("!message"),
+                body: Body::Map(vec![
+                    ("address".into(), Node::Inline(string(address))),
+                    ("args".into(), node(args)),
+                ]),
+            },
+            None => Node::Block {
+                tag: None,
+                body: Body::Map(entries.iter().map(|(k, v)| (string(k), node(v))).collect()),
+            },
+        },
+        #[cfg(feature = "osc10")]
+        IrValue::Bundle(b) => bundle(b),
+        _ => Node::Inline(scalar(v)),
+    }
+}
+
+/// Lays out a sequence, on one line when it only holds short scalars.
+fn sequence(items: Vec<Node>) -> Node {
+    let inline: Option<Vec<&str>> = items
+        .iter()
+        .map(|item| match item {
+            Node::Inline(text) => Some(text.as_str()),
+            Node::Block { .. } => None,
+        })
+        .collect();
+    match inline {
+        Some(texts) if texts.iter().map(|t| t.len() + 2).sum::<usize>() <= FLOW_WIDTH => {
+            Node::Inline(format!("[{}]", texts.join(", ")))
+        }
+        _ => Node::Block {
+            tag: None,
+            body: Body::Seq(items),
+        },
+    }
+}
+
+/// Recognizes the `{"$type": "osc.message", "address", "args"}` map written as `!message`.
+fn as_message(entries: &[(String, IrValue)]) -> Option<(&str, &IrValue)> {
+    match entries {
+        [(type_key, IrValue::String(type_name)), (address_key, IrValue::String(address)), (args_key, args @ IrValue::Array(_))]
+            if type_key == "$type"
+                && type_name.as_ref() == "osc.message"
+                && address_key == "address"
+                && args_key == "args" =>
+        {
+            Some((address, args))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(feature = "osc10")]
+fn bundle(b: &IrBundle) -> Node {
+    let timetag = if b.timetag.is_immediate() {
+        "immediate".to_string()
+    } else {
+        b.timetag.value.to_string()
+    };
+    let elements = if b.elements.is_empty() {
+        Node::Inline("[]".into())
+    } else {
+        Node::Block {
+            tag: None,
+            body: Body::Seq(
+                b.elements
+                    .iter()
+                    .map(|element| match element {
+                        IrBundleElement::Message(msg) => node(msg),
+                        IrBundleElement::Bundle(nested) => bundle(nested),
+                    })
+                    .collect(),
+            ),
+        }
+    };
+    Node::Block {
+        tag: Some("!bundle"),
+        body: Body::Map(vec![
+            ("timetag".into(), Node::Inline(timetag)),
+            ("elements".into(), elements),
+        ]),
+    }
+}
+
+fn scalar(v: &IrValue) -> String {
+    match v {
+        IrValue::Null => "null".into(),
+        IrValue::Bool(b) => b.to_string(),
+        IrValue::Integer(i) => i.to_string(),
+        // Values above i64::MAX read back as unsigned on their own
+        IrValue::Unsigned(u) if *u > i64::MAX as u64 => u.to_string(),
+        IrValue::Unsigned(u) => format!("!unsigned {}", u),
+        IrValue::Float(x) => float(*x),
+        IrValue::Float32(x) => format!("!float32 {}", float32(*x)),
+        IrValue::String(s) => string(s),
+        IrValue::Binary(data) => format!("!binary {}", base64(data)),
+        IrValue::Timestamp(ts) => match ts.to_rfc3339() {
+            Some(text) if ts.nanos < 1_000_000_000 => format!("!timestamp {}", text),
+            _ => format!("!timestamp {{seconds: {}, nanos: {}}}", ts.seconds, ts.nanos),
+        },
+        IrValue::Ext { type_id, data } => {
+            format!("!ext {{type: {}, data: !binary {}}}", type_id, base64(data))
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => {
+            format!("!color \"#{:02x}{:02x}{:02x}{:02x}\"", r, g, b, a)
+        }
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => format!("!midi [{}, {}, {}, {}]", port, status, data1, data2),
+        // Collections are handled by `node`; other variants osc-ir enables through
+        // features this crate does not
+        #[allow(unreachable_patterns)]
+        _ => "null".into(),
+    }
+}
+
+fn float(x: f64) -> String {
+    if x.is_nan() {
+        ".nan".into()
+    } else if x.is_infinite() {
+        if x > 0.0 { ".inf" } else { "-.inf" }.into()
+    } else {
+        // Debug formatting always keeps a `.` or exponent, so the value reads back as a float
+        format!("{:?}", x)
+    }
+}
+
+fn float32(x: f32) -> String {
+    if x.is_finite() {
+        format!("{:?}", x)
+    } else {
+        float(x.into())
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    if data.is_empty() {
+        "\"\"".into()
+    } else {
+        base64::engine::general_purpose::STANDARD.encode(data)
+    }
+}
+
+/// Writes a string plain when that reads back as the same string, double-quoted otherwise.
+fn string(s: &str) -> String {
+    if is_plain_safe(s) {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\x{:02X}", c as u32))
+            }
+            '\u{85}' | '\u{2028}' | '\u{2029}' | '\u{feff}' => {
+                out.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn is_plain_safe(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    // YAML 1.1 booleans and merge/value keys, which older parsers treat specially
+    const YAML11_BOOLS: [&str; 18] = [
+        "y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off",
+        "Off", "OFF", "=", "<<",
+    ];
+    !"-?:,[]{}#&*!|>'\"%@`".contains(first)
+        && !first.is_whitespace()
+        && !s.ends_with(char::is_whitespace)
+        && !s.ends_with(':')
+        && !s.starts_with("...")
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.contains([',', '[', ']', '{', '}'])
+        && !s.chars().any(|c| {
+            c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}')
+        })
+        && !YAML11_BOOLS.contains(&s)
+        && matches!(resolve_plain(s), IrValue::String(_))
+}
@@ -0,0 +1,154 @@
+//! Reconstruction of messages from CSV tables.
+
+use std::io::Read;
+
+use base64::Engine;
+use osc_ir::{IrTimestamp, IrValue};
+
+use crate::{parse_seconds, Error, TIME_COLUMN};
+
+/// Checks a type tag string, with or without its leading `,`, and returns the tags.
+fn type_tags(tags: &str) -> Result<Vec<char>, Error> {
+    let tags = tags.strip_prefix(',').unwrap_or(tags);
+    tags.chars()
+        .map(|tag| match tag {
+            'i' | 'h' | 'f' | 'd' | 's' | 'S' | 'b' | 't' | 'T' | 'F' | 'N' => Ok(tag),
+            #[cfg(feature = "osc11")]
+            'r' | 'm' => Ok(tag),
+            _ => Err(Error::InvalidTypeTag(tag)),
+        })
+        .collect()
+}
+
+/// Reads a `time,arg0,arg1,...` table back into messages sent to `address`.
+///
+/// `type_tags` gives the OSC type of each argument column, such as `fff` or `,sif`;
+/// the header must have exactly one argument column per tag. Returns the receive
+/// time and message of each row. Times may be decimal Unix seconds or RFC 3339.
+pub fn read_csv<R: Read>(
+    reader: R,
+    address: &str,
+    type_tags: &str,
+) -> Result<Vec<(IrTimestamp, IrValue)>, Error> {
+    let tags = self::type_tags(type_tags)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(reader);
+    let header = reader.headers()?.clone();
+    if header.get(0).map(str::trim) != Some(TIME_COLUMN) {
+        return Err(Error::Header(format!(
+            "the first column must be `{}`",
+            TIME_COLUMN
+        )));
+    }
+    if header.len() != tags.len() + 1 {
+        return Err(Error::Header(format!(
+            "{} argument columns for {} type tags",
+            header.len() - 1,
+            tags.len()
+        )));
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line());
+        let invalid = |column: usize, reason: String| Error::InvalidCell {
+            line,
+            column: header.get(column).unwrap_or_default().to_string(),
+            reason,
+        };
+        let time_cell = record.get(0).unwrap_or_default().trim();
+        let time = parse_time(time_cell)
+            .ok_or_else(|| invalid(0, format!("invalid time `{}`", time_cell)))?;
+        let args = tags
+            .iter()
+            .enumerate()
+            .map(|(i, &tag)| {
+                let text = record.get(i + 1).unwrap_or_default();
+                arg(tag, text).map_err(|reason| invalid(i + 1, reason))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push((time, IrValue::message(address, args)));
+    }
+    Ok(rows)
+}
+
+fn parse_time(text: &str) -> Option<IrTimestamp> {
+    parse_seconds(text).or_else(|| IrTimestamp::parse_rfc3339(text))
+}
+
+/// Parses one cell as the type of its tag.
+fn arg(tag: char, text: &str) -> Result<IrValue, String> {
+    if matches!(tag, 's' | 'S') {
+        return Ok(IrValue::from(text));
+    }
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(match tag {
+            'T' => IrValue::Bool(true),
+            'F' => IrValue::Bool(false),
+            _ => IrValue::Null,
+        });
+    }
+    let expected = |what: &str| format!("expected {}, found `{}`", what, text);
+    Ok(match tag {
+        'i' => match text.parse::<i32>() {
+            Ok(i) => IrValue::from(i),
+            Err(_) => return Err(expected("a 32-bit integer")),
+        },
+        'h' => match (text.parse::<i64>(), text.parse::<u64>()) {
+            (Ok(i), _) => IrValue::Integer(i),
+            (_, Ok(u)) => IrValue::Unsigned(u),
+            _ => return Err(expected("a 64-bit integer")),
+        },
+        'f' => match text.parse::<f32>() {
+            Ok(x) => IrValue::Float32(x),
+            Err(_) => return Err(expected("a number")),
+        },
+        'd' => match text.parse::<f64>() {
+            Ok(x) => IrValue::Float(x),
+            Err(_) => return Err(expected("a number")),
+        },
+        'b' => match base64::engine::general_purpose::STANDARD.decode(text) {
+            Ok(data) => IrValue::Binary(data),
+            Err(_) => return Err(expected("base64")),
+        },
+        't' => match parse_time(text) {
+            Some(ts) => IrValue::Timestamp(ts),
+            None => return Err(expected("an RFC 3339 date-time or Unix seconds")),
+        },
+        'T' | 'F' => match text {
+            "true" | "1" => IrValue::Bool(true),
+            "false" | "0" => IrValue::Bool(false),
+            _ => return Err(expected("`true` or `false`")),
+        },
+        'N' => return Err(expected("an empty cell")),
+        #[cfg(feature = "osc11")]
+        'r' => match hex4(text.strip_prefix('#').unwrap_or(text)) {
+            Some([r, g, b, a]) => IrValue::Color { r, g, b, a },
+            None => return Err(expected("a color `#RRGGBBAA`")),
+        },
+        #[cfg(feature = "osc11")]
+        'm' => match hex4(text) {
+            Some([port, status, data1, data2]) => IrValue::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            },
+            None => return Err(expected("8 hex digits")),
+        },
+        // `type_tags` rejects every other tag
+        _ => return Err(format!("unsupported type tag `{}`", tag)),
+    })
+}
+
+#[cfg(feature = "osc11")]
+fn hex4(text: &str) -> Option<[u8; 4]> {
+    if text.len() != 8 || !text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&text[i..i + 2], 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?, byte(6)?])
+}
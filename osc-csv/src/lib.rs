@@ -0,0 +1,574 @@
+//! # osc-csv
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! CSV export and import of per-address OSC time series.
+//!
+//! Sensor addresses usually carry a fixed list of numeric arguments, which fits a
+//! spreadsheet well: one table per address, one row per message, a `time` column and
+//! one column per argument. [`CsvExporter`] groups timestamped `osc-ir` messages by
+//! address into such tables; [`read_csv`] turns a table back into messages, given the
+//! address and the OSC type tags of its columns.
+//!
+//! ## Features
+//!
+//! - **Per-Address Tables**: `time,arg0,arg1,...` with as many argument columns as
+//!   the longest message sent to the address
+//! - **Plain Cells**: Numbers, booleans and strings are written as text; times as
+//!   decimal Unix seconds
+//! - **Typed Import**: Type tags such as `fff` or `,sif` choose the `IrValue` of each
+//!   column, with errors naming the line and column of a bad cell
+//! - **Directory Output**: [`write_csv_dir`] writes one file per address
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_csv::{read_csv, CsvExporter};
+//! use osc_ir::{IrTimestamp, IrValue};
+//!
+//! let args = vec![IrValue::Float32(0.5), IrValue::Float32(-1.0)];
+//! let message = IrValue::message("/imu/accel", args);
+//! let received = IrTimestamp { seconds: 1_760_000_000, nanos: 250_000_000 };
+//!
+//! let mut exporter = CsvExporter::new();
+//! exporter.push(&received, &message).unwrap();
+//! let tables = exporter.finish();
+//!
+//! let mut csv = Vec::new();
+//! tables[0].write_csv(&mut csv).unwrap();
+//! assert_eq!(csv, b"time,arg0,arg1\n1760000000.25,0.5,-1\n");
+//!
+//! let rows = read_csv(csv.as_slice(), "/imu/accel", "ff").unwrap();
+//! assert_eq!(rows, vec![(received, message)]);
+//! ```
+//!
+//! ## Cells
+//!
+//! | Type tag | `IrValue` | Cell |
+//! |----------|-----------|------|
+//! | `i`, `h` | `Integer` (`Unsigned` above `i64::MAX`) | decimal |
+//! | `f` | `Float32` | decimal, `inf`, `-inf`, `NaN` |
+//! | `d` | `Float` | decimal, `inf`, `-inf`, `NaN` |
+//! | `s`, `S` | `String` | text |
+//! | `b` | `Binary` | base64 |
+//! | `t` | `Timestamp` | RFC 3339 (decimal Unix seconds also accepted) |
+//! | `T`, `F` | `Bool` | `true` / `false`; empty means the value of the tag |
+//! | `N` | `Null` | empty |
+//! | `r` | `Color` | `#RRGGBBAA` (OSC 1.1) |
+//! | `m` | `Midi` | 8 hex digits: port, status, data1, data2 (OSC 1.1) |
+//!
+//! Nil arguments and arguments missing from shorter messages are written as empty
+//! cells, and empty cells read back as `Null` except in string columns. Arrays, maps
+//! and ext values have no cell form and are rejected by the exporter.
+
+use std::fmt;
+
+use osc_ir::IrTimestamp;
+
+mod de;
+mod ser;
+
+pub use de::read_csv;
+pub use ser::{write_csv_dir, AddressTable, CsvExporter};
+
+/// Name of the first column of every table.
+pub const TIME_COLUMN: &str = "time";
+
+/// Error produced while exporting or importing tables.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value pushed is neither a message map nor a bundle, so it has no table
+    NotAMessage,
+    /// An argument cannot be written as a single CSV cell, such as an array or a map
+    UnsupportedArgument {
+        /// Address of the message
+        address: String,
+        /// Position of the argument
+        index: usize,
+    },
+    /// The type tag string names a type this crate cannot import
+    InvalidTypeTag(char),
+    /// The header is not `time,arg0,...` with one column per type tag
+    Header(String),
+    /// A cell does not hold a value of its column type
+    InvalidCell {
+        /// 1-based line of the cell
+        line: u64,
+        /// Name of the column
+        column: String,
+        /// What was wrong with it
+        reason: String,
+    },
+    /// Error from the CSV reader or writer
+    Csv(csv::Error),
+    /// I/O error while writing files
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotAMessage => f.write_str("value is not an OSC message or bundle"),
+            Error::UnsupportedArgument { address, index } => {
+                write!(
+                    f,
+                    "argument {} of `{}` has no CSV cell form",
+                    index, address
+                )
+            }
+            Error::InvalidTypeTag(tag) => write!(f, "unsupported type tag `{}`", tag),
+            Error::Header(reason) => write!(f, "invalid header: {}", reason),
+            Error::InvalidCell {
+                line,
+                column,
+                reason,
+            } => write!(f, "line {} column `{}`: {}", line, column, reason),
+            Error::Csv(e) => write!(f, "csv: {}", e),
+            Error::Io(e) => write!(f, "io: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Csv(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Csv(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Formats a time as decimal Unix seconds without trailing zeros, e.g. `1760000000.25`.
+pub(crate) fn format_seconds(ts: &IrTimestamp) -> String {
+    let sign = if ts.seconds < 0 { "-" } else { "" };
+    let (seconds, nanos) = if ts.seconds < 0 && ts.nanos > 0 {
+        ((ts.seconds + 1).unsigned_abs(), 1_000_000_000 - ts.nanos)
+    } else {
+        (ts.seconds.unsigned_abs(), ts.nanos)
+    };
+    if nanos == 0 {
+        return format!("{}{}", sign, seconds);
+    }
+    let fraction = format!("{:09}", nanos);
+    format!("{}{}.{}", sign, seconds, fraction.trim_end_matches('0'))
+}
+
+/// Parses decimal Unix seconds with up to nine fractional digits.
+pub(crate) fn parse_seconds(text: &str) -> Option<IrTimestamp> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) || fraction.len() > 9 {
+        return None;
+    }
+    let seconds: i64 = whole.parse().ok()?;
+    let nanos = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u32>().ok()? * 10u32.pow(9 - fraction.len() as u32)
+    };
+    Some(match (negative, nanos) {
+        (false, _) => IrTimestamp { seconds, nanos },
+        (true, 0) => IrTimestamp {
+            seconds: -seconds,
+            nanos: 0,
+        },
+        (true, _) => IrTimestamp {
+            seconds: -seconds - 1,
+            nanos: 1_000_000_000 - nanos,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "osc10")]
+    use osc_ir::IrBundle;
+    use osc_ir::IrValue;
+
+    fn at(seconds: i64) -> IrTimestamp {
+        IrTimestamp { seconds, nanos: 0 }
+    }
+
+    fn csv_text(table: &AddressTable) -> String {
+        let mut out = Vec::new();
+        table.write_csv(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn groups_by_address_and_pads_short_messages() {
+        let mut exporter = CsvExporter::new();
+        exporter
+            .push(&at(2), &IrValue::message("/b", vec![IrValue::from("x, y")]))
+            .unwrap();
+        exporter
+            .push(
+                &at(1),
+                &IrValue::message("/a", vec![IrValue::from(1), IrValue::Float(0.5)]),
+            )
+            .unwrap();
+        exporter
+            .push(&at(3), &IrValue::message("/a", vec![IrValue::Null]))
+            .unwrap();
+        assert_eq!(exporter.len(), 3);
+
+        let tables = exporter.finish();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].address, "/a");
+        assert_eq!(tables[0].width(), 2);
+        assert_eq!(csv_text(&tables[0]), "time,arg0,arg1\n1,1,0.5\n3,,\n");
+        assert_eq!(csv_text(&tables[1]), "time,arg0\n2,\"x, y\"\n");
+    }
+
+    #[test]
+    fn tables_without_arguments_have_only_time() {
+        let tables = {
+            let mut exporter = CsvExporter::new();
+            exporter
+                .push(&at(5), &IrValue::message("/ping", vec![]))
+                .unwrap();
+            exporter.finish()
+        };
+        assert_eq!(csv_text(&tables[0]), "time\n5\n");
+        assert_eq!(
+            read_csv(csv_text(&tables[0]).as_bytes(), "/ping", "").unwrap(),
+            vec![(at(5), IrValue::message("/ping", vec![]))]
+        );
+    }
+
+    #[test]
+    fn export_import_roundtrip() {
+        let args = vec![
+            IrValue::from(-7),
+            IrValue::Integer(i64::MAX),
+            IrValue::Float32(0.1),
+            IrValue::Float(f64::NEG_INFINITY),
+            IrValue::from("quote \" and\nnewline"),
+            IrValue::Binary(vec![0, 1, 2, 255]),
+            IrValue::Timestamp(IrTimestamp {
+                seconds: 1_760_000_000,
+                nanos: 5,
+            }),
+            IrValue::Bool(true),
+            IrValue::Bool(false),
+            IrValue::Null,
+        ];
+        let time = IrTimestamp {
+            seconds: 1_760_000_000,
+            nanos: 123_456_789,
+        };
+        let msg = IrValue::message("/all", args);
+        let mut exporter = CsvExporter::new();
+        exporter.push(&time, &msg).unwrap();
+        let text = csv_text(&exporter.finish()[0]);
+
+        let rows = read_csv(text.as_bytes(), "/all", ",ihfdsbtTFN").unwrap();
+        assert_eq!(rows, vec![(time, msg)]);
+    }
+
+    #[test]
+    fn unsigned_values_above_i64_read_back() {
+        let text = "time,arg0\n0,18446744073709551615\n1,-1\n";
+        let rows = read_csv(text.as_bytes(), "/u", "h").unwrap();
+        assert_eq!(
+            rows[0].1,
+            IrValue::message("/u", vec![IrValue::Unsigned(u64::MAX)])
+        );
+        assert_eq!(rows[1].1, IrValue::message("/u", vec![IrValue::from(-1)]));
+    }
+
+    #[test]
+    fn import_accepts_rfc3339_times_and_empty_cells() {
+        let text = "time,arg0,arg1,arg2\n2025-10-09T08:53:20.5Z,,,\n";
+        let rows = read_csv(text.as_bytes(), "/e", "fsT").unwrap();
+        assert_eq!(
+            rows[0].0,
+            IrTimestamp {
+                seconds: 1_760_000_000,
+                nanos: 500_000_000
+            }
+        );
+        assert_eq!(
+            rows[0].1,
+            IrValue::message(
+                "/e",
+                vec![IrValue::Null, IrValue::from(""), IrValue::Bool(true)]
+            )
+        );
+    }
+
+    #[test]
+    fn import_errors_name_the_cell() {
+        let text = "time,arg0,arg1\n1,0.5,2\n2,0.5,two\n";
+        match read_csv(text.as_bytes(), "/x", "fi").unwrap_err() {
+            Error::InvalidCell {
+                line,
+                column,
+                reason,
+            } => {
+                assert_eq!(line, 3);
+                assert_eq!(column, "arg1");
+                assert_eq!(reason, "expected a 32-bit integer, found `two`");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        let bad_time = "time,arg0\nsoon,1\n";
+        assert!(matches!(
+            read_csv(bad_time.as_bytes(), "/x", "i"),
+            Err(Error::InvalidCell { line: 2, .. })
+        ));
+        assert!(matches!(
+            read_csv(text.as_bytes(), "/x", "f"),
+            Err(Error::Header(_))
+        ));
+        assert!(matches!(
+            read_csv("t,arg0\n".as_bytes(), "/x", "f"),
+            Err(Error::Header(_))
+        ));
+        assert!(matches!(
+            read_csv(text.as_bytes(), "/x", "f[i]"),
+            Err(Error::InvalidTypeTag('['))
+        ));
+    }
+
+    #[test]
+    fn rejected_messages_leave_the_table_unchanged() {
+        let mut exporter = CsvExporter::new();
+        exporter
+            .push(&at(1), &IrValue::message("/a", vec![IrValue::from(1)]))
+            .unwrap();
+        let wide = IrValue::message(
+            "/a",
+            vec![IrValue::from(2), IrValue::from(3), IrValue::Map(vec![])],
+        );
+        assert!(matches!(
+            exporter.push(&at(2), &wide),
+            Err(Error::UnsupportedArgument { index: 2, .. })
+        ));
+        assert!(matches!(
+            exporter.push(&at(3), &IrValue::from("/a")),
+            Err(Error::NotAMessage)
+        ));
+        assert_eq!(exporter.len(), 1);
+        // The rejected message does not widen the header either
+        assert_eq!(csv_text(&exporter.finish()[0]), "time,arg0\n1,1\n");
+    }
+
+    #[test]
+    fn strings_are_quoted_when_needed() {
+        let msg = IrValue::message(
+            "/say",
+            vec![
+                IrValue::from("plain"),
+                IrValue::from("say \"hi\""),
+                IrValue::from("one\ntwo"),
+                IrValue::from(""),
+            ],
+        );
+        let mut exporter = CsvExporter::new();
+        exporter.push(&at(0), &msg).unwrap();
+        let text = csv_text(&exporter.finish()[0]);
+        assert_eq!(
+            text,
+            "time,arg0,arg1,arg2,arg3\n0,plain,\"say \"\"hi\"\"\",\"one\ntwo\",\n"
+        );
+        assert_eq!(
+            read_csv(text.as_bytes(), "/say", "ssss").unwrap(),
+            vec![(at(0), msg)]
+        );
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn bundle_messages_become_rows_of_their_tables() {
+        let mut inner = IrBundle::immediate();
+        inner.add_message(IrValue::message("/b", vec![]));
+        let mut outer = IrBundle::immediate();
+        outer.add_message(IrValue::message("/a", vec![IrValue::from(1)]));
+        outer.add_bundle(inner);
+        outer.add_message(IrValue::message(
+            "/a",
+            vec![IrValue::from(2), IrValue::from(3)],
+        ));
+
+        let mut exporter = CsvExporter::new();
+        exporter.push(&at(9), &IrValue::Bundle(outer)).unwrap();
+        assert_eq!(exporter.len(), 3);
+        let tables = exporter.finish();
+        assert_eq!(csv_text(&tables[0]), "time,arg0,arg1\n9,1,\n9,2,3\n");
+        assert_eq!(csv_text(&tables[1]), "time\n9\n");
+    }
+
+    #[cfg(feature = "osc11")]
+    #[test]
+    fn colors_and_midi_roundtrip() {
+        let msg = IrValue::message(
+            "/rgb",
+            vec![
+                IrValue::Color {
+                    r: 255,
+                    g: 128,
+                    b: 0,
+                    a: 16,
+                },
+                IrValue::Midi {
+                    port: 0,
+                    status: 0x90,
+                    data1: 60,
+                    data2: 100,
+                },
+            ],
+        );
+        let mut exporter = CsvExporter::new();
+        exporter.push(&at(0), &msg).unwrap();
+        let text = csv_text(&exporter.finish()[0]);
+        assert_eq!(text, "time,arg0,arg1\n0,#ff800010,00903c64\n");
+        assert_eq!(
+            read_csv(text.as_bytes(), "/rgb", "rm").unwrap(),
+            vec![(at(0), msg)]
+        );
+    }
+
+    #[test]
+    fn writes_one_file_per_address() {
+        let mut exporter = CsvExporter::new();
+        exporter
+            .push(
+                &at(0),
+                &IrValue::message("/imu/accel", vec![IrValue::Float32(1.0)]),
+            )
+            .unwrap();
+        exporter
+            .push(&at(0), &IrValue::message("/imu_accel", vec![]))
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("osc-csv-dir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let paths = write_csv_dir(&dir, &exporter.finish()).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["imu_accel.csv", "imu_accel-2.csv"]);
+        assert_eq!(
+            std::fs::read_to_string(&paths[0]).unwrap(),
+            "time,arg0\n0,1\n"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_names_come_from_address_parts() {
+        let tables: Vec<_> = ["/", "/Fader/1", "/fader/1", "/mix//x y", "/ü"]
+            .into_iter()
+            .map(|address| AddressTable {
+                address: address.to_string(),
+                rows: vec![],
+            })
+            .collect();
+        let dir = std::env::temp_dir().join(format!("osc-csv-names-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let paths = write_csv_dir(&dir, &tables).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "root.csv",
+                "Fader_1.csv",
+                "fader_1-2.csv",
+                "mix_x_y.csv",
+                "_.csv"
+            ]
+        );
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "time\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn seconds_roundtrip() {
+        let cases = [
+            (
+                IrTimestamp {
+                    seconds: 0,
+                    nanos: 0,
+                },
+                "0",
+            ),
+            (
+                IrTimestamp {
+                    seconds: 1_760_000_000,
+                    nanos: 250_000_000,
+                },
+                "1760000000.25",
+            ),
+            (
+                IrTimestamp {
+                    seconds: 1,
+                    nanos: 1,
+                },
+                "1.000000001",
+            ),
+            (
+                IrTimestamp {
+                    seconds: -1,
+                    nanos: 500_000_000,
+                },
+                "-0.5",
+            ),
+            (
+                IrTimestamp {
+                    seconds: -2,
+                    nanos: 0,
+                },
+                "-2",
+            ),
+            (
+                IrTimestamp {
+                    seconds: -2,
+                    nanos: 1,
+                },
+                "-1.999999999",
+            ),
+        ];
+        for (ts, text) in cases {
+            assert_eq!(format_seconds(&ts), text);
+            assert_eq!(parse_seconds(text), Some(ts), "{}", text);
+        }
+        assert_eq!(
+            parse_seconds("12."),
+            Some(IrTimestamp {
+                seconds: 12,
+                nanos: 0
+            })
+        );
+        for bad in ["", "-", ".5", "1e3", "1.0000000001", "+1", "1.2.3"] {
+            assert_eq!(parse_seconds(bad), None, "{}", bad);
+        }
+    }
+}
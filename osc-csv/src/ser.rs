@@ -0,0 +1,218 @@
+//! Grouping of messages into per-address tables and CSV output.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+#[cfg(feature = "osc10")]
+use osc_ir::{IrBundle, IrBundleElement};
+use osc_ir::{IrTimestamp, IrValue};
+
+use crate::{format_seconds, Error, TIME_COLUMN};
+
+/// The messages sent to one address, ready to be written as CSV.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressTable {
+    /// OSC address of every row
+    pub address: String,
+    /// Receive time and arguments of each message, in the order they were pushed
+    pub rows: Vec<(IrTimestamp, Vec<IrValue>)>,
+}
+
+impl AddressTable {
+    /// Returns the number of argument columns, the length of the longest message.
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|(_, args)| args.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Writes the `time,arg0,arg1,...` table.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let width = self.width();
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(false)
+            .from_writer(writer);
+        let header =
+            std::iter::once(TIME_COLUMN.to_string()).chain((0..width).map(|i| format!("arg{}", i)));
+        writer.write_record(header)?;
+        let mut record = Vec::with_capacity(width + 1);
+        for (time, args) in &self.rows {
+            record.clear();
+            record.push(format_seconds(time));
+            record.extend(args.iter().map(cell));
+            record.resize(width + 1, String::new());
+            writer.write_record(&record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Returns true if the argument has a cell form.
+fn supported(arg: &IrValue) -> bool {
+    match arg {
+        IrValue::Null
+        | IrValue::Bool(_)
+        | IrValue::Integer(_)
+        | IrValue::Unsigned(_)
+        | IrValue::Float32(_)
+        | IrValue::Float(_)
+        | IrValue::String(_)
+        | IrValue::Binary(_)
+        | IrValue::Timestamp(_) => true,
+        #[cfg(feature = "osc11")]
+        IrValue::Color { .. } | IrValue::Midi { .. } => true,
+        _ => false,
+    }
+}
+
+fn cell(arg: &IrValue) -> String {
+    match arg {
+        IrValue::Bool(b) => b.to_string(),
+        IrValue::Integer(i) => i.to_string(),
+        IrValue::Unsigned(u) => u.to_string(),
+        IrValue::Float32(x) => x.to_string(),
+        IrValue::Float(x) => x.to_string(),
+        IrValue::String(s) => s.to_string(),
+        IrValue::Binary(data) => base64::engine::general_purpose::STANDARD.encode(data),
+        IrValue::Timestamp(ts) => ts.to_rfc3339().unwrap_or_else(|| format_seconds(ts)),
+        #[cfg(feature = "osc11")]
+        IrValue::Color { r, g, b, a } => format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+        #[cfg(feature = "osc11")]
+        IrValue::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => format!("{:02x}{:02x}{:02x}{:02x}", port, status, data1, data2),
+        // Nil, and values `supported` rejects before they get here
+        _ => String::new(),
+    }
+}
+
+/// Accumulates timestamped messages and groups them by address.
+#[derive(Clone, Debug, Default)]
+pub struct CsvExporter {
+    tables: BTreeMap<String, Vec<(IrTimestamp, Vec<IrValue>)>>,
+    rows: usize,
+}
+
+impl CsvExporter {
+    /// Creates an empty exporter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a row stamped `time` to the table of the message's address.
+    ///
+    /// Each message of a bundle, nested ones included, becomes a row of its own
+    /// address table stamped `time`; the bundle's timetag is not written. On error no
+    /// row is added for the offending message, while rows already added for earlier
+    /// messages of the same bundle stay.
+    pub fn push(&mut self, time: &IrTimestamp, value: &IrValue) -> Result<(), Error> {
+        #[cfg(feature = "osc10")]
+        if let IrValue::Bundle(bundle) = value {
+            return self.push_bundle(time, bundle);
+        }
+        let (address, args) = value.as_message().ok_or(Error::NotAMessage)?;
+        if let Some(index) = args.iter().position(|arg| !supported(arg)) {
+            return Err(Error::UnsupportedArgument {
+                address: address.to_string(),
+                index,
+            });
+        }
+        let row = (*time, args.to_vec());
+        match self.tables.get_mut(address) {
+            Some(rows) => rows.push(row),
+            None => {
+                self.tables.insert(address.to_string(), vec![row]);
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    #[cfg(feature = "osc10")]
+    fn push_bundle(&mut self, time: &IrTimestamp, bundle: &IrBundle) -> Result<(), Error> {
+        for element in &bundle.elements {
+            match element {
+                IrBundleElement::Message(message) => self.push(time, message)?,
+                IrBundleElement::Bundle(nested) => self.push_bundle(time, nested)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of messages recorded.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns true if no message has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Returns the tables, one per address in sorted address order.
+    pub fn finish(self) -> Vec<AddressTable> {
+        self.tables
+            .into_iter()
+            .map(|(address, rows)| AddressTable { address, rows })
+            .collect()
+    }
+}
+
+/// Writes each table to `<stem>.csv` in `dir` and returns the paths, in table order.
+///
+/// The stem is the address parts joined with `_`, keeping only ASCII letters, digits,
+/// `-` and `_` (`/imu/accel` is written to `imu_accel.csv`, `/` to `root.csv`). Names
+/// are compared ignoring case, so that tables never share a file on case-insensitive
+/// file systems, and a clash gets a `-2`, `-3`, ... suffix. `dir` is created if needed
+/// and files already in it are replaced.
+pub fn write_csv_dir(
+    dir: impl AsRef<Path>,
+    tables: &[AddressTable],
+) -> Result<Vec<PathBuf>, Error> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut used = BTreeSet::new();
+    let mut paths = Vec::with_capacity(tables.len());
+    for table in tables {
+        let stem = file_stem(&table.address);
+        let mut name = format!("{}.csv", stem);
+        let mut n = 2;
+        while !used.insert(name.to_ascii_lowercase()) {
+            name = format!("{}-{}.csv", stem, n);
+            n += 1;
+        }
+        let path = dir.join(name);
+        table.write_csv(File::create(&path)?)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn file_stem(address: &str) -> String {
+    let parts: Vec<String> = address
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+                    _ => '_',
+                })
+                .collect()
+        })
+        .collect();
+    if parts.is_empty() {
+        "root".to_string()
+    } else {
+        parts.join("_")
+    }
+}
@@ -1,5 +1,9 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
 use clap::{Parser, Subcommand};
-use osc_ir::{IrValue, IrBundle, IrTimetag};
+use osc_ir::{CodecRegistry, IrValue, IrBundle, IrTimetag};
 
 #[derive(Parser)]
 #[command(name = "osc-dev")] 
@@ -14,6 +18,47 @@ enum Cmd {
     JsonRoundtrip,
     MsgpackRoundtrip,
     BundleDemo,
+    /// Round-trip a sample value through any registered codec
+    Roundtrip {
+        #[arg(long, default_value = "json")]
+        codec: String,
+    },
+    /// Read a value from stdin in one format and write it to stdout in another
+    Convert {
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+    },
+    /// List the registered codecs
+    Codecs,
+    /// Split an NDJSON recording from stdin into one `time,arg0,...` CSV file per address
+    CsvExport {
+        /// Directory to write the CSV files to
+        #[arg(long)]
+        dir: PathBuf,
+    },
+    /// Read a CSV table from stdin and write its messages to stdout as an NDJSON recording
+    CsvImport {
+        /// Address of the messages
+        #[arg(long)]
+        address: String,
+        /// OSC type tags of the argument columns, e.g. `fff`
+        #[arg(long)]
+        types: String,
+    },
+}
+
+fn registry() -> CodecRegistry {
+    let mut registry = CodecRegistry::new();
+    registry
+        .register(osc_codec_json::JsonCodec)
+        .register(osc_codec_msgpack::MsgpackCodec)
+        .register(osc_codec_cbor::CborCodec)
+        .register(osc_codec_compact::CompactCodec)
+        .register(osc_codec_yaml::YamlCodec)
+        .register(osc_codec_toml::TomlCodec);
+    registry
 }
 
 fn main() -> anyhow::Result<()> {
@@ -26,8 +71,8 @@ fn main() -> anyhow::Result<()> {
             println!("{:?} -> {} -> {:?}", v, j, v2);
         }
         Cmd::MsgpackRoundtrip => {
-            let bytes = osc_codec_msgpack::to_msgpack(&v);
-            let v2 = osc_codec_msgpack::from_msgpack(&bytes);
+            let bytes = osc_codec_msgpack::try_to_msgpack(&v)?;
+            let v2 = osc_codec_msgpack::try_from_msgpack(&bytes)?;
             println!("{:?} -> {} bytes -> {:?}", v, bytes.len(), v2);
         }
         Cmd::BundleDemo => {
@@ -73,8 +118,8 @@ fn main() -> anyhow::Result<()> {
             }
             
             // Test MessagePack roundtrip
-            let msgpack = osc_codec_msgpack::to_msgpack(&bundle_value);
-            let from_msgpack = osc_codec_msgpack::from_msgpack(&msgpack);
+            let msgpack = osc_codec_msgpack::try_to_msgpack(&bundle_value)?;
+            let from_msgpack = osc_codec_msgpack::try_from_msgpack(&msgpack)?;
             println!("✓ MessagePack roundtrip: {}", if bundle_value == from_msgpack { "SUCCESS" } else { "FAILED" });
             println!("MessagePack size: {} bytes", msgpack.len());
             
@@ -84,6 +129,61 @@ fn main() -> anyhow::Result<()> {
             
             println!("\n=== Bundle nesting is working correctly! ===");
         }
+        Cmd::Roundtrip { codec } => {
+            let registry = registry();
+            let codec = registry
+                .by_name(&codec)
+                .with_context(|| format!("unknown codec `{}`", codec))?;
+            let bytes = codec.encode(&v)?;
+            let v2 = codec.decode(&bytes)?;
+            println!("{:?} -> {} bytes ({}) -> {:?}", v, bytes.len(), codec.mime_type(), v2);
+        }
+        Cmd::Convert { from, to } => {
+            let registry = registry();
+            let decoder = registry
+                .by_name(&from)
+                .with_context(|| format!("unknown codec `{}`", from))?;
+            let encoder = registry
+                .by_name(&to)
+                .with_context(|| format!("unknown codec `{}`", to))?;
+            let mut input = Vec::new();
+            std::io::stdin().read_to_end(&mut input)?;
+            let value = decoder.decode(&input)?;
+            let mut stdout = std::io::stdout().lock();
+            encoder.encode_to_writer(&value, &mut stdout)?;
+            stdout.flush()?;
+        }
+        Cmd::Codecs => {
+            let registry = registry();
+            for name in registry.names() {
+                let codec = registry.by_name(name).expect("registered codec");
+                println!("{}\t{}", name, codec.mime_type());
+            }
+        }
+        Cmd::CsvExport { dir } => {
+            let stdin = std::io::stdin().lock();
+            let mut exporter = osc_csv::CsvExporter::new();
+            for record in osc_codec_json::ndjson::NdjsonReader::new(stdin).records() {
+                let record = record?;
+                let Some(received) = record.received else {
+                    bail!("a record has no receive time; CSV tables need one per row");
+                };
+                exporter.push(&received, &record.value)?;
+            }
+            for path in osc_csv::write_csv_dir(&dir, &exporter.finish())? {
+                println!("{}", path.display());
+            }
+        }
+        Cmd::CsvImport { address, types } => {
+            let rows = osc_csv::read_csv(std::io::stdin().lock(), &address, &types)?;
+            let stdout = std::io::stdout().lock();
+            let mut writer = osc_codec_json::ndjson::NdjsonWriter::new(std::io::BufWriter::new(stdout));
+            for (received, message) in rows {
+                let record = osc_codec_json::ndjson::Record::new(message).with_received(received);
+                writer.write_record(&record)?;
+            }
+            writer.flush()?;
+        }
     }
     Ok(())
 }
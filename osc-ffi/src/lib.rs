@@ -133,7 +133,7 @@ pub unsafe extern "C" fn osc_message_add_f32(
     handle: *mut OscMessageHandle,
     value: f32,
 ) -> OscFfiError {
-    with_message_mut(handle, |msg| msg.push_arg(IrValue::Float(value as f64)))
+    with_message_mut(handle, |msg| msg.push_arg(IrValue::Float32(value)))
 }
 
 /// # Safety
@@ -279,7 +279,7 @@ mod tests {
         let args = args_value.as_array().expect("array");
         assert_eq!(args.len(), 4);
         assert_eq!(args[0].as_integer(), Some(42));
-        assert!((args[1].as_float().unwrap() - 1.5).abs() < f64::EPSILON);
+        assert_eq!(args[1], IrValue::Float32(1.5));
         assert_eq!(args[2].as_str(), Some("hello"));
         assert_eq!(args[3].as_binary(), Some(&blob[..]));
 
@@ -0,0 +1,298 @@
+//! # osc-framing
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Framing of OSC packets over byte streams such as TCP connections and serial lines.
+//!
+//! Datagrams carry one packet each, but a stream needs packet boundaries. OSC 1.0
+//! prefixes every packet with its size as a 32-bit big-endian integer; OSC 1.1
+//! recommends SLIP (RFC 1055) with an `END` byte on both sides of each packet. The
+//! decoders here are fed whatever chunks a read returns and yield complete packets,
+//! either as raw bytes or decoded to `IrValue` with any `osc_ir::Codec`, such as the
+//! OSC binary codec of `osc-adapter-rosc` or the MessagePack codec.
+//!
+//! ## Features
+//!
+//! - **SLIP**: [`SlipEncoder`] and [`SlipDecoder`], double-`END` as in OSC 1.1
+//! - **Size Prefix**: [`SizePrefixEncoder`] and [`SizePrefixDecoder`], as in OSC 1.0
+//! - **Incremental**: Decoders accept arbitrary chunks; packets split across reads or
+//!   coalesced into one read come out the same
+//! - **Codec-Agnostic**: [`FrameDecoder::next_value`] and [`FrameEncoder::encode_value`]
+//!   work with any `osc_ir::Codec`
+//! - **Bounded**: Frames longer than a configurable limit are reported and skipped
+//!   instead of growing the buffer without bound
+//! - **no_std**: Only needs `alloc`; the `std` feature (on by default) adds the
+//!   `std::error::Error` impl
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use osc_framing::{FrameDecoder, FrameEncoder, SlipDecoder, SlipEncoder};
+//!
+//! let mut stream = Vec::new();
+//! SlipEncoder.encode_frame(b"first", &mut stream).unwrap();
+//! SlipEncoder.encode_frame(b"second", &mut stream).unwrap();
+//!
+//! let mut decoder = SlipDecoder::new();
+//! let (a, b) = stream.split_at(4);
+//! decoder.feed(a);
+//! assert!(decoder.next_frame().is_none());
+//! decoder.feed(b);
+//! assert_eq!(decoder.next_frame().unwrap().unwrap(), b"first");
+//! assert_eq!(decoder.next_frame().unwrap().unwrap(), b"second");
+//! assert!(decoder.next_frame().is_none());
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use osc_ir::{Codec, CodecError, IrValue};
+
+mod size_prefix;
+mod slip;
+
+pub use size_prefix::{SizePrefixDecoder, SizePrefixEncoder};
+pub use slip::{SlipDecoder, SlipEncoder};
+
+/// Default maximum frame length accepted by the decoders: 1 MiB.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 1 << 20;
+
+/// Error produced while framing or unframing packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// A frame is longer than the limit; decoders skip it and continue
+    TooLarge {
+        /// Length of the frame, or the length reached when the limit was hit
+        len: usize,
+        /// The limit
+        max: usize,
+    },
+    /// A SLIP escape byte was followed by something other than an escape code; the
+    /// frame is skipped
+    InvalidEscape(u8),
+    /// The codec rejected the packet
+    Codec(CodecError),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::TooLarge { len, max } => {
+                write!(
+                    f,
+                    "frame of {} bytes exceeds the limit of {} bytes",
+                    len, max
+                )
+            }
+            FrameError::InvalidEscape(byte) => {
+                write!(f, "invalid SLIP escape sequence 0xDB 0x{:02X}", byte)
+            }
+            FrameError::Codec(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
+impl From<CodecError> for FrameError {
+    fn from(e: CodecError) -> Self {
+        FrameError::Codec(e)
+    }
+}
+
+/// Splits a byte stream into packets.
+pub trait FrameDecoder {
+    /// Appends bytes read from the stream.
+    fn feed(&mut self, chunk: &[u8]);
+
+    /// Returns the next complete packet, or `None` until more bytes are fed.
+    ///
+    /// An error reports a frame that was skipped; later calls continue with the
+    /// frames after it.
+    fn next_frame(&mut self) -> Option<Result<Vec<u8>, FrameError>>;
+
+    /// Returns the number of bytes fed but not yet returned as packets.
+    fn buffered(&self) -> usize;
+
+    /// Returns the next complete packet decoded with `codec`.
+    fn next_value(&mut self, codec: &dyn Codec) -> Option<Result<IrValue, FrameError>> {
+        let frame = self.next_frame()?;
+        Some(frame.and_then(|bytes| codec.decode(&bytes).map_err(FrameError::Codec)))
+    }
+}
+
+/// Wraps packets into frames.
+pub trait FrameEncoder {
+    /// Appends the frame of `packet` to `out`.
+    fn encode_frame(&self, packet: &[u8], out: &mut Vec<u8>) -> Result<(), FrameError>;
+
+    /// Encodes `value` with `codec` and appends its frame to `out`.
+    fn encode_value(
+        &self,
+        codec: &dyn Codec,
+        value: &IrValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FrameError> {
+        let packet = codec.encode(value)?;
+        self.encode_frame(&packet, out)
+    }
+}
+
+/// The framings of the OSC specifications, for choosing one at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// SLIP with an `END` byte before and after each packet (OSC 1.1)
+    Slip,
+    /// 32-bit big-endian size before each packet (OSC 1.0)
+    SizePrefix,
+}
+
+impl Framing {
+    /// Creates a decoder for this framing with the default frame limit.
+    pub fn decoder(self) -> Box<dyn FrameDecoder + Send> {
+        match self {
+            Framing::Slip => Box::new(SlipDecoder::new()),
+            Framing::SizePrefix => Box::new(SizePrefixDecoder::new()),
+        }
+    }
+
+    /// Appends the frame of `packet` to `out`.
+    pub fn encode_frame(self, packet: &[u8], out: &mut Vec<u8>) -> Result<(), FrameError> {
+        match self {
+            Framing::Slip => SlipEncoder.encode_frame(packet, out),
+            Framing::SizePrefix => SizePrefixEncoder.encode_frame(packet, out),
+        }
+    }
+
+    /// Encodes `value` with `codec` and appends its frame to `out`.
+    pub fn encode_value(
+        self,
+        codec: &dyn Codec,
+        value: &IrValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FrameError> {
+        self.encode_frame(&codec.encode(value)?, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_adapter_rosc::OscCodec;
+    use osc_codec_msgpack::MsgpackCodec;
+
+    fn messages() -> Vec<IrValue> {
+        vec![
+            IrValue::message("/a", vec![IrValue::from(1), IrValue::Float32(0.5)]),
+            // 0xC0 and 0xDB in the payload exercise SLIP escaping
+            IrValue::message(
+                "/blob",
+                vec![IrValue::Binary(vec![0xC0, 0xDB, 0xDC, 0xDD, 0])],
+            ),
+            IrValue::message("/empty", vec![]),
+        ]
+    }
+
+    fn encode_all(framing: Framing, codec: &dyn Codec, values: &[IrValue]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for value in values {
+            framing.encode_value(codec, value, &mut stream).unwrap();
+        }
+        stream
+    }
+
+    fn drain(decoder: &mut dyn FrameDecoder, codec: &dyn Codec) -> Vec<IrValue> {
+        let mut out = Vec::new();
+        while let Some(value) = decoder.next_value(codec) {
+            out.push(value.unwrap());
+        }
+        out
+    }
+
+    fn framings_and_codecs() -> Vec<(Framing, Box<dyn Codec>)> {
+        vec![
+            (Framing::Slip, Box::new(OscCodec)),
+            (Framing::Slip, Box::new(MsgpackCodec)),
+            (Framing::SizePrefix, Box::new(OscCodec)),
+            (Framing::SizePrefix, Box::new(MsgpackCodec)),
+        ]
+    }
+
+    #[test]
+    fn coalesced_reads_yield_every_packet() {
+        let values = messages();
+        for (framing, codec) in framings_and_codecs() {
+            let stream = encode_all(framing, codec.as_ref(), &values);
+            let mut decoder = framing.decoder();
+            decoder.feed(&stream);
+            assert_eq!(
+                drain(decoder.as_mut(), codec.as_ref()),
+                values,
+                "{:?}",
+                framing
+            );
+            assert_eq!(decoder.buffered(), 0);
+        }
+    }
+
+    #[test]
+    fn split_reads_yield_packets_once_complete() {
+        let values = messages();
+        for (framing, codec) in framings_and_codecs() {
+            let stream = encode_all(framing, codec.as_ref(), &values);
+            for chunk_len in [1, 2, 3, 7, 64] {
+                let mut decoder = framing.decoder();
+                let mut decoded = Vec::new();
+                for chunk in stream.chunks(chunk_len) {
+                    decoder.feed(chunk);
+                    decoded.extend(drain(decoder.as_mut(), codec.as_ref()));
+                }
+                assert_eq!(decoded, values, "{:?} in chunks of {}", framing, chunk_len);
+                assert_eq!(decoder.buffered(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn partial_frames_stay_buffered() {
+        for (framing, codec) in framings_and_codecs() {
+            let stream = encode_all(framing, codec.as_ref(), &messages()[..1]);
+            let mut decoder = framing.decoder();
+            decoder.feed(&stream[..stream.len() - 1]);
+            assert!(decoder.next_frame().is_none());
+            assert_ne!(decoder.buffered(), 0);
+            decoder.feed(&stream[stream.len() - 1..]);
+            assert!(decoder.next_frame().unwrap().is_ok());
+        }
+    }
+
+    #[test]
+    fn codec_errors_do_not_stop_the_stream() {
+        let mut stream = Vec::new();
+        Framing::SizePrefix
+            .encode_frame(b"not an OSC packet", &mut stream)
+            .unwrap();
+        let value = IrValue::message("/ok", vec![]);
+        Framing::SizePrefix
+            .encode_value(&OscCodec, &value, &mut stream)
+            .unwrap();
+
+        let mut decoder = SizePrefixDecoder::new();
+        decoder.feed(&stream);
+        assert!(matches!(
+            decoder.next_value(&OscCodec),
+            Some(Err(FrameError::Codec(_)))
+        ));
+        assert_eq!(decoder.next_value(&OscCodec), Some(Ok(value)));
+        assert_eq!(decoder.next_value(&OscCodec), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Size-prefix framing: a 32-bit big-endian packet size before each packet, as in
+//! OSC 1.0.
+
+use alloc::vec::Vec;
+
+use crate::{FrameDecoder, FrameEncoder, FrameError, DEFAULT_MAX_FRAME_LEN};
+
+/// Writes size-prefixed frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizePrefixEncoder;
+
+impl FrameEncoder for SizePrefixEncoder {
+    fn encode_frame(&self, packet: &[u8], out: &mut Vec<u8>) -> Result<(), FrameError> {
+        let size = u32::try_from(packet.len()).map_err(|_| FrameError::TooLarge {
+            len: packet.len(),
+            max: u32::MAX as usize,
+        })?;
+        out.reserve(packet.len() + 4);
+        out.extend_from_slice(&size.to_be_bytes());
+        out.extend_from_slice(packet);
+        Ok(())
+    }
+}
+
+/// Incremental size-prefix decoder.
+///
+/// Zero-length frames carry no packet and are skipped.
+#[derive(Clone, Debug)]
+pub struct SizePrefixDecoder {
+    buf: Vec<u8>,
+    /// Start of the next frame in `buf`
+    start: usize,
+    /// Bytes of an oversized frame still to be discarded
+    skip: usize,
+    max_len: usize,
+}
+
+impl Default for SizePrefixDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizePrefixDecoder {
+    /// Creates a decoder accepting frames up to [`DEFAULT_MAX_FRAME_LEN`] bytes.
+    pub fn new() -> Self {
+        Self::with_max_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a decoder accepting frames up to `max_len` bytes.
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            skip: 0,
+            max_len,
+        }
+    }
+}
+
+impl FrameDecoder for SizePrefixDecoder {
+    fn feed(&mut self, chunk: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+        // Bytes of a skipped frame are dropped without buffering them
+        let skipped = self.skip.min(chunk.len());
+        self.skip -= skipped;
+        self.buf.extend_from_slice(&chunk[skipped..]);
+    }
+
+    fn next_frame(&mut self) -> Option<Result<Vec<u8>, FrameError>> {
+        loop {
+            if self.skip > 0 {
+                let skipped = self.skip.min(self.buf.len() - self.start);
+                self.start += skipped;
+                self.skip -= skipped;
+                if self.skip > 0 {
+                    return None;
+                }
+            }
+            let available = &self.buf[self.start..];
+            let size: [u8; 4] = available.get(..4)?.try_into().ok()?;
+            let len = u32::from_be_bytes(size) as usize;
+            if len > self.max_len {
+                self.start += 4;
+                self.skip = len;
+                return Some(Err(FrameError::TooLarge {
+                    len,
+                    max: self.max_len,
+                }));
+            }
+            let frame = available.get(4..4 + len)?.to_vec();
+            self.start += 4 + len;
+            if !frame.is_empty() {
+                return Some(Ok(frame));
+            }
+        }
+    }
+
+    fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_big_endian_size() {
+        let mut out = Vec::new();
+        SizePrefixEncoder.encode_frame(b"abc", &mut out).unwrap();
+        assert_eq!(out, [0, 0, 0, 3, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn size_split_across_chunks() {
+        let mut decoder = SizePrefixDecoder::new();
+        decoder.feed(&[0, 0]);
+        assert_eq!(decoder.next_frame(), None);
+        decoder.feed(&[0, 2, 9]);
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.buffered(), 5);
+        decoder.feed(&[8, 0, 0, 0, 0]);
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![9, 8])));
+        // The zero-length frame is skipped
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn oversized_frames_are_skipped() {
+        let mut decoder = SizePrefixDecoder::with_max_len(2);
+        decoder.feed(&[0, 0, 0, 5, 1, 2]);
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::TooLarge { len: 5, max: 2 }))
+        );
+        assert_eq!(decoder.next_frame(), None);
+        decoder.feed(&[3, 4]);
+        assert_eq!(decoder.buffered(), 0);
+        decoder.feed(&[5, 0, 0, 0, 1, 7]);
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![7])));
+    }
+}
@@ -0,0 +1,235 @@
+//! SLIP framing (RFC 1055) with an `END` byte on both sides of each packet, as
+//! recommended by OSC 1.1.
+
+use alloc::vec::Vec;
+
+use crate::{FrameDecoder, FrameEncoder, FrameError, DEFAULT_MAX_FRAME_LEN};
+
+const END: u8 = 0xC0;
+const ESC: u8 = 0xDB;
+const ESC_END: u8 = 0xDC;
+const ESC_ESC: u8 = 0xDD;
+
+/// Writes double-`END` SLIP frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlipEncoder;
+
+impl FrameEncoder for SlipEncoder {
+    fn encode_frame(&self, packet: &[u8], out: &mut Vec<u8>) -> Result<(), FrameError> {
+        out.reserve(packet.len() + 2);
+        out.push(END);
+        for &byte in packet {
+            match byte {
+                END => out.extend_from_slice(&[ESC, ESC_END]),
+                ESC => out.extend_from_slice(&[ESC, ESC_ESC]),
+                _ => out.push(byte),
+            }
+        }
+        out.push(END);
+        Ok(())
+    }
+}
+
+/// Incremental SLIP decoder.
+///
+/// Every `END` byte ends a frame, so both single- and double-`END` streams decode;
+/// the empty frames between consecutive `END`s are skipped.
+#[derive(Clone, Debug)]
+pub struct SlipDecoder {
+    buf: Vec<u8>,
+    /// Start of the current frame in `buf`
+    start: usize,
+    /// Bytes of the current frame already searched for `END`
+    scanned: usize,
+    /// `ESC` bytes among the scanned ones, to size the unescaped frame
+    escapes: usize,
+    /// Discarding an oversized frame up to its `END`
+    skipping: bool,
+    max_len: usize,
+}
+
+impl Default for SlipDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlipDecoder {
+    /// Creates a decoder accepting frames up to [`DEFAULT_MAX_FRAME_LEN`] bytes.
+    pub fn new() -> Self {
+        Self::with_max_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a decoder accepting unescaped frames up to `max_len` bytes.
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            scanned: 0,
+            escapes: 0,
+            skipping: false,
+            max_len,
+        }
+    }
+
+    fn reset_frame(&mut self, next: usize) {
+        self.start = next;
+        self.scanned = next;
+        self.escapes = 0;
+    }
+}
+
+impl FrameDecoder for SlipDecoder {
+    fn feed(&mut self, chunk: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+
+    fn next_frame(&mut self) -> Option<Result<Vec<u8>, FrameError>> {
+        loop {
+            let unscanned = &self.buf[self.scanned..];
+            let Some(offset) = unscanned.iter().position(|&b| b == END) else {
+                self.escapes += unscanned.iter().filter(|&&b| b == ESC).count();
+                if self.skipping {
+                    self.buf.clear();
+                    self.reset_frame(0);
+                    return None;
+                }
+                self.scanned = self.buf.len();
+                let len = self.buf.len() - self.start - self.escapes;
+                if len > self.max_len {
+                    self.skipping = true;
+                    self.buf.clear();
+                    self.reset_frame(0);
+                    return Some(Err(FrameError::TooLarge {
+                        len,
+                        max: self.max_len,
+                    }));
+                }
+                return None;
+            };
+            let end = self.scanned + offset;
+            let raw = self.start..end;
+            self.reset_frame(end + 1);
+            if core::mem::take(&mut self.skipping) || raw.is_empty() {
+                continue;
+            }
+            return Some(unescape(&self.buf[raw], self.max_len));
+        }
+    }
+
+    fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+}
+
+fn unescape(raw: &[u8], max_len: usize) -> Result<Vec<u8>, FrameError> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut bytes = raw.iter();
+    while let Some(&byte) = bytes.next() {
+        out.push(match byte {
+            ESC => match bytes.next() {
+                Some(&ESC_END) => END,
+                Some(&ESC_ESC) => ESC,
+                // `ESC END` is a protocol violation; the frame ended after the `ESC`
+                Some(&other) => return Err(FrameError::InvalidEscape(other)),
+                None => return Err(FrameError::InvalidEscape(END)),
+            },
+            _ => byte,
+        });
+    }
+    if out.len() > max_len {
+        return Err(FrameError::TooLarge {
+            len: out.len(),
+            max: max_len,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(packet: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        SlipEncoder.encode_frame(packet, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn escapes_end_and_esc() {
+        assert_eq!(
+            frame(&[1, END, 2, ESC, 3]),
+            [END, 1, ESC, ESC_END, 2, ESC, ESC_ESC, 3, END]
+        );
+        assert_eq!(frame(&[]), [END, END]);
+    }
+
+    #[test]
+    fn single_end_streams_decode() {
+        let mut decoder = SlipDecoder::new();
+        decoder.feed(&[1, 2, END, 3, END, END, END]);
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![1, 2])));
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![3])));
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn escapes_split_across_chunks() {
+        let stream = frame(&[END, ESC]);
+        let mut decoder = SlipDecoder::new();
+        for byte in &stream {
+            assert_eq!(decoder.next_frame(), None);
+            decoder.feed(core::slice::from_ref(byte));
+        }
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![END, ESC])));
+    }
+
+    #[test]
+    fn invalid_escapes_skip_the_frame() {
+        let mut decoder = SlipDecoder::new();
+        decoder.feed(&[END, 1, ESC, 7, 2, END, ESC, END]);
+        decoder.feed(&frame(b"ok"));
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::InvalidEscape(7)))
+        );
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::InvalidEscape(END)))
+        );
+        assert_eq!(decoder.next_frame(), Some(Ok(b"ok".to_vec())));
+    }
+
+    #[test]
+    fn oversized_frames_are_skipped() {
+        let mut decoder = SlipDecoder::with_max_len(4);
+        // Complete in one chunk
+        decoder.feed(&frame(&[0; 5]));
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::TooLarge { len: 5, max: 4 }))
+        );
+
+        // Arriving in pieces: reported once the limit is passed, then discarded
+        decoder.feed(&[END, 1, 2, 3]);
+        assert_eq!(decoder.next_frame(), None);
+        decoder.feed(&[4, 5, 6]);
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::TooLarge { len: 6, max: 4 }))
+        );
+        decoder.feed(&[7, 8]);
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.buffered(), 0);
+        decoder.feed(&[9, END]);
+        decoder.feed(&frame(&[ESC; 4]));
+        assert_eq!(decoder.next_frame(), Some(Ok(vec![ESC; 4])));
+    }
+}
@@ -0,0 +1,241 @@
+//! Format-agnostic codec abstraction and registry.
+//!
+//! Codec crates (`osc-codec-json`, `osc-codec-msgpack`, ...) implement [`Codec`] so that
+//! tools can encode and decode [`IrValue`]s without depending on a particular format.
+//! A [`CodecRegistry`] looks codecs up by name or content type at runtime.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+
+use crate::IrValue;
+
+/// Category of a [`CodecError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodecErrorKind {
+    /// The value could not be encoded
+    Encode,
+    /// The input could not be decoded
+    Decode,
+    /// Writing encoded output failed
+    Io,
+}
+
+/// Error returned by [`Codec`] implementations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodecError {
+    kind: CodecErrorKind,
+    message: String,
+}
+
+impl CodecError {
+    /// Creates an error of the given kind with a human-readable message.
+    pub fn new(kind: CodecErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an encoding error.
+    pub fn encode(message: impl Into<String>) -> Self {
+        Self::new(CodecErrorKind::Encode, message)
+    }
+
+    /// Creates a decoding error.
+    pub fn decode(message: impl Into<String>) -> Self {
+        Self::new(CodecErrorKind::Decode, message)
+    }
+
+    /// Returns the category of this error.
+    pub fn kind(&self) -> CodecErrorKind {
+        self.kind
+    }
+
+    /// Returns the error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            CodecErrorKind::Encode => "encode",
+            CodecErrorKind::Decode => "decode",
+            CodecErrorKind::Io => "io",
+        };
+        write!(f, "{} error: {}", kind, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodecError {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for CodecError {
+    fn from(err: std::io::Error) -> Self {
+        use alloc::string::ToString;
+        Self::new(CodecErrorKind::Io, err.to_string())
+    }
+}
+
+/// A wire format that can encode and decode [`IrValue`]s.
+pub trait Codec: Send + Sync {
+    /// Short, lowercase name of the format (e.g. `"json"`).
+    fn name(&self) -> &'static str;
+
+    /// MIME type of the encoded output (e.g. `"application/json"`).
+    fn mime_type(&self) -> &'static str;
+
+    /// Encodes a value into a new byte buffer.
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError>;
+
+    /// Decodes a single value from a complete byte slice.
+    fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError>;
+
+    /// Encodes a value into a writer.
+    #[cfg(feature = "std")]
+    fn encode_to_writer(
+        &self,
+        value: &IrValue,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<(), CodecError> {
+        writer.write_all(&self.encode(value)?)?;
+        Ok(())
+    }
+}
+
+/// Runtime lookup table of [`Codec`]s by name or content type.
+#[derive(Default)]
+pub struct CodecRegistry {
+    codecs: Vec<Box<dyn Codec>>,
+}
+
+impl CodecRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a codec. A codec registered later with the same name or MIME type
+    /// takes precedence over earlier ones.
+    pub fn register<C: Codec + 'static>(&mut self, codec: C) -> &mut Self {
+        self.codecs.push(Box::new(codec));
+        self
+    }
+
+    /// Looks up a codec by name, ignoring ASCII case.
+    pub fn by_name(&self, name: &str) -> Option<&dyn Codec> {
+        self.codecs
+            .iter()
+            .rev()
+            .find(|codec| codec.name().eq_ignore_ascii_case(name))
+            .map(|codec| codec.as_ref())
+    }
+
+    /// Looks up a codec by content type, ignoring ASCII case and any parameters
+    /// such as `; charset=utf-8`.
+    pub fn by_mime_type(&self, content_type: &str) -> Option<&dyn Codec> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        self.codecs
+            .iter()
+            .rev()
+            .find(|codec| codec.mime_type().eq_ignore_ascii_case(essence))
+            .map(|codec| codec.as_ref())
+    }
+
+    /// Returns the names of all registered codecs in registration order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.codecs.iter().map(|codec| codec.name())
+    }
+
+    /// Returns the number of registered codecs.
+    pub fn len(&self) -> usize {
+        self.codecs.len()
+    }
+
+    /// Returns true if no codecs are registered.
+    pub fn is_empty(&self) -> bool {
+        self.codecs.is_empty()
+    }
+}
+
+impl fmt::Debug for CodecRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// Encodes strings as their raw UTF-8 bytes.
+    struct Utf8Codec(&'static str);
+
+    impl Codec for Utf8Codec {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn mime_type(&self) -> &'static str {
+            "text/plain"
+        }
+
+        fn encode(&self, value: &IrValue) -> Result<Vec<u8>, CodecError> {
+            value
+                .as_str()
+                .map(|s| s.as_bytes().to_vec())
+                .ok_or_else(|| CodecError::encode("expected string"))
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<IrValue, CodecError> {
+            core::str::from_utf8(bytes)
+                .map(IrValue::from)
+                .map_err(|_| CodecError::decode("invalid utf-8"))
+        }
+    }
+
+    #[test]
+    fn registry_lookup_by_name_and_mime_type() {
+        let mut registry = CodecRegistry::new();
+        assert!(registry.is_empty());
+        registry.register(Utf8Codec("utf8"));
+
+        let codec = registry.by_name("UTF8").expect("codec by name");
+        let bytes = codec.encode(&IrValue::from("hi")).unwrap();
+        assert_eq!(bytes, b"hi");
+        assert_eq!(codec.decode(&bytes).unwrap(), IrValue::from("hi"));
+
+        let codec = registry
+            .by_mime_type("Text/Plain; charset=utf-8")
+            .expect("codec by mime type");
+        assert_eq!(codec.name(), "utf8");
+
+        assert!(registry.by_name("json").is_none());
+        assert!(registry.by_mime_type("application/json").is_none());
+    }
+
+    #[test]
+    fn later_registrations_take_precedence() {
+        let mut registry = CodecRegistry::new();
+        registry.register(Utf8Codec("first")).register(Utf8Codec("second"));
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["first", "second"]);
+        assert_eq!(registry.by_mime_type("text/plain").unwrap().name(), "second");
+    }
+
+    #[test]
+    fn codec_errors_report_kind() {
+        let codec = Utf8Codec("utf8");
+        let err = codec.encode(&IrValue::Null).unwrap_err();
+        assert_eq!(err.kind(), CodecErrorKind::Encode);
+        assert_eq!(err.message(), "expected string");
+
+        let err = codec.decode(&[0xFF]).unwrap_err();
+        assert_eq!(err.kind(), CodecErrorKind::Decode);
+        assert_eq!(alloc::format!("{}", err), "decode error: invalid utf-8");
+    }
+}
@@ -13,6 +13,7 @@
 //! - **Bundle Support**: Full OSC Bundle implementation with nested bundle support  
 //! - **Flexible Types**: Support for all OSC types including timestamps, binary data, and extensible types
 //! - **Serde Integration**: Optional serde support for JSON/MessagePack serialization
+//! - **Codec Abstraction**: A shared [`Codec`] trait and [`CodecRegistry`] for format-agnostic tooling
 //!
 //! ## Basic Usage
 //!
@@ -45,9 +46,20 @@
 #![cfg_attr(not(test), no_std)]
 
 extern crate alloc;
-use alloc::{boxed::Box, string::String, vec::Vec};
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
 use core::fmt;
 
+mod codec;
+mod time;
+
+pub use codec::{Codec, CodecError, CodecErrorKind, CodecRegistry};
+
+/// `$type` of the maps representing OSC messages.
+const MESSAGE_TYPE: &str = "osc.message";
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -234,6 +246,16 @@ pub enum IrValue {
         data1: u8,
         data2: u8,
     },
+    /// Unsigned integer above `i64::MAX`.
+    ///
+    /// Values that fit in `i64` are represented as [`IrValue::Integer`]; codecs and
+    /// `From<u64>` only produce `Unsigned` for larger values.
+    Unsigned(u64),
+    /// Single-precision float, e.g. an OSC `f` argument.
+    ///
+    /// [`IrValue::Float`] holds double-precision values; keeping the two apart lets
+    /// codecs and adapters preserve the precision class of the original value.
+    Float32(f32),
 }
 
 impl fmt::Display for IrValue {
@@ -265,9 +287,28 @@ impl IrValue {
         }
     }
 
+    /// Returns the value as `u64` if it is a non-negative `Integer` or an `Unsigned`.
+    pub fn as_unsigned(&self) -> Option<u64> {
+        match self {
+            IrValue::Integer(v) => u64::try_from(*v).ok(),
+            IrValue::Unsigned(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Float` or (widened) `Float32`.
     pub fn as_float(&self) -> Option<f64> {
         match self {
             IrValue::Float(v) => Some(*v),
+            IrValue::Float32(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Float32`.
+    pub fn as_float32(&self) -> Option<f32> {
+        match self {
+            IrValue::Float32(v) => Some(*v),
             _ => None,
         }
     }
@@ -337,6 +378,20 @@ impl IrValue {
             _ => None,
         }
     }
+
+    /// Returns the address and arguments of an OSC message map, as built by
+    /// [`IrValue::message`].
+    ///
+    /// Returns `None` unless the value is a map with `$type` `"osc.message"`, a string
+    /// `address` and an array of `args`.
+    pub fn as_message(&self) -> Option<(&str, &[IrValue])> {
+        let entries = self.as_map()?;
+        let field = |name: &str| entries.iter().find(|(k, _)| k == name).map(|(_, v)| v);
+        if field("$type")?.as_str()? != MESSAGE_TYPE {
+            return None;
+        }
+        Some((field("address")?.as_str()?, field("args")?.as_array()?))
+    }
 }
 
 impl From<()> for IrValue {
@@ -399,9 +454,15 @@ impl From<u32> for IrValue {
     }
 }
 
+impl From<u64> for IrValue {
+    fn from(v: u64) -> Self {
+        i64::try_from(v).map_or(IrValue::Unsigned(v), IrValue::Integer)
+    }
+}
+
 impl From<f32> for IrValue {
     fn from(v: f32) -> Self {
-        IrValue::Float(v as f64)
+        IrValue::Float32(v)
     }
 }
 
@@ -491,6 +552,16 @@ impl From<IrBundle> for IrBundleElement {
 }
 
 impl IrValue {
+    /// Creates an OSC message: the map `{"$type": "osc.message", "address", "args"}`
+    /// shared by the codecs and adapters.
+    pub fn message(address: impl Into<String>, args: Vec<IrValue>) -> Self {
+        IrValue::Map(vec![
+            (String::from("$type"), IrValue::from(MESSAGE_TYPE)),
+            (String::from("address"), IrValue::from(address.into())),
+            (String::from("args"), IrValue::Array(args)),
+        ])
+    }
+
     /// Creates a new OSC 1.1 Color value
     #[cfg(feature = "osc11")]
     pub fn color(r: u8, g: u8, b: u8, a: u8) -> Self {
@@ -525,6 +596,26 @@ mod tests {
         assert_eq!(IrValue::from(ts).as_timestamp(), Some(&ts));
     }
 
+    #[test]
+    fn unsigned_values_stay_canonical() {
+        assert_eq!(IrValue::from(i64::MAX as u64), IrValue::Integer(i64::MAX));
+        assert_eq!(IrValue::from(i64::MAX as u64 + 1), IrValue::Unsigned(1 << 63));
+        assert_eq!(IrValue::from(u64::MAX).as_unsigned(), Some(u64::MAX));
+        assert_eq!(IrValue::from(u64::MAX).as_integer(), None);
+        assert_eq!(IrValue::from(0_u64).as_unsigned(), Some(0));
+        assert_eq!(IrValue::from(-1).as_unsigned(), None);
+    }
+
+    #[test]
+    fn float_precision_class_is_kept() {
+        assert_eq!(IrValue::from(0.1_f32), IrValue::Float32(0.1));
+        assert_eq!(IrValue::from(0.1_f64), IrValue::Float(0.1));
+        assert_ne!(IrValue::from(0.5_f32), IrValue::from(0.5_f64));
+        assert_eq!(IrValue::from(0.1_f32).as_float(), Some(0.1_f32 as f64));
+        assert_eq!(IrValue::from(0.1_f32).as_float32(), Some(0.1));
+        assert_eq!(IrValue::from(0.1_f64).as_float32(), None);
+    }
+
     #[test]
     fn ext_and_default_helpers() {
         let ext = IrValue::Ext {
@@ -538,6 +629,42 @@ mod tests {
         assert!(default.as_array().is_none());
     }
 
+    #[test]
+    fn message_helpers_roundtrip() {
+        let message = IrValue::message("/synth/freq", vec![IrValue::from(440.0_f32)]);
+        assert_eq!(
+            message,
+            IrValue::Map(vec![
+                ("$type".into(), IrValue::from("osc.message")),
+                ("address".into(), IrValue::from("/synth/freq")),
+                ("args".into(), IrValue::Array(vec![IrValue::Float32(440.0)])),
+            ])
+        );
+        assert_eq!(
+            message.as_message(),
+            Some(("/synth/freq", &[IrValue::Float32(440.0)][..]))
+        );
+
+        let reordered = IrValue::Map(vec![
+            ("args".into(), IrValue::Array(vec![])),
+            ("address".into(), IrValue::from("/a")),
+            ("$type".into(), IrValue::from("osc.message")),
+        ]);
+        assert_eq!(reordered.as_message(), Some(("/a", &[][..])));
+
+        let without_type = IrValue::Map(vec![
+            ("address".into(), IrValue::from("/a")),
+            ("args".into(), IrValue::Array(vec![])),
+        ]);
+        assert!(without_type.as_message().is_none());
+        let without_args = IrValue::Map(vec![
+            ("$type".into(), IrValue::from("osc.message")),
+            ("address".into(), IrValue::from("/a")),
+        ]);
+        assert!(without_args.as_message().is_none());
+        assert!(IrValue::from("/a").as_message().is_none());
+    }
+
     #[test]
     #[cfg(feature = "osc10")]
     fn bundle_creation_and_nesting() {
@@ -0,0 +1,234 @@
+//! Calendar conversions for timestamps and timetags.
+
+use alloc::{format, string::String};
+
+#[cfg(feature = "osc10")]
+use crate::IrTimetag;
+use crate::IrTimestamp;
+
+/// Seconds from the NTP epoch (1900-01-01) to the Unix epoch (1970-01-01).
+#[cfg(feature = "osc10")]
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+impl IrTimestamp {
+    /// Formats the timestamp as an RFC 3339 UTC string, e.g. `2023-11-14T22:13:20.25Z`.
+    ///
+    /// Returns `None` for years outside 0000-9999, which RFC 3339 cannot express.
+    pub fn to_rfc3339(&self) -> Option<String> {
+        let days = self.seconds.div_euclid(86_400);
+        let secs = self.seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+        let mut out = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        if self.nanos != 0 {
+            let fraction = format!("{:09}", self.nanos.min(999_999_999));
+            out.push('.');
+            out.push_str(fraction.trim_end_matches('0'));
+        }
+        out.push('Z');
+        Some(out)
+    }
+
+    /// Parses an RFC 3339 date/time with a `Z` or numeric UTC offset.
+    ///
+    /// Fractions beyond nanoseconds are truncated; a leap second `:60` rolls over into
+    /// the next minute.
+    pub fn parse_rfc3339(s: &str) -> Option<Self> {
+        let b = s.as_bytes();
+        let number = |range: core::ops::Range<usize>| -> Option<i64> {
+            let digits = b.get(range)?;
+            digits.iter().try_fold(0i64, |acc, d| {
+                d.is_ascii_digit().then(|| acc * 10 + i64::from(d - b'0'))
+            })
+        };
+        let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+        if b.len() < 20
+            || separators.iter().any(|&(i, c)| b[i] != c)
+            || !matches!(b[10], b'T' | b't')
+        {
+            return None;
+        }
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+
+        let mut rest = &b[19..];
+        let mut nanos = 0u32;
+        if let Some(fraction) = rest.strip_prefix(b".") {
+            let len = fraction.iter().take_while(|d| d.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            for (i, d) in fraction[..len].iter().enumerate().take(9) {
+                nanos += u32::from(d - b'0') * 10u32.pow(8 - i as u32);
+            }
+            rest = &fraction[len..];
+        }
+        let offset = match rest {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+                let digits = [*h1, *h2, *m1, *m2];
+                if !digits.iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+                let value = |hi: u8, lo: u8| i64::from(hi - b'0') * 10 + i64::from(lo - b'0');
+                let (hours, minutes) = (value(*h1, *h2), value(*m1, *m2));
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+                let offset = hours * 3600 + minutes * 60;
+                if *sign == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => return None,
+        };
+
+        let days = days_from_civil(year, month as u32, day as u32);
+        Some(IrTimestamp {
+            seconds: days * 86_400 + hour * 3600 + minute * 60 + second - offset,
+            nanos,
+        })
+    }
+}
+
+#[cfg(feature = "osc10")]
+impl IrTimetag {
+    /// Converts a wall-clock timestamp to an NTP timetag.
+    ///
+    /// Returns `None` outside NTP era 0 (1900-01-01 to 2036-02-07). The fraction is
+    /// rounded up so that [`IrTimetag::to_timestamp`] gives back the same nanoseconds.
+    pub fn from_timestamp(ts: &IrTimestamp) -> Option<Self> {
+        let seconds = u32::try_from(ts.seconds.checked_add(NTP_UNIX_OFFSET)?).ok()?;
+        let nanos = u64::from(ts.nanos.min(999_999_999));
+        let fraction = (nanos << 32).div_ceil(1_000_000_000);
+        Some(Self {
+            value: (u64::from(seconds) << 32) + fraction,
+        })
+    }
+
+    /// Converts the timetag to a wall-clock timestamp, truncating the fraction to
+    /// nanoseconds. The "immediately" value 1 maps to just after 1900-01-01.
+    pub fn to_timestamp(&self) -> IrTimestamp {
+        let fraction = self.value & 0xFFFF_FFFF;
+        IrTimestamp {
+            seconds: (self.value >> 32) as i64 - NTP_UNIX_OFFSET,
+            nanos: ((fraction * 1_000_000_000) >> 32) as u32,
+        }
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian (year, month, day).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a proleptic Gregorian date to days since 1970-01-01.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(seconds: i64, nanos: u32) -> IrTimestamp {
+        IrTimestamp { seconds, nanos }
+    }
+
+    #[test]
+    fn format_and_parse_roundtrip() {
+        for value in [
+            ts(0, 0),
+            ts(-1, 500),
+            ts(951_782_400, 0),
+            ts(1_700_000_000, 250_000_000),
+            ts(-62_167_219_200, 1),
+            ts(253_402_300_799, 999_999_999),
+        ] {
+            let text = value.to_rfc3339().unwrap();
+            assert_eq!(IrTimestamp::parse_rfc3339(&text), Some(value), "{}", text);
+        }
+        assert_eq!(ts(253_402_300_800, 0).to_rfc3339(), None);
+        assert_eq!(ts(-62_167_219_201, 0).to_rfc3339(), None);
+    }
+
+    #[test]
+    fn parses_offsets_and_rejects_malformed_input() {
+        assert_eq!(IrTimestamp::parse_rfc3339("2013-03-21T20:04:00Z"), Some(ts(1_363_896_240, 0)));
+        assert_eq!(IrTimestamp::parse_rfc3339("2013-03-21t22:04:00+02:00"), Some(ts(1_363_896_240, 0)));
+        assert_eq!(IrTimestamp::parse_rfc3339("2013-03-21T19:34:00.1234567891-00:30"), Some(ts(1_363_896_240, 123_456_789)));
+        assert_eq!(IrTimestamp::parse_rfc3339("2016-12-31T23:59:60Z"), Some(ts(1_483_228_800, 0)));
+        for bad in [
+            "2013-03-21T20:04:00",
+            "2013-03-21 20:04:00Z",
+            "2013-02-29T20:04:00Z",
+            "2013-13-01T00:00:00Z",
+            "2013-03-21T20:04:00.Z",
+            "2013-03-21T20:04:00+2:00",
+            "+013-03-21T20:04:00Z",
+        ] {
+            assert_eq!(IrTimestamp::parse_rfc3339(bad), None, "{}", bad);
+        }
+    }
+
+    #[cfg(feature = "osc10")]
+    #[test]
+    fn timetags_convert_to_and_from_timestamps() {
+        let unix_epoch = IrTimetag::from_ntp(2_208_988_800 << 32);
+        assert_eq!(unix_epoch.to_timestamp(), ts(0, 0));
+        assert_eq!(IrTimetag::from_timestamp(&ts(0, 0)), Some(unix_epoch));
+        assert_eq!(IrTimetag::from_timestamp(&ts(0, 500_000_000)).unwrap().value, (2_208_988_800 << 32) + (1 << 31));
+
+        for nanos in [1, 123_456_789, 999_999_999] {
+            let value = ts(1_700_000_000, nanos);
+            assert_eq!(IrTimetag::from_timestamp(&value).unwrap().to_timestamp(), value);
+        }
+        assert_eq!(IrTimetag::from_timestamp(&ts(-2_208_988_801, 0)), None);
+        assert_eq!(IrTimetag::from_timestamp(&ts(2_085_978_496, 0)), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! The HTTP and WebSocket client.
+
+use std::net::SocketAddr;
+
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use hyper::header;
+use hyper::{Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use osc_adapter_rosc::OscCodec;
+use osc_ir::{Codec, IrValue};
+use serde_json::Value as J;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::{Error, HostInfo, Namespace, Node};
+
+/// A client browsing the namespace of a remote OSCQuery server.
+///
+/// Each request opens its own HTTP connection; [`listen`](OscQueryClient::listen) opens
+/// a WebSocket connection for value updates.
+#[derive(Clone, Debug)]
+pub struct OscQueryClient {
+    addr: SocketAddr,
+}
+
+impl OscQueryClient {
+    /// A client of the server at `addr`.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { addr }
+    }
+
+    /// Returns the address of the server.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Fetches the server's `HOST_INFO`.
+    pub async fn host_info(&self) -> Result<HostInfo, Error> {
+        match self.get("/?HOST_INFO").await? {
+            Some(json) => HostInfo::from_json(&json),
+            None => Err(Error::Protocol("empty HOST_INFO")),
+        }
+    }
+
+    /// Fetches the whole namespace.
+    pub async fn namespace(&self) -> Result<Namespace, Error> {
+        match self.get("/").await? {
+            Some(json) => Namespace::from_json(&json),
+            None => Err(Error::Protocol("empty namespace")),
+        }
+    }
+
+    /// Fetches the node at `path` and everything below it.
+    ///
+    /// Fails with [`Error::UnknownPath`] if the server has no such node.
+    pub async fn node(&self, path: &str) -> Result<Node, Error> {
+        match self.get(&percent_encode(path)).await {
+            Ok(Some(json)) => Node::from_json(&json),
+            Ok(None) => Err(Error::Protocol("empty node")),
+            Err(Error::Status(404)) => Err(Error::UnknownPath(path.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches one attribute, such as `"VALUE"` or `"RANGE"`, of the node at `path`.
+    ///
+    /// Returns `None` if the node does not have the attribute.
+    pub async fn attribute(&self, path: &str, attribute: &str) -> Result<Option<J>, Error> {
+        let target = format!("{}?{}", percent_encode(path), attribute);
+        match self.get(&target).await {
+            Ok(Some(mut json)) => Ok(json.get_mut(attribute).map(J::take)),
+            Ok(None) => Ok(None),
+            Err(Error::Status(404)) => Err(Error::UnknownPath(path.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches the current value of the parameter at `path`, typed by its `TYPE`.
+    ///
+    /// Fails with [`Error::UnknownPath`] if there is no parameter at `path`, and
+    /// returns an empty value if the parameter is not readable.
+    pub async fn value(&self, path: &str) -> Result<Vec<IrValue>, Error> {
+        let node = self.node(path).await?;
+        let parameter = node
+            .parameter
+            .ok_or_else(|| Error::UnknownPath(path.to_string()))?;
+        Ok(parameter.value().to_vec())
+    }
+
+    /// Opens a WebSocket connection to listen to value updates.
+    pub async fn listen(&self) -> Result<ValueListener, Error> {
+        let url = format!("ws://{}/", self.addr);
+        let (ws, _) = tokio_tungstenite::connect_async(url).await?;
+        Ok(ValueListener { ws })
+    }
+
+    /// Sends a GET request, returning the JSON body or `None` for 204 No Content.
+    async fn get(&self, target: &str) -> Result<Option<J>, Error> {
+        let stream = TcpStream::connect(self.addr).await?;
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        // Ends once `sender` is dropped
+        tokio::spawn(connection);
+        let request = Request::get(target)
+            .header(header::HOST, self.addr.to_string())
+            .body(Empty::<Bytes>::new())
+            .map_err(|_| Error::InvalidPath(target.to_string()))?;
+        let response = sender.send_request(request).await?;
+        match response.status() {
+            StatusCode::OK => {
+                let body = response.into_body().collect().await?.to_bytes();
+                Ok(Some(serde_json::from_slice(&body)?))
+            }
+            StatusCode::NO_CONTENT => Ok(None),
+            status => Err(Error::Status(status.as_u16())),
+        }
+    }
+}
+
+/// A change reported by an OSCQuery server over WebSocket.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Update {
+    /// The value of a listened path changed
+    Value {
+        /// Path of the parameter
+        path: String,
+        /// New value
+        value: Vec<IrValue>,
+    },
+    /// A node was added (`PATH_ADDED`)
+    PathAdded(String),
+    /// A node and everything below it was removed (`PATH_REMOVED`)
+    PathRemoved(String),
+}
+
+/// A WebSocket connection to an OSCQuery server, returned by
+/// [`OscQueryClient::listen`].
+///
+/// The server sends the values of the paths passed to
+/// [`listen`](ValueListener::listen) as OSC messages, so they carry OSC types rather
+/// than the server's `TYPE` strings.
+pub struct ValueListener {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl ValueListener {
+    /// Asks the server to send the value of `path` whenever it changes.
+    pub async fn listen(&mut self, path: &str) -> Result<(), Error> {
+        self.command("LISTEN", path).await
+    }
+
+    /// Asks the server to stop sending the value of `path`.
+    pub async fn ignore(&mut self, path: &str) -> Result<(), Error> {
+        self.command("IGNORE", path).await
+    }
+
+    /// Waits for the next update, returning `None` once the connection is closed.
+    ///
+    /// Frames that cannot be decoded are returned as errors; the connection stays
+    /// usable.
+    pub async fn next_update(&mut self) -> Option<Result<Update, Error>> {
+        loop {
+            let update = match self.ws.next().await? {
+                Ok(Message::Binary(packet)) => value_update(&packet),
+                Ok(Message::Text(text)) => command_update(&text),
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(e) => Err(e.into()),
+            };
+            return Some(update);
+        }
+    }
+
+    /// Closes the connection.
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.ws.close(None).await?;
+        Ok(())
+    }
+
+    async fn command(&mut self, command: &str, path: &str) -> Result<(), Error> {
+        let text = serde_json::json!({ "COMMAND": command, "DATA": path }).to_string();
+        self.ws.send(Message::Text(text)).await?;
+        Ok(())
+    }
+}
+
+fn value_update(packet: &[u8]) -> Result<Update, Error> {
+    let message = OscCodec.decode(packet)?;
+    let (path, value) = message
+        .as_message()
+        .ok_or(Error::Protocol("value updates must be OSC messages"))?;
+    Ok(Update::Value {
+        path: path.to_string(),
+        value: value.to_vec(),
+    })
+}
+
+fn command_update(text: &str) -> Result<Update, Error> {
+    let command: J = serde_json::from_str(text)?;
+    let path = command
+        .get("DATA")
+        .and_then(J::as_str)
+        .ok_or(Error::Protocol("commands need a DATA path"))?
+        .to_string();
+    match command.get("COMMAND").and_then(J::as_str) {
+        Some("PATH_ADDED") => Ok(Update::PathAdded(path)),
+        Some("PATH_REMOVED") => Ok(Update::PathRemoved(path)),
+        _ => Err(Error::Protocol("unknown command")),
+    }
+}
+
+/// Escapes the characters of an OSC path that are not allowed in a URL path.
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,150 @@
+//! The `HOST_INFO` description of a server.
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+
+use serde_json::{Map, Value as J};
+
+use crate::Error;
+
+/// OSCQuery extensions this crate's server implements.
+pub(crate) const EXTENSIONS: &[&str] = &[
+    "ACCESS",
+    "DESCRIPTION",
+    "LISTEN",
+    "PATH_ADDED",
+    "PATH_REMOVED",
+    "RANGE",
+    "TYPE",
+    "VALUE",
+];
+
+/// Transport of a server's OSC port (`OSC_TRANSPORT`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OscTransport {
+    /// `"UDP"`
+    #[default]
+    Udp,
+    /// `"TCP"`
+    Tcp,
+}
+
+/// What a server reports for `?HOST_INFO`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostInfo {
+    /// Name of the server (`NAME`)
+    pub name: String,
+    /// Address to send OSC to (`OSC_IP`), if not the server's
+    pub osc_ip: Option<IpAddr>,
+    /// Port to send OSC to (`OSC_PORT`)
+    pub osc_port: Option<u16>,
+    /// Transport of the OSC port (`OSC_TRANSPORT`)
+    pub osc_transport: OscTransport,
+    /// Which OSCQuery extensions are supported (`EXTENSIONS`)
+    pub extensions: BTreeMap<String, bool>,
+}
+
+impl HostInfo {
+    /// Host information named `name`, listing the extensions this crate's server
+    /// implements.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            osc_ip: None,
+            osc_port: None,
+            osc_transport: OscTransport::Udp,
+            extensions: EXTENSIONS
+                .iter()
+                .map(|&extension| (extension.to_string(), true))
+                .collect(),
+        }
+    }
+
+    /// Sets the address OSC is received on.
+    pub fn with_osc_addr(mut self, addr: SocketAddr) -> Self {
+        self.osc_ip = Some(addr.ip());
+        self.osc_port = Some(addr.port());
+        self
+    }
+
+    /// Sets the transport of the OSC port.
+    pub fn with_osc_transport(mut self, transport: OscTransport) -> Self {
+        self.osc_transport = transport;
+        self
+    }
+
+    /// Returns `true` if the server reports supporting `extension`.
+    pub fn supports(&self, extension: &str) -> bool {
+        self.extensions.get(extension).copied().unwrap_or(false)
+    }
+
+    /// Returns the OSCQuery JSON form.
+    pub fn to_json(&self) -> J {
+        let mut object = Map::new();
+        object.insert("NAME".into(), J::from(self.name.as_str()));
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|(name, &supported)| (name.clone(), J::Bool(supported)))
+            .collect();
+        object.insert("EXTENSIONS".into(), J::Object(extensions));
+        if let Some(ip) = self.osc_ip {
+            object.insert("OSC_IP".into(), J::from(ip.to_string()));
+        }
+        if let Some(port) = self.osc_port {
+            object.insert("OSC_PORT".into(), J::from(port));
+        }
+        let transport = match self.osc_transport {
+            OscTransport::Udp => "UDP",
+            OscTransport::Tcp => "TCP",
+        };
+        object.insert("OSC_TRANSPORT".into(), J::from(transport));
+        J::Object(object)
+    }
+
+    /// Reads the OSCQuery JSON form.
+    pub fn from_json(json: &J) -> Result<Self, Error> {
+        let object = json
+            .as_object()
+            .ok_or(Error::Protocol("HOST_INFO must be an object"))?;
+        let osc_ip = match object.get("OSC_IP") {
+            None => None,
+            Some(ip) => Some(
+                ip.as_str()
+                    .and_then(|ip| ip.parse().ok())
+                    .ok_or(Error::Protocol("OSC_IP must be an IP address"))?,
+            ),
+        };
+        let osc_port = match object.get("OSC_PORT") {
+            None => None,
+            Some(port) => Some(
+                port.as_u64()
+                    .and_then(|port| u16::try_from(port).ok())
+                    .ok_or(Error::Protocol("OSC_PORT must be a port number"))?,
+            ),
+        };
+        let osc_transport = match object.get("OSC_TRANSPORT").and_then(J::as_str) {
+            Some("TCP") => OscTransport::Tcp,
+            _ => OscTransport::Udp,
+        };
+        let extensions = match object.get("EXTENSIONS") {
+            None => BTreeMap::new(),
+            Some(J::Object(extensions)) => extensions
+                .iter()
+                .map(|(name, supported)| (name.clone(), supported.as_bool().unwrap_or(false)))
+                .collect(),
+            Some(_) => return Err(Error::Protocol("EXTENSIONS must be an object")),
+        };
+        Ok(Self {
+            name: object
+                .get("NAME")
+                .and_then(J::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            osc_ip,
+            osc_port,
+            osc_transport,
+            extensions,
+        })
+    }
+}
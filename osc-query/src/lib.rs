@@ -0,0 +1,171 @@
+//! # osc-query
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! [OSCQuery](https://github.com/Vidvox/OSCQueryProposal) server and client for
+//! `osc-ir` values.
+//!
+//! A [`Namespace`] holds the OSC addresses an application exposes, with their types,
+//! current values and metadata. [`OscQueryServer`] describes it as JSON over HTTP and
+//! pushes value changes to WebSocket listeners; [`OscQueryClient`] browses a remote
+//! namespace and reads its values back as typed [`IrValue`](osc_ir::IrValue)s.
+//!
+//! ## Features
+//!
+//! - **Namespace**: parameters with `TYPE`, `VALUE`, `ACCESS`, `RANGE` and
+//!   `DESCRIPTION`, arranged in a tree of nodes
+//! - **HTTP Server**: `GET /path` returns a node, `GET /path?VALUE` a single attribute
+//!   and `GET /?HOST_INFO` the [`HostInfo`]
+//! - **Value Listening**: WebSocket clients send `LISTEN` and `IGNORE` commands and
+//!   receive values as OSC messages, plus `PATH_ADDED` and `PATH_REMOVED` notifications
+//! - **Client**: [`OscQueryClient`] fetches nodes, attributes and typed values, and
+//!   listens to updates with a [`ValueListener`]
+//!
+//! ## Usage
+//!
+//! ```rust,no_run
+//! use osc_ir::IrValue;
+//! use osc_query::{Access, HostInfo, Namespace, OscQueryClient, OscQueryServer, Parameter, Range};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), osc_query::Error> {
+//! let mut namespace = Namespace::new();
+//! namespace.add(
+//!     "/synth/freq",
+//!     Parameter::new(vec![IrValue::Float32(440.0)])?
+//!         .with_range(vec![Range::new(IrValue::Float32(20.0), IrValue::Float32(20_000.0))])
+//!         .with_description("Oscillator frequency"),
+//! )?;
+//! namespace.add("/synth/level", Parameter::new(vec![IrValue::Float32(0.5)])?.with_access(Access::ReadOnly))?;
+//!
+//! let server = OscQueryServer::bind("127.0.0.1:8080", namespace, HostInfo::new("synth")).await?;
+//! server.set_value("/synth/level", vec![IrValue::Float32(0.8)])?;
+//!
+//! let client = OscQueryClient::new(server.local_addr());
+//! assert_eq!(client.value("/synth/freq").await?, vec![IrValue::Float32(440.0)]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+
+use osc_ir::CodecError;
+use tokio_tungstenite::tungstenite;
+
+mod client;
+mod host;
+mod namespace;
+mod server;
+
+pub use client::{OscQueryClient, Update, ValueListener};
+pub use host::{HostInfo, OscTransport};
+pub use namespace::{Access, Namespace, Node, Parameter, Range};
+pub use server::OscQueryServer;
+
+/// Error produced by the server, the client or the namespace.
+#[derive(Debug)]
+pub enum Error {
+    /// Socket error
+    Io(io::Error),
+    /// HTTP protocol error
+    Http(hyper::Error),
+    /// WebSocket handshake or protocol error
+    WebSocket(Box<tungstenite::Error>),
+    /// A response is not valid JSON
+    Json(serde_json::Error),
+    /// A value could not be encoded or decoded
+    Codec(CodecError),
+    /// The server answered with an unexpected HTTP status
+    Status(u16),
+    /// A response or command does not follow OSCQuery
+    Protocol(&'static str),
+    /// A path is not a valid OSC address
+    InvalidPath(String),
+    /// There is no node or parameter at a path
+    UnknownPath(String),
+    /// A message tried to set a read-only parameter
+    NotWritable(String),
+    /// A value does not have the types of its parameter
+    TypeMismatch {
+        /// Path of the parameter
+        path: String,
+        /// Type tags of the parameter
+        expected: String,
+        /// Type tags of the value
+        found: String,
+    },
+    /// A value has no OSC type, such as a map or a null
+    UnsupportedValue(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "socket error: {}", e),
+            Error::Http(e) => write!(f, "http error: {}", e),
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::Json(e) => write!(f, "invalid json: {}", e),
+            Error::Codec(e) => write!(f, "{}", e),
+            Error::Status(status) => write!(f, "unexpected http status {}", status),
+            Error::Protocol(reason) => write!(f, "oscquery protocol error: {}", reason),
+            Error::InvalidPath(path) => write!(f, "invalid OSC address {:?}", path),
+            Error::UnknownPath(path) => write!(f, "no node at {:?}", path),
+            Error::NotWritable(path) => write!(f, "{:?} is not writable", path),
+            Error::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{:?} has type {:?}, got a value of type {:?}",
+                path, expected, found
+            ),
+            Error::UnsupportedValue(reason) => write!(f, "unsupported value: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Http(e) => Some(e),
+            Error::WebSocket(e) => Some(e.as_ref()),
+            Error::Json(e) => Some(e),
+            Error::Codec(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<CodecError> for Error {
+    fn from(e: CodecError) -> Self {
+        Error::Codec(e)
+    }
+}
@@ -0,0 +1,743 @@
+//! The OSCQuery address space.
+
+use std::collections::BTreeMap;
+
+use osc_codec_json::{from_json_with, to_json_with, JsonOptions};
+use osc_ir::IrValue;
+use serde_json::{Map, Value as J};
+
+use crate::Error;
+
+/// Characters OSC forbids in an address.
+const RESERVED: &[char] = &[' ', '#', '*', ',', '?', '[', ']', '{', '}'];
+
+/// Who may read and write a parameter (OSCQuery `ACCESS`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// Neither readable nor writable (`0`)
+    None,
+    /// The value can be queried but not set (`1`)
+    ReadOnly,
+    /// The value can be set but not queried (`2`)
+    WriteOnly,
+    /// The value can be queried and set (`3`)
+    #[default]
+    ReadWrite,
+}
+
+impl Access {
+    /// Returns the OSCQuery code.
+    pub fn code(self) -> u8 {
+        match self {
+            Access::None => 0,
+            Access::ReadOnly => 1,
+            Access::WriteOnly => 2,
+            Access::ReadWrite => 3,
+        }
+    }
+
+    /// Parses an OSCQuery code.
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(Access::None),
+            1 => Some(Access::ReadOnly),
+            2 => Some(Access::WriteOnly),
+            3 => Some(Access::ReadWrite),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value can be queried.
+    pub fn is_readable(self) -> bool {
+        matches!(self, Access::ReadOnly | Access::ReadWrite)
+    }
+
+    /// Returns `true` if the value can be set.
+    pub fn is_writable(self) -> bool {
+        matches!(self, Access::WriteOnly | Access::ReadWrite)
+    }
+}
+
+/// Bounds of one argument of a parameter (an OSCQuery `RANGE` entry).
+///
+/// Numbers read from a remote namespace come back as `Integer` or `Float`, whatever
+/// the argument's type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    /// Smallest value (`MIN`)
+    pub min: Option<IrValue>,
+    /// Largest value (`MAX`)
+    pub max: Option<IrValue>,
+    /// The only values allowed (`VALS`), if not empty
+    pub vals: Vec<IrValue>,
+}
+
+impl Range {
+    /// A range from `min` to `max`.
+    pub fn new(min: impl Into<IrValue>, max: impl Into<IrValue>) -> Self {
+        Self {
+            min: Some(min.into()),
+            max: Some(max.into()),
+            vals: Vec::new(),
+        }
+    }
+
+    /// A range allowing only `vals`.
+    pub fn values(vals: Vec<IrValue>) -> Self {
+        Self {
+            vals,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if the range sets no bound.
+    pub fn is_unbounded(&self) -> bool {
+        self.min.is_none() && self.max.is_none() && self.vals.is_empty()
+    }
+
+    fn to_json(&self) -> J {
+        if self.is_unbounded() {
+            return J::Null;
+        }
+        let mut object = Map::new();
+        if let Some(min) = &self.min {
+            object.insert("MIN".into(), plain(min));
+        }
+        if let Some(max) = &self.max {
+            object.insert("MAX".into(), plain(max));
+        }
+        if !self.vals.is_empty() {
+            object.insert("VALS".into(), self.vals.iter().map(plain).collect());
+        }
+        J::Object(object)
+    }
+
+    /// Reads a range, converting numbers to the argument's type tag since plain JSON
+    /// does not tell floats from doubles.
+    fn from_json(json: &J, tag: Option<char>) -> Result<Self, Error> {
+        let object = match json {
+            J::Null => return Ok(Self::default()),
+            J::Object(object) => object,
+            _ => return Err(Error::Protocol("RANGE entries must be objects")),
+        };
+        let decode = |j: &J| typed(from_json_with(j, &JsonOptions::plain()), tag);
+        Ok(Self {
+            min: object.get("MIN").map(decode),
+            max: object.get("MAX").map(decode),
+            vals: match object.get("VALS") {
+                None => Vec::new(),
+                Some(J::Array(vals)) => vals.iter().map(decode).collect(),
+                Some(_) => return Err(Error::Protocol("VALS must be an array")),
+            },
+        })
+    }
+}
+
+/// Converts a number decoded from plain JSON to the type of `tag`.
+fn typed(value: IrValue, tag: Option<char>) -> IrValue {
+    match (tag, value) {
+        (Some('f'), IrValue::Float(f)) => IrValue::Float32(f as f32),
+        (Some('f'), IrValue::Integer(i)) => IrValue::Float32(i as f32),
+        (Some('d'), IrValue::Integer(i)) => IrValue::Float(i as f64),
+        (_, value) => value,
+    }
+}
+
+fn plain(value: &IrValue) -> J {
+    to_json_with(value, &JsonOptions::plain()).unwrap_or(J::Null)
+}
+
+/// A value in the namespace: the arguments of the OSC message that sets it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    type_tags: String,
+    value: Vec<IrValue>,
+    /// Who may read and write the value (default [`Access::ReadWrite`])
+    pub access: Access,
+    /// Bounds of each argument
+    pub range: Vec<Range>,
+    /// Human-readable description
+    pub description: Option<String>,
+}
+
+impl Parameter {
+    /// A read-write parameter whose type is given by its current `value`.
+    ///
+    /// Fails with [`Error::UnsupportedValue`] if an argument has no OSC type, such as a
+    /// map or a bundle.
+    pub fn new(value: Vec<IrValue>) -> Result<Self, Error> {
+        Ok(Self {
+            type_tags: type_tags(&value)?,
+            value,
+            access: Access::ReadWrite,
+            range: Vec::new(),
+            description: None,
+        })
+    }
+
+    /// Sets who may read and write the value.
+    pub fn with_access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Sets the bounds of each argument.
+    pub fn with_range(mut self, range: Vec<Range>) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Sets the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Returns the OSC type tags of the arguments, without the leading `,`.
+    pub fn type_tags(&self) -> &str {
+        &self.type_tags
+    }
+
+    /// Returns the current value.
+    ///
+    /// Empty for parameters read from a remote namespace that did not report it, such
+    /// as write-only ones.
+    pub fn value(&self) -> &[IrValue] {
+        &self.value
+    }
+
+    /// Replaces the value, which must have the same types.
+    pub(crate) fn set_value(&mut self, path: &str, value: Vec<IrValue>) -> Result<(), Error> {
+        let found = type_tags(&value)?;
+        if !same_types(&self.type_tags, &found) {
+            return Err(Error::TypeMismatch {
+                path: path.to_string(),
+                expected: self.type_tags.clone(),
+                found,
+            });
+        }
+        self.value = value;
+        Ok(())
+    }
+
+    /// Adds `TYPE`, `ACCESS`, `VALUE`, `RANGE` and `DESCRIPTION` to a node object.
+    fn write_json(&self, path: &str, object: &mut Map<String, J>) {
+        // The codec writes the tags of the current value, which differ from the
+        // declared ones for booleans (`T`/`F`) and large integers (`h`)
+        let message = IrValue::message(path, self.value.clone());
+        let encoded = to_json_with(&message, &JsonOptions::oscquery()).ok();
+        let mut encoded = match encoded {
+            Some(J::Object(encoded)) if !self.value.is_empty() => encoded,
+            _ => Map::new(),
+        };
+        let type_tags = encoded
+            .remove("TYPE")
+            .unwrap_or_else(|| J::from(self.type_tags.as_str()));
+        object.insert("TYPE".into(), type_tags);
+        object.insert("ACCESS".into(), J::from(self.access.code()));
+        if self.access.is_readable() {
+            if let Some(value) = encoded.remove("VALUE") {
+                object.insert("VALUE".into(), value);
+            }
+        }
+        if !self.range.is_empty() {
+            let range = self.range.iter().map(Range::to_json).collect();
+            object.insert("RANGE".into(), range);
+        }
+        if let Some(description) = &self.description {
+            object.insert("DESCRIPTION".into(), J::from(description.as_str()));
+        }
+    }
+
+    fn from_json(path: &str, object: &Map<String, J>, type_tags: &str) -> Result<Self, Error> {
+        let value = match object.get("VALUE") {
+            None => Vec::new(),
+            Some(value) => {
+                let node = J::Object(
+                    [
+                        ("FULL_PATH".to_string(), J::from(path)),
+                        ("TYPE".to_string(), J::from(type_tags)),
+                        ("VALUE".to_string(), value.clone()),
+                    ]
+                    .into_iter()
+                    .collect(),
+                );
+                match from_json_with(&node, &JsonOptions::oscquery()) {
+                    IrValue::Map(mut entries) => match entries.pop() {
+                        Some((key, IrValue::Array(args))) if key == "args" => args,
+                        _ => return Err(Error::Protocol("VALUE does not match TYPE")),
+                    },
+                    _ => return Err(Error::Protocol("VALUE does not match TYPE")),
+                }
+            }
+        };
+        let access = match object.get("ACCESS") {
+            None => Access::ReadWrite,
+            Some(code) => code
+                .as_u64()
+                .and_then(Access::from_code)
+                .ok_or(Error::Protocol("ACCESS must be 0 to 3"))?,
+        };
+        let range = match object.get("RANGE") {
+            None => Vec::new(),
+            Some(J::Array(entries)) => {
+                // Ranges describe the arguments in order; arrays have no single tag
+                let mut tags = type_tags.chars().filter(|&tag| tag != '[' && tag != ']');
+                entries
+                    .iter()
+                    .map(|entry| Range::from_json(entry, tags.next()))
+                    .collect::<Result<_, _>>()?
+            }
+            Some(_) => return Err(Error::Protocol("RANGE must be an array")),
+        };
+        Ok(Self {
+            type_tags: type_tags.to_string(),
+            value,
+            access,
+            range,
+            description: description(object)?,
+        })
+    }
+}
+
+/// A container or parameter, and the nodes below it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Node {
+    /// Human-readable description
+    pub description: Option<String>,
+    /// The value at this address, if it is not only a container
+    pub parameter: Option<Parameter>,
+    /// Child nodes by name
+    pub contents: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Returns the node below this one at a relative `path` such as `"synth/freq"`.
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |node, part| node.contents.get(part))
+    }
+
+    /// Returns the OSCQuery JSON description of this node, found at `path`.
+    pub fn to_json(&self, path: &str) -> J {
+        let mut object = Map::new();
+        object.insert("FULL_PATH".into(), J::from(path));
+        if let Some(parameter) = &self.parameter {
+            parameter.write_json(path, &mut object);
+        } else if let Some(description) = &self.description {
+            object.insert("DESCRIPTION".into(), J::from(description.as_str()));
+        }
+        if !self.contents.is_empty() {
+            let contents = self
+                .contents
+                .iter()
+                .map(|(name, child)| (name.clone(), child.to_json(&join(path, name))))
+                .collect();
+            object.insert("CONTENTS".into(), J::Object(contents));
+        }
+        J::Object(object)
+    }
+
+    /// Reads an OSCQuery JSON node description.
+    pub fn from_json(json: &J) -> Result<Self, Error> {
+        let object = json
+            .as_object()
+            .ok_or(Error::Protocol("nodes must be objects"))?;
+        let path = object
+            .get("FULL_PATH")
+            .and_then(J::as_str)
+            .ok_or(Error::Protocol("nodes need a FULL_PATH"))?;
+        let parameter = match object.get("TYPE") {
+            None => None,
+            Some(J::String(type_tags)) => Some(Parameter::from_json(path, object, type_tags)?),
+            Some(_) => return Err(Error::Protocol("TYPE must be a string")),
+        };
+        let contents = match object.get("CONTENTS") {
+            None => BTreeMap::new(),
+            Some(J::Object(contents)) => contents
+                .iter()
+                .map(|(name, child)| Ok((name.clone(), Node::from_json(child)?)))
+                .collect::<Result<_, Error>>()?,
+            Some(_) => return Err(Error::Protocol("CONTENTS must be an object")),
+        };
+        Ok(Self {
+            description: if parameter.is_some() {
+                None
+            } else {
+                description(object)?
+            },
+            parameter,
+            contents,
+        })
+    }
+}
+
+/// The tree of parameters a server describes.
+///
+/// ```rust
+/// use osc_ir::IrValue;
+/// use osc_query::{Access, Namespace, Parameter, Range};
+///
+/// let mut namespace = Namespace::new();
+/// namespace.add(
+///     "/synth/freq",
+///     Parameter::new(vec![IrValue::Float32(440.0)])?
+///         .with_range(vec![Range::new(20.0, 20_000.0)])
+///         .with_description("Oscillator frequency"),
+/// )?;
+/// namespace.add(
+///     "/synth/name",
+///     Parameter::new(vec![IrValue::from("lead")])?.with_access(Access::ReadOnly),
+/// )?;
+/// namespace.set_value("/synth/freq", vec![IrValue::Float32(220.0)])?;
+/// assert_eq!(namespace.value("/synth/freq"), Some(&[IrValue::Float32(220.0)][..]));
+/// # Ok::<(), osc_query::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Namespace {
+    root: Node,
+}
+
+impl Namespace {
+    /// An empty namespace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the root node.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Adds a parameter at `path`, creating the containers above it and replacing any
+    /// parameter already there.
+    pub fn add(&mut self, path: &str, parameter: Parameter) -> Result<(), Error> {
+        self.node_mut(path)?.parameter = Some(parameter);
+        Ok(())
+    }
+
+    /// Sets the description of the container at `path`, creating it if needed.
+    pub fn describe(&mut self, path: &str, description: impl Into<String>) -> Result<(), Error> {
+        self.node_mut(path)?.description = Some(description.into());
+        Ok(())
+    }
+
+    /// Removes the node at `path` and everything below it.
+    pub fn remove(&mut self, path: &str) -> Option<Node> {
+        let parts = split(path).ok()?;
+        let (last, parents) = parts.split_last()?;
+        let mut node = &mut self.root;
+        for part in parents {
+            node = node.contents.get_mut(*part)?;
+        }
+        node.contents.remove(*last)
+    }
+
+    /// Returns the node at `path`.
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        self.root.get(path)
+    }
+
+    /// Returns the parameter at `path`.
+    pub fn parameter(&self, path: &str) -> Option<&Parameter> {
+        self.get(path)?.parameter.as_ref()
+    }
+
+    /// Returns the current value of the parameter at `path`.
+    pub fn value(&self, path: &str) -> Option<&[IrValue]> {
+        self.parameter(path).map(Parameter::value)
+    }
+
+    /// Returns the paths of every parameter, in order.
+    pub fn parameters(&self) -> Vec<(String, &Parameter)> {
+        fn collect<'a>(node: &'a Node, path: &str, out: &mut Vec<(String, &'a Parameter)>) {
+            if let Some(parameter) = &node.parameter {
+                out.push((path.to_string(), parameter));
+            }
+            for (name, child) in &node.contents {
+                collect(child, &join(path, name), out);
+            }
+        }
+        let mut parameters = Vec::new();
+        collect(&self.root, "/", &mut parameters);
+        parameters
+    }
+
+    /// Sets the value of the parameter at `path`, whatever its access.
+    ///
+    /// The new value must have the same OSC types as the parameter; `T` and `F`, and
+    /// `i` and `h`, count as the same type.
+    pub fn set_value(&mut self, path: &str, value: Vec<IrValue>) -> Result<(), Error> {
+        self.parameter_mut(path)?.set_value(path, value)
+    }
+
+    /// Sets a parameter from an OSC message map, as a client writing to it would.
+    ///
+    /// Fails with [`Error::NotWritable`] if the parameter is read-only.
+    pub fn apply(&mut self, message: &IrValue) -> Result<(), Error> {
+        let (path, args) = message
+            .as_message()
+            .ok_or_else(|| Error::UnsupportedValue(String::from("not an OSC message")))?;
+        let parameter = self.parameter_mut(path)?;
+        if !parameter.access.is_writable() {
+            return Err(Error::NotWritable(path.to_string()));
+        }
+        parameter.set_value(path, args.to_vec())
+    }
+
+    /// Returns the OSCQuery JSON description of the node at `path`.
+    pub fn to_json(&self, path: &str) -> Option<J> {
+        let parts = split(path).ok()?;
+        let node = self.get(path)?;
+        Some(node.to_json(&format!("/{}", parts.join("/"))))
+    }
+
+    /// Reads the OSCQuery JSON description of a whole namespace, as served for `/`.
+    pub fn from_json(json: &J) -> Result<Self, Error> {
+        Ok(Self {
+            root: Node::from_json(json)?,
+        })
+    }
+
+    fn node_mut(&mut self, path: &str) -> Result<&mut Node, Error> {
+        Ok(split(path)?.into_iter().fold(&mut self.root, |node, part| {
+            node.contents.entry(part.to_string()).or_default()
+        }))
+    }
+
+    fn parameter_mut(&mut self, path: &str) -> Result<&mut Parameter, Error> {
+        let unknown = || Error::UnknownPath(path.to_string());
+        let mut node = &mut self.root;
+        for part in split(path)? {
+            node = node.contents.get_mut(part).ok_or_else(unknown)?;
+        }
+        node.parameter.as_mut().ok_or_else(unknown)
+    }
+}
+
+/// Splits an address into its parts, checking it is a valid OSC address.
+pub(crate) fn split(path: &str) -> Result<Vec<&str>, Error> {
+    let invalid = || Error::InvalidPath(path.to_string());
+    let rest = path.strip_prefix('/').ok_or_else(invalid)?;
+    if rest.is_empty() {
+        return Ok(Vec::new());
+    }
+    rest.split('/')
+        .map(|part| {
+            if part.is_empty() || part.contains(RESERVED) {
+                Err(invalid())
+            } else {
+                Ok(part)
+            }
+        })
+        .collect()
+}
+
+fn join(path: &str, name: &str) -> String {
+    format!("{}/{}", path.trim_end_matches('/'), name)
+}
+
+fn description(object: &Map<String, J>) -> Result<Option<String>, Error> {
+    match object.get("DESCRIPTION") {
+        None => Ok(None),
+        Some(J::String(description)) => Ok(Some(description.clone())),
+        Some(_) => Err(Error::Protocol("DESCRIPTION must be a string")),
+    }
+}
+
+/// Returns the OSC type tags of `args`.
+fn type_tags(args: &[IrValue]) -> Result<String, Error> {
+    let mut tags = String::new();
+    for arg in args {
+        push_tags(arg, &mut tags)?;
+    }
+    Ok(tags)
+}
+
+fn push_tags(arg: &IrValue, tags: &mut String) -> Result<(), Error> {
+    let tag = match arg {
+        IrValue::Null => 'N',
+        IrValue::Bool(true) => 'T',
+        IrValue::Bool(false) => 'F',
+        IrValue::Integer(i) if i32::try_from(*i).is_ok() => 'i',
+        IrValue::Integer(_) | IrValue::Unsigned(_) => 'h',
+        IrValue::Float(_) => 'd',
+        IrValue::Float32(_) => 'f',
+        IrValue::String(_) => 's',
+        IrValue::Binary(_) => 'b',
+        IrValue::Timestamp(_) => 't',
+        IrValue::Array(items) => {
+            tags.push('[');
+            for item in items {
+                push_tags(item, tags)?;
+            }
+            ']'
+        }
+        other => {
+            return Err(Error::UnsupportedValue(format!(
+                "{:?} has no OSC type",
+                other
+            )))
+        }
+    };
+    tags.push(tag);
+    Ok(())
+}
+
+/// Compares type tags, treating booleans alike and integers alike.
+fn same_types(a: &str, b: &str) -> bool {
+    let class = |tag| match tag {
+        'F' => 'T',
+        'h' => 'i',
+        tag => tag,
+    };
+    a.len() == b.len() && a.chars().map(class).eq(b.chars().map(class))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namespace() -> Namespace {
+        let mut namespace = Namespace::new();
+        namespace.describe("/synth", "A synthesizer").unwrap();
+        namespace
+            .add(
+                "/synth/freq",
+                Parameter::new(vec![IrValue::Float32(440.0)])
+                    .unwrap()
+                    .with_range(vec![Range::new(20, 20_000)])
+                    .with_description("Frequency"),
+            )
+            .unwrap();
+        namespace
+            .add(
+                "/synth/on",
+                Parameter::new(vec![IrValue::Bool(true)]).unwrap(),
+            )
+            .unwrap();
+        namespace
+            .add(
+                "/synth/trigger",
+                Parameter::new(vec![IrValue::from(0)])
+                    .unwrap()
+                    .with_access(Access::WriteOnly),
+            )
+            .unwrap();
+        namespace
+            .add(
+                "/synth/wave",
+                Parameter::new(vec![IrValue::from("sine")])
+                    .unwrap()
+                    .with_access(Access::ReadOnly)
+                    .with_range(vec![Range::values(vec![
+                        IrValue::from("sine"),
+                        IrValue::from("square"),
+                    ])]),
+            )
+            .unwrap();
+        namespace
+    }
+
+    #[test]
+    fn nodes_are_described_in_oscquery_json() {
+        let json = namespace().to_json("/synth/freq").unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "FULL_PATH": "/synth/freq",
+                "TYPE": "f",
+                "ACCESS": 3,
+                "VALUE": [440.0],
+                "RANGE": [{"MIN": 20, "MAX": 20000}],
+                "DESCRIPTION": "Frequency",
+            })
+        );
+        let json = namespace().to_json("/synth/trigger").unwrap();
+        assert!(json.get("VALUE").is_none());
+        let root = namespace().to_json("/").unwrap();
+        assert_eq!(root["FULL_PATH"], "/");
+        assert_eq!(root["CONTENTS"]["synth"]["DESCRIPTION"], "A synthesizer");
+        assert_eq!(
+            root["CONTENTS"]["synth"]["CONTENTS"]["wave"]["FULL_PATH"],
+            "/synth/wave"
+        );
+    }
+
+    #[test]
+    fn json_round_trips_with_types() {
+        let mut namespace = namespace();
+        namespace
+            .set_value("/synth/on", vec![IrValue::Bool(false)])
+            .unwrap();
+        let json = namespace.to_json("/").unwrap();
+        let read = Namespace::from_json(&json).unwrap();
+        assert_eq!(
+            read.value("/synth/freq"),
+            Some(&[IrValue::Float32(440.0)][..])
+        );
+        assert_eq!(read.value("/synth/on"), Some(&[IrValue::Bool(false)][..]));
+        assert_eq!(read.value("/synth/trigger"), Some(&[][..]));
+        assert_eq!(read.parameter("/synth/trigger").unwrap().type_tags(), "i");
+        let wave = read.parameter("/synth/wave").unwrap();
+        assert_eq!(wave.access, Access::ReadOnly);
+        assert_eq!(wave.range[0].vals.len(), 2);
+        assert_eq!(
+            read.get("/synth").unwrap().description.as_deref(),
+            Some("A synthesizer")
+        );
+    }
+
+    #[test]
+    fn values_keep_their_types() {
+        let mut namespace = namespace();
+        assert!(matches!(
+            namespace.set_value("/synth/freq", vec![IrValue::from("high")]),
+            Err(Error::TypeMismatch { .. })
+        ));
+        namespace
+            .set_value("/synth/trigger", vec![IrValue::from(1_i64 << 40)])
+            .unwrap();
+        assert!(matches!(
+            namespace.set_value("/nothing", vec![]),
+            Err(Error::UnknownPath(_))
+        ));
+        assert!(matches!(
+            namespace.apply(&IrValue::message(
+                "/synth/wave",
+                vec![IrValue::from("square")]
+            )),
+            Err(Error::NotWritable(_))
+        ));
+        namespace
+            .apply(&IrValue::message(
+                "/synth/freq",
+                vec![IrValue::Float32(1.0)],
+            ))
+            .unwrap();
+        assert_eq!(
+            namespace.value("/synth/freq"),
+            Some(&[IrValue::Float32(1.0)][..])
+        );
+    }
+
+    #[test]
+    fn paths_are_validated() {
+        let parameter = Parameter::new(vec![]).unwrap();
+        for path in ["synth", "/synth//freq", "/synth/*", "/a b"] {
+            assert!(matches!(
+                Namespace::new().add(path, parameter.clone()),
+                Err(Error::InvalidPath(_))
+            ));
+        }
+        assert!(matches!(
+            Parameter::new(vec![IrValue::Map(vec![])]),
+            Err(Error::UnsupportedValue(_))
+        ));
+        let mut namespace = namespace();
+        assert!(namespace.remove("/synth/freq").is_some());
+        assert!(namespace.get("/synth/freq").is_none());
+        assert_eq!(namespace.parameters().len(), 3);
+    }
+}
@@ -0,0 +1,409 @@
+//! The HTTP and WebSocket server.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{self, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::upgrade::OnUpgrade;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use osc_adapter_rosc::OscCodec;
+use osc_ir::{Codec, IrValue};
+use serde_json::Value as J;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::{Error, HostInfo, Namespace, Node, Parameter};
+
+/// Messages waiting to be written to one WebSocket client; a client that falls
+/// further behind misses updates.
+const OUTGOING_CAPACITY: usize = 256;
+/// Node attributes; asking for one a node lacks is answered with 204 rather than 400.
+const ATTRIBUTES: &[&str] = &[
+    "FULL_PATH",
+    "CONTENTS",
+    "TYPE",
+    "ACCESS",
+    "VALUE",
+    "RANGE",
+    "DESCRIPTION",
+];
+
+/// A WebSocket client and the paths it listens to.
+struct Listener {
+    paths: HashSet<String>,
+    outgoing: mpsc::Sender<Message>,
+}
+
+struct State {
+    namespace: Namespace,
+    host_info: HostInfo,
+    listeners: HashMap<u64, Listener>,
+    next_listener: u64,
+}
+
+impl State {
+    /// Sends the value of `path` to the clients listening to it.
+    fn notify_value(&self, path: &str) {
+        let Some(parameter) = self.namespace.parameter(path) else {
+            return;
+        };
+        if !parameter.access.is_readable() {
+            return;
+        }
+        let Ok(packet) = OscCodec.encode(&IrValue::message(path, parameter.value().to_vec()))
+        else {
+            return;
+        };
+        for listener in self.listeners.values() {
+            if listener.paths.contains(path) {
+                let _ = listener.outgoing.try_send(Message::Binary(packet.clone()));
+            }
+        }
+    }
+
+    /// Sends a `PATH_ADDED` or `PATH_REMOVED` command to every client.
+    fn notify_all(&self, command: &str, path: &str) {
+        let text = serde_json::json!({ "COMMAND": command, "DATA": path }).to_string();
+        for listener in self.listeners.values() {
+            let _ = listener.outgoing.try_send(Message::Text(text.clone()));
+        }
+    }
+}
+
+type Shared = Arc<Mutex<State>>;
+
+/// An OSCQuery server describing a [`Namespace`] over HTTP.
+///
+/// `GET /path` answers with the JSON description of the node, `GET /path?VALUE` (or
+/// any other attribute) with that attribute alone, and `GET /?HOST_INFO` with the
+/// [`HostInfo`]. WebSocket clients connecting to the same port send
+/// `{"COMMAND": "LISTEN", "DATA": "/path"}` (or `"IGNORE"`) and receive an OSC
+/// binary message whenever the value of a path they listen to changes, plus
+/// `PATH_ADDED` and `PATH_REMOVED` commands.
+///
+/// The server only describes the namespace: OSC messages setting values are received
+/// by the application on its own socket (see [`HostInfo::osc_port`]) and passed to
+/// [`apply`](OscQueryServer::apply). Dropping the server closes the listener and every
+/// connection.
+pub struct OscQueryServer {
+    local_addr: SocketAddr,
+    state: Shared,
+    accept: JoinHandle<()>,
+}
+
+impl fmt::Debug for OscQueryServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OscQueryServer")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
+
+impl OscQueryServer {
+    /// Serves `namespace` over HTTP on `addr`.
+    pub async fn bind(
+        addr: impl ToSocketAddrs,
+        namespace: Namespace,
+        host_info: HostInfo,
+    ) -> Result<Self, Error> {
+        Self::from_listener(TcpListener::bind(addr).await?, namespace, host_info)
+    }
+
+    /// Serves `namespace` on an already bound listener.
+    pub fn from_listener(
+        listener: TcpListener,
+        namespace: Namespace,
+        host_info: HostInfo,
+    ) -> Result<Self, Error> {
+        let local_addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            namespace,
+            host_info,
+            listeners: HashMap::new(),
+            next_listener: 0,
+        }));
+        let accept = tokio::spawn(accept_loop(listener, state.clone()));
+        Ok(Self {
+            local_addr,
+            state,
+            accept,
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns a copy of the namespace.
+    pub fn namespace(&self) -> Namespace {
+        self.state.lock().unwrap().namespace.clone()
+    }
+
+    /// Returns the host information.
+    pub fn host_info(&self) -> HostInfo {
+        self.state.lock().unwrap().host_info.clone()
+    }
+
+    /// Returns the current value of the parameter at `path`.
+    pub fn value(&self, path: &str) -> Option<Vec<IrValue>> {
+        let state = self.state.lock().unwrap();
+        state.namespace.value(path).map(<[IrValue]>::to_vec)
+    }
+
+    /// Adds a parameter, telling WebSocket clients if the path is new.
+    pub fn add(&self, path: &str, parameter: Parameter) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let existed = state.namespace.get(path).is_some();
+        state.namespace.add(path, parameter)?;
+        if !existed {
+            state.notify_all("PATH_ADDED", path);
+        }
+        state.notify_value(path);
+        Ok(())
+    }
+
+    /// Removes the node at `path` and everything below it, telling WebSocket clients.
+    pub fn remove(&self, path: &str) -> Option<Node> {
+        let mut state = self.state.lock().unwrap();
+        let removed = state.namespace.remove(path)?;
+        state.notify_all("PATH_REMOVED", path);
+        Some(removed)
+    }
+
+    /// Sets the value of the parameter at `path` and sends it to the clients
+    /// listening to it.
+    pub fn set_value(&self, path: &str, value: Vec<IrValue>) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        state.namespace.set_value(path, value)?;
+        state.notify_value(path);
+        Ok(())
+    }
+
+    /// Sets a writable parameter from a received OSC message map and sends the new
+    /// value to the clients listening to it.
+    pub fn apply(&self, message: &IrValue) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        state.namespace.apply(message)?;
+        if let Some((path, _)) = message.as_message() {
+            state.notify_value(path);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if a WebSocket client listens to `path`.
+    pub fn is_listened(&self, path: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        state.listeners.values().any(|l| l.paths.contains(path))
+    }
+}
+
+impl Drop for OscQueryServer {
+    fn drop(&mut self) {
+        // The accept task owns the connection tasks; aborting it aborts them too
+        self.accept.abort();
+    }
+}
+
+async fn accept_loop(listener: TcpListener, state: Shared) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    connections.spawn(serve(stream, state.clone()));
+                }
+                // Errors such as running out of file descriptors persist for a
+                // while; avoid spinning on them
+                Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+/// Serves HTTP requests on one connection, then the WebSocket session if the
+/// connection was upgraded.
+async fn serve(stream: TcpStream, state: Shared) {
+    let upgrade = Arc::new(Mutex::new(None));
+    let service = service_fn({
+        let state = state.clone();
+        let upgrade = upgrade.clone();
+        move |request| {
+            let response = respond(request, &state, &upgrade);
+            async move { Ok::<_, Infallible>(response) }
+        }
+    });
+    let served = http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades()
+        .await;
+    let upgrade = upgrade.lock().unwrap().take();
+    if let (Ok(()), Some(upgrade)) = (served, upgrade) {
+        if let Ok(upgraded) = upgrade.await {
+            let ws =
+                WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None).await;
+            listen(ws, state).await;
+        }
+    }
+}
+
+fn respond(
+    request: Request<Incoming>,
+    state: &Shared,
+    upgrade: &Mutex<Option<OnUpgrade>>,
+) -> Response<Full<Bytes>> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    if let Some(key) = websocket_key(&request) {
+        let accept = derive_accept_key(key.as_bytes());
+        *upgrade.lock().unwrap() = Some(hyper::upgrade::on(request));
+        let mut response = status(StatusCode::SWITCHING_PROTOCOLS);
+        let headers = response.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        if let Ok(accept) = HeaderValue::from_str(&accept) {
+            headers.insert(header::SEC_WEBSOCKET_ACCEPT, accept);
+        }
+        return response;
+    }
+
+    let path = percent_decode(request.uri().path());
+    let query = request.uri().query().unwrap_or_default();
+    let state = state.lock().unwrap();
+    if query == "HOST_INFO" {
+        return json(&state.host_info.to_json());
+    }
+    let Some(node) = state.namespace.to_json(&path) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    if query.is_empty() {
+        return json(&node);
+    }
+    match node.get(query) {
+        Some(attribute) => json(&serde_json::json!({ query: attribute })),
+        None if ATTRIBUTES.contains(&query) => status(StatusCode::NO_CONTENT),
+        None => status(StatusCode::BAD_REQUEST),
+    }
+}
+
+fn websocket_key<B>(request: &Request<B>) -> Option<&HeaderValue> {
+    let upgrade = request.headers().get(header::UPGRADE)?;
+    if !upgrade.as_bytes().eq_ignore_ascii_case(b"websocket") {
+        return None;
+    }
+    request.headers().get(header::SEC_WEBSOCKET_KEY)
+}
+
+fn status(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = status;
+    response
+}
+
+fn json(json: &J) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(json.to_string())));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Decodes `%XX` escapes; invalid escapes are kept as they are.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Runs a WebSocket session, taking `LISTEN` and `IGNORE` commands and sending
+/// updates.
+async fn listen<S>(mut ws: WebSocketStream<S>, state: Shared)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (outgoing, mut queued) = mpsc::channel(OUTGOING_CAPACITY);
+    let id = {
+        let mut state = state.lock().unwrap();
+        state.next_listener += 1;
+        let id = state.next_listener;
+        let listener = Listener {
+            paths: HashSet::new(),
+            outgoing,
+        };
+        state.listeners.insert(id, listener);
+        id
+    };
+    loop {
+        tokio::select! {
+            received = ws.next() => match received {
+                Some(Ok(Message::Text(text))) => command(&state, id, &text),
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break,
+            },
+            Some(message) = queued.recv() => {
+                if ws.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    state.lock().unwrap().listeners.remove(&id);
+}
+
+/// Applies a client command; malformed and unknown commands are ignored.
+fn command(state: &Shared, id: u64, text: &str) {
+    let Ok(command) = serde_json::from_str::<J>(text) else {
+        return;
+    };
+    let Some(path) = command.get("DATA").and_then(J::as_str) else {
+        return;
+    };
+    let mut state = state.lock().unwrap();
+    let Some(listener) = state.listeners.get_mut(&id) else {
+        return;
+    };
+    match command.get("COMMAND").and_then(J::as_str) {
+        Some("LISTEN") => {
+            listener.paths.insert(path.to_string());
+        }
+        Some("IGNORE") => {
+            listener.paths.remove(path);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,270 @@
+use std::future::Future;
+use std::time::Duration;
+
+use osc_ir::IrValue;
+use osc_query::{
+    Access, Error, HostInfo, Namespace, OscQueryClient, OscQueryServer, OscTransport, Parameter,
+    Range, Update, ValueListener,
+};
+use serde_json::json;
+
+async fn timeout<T>(future: impl Future<Output = T>) -> T {
+    tokio::time::timeout(Duration::from_secs(5), future)
+        .await
+        .expect("timed out")
+}
+
+fn synth() -> Namespace {
+    let mut namespace = Namespace::new();
+    namespace
+        .add(
+            "/synth/freq",
+            Parameter::new(vec![IrValue::Float32(440.0)])
+                .unwrap()
+                .with_range(vec![Range::new(
+                    IrValue::Float32(20.0),
+                    IrValue::Float32(20_000.0),
+                )])
+                .with_description("Oscillator frequency"),
+        )
+        .unwrap();
+    namespace
+        .add(
+            "/synth/wave",
+            Parameter::new(vec![IrValue::from("sine"), IrValue::from(2)])
+                .unwrap()
+                .with_range(vec![
+                    Range::values(vec![IrValue::from("sine"), IrValue::from("saw")]),
+                    Range::new(1, 8),
+                ]),
+        )
+        .unwrap();
+    namespace
+        .add(
+            "/synth/level",
+            Parameter::new(vec![IrValue::Float(0.5)])
+                .unwrap()
+                .with_access(Access::ReadOnly),
+        )
+        .unwrap();
+    namespace
+        .add(
+            "/synth/gate",
+            Parameter::new(vec![IrValue::Bool(false)])
+                .unwrap()
+                .with_access(Access::WriteOnly),
+        )
+        .unwrap();
+    namespace.describe("/synth", "A tiny synthesizer").unwrap();
+    namespace
+}
+
+async fn server() -> (OscQueryServer, OscQueryClient) {
+    let host_info = HostInfo::new("synth")
+        .with_osc_addr("127.0.0.1:9000".parse().unwrap())
+        .with_osc_transport(OscTransport::Udp);
+    let server = OscQueryServer::bind("127.0.0.1:0", synth(), host_info)
+        .await
+        .unwrap();
+    let client = OscQueryClient::new(server.local_addr());
+    (server, client)
+}
+
+/// Starts listening to `path` and waits until the server has taken the command.
+async fn listen(server: &OscQueryServer, listener: &mut ValueListener, path: &str) {
+    listener.listen(path).await.unwrap();
+    timeout(async {
+        while !server.is_listened(path) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+}
+
+async fn next_update(listener: &mut ValueListener) -> Update {
+    timeout(listener.next_update()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn client_reads_the_namespace_back() {
+    let (server, client) = server().await;
+    let namespace = timeout(client.namespace()).await.unwrap();
+    for path in ["/synth/freq", "/synth/wave", "/synth/level"] {
+        assert_eq!(
+            namespace.parameter(path),
+            server.namespace().parameter(path)
+        );
+    }
+
+    let freq = namespace.parameter("/synth/freq").unwrap();
+    assert_eq!(freq.type_tags(), "f");
+    assert_eq!(freq.value(), [IrValue::Float32(440.0)]);
+    assert_eq!(freq.description.as_deref(), Some("Oscillator frequency"));
+    assert_eq!(
+        namespace.get("/synth").unwrap().description.as_deref(),
+        Some("A tiny synthesizer")
+    );
+    // Write-only parameters have no value to report
+    assert_eq!(namespace.parameter("/synth/gate").unwrap().value(), []);
+}
+
+#[tokio::test]
+async fn values_are_typed() {
+    let (_server, client) = server().await;
+    assert_eq!(
+        timeout(client.value("/synth/wave")).await.unwrap(),
+        vec![IrValue::from("sine"), IrValue::from(2)]
+    );
+    assert_eq!(
+        timeout(client.value("/synth/level")).await.unwrap(),
+        vec![IrValue::Float(0.5)]
+    );
+    assert!(matches!(
+        timeout(client.value("/synth")).await,
+        Err(Error::UnknownPath(_))
+    ));
+    assert!(matches!(
+        timeout(client.node("/drums")).await,
+        Err(Error::UnknownPath(_))
+    ));
+}
+
+#[tokio::test]
+async fn attributes_can_be_queried_alone() {
+    let (_server, client) = server().await;
+    assert_eq!(
+        timeout(client.attribute("/synth/freq", "VALUE"))
+            .await
+            .unwrap(),
+        Some(json!([440.0]))
+    );
+    assert_eq!(
+        timeout(client.attribute("/synth/wave", "RANGE"))
+            .await
+            .unwrap(),
+        Some(json!([{"VALS": ["sine", "saw"]}, {"MIN": 1, "MAX": 8}]))
+    );
+    assert_eq!(
+        timeout(client.attribute("/synth/level", "ACCESS"))
+            .await
+            .unwrap(),
+        Some(json!(1))
+    );
+    // Known attributes the node lacks answer 204, unknown ones 400
+    assert_eq!(
+        timeout(client.attribute("/synth/gate", "VALUE"))
+            .await
+            .unwrap(),
+        None
+    );
+    assert!(matches!(
+        timeout(client.attribute("/synth/freq", "COLOR")).await,
+        Err(Error::Status(400))
+    ));
+    assert!(matches!(
+        timeout(client.attribute("/drums", "VALUE")).await,
+        Err(Error::UnknownPath(_))
+    ));
+}
+
+#[tokio::test]
+async fn host_info_is_served() {
+    let (server, client) = server().await;
+    let host_info = timeout(client.host_info()).await.unwrap();
+    assert_eq!(host_info, server.host_info());
+    assert_eq!(host_info.name, "synth");
+    assert_eq!(host_info.osc_port, Some(9000));
+    assert!(host_info.supports("LISTEN"));
+}
+
+#[tokio::test]
+async fn listeners_receive_value_changes() {
+    let (server, client) = server().await;
+    let mut listener = timeout(client.listen()).await.unwrap();
+    listen(&server, &mut listener, "/synth/freq").await;
+
+    server
+        .set_value("/synth/freq", vec![IrValue::Float32(220.0)])
+        .unwrap();
+    assert_eq!(
+        next_update(&mut listener).await,
+        Update::Value {
+            path: "/synth/freq".into(),
+            value: vec![IrValue::Float32(220.0)],
+        }
+    );
+
+    // Values received over OSC reach listeners too
+    server
+        .apply(&IrValue::message(
+            "/synth/freq",
+            vec![IrValue::Float32(110.0)],
+        ))
+        .unwrap();
+    assert_eq!(
+        next_update(&mut listener).await,
+        Update::Value {
+            path: "/synth/freq".into(),
+            value: vec![IrValue::Float32(110.0)],
+        }
+    );
+    assert_eq!(
+        server.value("/synth/freq"),
+        Some(vec![IrValue::Float32(110.0)])
+    );
+
+    listener.ignore("/synth/freq").await.unwrap();
+    timeout(async {
+        while server.is_listened("/synth/freq") {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn invalid_values_are_rejected() {
+    let (server, _client) = server().await;
+    assert!(matches!(
+        server.set_value("/synth/freq", vec![IrValue::from("loud")]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        server.apply(&IrValue::message("/synth/level", vec![IrValue::Float(1.0)])),
+        Err(Error::NotWritable(_))
+    ));
+    assert!(matches!(
+        server.set_value("/drums", vec![]),
+        Err(Error::UnknownPath(_))
+    ));
+}
+
+#[tokio::test]
+async fn listeners_are_told_about_new_and_removed_paths() {
+    let (server, client) = server().await;
+    let mut listener = timeout(client.listen()).await.unwrap();
+    // Wait for the connection to be registered
+    listen(&server, &mut listener, "/synth/freq").await;
+
+    server
+        .add(
+            "/synth/detune",
+            Parameter::new(vec![IrValue::Float32(0.0)]).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        next_update(&mut listener).await,
+        Update::PathAdded("/synth/detune".into())
+    );
+    assert!(timeout(client.node("/synth/detune")).await.is_ok());
+
+    assert!(server.remove("/synth").is_some());
+    assert_eq!(
+        next_update(&mut listener).await,
+        Update::PathRemoved("/synth".into())
+    );
+    assert!(matches!(
+        timeout(client.node("/synth/freq")).await,
+        Err(Error::UnknownPath(_))
+    ));
+}
@@ -0,0 +1,158 @@
+//! Sources of the current time.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use osc_ir::{IrTimestamp, IrTimetag};
+
+/// Source of the current time for a [`Scheduler`](crate::Scheduler).
+pub trait Clock {
+    /// Returns the current time as an NTP timetag.
+    fn now(&self) -> IrTimetag;
+
+    /// Waits for `duration`; used by [`Scheduler::wait`](crate::Scheduler::wait).
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// The system's wall clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> IrTimetag {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = IrTimestamp {
+            seconds: since_epoch.as_secs() as i64,
+            nanos: since_epoch.subsec_nanos(),
+        };
+        // NTP era 0 ends in 2036; saturate rather than wrap around to 1900
+        IrTimetag::from_timestamp(&timestamp).unwrap_or(IrTimetag::from_ntp(u64::MAX))
+    }
+}
+
+/// A simulated clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one to advance the clock of a
+/// scheduler. [`sleep`](Clock::sleep) advances the clock instead of blocking, which
+/// makes [`Scheduler::wait`](crate::Scheduler::wait) return at once with the next
+/// message.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// A clock stopped at `start`.
+    pub fn new(start: IrTimetag) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(start.value)),
+        }
+    }
+
+    /// Moves the clock to `now`, which may be in the past.
+    pub fn set(&self, now: IrTimetag) {
+        self.now.store(now.value, Ordering::SeqCst);
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let _ = self
+            .now
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |now| {
+                Some(add(IrTimetag::from_ntp(now), duration).value)
+            });
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> IrTimetag {
+        IrTimetag::from_ntp(self.now.load(Ordering::SeqCst))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> IrTimetag {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// Returns `timetag` moved forward by `duration`, saturating.
+///
+/// The fraction is rounded up so that a scheduler's clock advanced by
+/// [`Scheduler::time_until_next`](crate::Scheduler::time_until_next) reaches the timetag.
+pub(crate) fn add(timetag: IrTimetag, duration: Duration) -> IrTimetag {
+    let seconds = duration.as_secs().min(u64::from(u32::MAX)) << 32;
+    let fraction = (u64::from(duration.subsec_nanos()) << 32).div_ceil(1_000_000_000);
+    IrTimetag::from_ntp(timetag.value.saturating_add(seconds + fraction))
+}
+
+/// Returns the time from `earlier` to `later`, rounded up to the nanosecond, or zero
+/// if `later` is not after `earlier`.
+pub(crate) fn between(earlier: IrTimetag, later: IrTimetag) -> Duration {
+    let ntp = later.value.saturating_sub(earlier.value);
+    let nanos = ((ntp & 0xFFFF_FFFF) * 1_000_000_000).div_ceil(1 << 32);
+    Duration::new(ntp >> 32, 0) + Duration::from_nanos(nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_convert_to_ntp_and_back() {
+        let start = IrTimetag::from_ntp(100 << 32);
+        for duration in [
+            Duration::ZERO,
+            Duration::from_nanos(1),
+            Duration::from_millis(250),
+            Duration::new(3, 999_999_999),
+        ] {
+            // Both conversions round up, by less than a nanosecond each way
+            let elapsed = between(start, add(start, duration));
+            assert!(elapsed >= duration && elapsed <= duration + Duration::from_nanos(1));
+        }
+        for fraction in [0, 1, 4_294_967, 1 << 31, u32::MAX as u64] {
+            let later = IrTimetag::from_ntp((101 << 32) + fraction);
+            assert!(add(start, between(start, later)).value >= later.value);
+        }
+        assert_eq!(
+            add(start, Duration::from_millis(500)).value,
+            (100 << 32) + (1 << 31)
+        );
+        assert_eq!(
+            between(add(start, Duration::from_secs(1)), start),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn manual_clock_is_shared_between_clones() {
+        let clock = ManualClock::new(IrTimetag::from_ntp(1 << 32));
+        let other = clock.clone();
+        other.advance(Duration::from_secs(2));
+        assert_eq!(clock.now(), IrTimetag::from_ntp(3 << 32));
+        clock.sleep(Duration::from_secs(1));
+        assert_eq!(other.now(), IrTimetag::from_ntp(4 << 32));
+        clock.set(IrTimetag::from_ntp(2 << 32));
+        assert_eq!(other.now(), IrTimetag::from_ntp(2 << 32));
+    }
+
+    #[test]
+    fn system_clock_is_after_2020() {
+        let now = SystemClock.now().to_timestamp();
+        assert!(now.seconds > 1_577_836_800);
+    }
+}
@@ -0,0 +1,53 @@
+//! # osc-scheduler
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Timetag-aware scheduler for `osc-ir` bundles.
+//!
+//! An [`IrBundle`](osc_ir::IrBundle) carries the time its messages should take
+//! effect. [`Scheduler`] flattens bundles, nested ones included, queues their messages
+//! by effective timetag and releases each one when its time comes on a [`Clock`].
+//!
+//! ## Features
+//!
+//! - **Nested Bundles**: messages of nested bundles are queued under their effective
+//!   timetag, never earlier than the enclosing bundle's
+//! - **Priority Queue**: messages are released in timetag order; "immediately"
+//!   (`IrTimetag::immediate()`) and plain messages come first
+//! - **Pluggable Clock**: the [`SystemClock`] by default, or a [`ManualClock`] for
+//!   deterministic tests on simulated time
+//! - **Late Bundles**: a [`LatePolicy`] drops, delivers or reports messages whose time
+//!   has already passed
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use osc_ir::{IrBundle, IrTimetag, IrValue};
+//! use osc_scheduler::{ManualClock, Scheduler, SchedulerEvent};
+//!
+//! let clock = ManualClock::new(IrTimetag::from_ntp(1_000 << 32));
+//! let mut scheduler = Scheduler::with_clock(clock.clone());
+//!
+//! let note = IrValue::message("/note", vec![IrValue::from(60)]);
+//! let mut bundle = IrBundle::new(IrTimetag::from_ntp(1_002 << 32));
+//! bundle.add_message(note.clone());
+//! scheduler.schedule(IrValue::Bundle(bundle));
+//!
+//! assert_eq!(scheduler.poll(), None);
+//! assert_eq!(scheduler.time_until_next(), Some(Duration::from_secs(2)));
+//!
+//! clock.advance(Duration::from_secs(2));
+//! match scheduler.poll() {
+//!     Some(SchedulerEvent::Due { message, .. }) => assert_eq!(message, note),
+//!     other => panic!("expected the note, got {:?}", other),
+//! }
+//! ```
+
+mod clock;
+mod scheduler;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use scheduler::{LatePolicy, Scheduler, SchedulerEvent};
@@ -0,0 +1,366 @@
+//! The priority queue of scheduled messages.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+use osc_ir::{IrBundle, IrBundleElement, IrTimetag, IrValue};
+
+use crate::clock::{self, Clock, SystemClock};
+
+/// What to do with messages whose timetag has already passed when they are scheduled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LatePolicy {
+    /// Discard them, counting them in [`Scheduler::dropped`]
+    Drop,
+    /// Release them at once as [`SchedulerEvent::Due`]
+    #[default]
+    Deliver,
+    /// Release them at once as [`SchedulerEvent::Late`] instead of delivering them
+    Report,
+}
+
+/// A message released by [`Scheduler::poll`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchedulerEvent {
+    /// A message whose time has come, or a late one under [`LatePolicy::Deliver`]
+    Due {
+        /// The message
+        message: IrValue,
+        /// Effective timetag of the message
+        timetag: IrTimetag,
+    },
+    /// A message that arrived after its time, under [`LatePolicy::Report`]
+    Late {
+        /// The message
+        message: IrValue,
+        /// Effective timetag of the message
+        timetag: IrTimetag,
+        /// How far past its timetag the message was scheduled
+        lateness: Duration,
+    },
+}
+
+impl SchedulerEvent {
+    /// Returns the message.
+    pub fn message(&self) -> &IrValue {
+        match self {
+            SchedulerEvent::Due { message, .. } | SchedulerEvent::Late { message, .. } => message,
+        }
+    }
+
+    /// Returns the effective timetag of the message.
+    pub fn timetag(&self) -> IrTimetag {
+        match self {
+            SchedulerEvent::Due { timetag, .. } | SchedulerEvent::Late { timetag, .. } => *timetag,
+        }
+    }
+}
+
+/// A queued message.
+#[derive(Debug)]
+struct Entry {
+    /// Release order: immediate messages first, then by timetag, then by arrival
+    key: (u64, u64),
+    timetag: IrTimetag,
+    message: IrValue,
+    lateness: Option<Duration>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Releases the messages of OSC bundles when their timetags come.
+///
+/// Nested bundles are flattened: each message is queued under its effective timetag,
+/// the later of its own bundle's and the enclosing ones', with "immediately" taking
+/// the enclosing timetag. Messages are released in timetag order, and in the order
+/// they were scheduled when their timetags are equal. Messages with the "immediately"
+/// timetag, and messages scheduled outside a bundle, are released first.
+///
+/// The scheduler does not run on its own: [`poll`](Scheduler::poll) returns the next
+/// message that is due, [`time_until_next`](Scheduler::time_until_next) says how long
+/// to wait for one (for example with `tokio::time::sleep`), and
+/// [`wait`](Scheduler::wait) blocks until one is due. The time comes from a [`Clock`],
+/// the [`SystemClock`] by default or a [`ManualClock`](crate::ManualClock) in tests.
+///
+/// A message is late if its timetag, plus the [`tolerance`](Scheduler::with_tolerance),
+/// has passed when it is scheduled; the [`LatePolicy`] decides what happens to it.
+/// Messages scheduled in time are always released as due, however late `poll` is
+/// called.
+#[derive(Debug)]
+pub struct Scheduler<C = SystemClock> {
+    clock: C,
+    late_policy: LatePolicy,
+    tolerance: Duration,
+    queue: BinaryHeap<Reverse<Entry>>,
+    next_seq: u64,
+    dropped: u64,
+}
+
+impl Scheduler<SystemClock> {
+    /// A scheduler following the system clock.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for Scheduler<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> Scheduler<C> {
+    /// A scheduler following `clock`, delivering late messages.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            late_policy: LatePolicy::default(),
+            tolerance: Duration::ZERO,
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Sets what happens to late messages.
+    pub fn with_late_policy(mut self, late_policy: LatePolicy) -> Self {
+        self.late_policy = late_policy;
+        self
+    }
+
+    /// Sets how far past its timetag a message may be scheduled without being late.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Returns the clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the late policy.
+    pub fn late_policy(&self) -> LatePolicy {
+        self.late_policy
+    }
+
+    /// Returns the tolerance for late messages.
+    pub fn tolerance(&self) -> Duration {
+        self.tolerance
+    }
+
+    /// Schedules a packet: the messages of a bundle at their timetags, anything else
+    /// as a message to release immediately.
+    pub fn schedule(&mut self, packet: IrValue) {
+        match packet {
+            IrValue::Bundle(bundle) => self.schedule_bundle(bundle),
+            message => {
+                let now = self.clock.now();
+                self.push(IrTimetag::immediate(), message, now);
+            }
+        }
+    }
+
+    /// Schedules the messages of `bundle` and its nested bundles.
+    pub fn schedule_bundle(&mut self, bundle: IrBundle) {
+        let now = self.clock.now();
+        let mut messages = Vec::new();
+        flatten(bundle, IrTimetag::immediate(), &mut messages);
+        for (timetag, message) in messages {
+            self.push(timetag, message, now);
+        }
+    }
+
+    fn push(&mut self, timetag: IrTimetag, message: IrValue, now: IrTimetag) {
+        let lateness = if timetag.is_immediate() {
+            None
+        } else {
+            let lateness = clock::between(timetag, now);
+            (lateness > self.tolerance).then_some(lateness)
+        };
+        if lateness.is_some() && self.late_policy == LatePolicy::Drop {
+            self.dropped += 1;
+            return;
+        }
+        let order = if timetag.is_immediate() {
+            0
+        } else {
+            timetag.value
+        };
+        self.queue.push(Reverse(Entry {
+            key: (order, self.next_seq),
+            timetag,
+            message,
+            lateness,
+        }));
+        self.next_seq += 1;
+    }
+
+    /// Returns the next message that is due, if any.
+    pub fn poll(&mut self) -> Option<SchedulerEvent> {
+        let next = &self.queue.peek()?.0;
+        if !next.timetag.is_immediate()
+            && next.lateness.is_none()
+            && next.timetag.value > self.clock.now().value
+        {
+            return None;
+        }
+        let Reverse(entry) = self.queue.pop()?;
+        Some(match (entry.lateness, self.late_policy) {
+            (Some(lateness), LatePolicy::Report) => SchedulerEvent::Late {
+                message: entry.message,
+                timetag: entry.timetag,
+                lateness,
+            },
+            _ => SchedulerEvent::Due {
+                message: entry.message,
+                timetag: entry.timetag,
+            },
+        })
+    }
+
+    /// Waits on the clock until a message is due and returns it, or returns `None` at
+    /// once if nothing is scheduled.
+    pub fn wait(&mut self) -> Option<SchedulerEvent> {
+        loop {
+            if let Some(event) = self.poll() {
+                return Some(event);
+            }
+            let duration = self.time_until_next()?;
+            self.clock.sleep(duration);
+        }
+    }
+
+    /// Returns how long until the next message is due: zero if one is due now, `None`
+    /// if nothing is scheduled.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        let next = &self.queue.peek()?.0;
+        if next.timetag.is_immediate() || next.lateness.is_some() {
+            return Some(Duration::ZERO);
+        }
+        Some(clock::between(self.clock.now(), next.timetag))
+    }
+
+    /// Returns the effective timetag of the next message to be released.
+    pub fn next_timetag(&self) -> Option<IrTimetag> {
+        self.queue.peek().map(|entry| entry.0.timetag)
+    }
+
+    /// Returns the number of queued messages.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no message is queued.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the number of late messages discarded under [`LatePolicy::Drop`].
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Discards every queued message.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+/// Appends the messages of `bundle`, enclosed in bundles timed at `outer`, with their
+/// effective timetags.
+fn flatten(bundle: IrBundle, outer: IrTimetag, messages: &mut Vec<(IrTimetag, IrValue)>) {
+    let timetag = effective(outer, bundle.timetag);
+    for element in bundle.elements {
+        match element {
+            IrBundleElement::Bundle(inner) | IrBundleElement::Message(IrValue::Bundle(inner)) => {
+                flatten(inner, timetag, messages)
+            }
+            IrBundleElement::Message(message) => messages.push((timetag, message)),
+        }
+    }
+}
+
+/// Returns the timetag of a bundle timed at `inner` nested in one timed at `outer`.
+///
+/// OSC requires nested bundles to be timed no earlier than the enclosing one, so an
+/// earlier `inner` is raised to `outer`.
+fn effective(outer: IrTimetag, inner: IrTimetag) -> IrTimetag {
+    if inner.is_immediate() {
+        outer
+    } else if outer.is_immediate() {
+        inner
+    } else {
+        IrTimetag::from_ntp(outer.value.max(inner.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(address: &str) -> IrValue {
+        IrValue::message(address, vec![])
+    }
+
+    fn at(seconds: u64) -> IrTimetag {
+        IrTimetag::from_ntp(seconds << 32)
+    }
+
+    #[test]
+    fn nested_bundles_take_the_later_timetag() {
+        let mut immediate = IrBundle::immediate();
+        immediate.add_message(message("/inherits"));
+        let mut earlier = IrBundle::new(at(5));
+        earlier.add_message(message("/raised"));
+        let mut later = IrBundle::new(at(20));
+        later.add_message(message("/later"));
+        later.add_bundle(immediate);
+
+        let mut outer = IrBundle::new(at(10));
+        outer.add_message(message("/outer"));
+        outer.add_bundle(earlier);
+        outer.add_element(IrBundleElement::Message(IrValue::Bundle(later)));
+
+        let mut messages = Vec::new();
+        flatten(outer, IrTimetag::immediate(), &mut messages);
+        assert_eq!(
+            messages,
+            vec![
+                (at(10), message("/outer")),
+                (at(10), message("/raised")),
+                (at(20), message("/later")),
+                (at(20), message("/inherits")),
+            ]
+        );
+    }
+
+    #[test]
+    fn immediate_bundles_keep_nested_timetags() {
+        assert_eq!(effective(IrTimetag::immediate(), at(3)), at(3));
+        assert_eq!(
+            effective(IrTimetag::immediate(), IrTimetag::immediate()),
+            IrTimetag::immediate()
+        );
+        assert_eq!(effective(at(3), at(4)), at(4));
+    }
+}
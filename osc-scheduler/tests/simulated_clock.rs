@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use osc_ir::{IrBundle, IrTimetag, IrValue};
+use osc_scheduler::{Clock, LatePolicy, ManualClock, Scheduler, SchedulerEvent};
+
+fn message(address: &str) -> IrValue {
+    IrValue::message(address, vec![])
+}
+
+fn at(seconds: u64) -> IrTimetag {
+    IrTimetag::from_ntp(seconds << 32)
+}
+
+fn bundle(timetag: IrTimetag, addresses: &[&str]) -> IrBundle {
+    let mut bundle = IrBundle::new(timetag);
+    for address in addresses {
+        bundle.add_message(message(address));
+    }
+    bundle
+}
+
+fn scheduler(policy: LatePolicy) -> (Scheduler<ManualClock>, ManualClock) {
+    let clock = ManualClock::new(at(100));
+    let scheduler = Scheduler::with_clock(clock.clone()).with_late_policy(policy);
+    (scheduler, clock)
+}
+
+fn due(address: &str, timetag: IrTimetag) -> SchedulerEvent {
+    SchedulerEvent::Due {
+        message: message(address),
+        timetag,
+    }
+}
+
+#[test]
+fn messages_are_released_at_their_timetags() {
+    let (mut scheduler, clock) = scheduler(LatePolicy::Deliver);
+    scheduler.schedule_bundle(bundle(at(103), &["/c"]));
+    scheduler.schedule_bundle(bundle(at(101), &["/a1", "/a2"]));
+    scheduler.schedule_bundle(bundle(at(102), &["/b"]));
+    assert_eq!(scheduler.len(), 4);
+    assert_eq!(scheduler.next_timetag(), Some(at(101)));
+    assert_eq!(scheduler.poll(), None);
+
+    clock.advance(Duration::from_millis(999));
+    assert_eq!(scheduler.poll(), None);
+    assert_eq!(scheduler.time_until_next(), Some(Duration::from_millis(1)));
+
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(scheduler.poll(), Some(due("/a1", at(101))));
+    assert_eq!(scheduler.poll(), Some(due("/a2", at(101))));
+    assert_eq!(scheduler.poll(), None);
+
+    // A caller polling late still gets everything due, in order
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(scheduler.poll(), Some(due("/b", at(102))));
+    assert_eq!(scheduler.poll(), Some(due("/c", at(103))));
+    assert_eq!(scheduler.poll(), None);
+    assert!(scheduler.is_empty());
+    assert_eq!(scheduler.time_until_next(), None);
+}
+
+#[test]
+fn immediate_messages_come_first() {
+    let (mut scheduler, _clock) = scheduler(LatePolicy::Deliver);
+    scheduler.schedule_bundle(bundle(at(100), &["/timed"]));
+    scheduler.schedule_bundle(bundle(IrTimetag::immediate(), &["/immediate"]));
+    scheduler.schedule(message("/plain"));
+    assert_eq!(scheduler.time_until_next(), Some(Duration::ZERO));
+
+    let immediate = IrTimetag::immediate();
+    assert_eq!(scheduler.poll(), Some(due("/immediate", immediate)));
+    assert_eq!(scheduler.poll(), Some(due("/plain", immediate)));
+    assert_eq!(scheduler.poll(), Some(due("/timed", at(100))));
+}
+
+#[test]
+fn nested_bundles_are_flattened() {
+    let (mut scheduler, clock) = scheduler(LatePolicy::Deliver);
+    let mut outer = bundle(at(110), &["/outer"]);
+    outer.add_bundle(bundle(at(120), &["/inner"]));
+    outer.add_bundle(bundle(IrTimetag::immediate(), &["/inherited"]));
+    scheduler.schedule(IrValue::Bundle(outer));
+
+    let mut released = Vec::new();
+    while let Some(event) = scheduler.wait() {
+        released.push((event.timetag(), clock.now()));
+    }
+    assert_eq!(
+        released,
+        vec![(at(110), at(110)), (at(110), at(110)), (at(120), at(120))]
+    );
+}
+
+#[test]
+fn wait_sleeps_until_the_next_message() {
+    let (mut scheduler, clock) = scheduler(LatePolicy::Deliver);
+    let timetag = IrTimetag::from_ntp((100 << 32) + 12_345_678);
+    scheduler.schedule_bundle(bundle(timetag, &["/a"]));
+    assert_eq!(scheduler.wait(), Some(due("/a", timetag)));
+    assert!(clock.now().value >= timetag.value);
+    assert!(clock.now().value - timetag.value < 5);
+    assert_eq!(scheduler.wait(), None);
+}
+
+#[test]
+fn late_messages_can_be_delivered() {
+    let (mut scheduler, _clock) = scheduler(LatePolicy::Deliver);
+    scheduler.schedule_bundle(bundle(at(105), &["/on-time"]));
+    scheduler.schedule_bundle(bundle(at(90), &["/late"]));
+    assert_eq!(scheduler.time_until_next(), Some(Duration::ZERO));
+    assert_eq!(scheduler.poll(), Some(due("/late", at(90))));
+    assert_eq!(scheduler.poll(), None);
+}
+
+#[test]
+fn late_messages_can_be_dropped() {
+    let (mut scheduler, clock) = scheduler(LatePolicy::Drop);
+    let mut outer = bundle(at(90), &["/late"]);
+    outer.add_bundle(bundle(at(101), &["/inner"]));
+    scheduler.schedule_bundle(outer);
+    assert_eq!(scheduler.dropped(), 1);
+    assert_eq!(scheduler.len(), 1);
+
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(scheduler.poll(), Some(due("/inner", at(101))));
+}
+
+#[test]
+fn late_messages_can_be_reported() {
+    let (mut scheduler, _clock) = scheduler(LatePolicy::Report);
+    scheduler.schedule_bundle(bundle(at(98), &["/late"]));
+    assert_eq!(
+        scheduler.poll(),
+        Some(SchedulerEvent::Late {
+            message: message("/late"),
+            timetag: at(98),
+            lateness: Duration::from_secs(2),
+        })
+    );
+}
+
+#[test]
+fn tolerance_excuses_slightly_late_messages() {
+    let (scheduler, _clock) = scheduler(LatePolicy::Drop);
+    let mut scheduler = scheduler.with_tolerance(Duration::from_millis(20));
+    let slightly_late = IrTimetag::from_ntp((99 << 32) + (u32::MAX as u64 - 1_000));
+    scheduler.schedule_bundle(bundle(slightly_late, &["/slightly-late"]));
+    scheduler.schedule_bundle(bundle(at(99), &["/late"]));
+    assert_eq!(scheduler.dropped(), 1);
+    assert_eq!(scheduler.poll(), Some(due("/slightly-late", slightly_late)));
+
+    // Immediate messages are never late
+    scheduler.schedule_bundle(bundle(IrTimetag::immediate(), &["/now"]));
+    assert_eq!(scheduler.dropped(), 1);
+    scheduler.clear();
+    assert!(scheduler.is_empty());
+}
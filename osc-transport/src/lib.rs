@@ -0,0 +1,121 @@
+//! # osc-transport
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Async network transport for `osc-ir` values, built on tokio.
+//!
+//! [`OscSocket`] sends and receives one `IrValue` per UDP datagram. [`TcpServer`] and
+//! [`TcpClient`] carry values over TCP connections, framed with SLIP (OSC 1.1) or a
+//! size prefix (OSC 1.0). Packets are encoded as OSC binary by default, and any
+//! other `osc_ir::Codec`, such as the MessagePack or JSON codecs, can be plugged in
+//! instead.
+//!
+//! ## Features
+//!
+//! - **UDP**: [`OscSocket::bind`], [`OscSocket::send_to`] and [`OscSocket::recv_from`]
+//! - **Stream**: [`OscSocket::incoming`] yields `(IrValue, SocketAddr)` pairs as a
+//!   `futures_core::Stream`
+//! - **Pluggable Codec**: OSC binary by default; [`OscSocket::with_codec`] switches to
+//!   any `osc_ir::Codec`
+//! - **Broadcast and Multicast**: [`SocketOptions`] sets `SO_BROADCAST`, address reuse,
+//!   multicast groups, loopback and TTL when binding
+//! - **TCP Server**: [`TcpServer`] serves any number of clients and reports
+//!   [`ServerEvent`]s carrying the [`Peer`] each value came from
+//! - **TCP Client**: [`TcpClient`] reconnects with exponential backoff following a
+//!   [`ReconnectPolicy`]
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use futures_util::StreamExt;
+//! use osc_ir::IrValue;
+//! use osc_transport::OscSocket;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), osc_transport::Error> {
+//! let receiver = OscSocket::bind("127.0.0.1:0").await?;
+//! let sender = OscSocket::bind("127.0.0.1:0").await?;
+//!
+//! let message = IrValue::message("/fader/1", vec![IrValue::Float32(0.5)]);
+//! sender.send_to(&message, receiver.local_addr()?).await?;
+//!
+//! let mut incoming = receiver.incoming();
+//! let (value, from) = incoming.next().await.unwrap()?;
+//! assert_eq!(value, message);
+//! assert_eq!(from, sender.local_addr()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use osc_framing::FrameError;
+use osc_ir::CodecError;
+
+mod tcp;
+mod udp;
+
+pub use osc_framing::Framing;
+pub use tcp::{
+    ClientEvent, Peer, PeerId, ReconnectPolicy, ServerEvent, TcpClient, TcpConfig, TcpServer,
+};
+pub use udp::{Incoming, OscSocket, SocketOptions};
+
+/// Size of the receive buffer, enough for any UDP datagram.
+pub const MAX_DATAGRAM_LEN: usize = 65_536;
+
+/// Error produced by the transport.
+#[derive(Debug)]
+pub enum Error {
+    /// Socket error
+    Io(io::Error),
+    /// The codec could not encode the value to send
+    Encode(CodecError),
+    /// A received datagram or frame could not be decoded; later ones are unaffected
+    Decode {
+        /// Sender of the datagram or frame
+        from: SocketAddr,
+        /// The codec error
+        source: CodecError,
+    },
+    /// A frame on a TCP connection is too large or malformed, and was skipped
+    Frame(FrameError),
+    /// The peer is not connected to the server
+    UnknownPeer(PeerId),
+    /// The client has given up reconnecting
+    Closed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "socket error: {}", e),
+            Error::Encode(e) => write!(f, "{}", e),
+            Error::Decode { from, source } => write!(f, "packet from {}: {}", from, source),
+            Error::Frame(e) => write!(f, "{}", e),
+            Error::UnknownPeer(peer) => write!(f, "peer {} is not connected", peer),
+            Error::Closed => f.write_str("the client has given up reconnecting"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Encode(e) => Some(e),
+            Error::Decode { source, .. } => Some(source),
+            Error::Frame(e) => Some(e),
+            Error::UnknownPeer(_) | Error::Closed => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,634 @@
+//! TCP connections carrying framed values.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use osc_adapter_rosc::OscCodec;
+use osc_framing::{FrameError, Framing};
+use osc_ir::{Codec, IrValue};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::Error;
+
+/// Frames waiting to be written to one connection.
+const OUTGOING_CAPACITY: usize = 64;
+/// Events waiting to be taken by the application.
+const EVENT_CAPACITY: usize = 256;
+/// Size of the socket read buffer.
+const READ_CHUNK_LEN: usize = 8192;
+
+/// Framing and codec of a TCP connection.
+///
+/// Defaults to SLIP framing (OSC 1.1) with the OSC binary codec.
+#[derive(Clone)]
+pub struct TcpConfig {
+    framing: Framing,
+    codec: Arc<dyn Codec>,
+}
+
+impl TcpConfig {
+    /// Uses `framing` with the OSC binary codec.
+    pub fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            codec: Arc::new(OscCodec),
+        }
+    }
+
+    /// Switches the wire codec, e.g. to `osc_codec_msgpack::MsgpackCodec`.
+    pub fn with_codec<C: Codec + 'static>(mut self, codec: C) -> Self {
+        self.codec = Arc::new(codec);
+        self
+    }
+
+    /// Returns the framing.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Returns the wire codec.
+    pub fn codec(&self) -> &dyn Codec {
+        self.codec.as_ref()
+    }
+
+    fn encode(&self, value: &IrValue) -> Result<Vec<u8>, Error> {
+        let mut frame = Vec::new();
+        self.framing
+            .encode_value(self.codec.as_ref(), value, &mut frame)
+            .map_err(|e| match e {
+                FrameError::Codec(e) => Error::Encode(e),
+                e => Error::Frame(e),
+            })?;
+        Ok(frame)
+    }
+}
+
+impl Default for TcpConfig {
+    fn default() -> Self {
+        Self::new(Framing::Slip)
+    }
+}
+
+impl fmt::Debug for TcpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpConfig")
+            .field("framing", &self.framing)
+            .field("codec", &self.codec.name())
+            .finish()
+    }
+}
+
+/// Why a connection ended.
+enum Closed {
+    /// The peer closed the connection or it failed
+    Remote(Option<io::Error>),
+    /// The local side dropped the outgoing queue or stopped taking events
+    Local,
+}
+
+/// Reads values from `stream` into `events` and writes frames from `outgoing` until
+/// either side closes.
+async fn drive<E>(
+    stream: TcpStream,
+    peer: SocketAddr,
+    config: &TcpConfig,
+    outgoing: &mut mpsc::Receiver<Vec<u8>>,
+    events: &mpsc::Sender<E>,
+    event: impl Fn(Result<IrValue, Error>) -> E,
+) -> Closed {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut decoder = config.framing.decoder();
+    let mut buf = vec![0; READ_CHUNK_LEN];
+    loop {
+        tokio::select! {
+            read = reader.read(&mut buf) => {
+                let len = match read {
+                    Ok(0) => return Closed::Remote(None),
+                    Ok(len) => len,
+                    Err(e) => return Closed::Remote(Some(e)),
+                };
+                decoder.feed(&buf[..len]);
+                while let Some(value) = decoder.next_value(config.codec.as_ref()) {
+                    let value = value.map_err(|e| match e {
+                        FrameError::Codec(source) => Error::Decode { from: peer, source },
+                        e => Error::Frame(e),
+                    });
+                    if events.send(event(value)).await.is_err() {
+                        return Closed::Local;
+                    }
+                }
+            }
+            frame = outgoing.recv() => match frame {
+                Some(frame) => {
+                    if let Err(e) = writer.write_all(&frame).await {
+                        return Closed::Remote(Some(e));
+                    }
+                }
+                None => return Closed::Local,
+            },
+        }
+    }
+}
+
+/// Identifier of a connection accepted by a [`TcpServer`], unique for the server's
+/// lifetime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeerId(u64);
+
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A client connected to a [`TcpServer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Peer {
+    /// Identifier of the connection
+    pub id: PeerId,
+    /// Address of the client
+    pub addr: SocketAddr,
+}
+
+/// Something that happened on a [`TcpServer`].
+#[derive(Debug)]
+pub enum ServerEvent {
+    /// A client connected
+    Connected(Peer),
+    /// A client sent a value
+    Packet {
+        /// The sender
+        peer: Peer,
+        /// The decoded value
+        value: IrValue,
+    },
+    /// A frame from a client could not be decoded, or accepting a connection failed
+    /// (`peer` is `None`); the server keeps running
+    Error {
+        /// The client the frame came from
+        peer: Option<Peer>,
+        /// What went wrong
+        error: Error,
+    },
+    /// A client disconnected or was disconnected
+    Disconnected {
+        /// The client
+        peer: Peer,
+        /// The socket error that ended the connection, if any
+        error: Option<Error>,
+    },
+}
+
+/// Outgoing queues of the connected clients.
+type Peers = Arc<Mutex<HashMap<PeerId, (SocketAddr, mpsc::Sender<Vec<u8>>)>>>;
+
+/// A TCP server accepting any number of clients.
+///
+/// Connections are served by background tasks; the server reports what happens on
+/// them as [`ServerEvent`]s, through [`next_event`](TcpServer::next_event) or as a
+/// `Stream`. Dropping the server closes the listener and every connection.
+pub struct TcpServer {
+    local_addr: SocketAddr,
+    config: TcpConfig,
+    peers: Peers,
+    events: mpsc::Receiver<ServerEvent>,
+    accept: JoinHandle<()>,
+}
+
+impl fmt::Debug for TcpServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpServer")
+            .field("local_addr", &self.local_addr)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TcpServer {
+    /// Listens on `addr` and starts accepting clients.
+    pub async fn bind(addr: impl ToSocketAddrs, config: TcpConfig) -> Result<Self, Error> {
+        Self::from_listener(TcpListener::bind(addr).await?, config)
+    }
+
+    /// Starts accepting clients on an already bound listener.
+    pub fn from_listener(listener: TcpListener, config: TcpConfig) -> Result<Self, Error> {
+        let local_addr = listener.local_addr()?;
+        let peers = Peers::default();
+        let (events_tx, events) = mpsc::channel(EVENT_CAPACITY);
+        let accept = tokio::spawn(accept_loop(
+            listener,
+            config.clone(),
+            peers.clone(),
+            events_tx,
+        ));
+        Ok(Self {
+            local_addr,
+            config,
+            peers,
+            events,
+            accept,
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the framing and codec.
+    pub fn config(&self) -> &TcpConfig {
+        &self.config
+    }
+
+    /// Returns the connected clients.
+    pub fn peers(&self) -> Vec<Peer> {
+        let peers = self.peers.lock().unwrap();
+        let mut peers: Vec<Peer> = peers
+            .iter()
+            .map(|(&id, &(addr, _))| Peer { id, addr })
+            .collect();
+        peers.sort_by_key(|peer| peer.id);
+        peers
+    }
+
+    /// Waits for the next event.
+    pub async fn next_event(&mut self) -> Option<ServerEvent> {
+        self.events.recv().await
+    }
+
+    /// Sends `value` to one client.
+    ///
+    /// Waits while the client's outgoing queue is full. A value queued just before
+    /// the connection ends is lost.
+    pub async fn send_to(&self, peer: PeerId, value: &IrValue) -> Result<(), Error> {
+        let frame = self.config.encode(value)?;
+        let outgoing = self.outgoing(peer).ok_or(Error::UnknownPeer(peer))?;
+        outgoing
+            .send(frame)
+            .await
+            .map_err(|_| Error::UnknownPeer(peer))
+    }
+
+    /// Sends `value` to every connected client and returns how many it was queued for.
+    pub async fn broadcast(&self, value: &IrValue) -> Result<usize, Error> {
+        let frame = self.config.encode(value)?;
+        let queues: Vec<_> = {
+            let peers = self.peers.lock().unwrap();
+            peers.values().map(|(_, tx)| tx.clone()).collect()
+        };
+        let mut sent = 0;
+        for outgoing in queues {
+            if outgoing.send(frame.clone()).await.is_ok() {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Closes the connection to a client after the values already queued for it are
+    /// written; a [`ServerEvent::Disconnected`] follows.
+    pub fn disconnect(&self, peer: PeerId) -> Result<(), Error> {
+        match self.peers.lock().unwrap().remove(&peer) {
+            Some(_) => Ok(()),
+            None => Err(Error::UnknownPeer(peer)),
+        }
+    }
+
+    fn outgoing(&self, peer: PeerId) -> Option<mpsc::Sender<Vec<u8>>> {
+        let peers = self.peers.lock().unwrap();
+        peers.get(&peer).map(|(_, tx)| tx.clone())
+    }
+}
+
+impl Stream for TcpServer {
+    type Item = ServerEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
+
+impl Drop for TcpServer {
+    fn drop(&mut self) {
+        // The accept task owns the connection tasks; aborting it aborts them too
+        self.accept.abort();
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    config: TcpConfig,
+    peers: Peers,
+    events: mpsc::Sender<ServerEvent>,
+) {
+    let mut connections = JoinSet::new();
+    let mut next_id = 0;
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, addr) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        let event = ServerEvent::Error { peer: None, error: Error::Io(e) };
+                        if events.send(event).await.is_err() {
+                            return;
+                        }
+                        // Errors such as running out of file descriptors persist
+                        // for a while; avoid spinning on them
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                next_id += 1;
+                let peer = Peer { id: PeerId(next_id), addr };
+                // Latency matters more than throughput for control messages
+                let _ = stream.set_nodelay(true);
+                let (tx, rx) = mpsc::channel(OUTGOING_CAPACITY);
+                peers.lock().unwrap().insert(peer.id, (addr, tx));
+                if events.send(ServerEvent::Connected(peer)).await.is_err() {
+                    return;
+                }
+                connections.spawn(serve(
+                    stream,
+                    peer,
+                    config.clone(),
+                    rx,
+                    peers.clone(),
+                    events.clone(),
+                ));
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    peer: Peer,
+    config: TcpConfig,
+    mut outgoing: mpsc::Receiver<Vec<u8>>,
+    peers: Peers,
+    events: mpsc::Sender<ServerEvent>,
+) {
+    let closed = drive(
+        stream,
+        peer.addr,
+        &config,
+        &mut outgoing,
+        &events,
+        |value| match value {
+            Ok(value) => ServerEvent::Packet { peer, value },
+            Err(error) => ServerEvent::Error {
+                peer: Some(peer),
+                error,
+            },
+        },
+    )
+    .await;
+    peers.lock().unwrap().remove(&peer.id);
+    let error = match closed {
+        Closed::Remote(error) => error.map(Error::Io),
+        Closed::Local => None,
+    };
+    let _ = events.send(ServerEvent::Disconnected { peer, error }).await;
+}
+
+/// How a [`TcpClient`] retries after a failed connection attempt or a lost
+/// connection.
+///
+/// The delay before retry `n` (counting from 0) is `initial_delay * multiplier^n`,
+/// capped at `max_delay`. The count restarts after every successful connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first retry (default 100 ms)
+    pub initial_delay: Duration,
+    /// Longest delay between retries (default 10 s)
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each failed retry (default 2)
+    pub multiplier: u32,
+    /// Retries before giving up, or `None` to retry forever (the default)
+    pub max_retries: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// Never retries: the client stops after the first failed attempt or lost
+    /// connection.
+    pub fn never() -> Self {
+        Self::default().with_max_retries(Some(0))
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the longest delay between retries.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets the factor applied to the delay after each failed retry.
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the number of retries before giving up.
+    pub fn with_max_retries(mut self, retries: Option<u32>) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Returns the delay before retry `retry`, counting from 0.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.checked_pow(retry).unwrap_or(u32::MAX);
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2,
+            max_retries: None,
+        }
+    }
+}
+
+/// Something that happened on a [`TcpClient`].
+#[derive(Debug)]
+pub enum ClientEvent {
+    /// The client connected, or reconnected, to the server at this address
+    Connected(SocketAddr),
+    /// The server sent a value
+    Packet {
+        /// Address of the server
+        from: SocketAddr,
+        /// The decoded value
+        value: IrValue,
+    },
+    /// A connection attempt failed, or a frame from the server could not be decoded
+    Error(Error),
+    /// The connection was lost; the client reconnects according to its policy
+    Disconnected {
+        /// The socket error that ended the connection, if any
+        error: Option<Error>,
+    },
+    /// The retries of the reconnect policy are used up; no events follow
+    GaveUp,
+}
+
+/// A TCP client that keeps a connection to a server, reconnecting with backoff.
+///
+/// The connection is driven by a background task; values sent while disconnected
+/// are queued and written once connected. Dropping the client closes the
+/// connection.
+pub struct TcpClient {
+    config: TcpConfig,
+    outgoing: mpsc::Sender<Vec<u8>>,
+    events: mpsc::Receiver<ClientEvent>,
+    task: JoinHandle<()>,
+}
+
+impl fmt::Debug for TcpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpClient")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TcpClient {
+    /// Spawns a task connecting to `addr`, which is resolved again on every attempt.
+    ///
+    /// Must be called within a tokio runtime. A [`ClientEvent::Connected`] reports
+    /// each successful connection.
+    pub fn connect<A>(addr: A, config: TcpConfig, policy: ReconnectPolicy) -> Self
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+    {
+        let (outgoing, outgoing_rx) = mpsc::channel(OUTGOING_CAPACITY);
+        let (events_tx, events) = mpsc::channel(EVENT_CAPACITY);
+        let task = tokio::spawn(run_client(
+            addr,
+            config.clone(),
+            policy,
+            outgoing_rx,
+            events_tx,
+        ));
+        Self {
+            config,
+            outgoing,
+            events,
+            task,
+        }
+    }
+
+    /// Returns the framing and codec.
+    pub fn config(&self) -> &TcpConfig {
+        &self.config
+    }
+
+    /// Queues `value` to be sent to the server.
+    ///
+    /// Waits while the queue is full, which happens while disconnected. A value
+    /// queued just before the connection is lost may be lost with it. Fails with
+    /// [`Error::Closed`] after [`ClientEvent::GaveUp`].
+    pub async fn send(&self, value: &IrValue) -> Result<(), Error> {
+        let frame = self.config.encode(value)?;
+        self.outgoing.send(frame).await.map_err(|_| Error::Closed)
+    }
+
+    /// Waits for the next event; `None` once the client has given up.
+    pub async fn next_event(&mut self) -> Option<ClientEvent> {
+        self.events.recv().await
+    }
+}
+
+impl Stream for TcpClient {
+    type Item = ClientEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
+
+impl Drop for TcpClient {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_client<A: ToSocketAddrs + Clone>(
+    addr: A,
+    config: TcpConfig,
+    policy: ReconnectPolicy,
+    mut outgoing: mpsc::Receiver<Vec<u8>>,
+    events: mpsc::Sender<ClientEvent>,
+) {
+    let mut retry = 0;
+    loop {
+        let connected = match TcpStream::connect(addr.clone()).await {
+            Ok(stream) => stream.peer_addr().map(|remote| (stream, remote)),
+            Err(e) => Err(e),
+        };
+        let event = match connected {
+            Ok((stream, remote)) => {
+                retry = 0;
+                let _ = stream.set_nodelay(true);
+                if events.send(ClientEvent::Connected(remote)).await.is_err() {
+                    return;
+                }
+                let closed =
+                    drive(
+                        stream,
+                        remote,
+                        &config,
+                        &mut outgoing,
+                        &events,
+                        |value| match value {
+                            Ok(value) => ClientEvent::Packet {
+                                from: remote,
+                                value,
+                            },
+                            Err(error) => ClientEvent::Error(error),
+                        },
+                    )
+                    .await;
+                match closed {
+                    Closed::Remote(error) => ClientEvent::Disconnected {
+                        error: error.map(Error::Io),
+                    },
+                    Closed::Local => return,
+                }
+            }
+            Err(e) => ClientEvent::Error(Error::Io(e)),
+        };
+        if events.send(event).await.is_err() {
+            return;
+        }
+        if policy.max_retries.is_some_and(|max| retry >= max) {
+            let _ = events.send(ClientEvent::GaveUp).await;
+            return;
+        }
+        tokio::time::sleep(policy.delay(retry)).await;
+        retry += 1;
+    }
+}
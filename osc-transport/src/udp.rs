@@ -0,0 +1,267 @@
+//! UDP sockets carrying one value per datagram.
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use osc_adapter_rosc::OscCodec;
+use osc_ir::{Codec, IrValue};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::io::ReadBuf;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+use crate::{Error, MAX_DATAGRAM_LEN};
+
+/// Socket options applied by [`OscSocket::bind_with`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Sets `SO_REUSEADDR` before binding, so several sockets can bind the same
+    /// multicast port
+    pub reuse_address: bool,
+    /// Allows sending to broadcast addresses
+    pub broadcast: bool,
+    /// IPv4 multicast groups to join, each with the address of the interface to
+    /// join on (`0.0.0.0` lets the system choose)
+    pub multicast_v4: Vec<(Ipv4Addr, Ipv4Addr)>,
+    /// IPv6 multicast groups to join, each with the index of the interface to join
+    /// on (`0` lets the system choose)
+    pub multicast_v6: Vec<(Ipv6Addr, u32)>,
+    /// Whether multicast datagrams sent are looped back to the local host (system
+    /// default if `None`)
+    pub multicast_loop: Option<bool>,
+    /// Time-to-live, or hop limit for IPv6, of multicast datagrams sent (system
+    /// default if `None`)
+    pub multicast_ttl: Option<u32>,
+}
+
+impl SocketOptions {
+    /// Options with every setting at the system default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets address reuse.
+    pub fn with_reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Sets broadcast permission.
+    pub fn with_broadcast(mut self, broadcast: bool) -> Self {
+        self.broadcast = broadcast;
+        self
+    }
+
+    /// Adds an IPv4 multicast group to join on `interface`.
+    pub fn with_multicast_v4(mut self, group: Ipv4Addr, interface: Ipv4Addr) -> Self {
+        self.multicast_v4.push((group, interface));
+        self
+    }
+
+    /// Adds an IPv6 multicast group to join on the interface with index `interface`.
+    pub fn with_multicast_v6(mut self, group: Ipv6Addr, interface: u32) -> Self {
+        self.multicast_v6.push((group, interface));
+        self
+    }
+
+    /// Sets multicast loopback.
+    pub fn with_multicast_loop(mut self, enabled: bool) -> Self {
+        self.multicast_loop = Some(enabled);
+        self
+    }
+
+    /// Sets the multicast time-to-live or hop limit.
+    pub fn with_multicast_ttl(mut self, ttl: u32) -> Self {
+        self.multicast_ttl = Some(ttl);
+        self
+    }
+
+    /// Creates a bound, non-blocking socket with these options.
+    fn bind(&self, addr: SocketAddr) -> std::io::Result<std::net::UdpSocket> {
+        let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+        if self.reuse_address {
+            socket.set_reuse_address(true)?;
+        }
+        socket.set_nonblocking(true)?;
+        socket.bind(&addr.into())?;
+        if self.broadcast {
+            socket.set_broadcast(true)?;
+        }
+        for (group, interface) in &self.multicast_v4 {
+            socket.join_multicast_v4(group, interface)?;
+        }
+        for (group, interface) in &self.multicast_v6 {
+            socket.join_multicast_v6(group, *interface)?;
+        }
+        match (self.multicast_loop, addr) {
+            (Some(enabled), SocketAddr::V4(_)) => socket.set_multicast_loop_v4(enabled)?,
+            (Some(enabled), SocketAddr::V6(_)) => socket.set_multicast_loop_v6(enabled)?,
+            (None, _) => {}
+        }
+        match (self.multicast_ttl, addr) {
+            (Some(ttl), SocketAddr::V4(_)) => socket.set_multicast_ttl_v4(ttl)?,
+            (Some(hops), SocketAddr::V6(_)) => socket.set_multicast_hops_v6(hops)?,
+            (None, _) => {}
+        }
+        Ok(socket.into())
+    }
+}
+
+/// A UDP socket sending and receiving one [`IrValue`] per datagram.
+///
+/// Clones share the underlying socket, so one task can send while another reads
+/// [`incoming`](OscSocket::incoming) values.
+#[derive(Clone)]
+pub struct OscSocket {
+    socket: Arc<UdpSocket>,
+    codec: Arc<dyn Codec>,
+}
+
+impl fmt::Debug for OscSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OscSocket")
+            .field("local_addr", &self.socket.local_addr().ok())
+            .field("codec", &self.codec.name())
+            .finish()
+    }
+}
+
+impl OscSocket {
+    /// Binds a socket to `addr` that sends and receives OSC binary packets.
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        Ok(Self::from_socket(UdpSocket::bind(addr).await?))
+    }
+
+    /// Binds a socket to `addr` with broadcast, multicast and reuse options.
+    ///
+    /// To receive multicast, bind the group's port on the unspecified address (for
+    /// example `0.0.0.0:9000`) and join the group. Must be called within a tokio
+    /// runtime.
+    pub async fn bind_with(addr: SocketAddr, options: &SocketOptions) -> Result<Self, Error> {
+        let socket = options.bind(addr)?;
+        Ok(Self::from_socket(UdpSocket::from_std(socket)?))
+    }
+
+    /// Wraps an already bound tokio socket, using the OSC binary codec.
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Self {
+            socket: Arc::new(socket),
+            codec: Arc::new(OscCodec),
+        }
+    }
+
+    /// Switches the wire codec, e.g. to `osc_codec_msgpack::MsgpackCodec`.
+    ///
+    /// Clones made before the switch keep their codec.
+    pub fn with_codec<C: Codec + 'static>(mut self, codec: C) -> Self {
+        self.codec = Arc::new(codec);
+        self
+    }
+
+    /// Returns the wire codec.
+    pub fn codec(&self) -> &dyn Codec {
+        self.codec.as_ref()
+    }
+
+    /// Returns the underlying tokio socket, for options not covered here.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the local address the socket is bound to.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Encodes `value` and sends it as one datagram to `addr`; returns the number of
+    /// bytes sent.
+    pub async fn send_to(&self, value: &IrValue, addr: impl ToSocketAddrs) -> Result<usize, Error> {
+        let bytes = self.codec.encode(value).map_err(Error::Encode)?;
+        Ok(self.socket.send_to(&bytes, addr).await?)
+    }
+
+    /// Receives one datagram and decodes it.
+    ///
+    /// Allocates a receive buffer on every call; [`incoming`](OscSocket::incoming)
+    /// reuses one.
+    pub async fn recv_from(&self) -> Result<(IrValue, SocketAddr), Error> {
+        let mut buf = vec![0; MAX_DATAGRAM_LEN];
+        let (len, from) = self.socket.recv_from(&mut buf).await?;
+        self.decode(&buf[..len], from)
+    }
+
+    /// Returns a stream of received values and their senders.
+    ///
+    /// The stream never ends. A datagram that fails to decode, or a socket error,
+    /// is yielded as an error and the stream continues with the next datagram.
+    pub fn incoming(&self) -> Incoming {
+        Incoming {
+            socket: self.clone(),
+            buf: vec![0; MAX_DATAGRAM_LEN].into_boxed_slice(),
+        }
+    }
+
+    /// Allows or forbids sending to broadcast addresses.
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), Error> {
+        Ok(self.socket.set_broadcast(broadcast)?)
+    }
+
+    /// Joins an IPv4 multicast group on `interface`.
+    pub fn join_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<(), Error> {
+        Ok(self.socket.join_multicast_v4(group, interface)?)
+    }
+
+    /// Leaves an IPv4 multicast group.
+    pub fn leave_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<(), Error> {
+        Ok(self.socket.leave_multicast_v4(group, interface)?)
+    }
+
+    /// Joins an IPv6 multicast group on the interface with index `interface`.
+    pub fn join_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), Error> {
+        Ok(self.socket.join_multicast_v6(group, interface)?)
+    }
+
+    /// Leaves an IPv6 multicast group.
+    pub fn leave_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), Error> {
+        Ok(self.socket.leave_multicast_v6(group, interface)?)
+    }
+
+    fn decode(&self, datagram: &[u8], from: SocketAddr) -> Result<(IrValue, SocketAddr), Error> {
+        match self.codec.decode(datagram) {
+            Ok(value) => Ok((value, from)),
+            Err(source) => Err(Error::Decode { from, source }),
+        }
+    }
+}
+
+/// Stream of values received by an [`OscSocket`], created by
+/// [`OscSocket::incoming`].
+#[derive(Debug)]
+pub struct Incoming {
+    socket: OscSocket,
+    buf: Box<[u8]>,
+}
+
+impl Incoming {
+    /// Returns the socket the values are received on.
+    pub fn socket(&self) -> &OscSocket {
+        &self.socket
+    }
+}
+
+impl Stream for Incoming {
+    type Item = Result<(IrValue, SocketAddr), Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut buf = ReadBuf::new(&mut this.buf);
+        match this.socket.socket.poll_recv_from(cx, &mut buf) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(Error::Io(e)))),
+            Poll::Ready(Ok(from)) => Poll::Ready(Some(this.socket.decode(buf.filled(), from))),
+        }
+    }
+}
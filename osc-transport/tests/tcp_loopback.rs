@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+
+use osc_ir::IrValue;
+use osc_transport::{
+    ClientEvent, Error, Framing, Peer, ReconnectPolicy, ServerEvent, TcpClient, TcpConfig,
+    TcpServer,
+};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+
+async fn timeout<T>(future: impl Future<Output = T>) -> T {
+    tokio::time::timeout(Duration::from_secs(5), future)
+        .await
+        .expect("timed out")
+}
+
+fn fast_retries() -> ReconnectPolicy {
+    ReconnectPolicy::default()
+        .with_initial_delay(Duration::from_millis(10))
+        .with_max_delay(Duration::from_millis(50))
+}
+
+async fn connected(client: &mut TcpClient) {
+    match timeout(client.next_event()).await {
+        Some(ClientEvent::Connected(_)) => {}
+        other => panic!("expected a connection, got {:?}", other),
+    }
+}
+
+async fn accepted(server: &mut TcpServer) -> Peer {
+    match timeout(server.next_event()).await {
+        Some(ServerEvent::Connected(peer)) => peer,
+        other => panic!("expected a connection, got {:?}", other),
+    }
+}
+
+async fn packet(server: &mut TcpServer) -> (Peer, IrValue) {
+    match timeout(server.next_event()).await {
+        Some(ServerEvent::Packet { peer, value }) => (peer, value),
+        other => panic!("expected a packet, got {:?}", other),
+    }
+}
+
+async fn client_packet(client: &mut TcpClient) -> IrValue {
+    match timeout(client.next_event()).await {
+        Some(ClientEvent::Packet { value, .. }) => value,
+        other => panic!("expected a packet, got {:?}", other),
+    }
+}
+
+async fn many_clients_with(config: TcpConfig) {
+    let mut server = TcpServer::bind("127.0.0.1:0", config.clone())
+        .await
+        .unwrap();
+    let mut clients = Vec::new();
+    let mut peers = Vec::new();
+    for _ in 0..3 {
+        let mut client = TcpClient::connect(
+            server.local_addr(),
+            config.clone(),
+            ReconnectPolicy::never(),
+        );
+        connected(&mut client).await;
+        peers.push(accepted(&mut server).await);
+        clients.push(client);
+    }
+    assert_eq!(server.peers(), peers);
+    let ids: HashSet<_> = peers.iter().map(|peer| peer.id).collect();
+    assert_eq!(ids.len(), 3);
+
+    // Each value arrives with the identity of the connection it came from
+    for (i, client) in clients.iter().enumerate() {
+        let value = IrValue::message("/client", vec![IrValue::from(i as i64)]);
+        client.send(&value).await.unwrap();
+        let (peer, received) = packet(&mut server).await;
+        assert_eq!(peer, peers[i]);
+        assert_eq!(received, value);
+    }
+
+    let reply = IrValue::message("/reply", vec![IrValue::from("only you")]);
+    server.send_to(peers[1].id, &reply).await.unwrap();
+    assert_eq!(client_packet(&mut clients[1]).await, reply);
+
+    let all = IrValue::message("/all", vec![]);
+    assert_eq!(server.broadcast(&all).await.unwrap(), 3);
+    for client in &mut clients {
+        assert_eq!(client_packet(client).await, all);
+    }
+}
+
+#[tokio::test]
+async fn slip_server_with_many_clients() {
+    many_clients_with(TcpConfig::new(Framing::Slip)).await;
+}
+
+#[tokio::test]
+async fn size_prefix_server_with_many_clients() {
+    many_clients_with(TcpConfig::new(Framing::SizePrefix)).await;
+}
+
+#[tokio::test]
+async fn msgpack_over_tcp() {
+    many_clients_with(
+        TcpConfig::new(Framing::SizePrefix).with_codec(osc_codec_msgpack::MsgpackCodec),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn client_reconnects_once_the_server_is_up() {
+    // Reserve a port, then free it so the first attempts are refused
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut client = TcpClient::connect(addr, TcpConfig::default(), fast_retries());
+    match timeout(client.next_event()).await {
+        Some(ClientEvent::Error(Error::Io(_))) => {}
+        other => panic!("expected a refused connection, got {:?}", other),
+    }
+
+    // Sent while disconnected: queued until the connection is up
+    let early = IrValue::message("/early", vec![]);
+    client.send(&early).await.unwrap();
+
+    let mut server = TcpServer::bind(addr, TcpConfig::default()).await.unwrap();
+    loop {
+        match timeout(client.next_event()).await {
+            Some(ClientEvent::Error(_)) => continue,
+            Some(ClientEvent::Connected(remote)) => {
+                assert_eq!(remote, addr);
+                break;
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    let peer = accepted(&mut server).await;
+    assert_eq!(packet(&mut server).await, (peer, early));
+}
+
+#[tokio::test]
+async fn client_reconnects_after_being_disconnected() {
+    let mut server = TcpServer::bind("127.0.0.1:0", TcpConfig::default())
+        .await
+        .unwrap();
+    let mut client = TcpClient::connect(server.local_addr(), TcpConfig::default(), fast_retries());
+    connected(&mut client).await;
+    let first = accepted(&mut server).await;
+
+    server.disconnect(first.id).unwrap();
+    match timeout(server.next_event()).await {
+        Some(ServerEvent::Disconnected { peer, error: None }) => assert_eq!(peer, first),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(
+        timeout(client.next_event()).await,
+        Some(ClientEvent::Disconnected { .. })
+    ));
+    assert!(matches!(
+        server.send_to(first.id, &IrValue::message("/gone", vec![])).await,
+        Err(Error::UnknownPeer(id)) if id == first.id
+    ));
+
+    connected(&mut client).await;
+    let second = accepted(&mut server).await;
+    assert_ne!(second.id, first.id);
+    let value = IrValue::message("/again", vec![]);
+    client.send(&value).await.unwrap();
+    assert_eq!(packet(&mut server).await, (second, value));
+}
+
+#[tokio::test]
+async fn client_gives_up_after_its_retries() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let policy = fast_retries().with_max_retries(Some(2));
+    let mut client = TcpClient::connect(addr, TcpConfig::default(), policy);
+    for _ in 0..3 {
+        assert!(matches!(
+            timeout(client.next_event()).await,
+            Some(ClientEvent::Error(Error::Io(_)))
+        ));
+    }
+    assert!(matches!(
+        timeout(client.next_event()).await,
+        Some(ClientEvent::GaveUp)
+    ));
+    assert!(timeout(client.next_event()).await.is_none());
+    assert!(matches!(
+        client.send(&IrValue::message("/late", vec![])).await,
+        Err(Error::Closed)
+    ));
+}
+
+#[tokio::test]
+async fn bad_frames_are_reported_and_skipped() {
+    let config = TcpConfig::new(Framing::SizePrefix);
+    let mut server = TcpServer::bind("127.0.0.1:0", config.clone())
+        .await
+        .unwrap();
+    let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+    let peer = accepted(&mut server).await;
+
+    let value = IrValue::message("/ok", vec![IrValue::from(1)]);
+    let mut bytes = Vec::new();
+    Framing::SizePrefix
+        .encode_frame(b"not OSC", &mut bytes)
+        .unwrap();
+    Framing::SizePrefix
+        .encode_value(config.codec(), &value, &mut bytes)
+        .unwrap();
+    // Split inside the second frame
+    let (a, b) = bytes.split_at(bytes.len() - 3);
+    stream.write_all(a).await.unwrap();
+    stream.flush().await.unwrap();
+    match timeout(server.next_event()).await {
+        Some(ServerEvent::Error {
+            peer: Some(from),
+            error: Error::Decode { .. },
+        }) => assert_eq!(from, peer),
+        other => panic!("unexpected {:?}", other),
+    }
+    stream.write_all(b).await.unwrap();
+    assert_eq!(packet(&mut server).await, (peer, value));
+
+    drop(stream);
+    assert!(matches!(
+        timeout(server.next_event()).await,
+        Some(ServerEvent::Disconnected { .. })
+    ));
+    assert!(server.peers().is_empty());
+}
+
+#[test]
+fn backoff_grows_up_to_the_maximum() {
+    let policy = ReconnectPolicy::default();
+    assert_eq!(policy.delay(0), Duration::from_millis(100));
+    assert_eq!(policy.delay(1), Duration::from_millis(200));
+    assert_eq!(policy.delay(3), Duration::from_millis(800));
+    assert_eq!(policy.delay(10), Duration::from_secs(10));
+    assert_eq!(policy.delay(u32::MAX), Duration::from_secs(10));
+}
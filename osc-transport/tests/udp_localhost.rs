@@ -0,0 +1,158 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use osc_ir::{Codec, IrBundle, IrTimetag, IrValue};
+use osc_transport::{Error, OscSocket, SocketOptions};
+
+fn values() -> Vec<IrValue> {
+    let mut bundle = IrBundle::new(IrTimetag::from_ntp(1 << 32));
+    bundle.add_message(IrValue::message("/in/bundle", vec![IrValue::from(1)]));
+    vec![
+        IrValue::message(
+            "/a",
+            vec![
+                // JSON has no single-precision floats
+                IrValue::Float(0.25),
+                IrValue::from("text"),
+                IrValue::Binary(vec![1, 2, 3]),
+            ],
+        ),
+        IrValue::message("/empty", vec![]),
+        IrValue::Bundle(bundle),
+    ]
+}
+
+async fn pair() -> (OscSocket, OscSocket) {
+    let a = OscSocket::bind("127.0.0.1:0").await.unwrap();
+    let b = OscSocket::bind("127.0.0.1:0").await.unwrap();
+    (a, b)
+}
+
+async fn next(incoming: &mut osc_transport::Incoming) -> Result<(IrValue, SocketAddr), Error> {
+    tokio::time::timeout(Duration::from_secs(5), incoming.next())
+        .await
+        .expect("timed out waiting for a datagram")
+        .expect("the stream never ends")
+}
+
+async fn roundtrip_with(codec: impl Codec + Clone + 'static) {
+    let (sender, receiver) = pair().await;
+    let sender = sender.with_codec(codec.clone());
+    let receiver = receiver.with_codec(codec);
+    let mut incoming = receiver.incoming();
+    for value in values() {
+        sender
+            .send_to(&value, receiver.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (received, from) = next(&mut incoming).await.unwrap();
+        assert_eq!(received, value, "{}", sender.codec().name());
+        assert_eq!(from, sender.local_addr().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn osc_binary_roundtrip() {
+    roundtrip_with(osc_adapter_rosc::OscCodec).await;
+}
+
+#[tokio::test]
+async fn msgpack_roundtrip() {
+    roundtrip_with(osc_codec_msgpack::MsgpackCodec).await;
+}
+
+#[tokio::test]
+async fn json_roundtrip() {
+    roundtrip_with(osc_codec_json::JsonCodec).await;
+}
+
+#[tokio::test]
+async fn recv_from_and_replies() {
+    let (client, server) = pair().await;
+    let ping = IrValue::message("/ping", vec![]);
+    client
+        .send_to(&ping, server.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (value, from) = server.recv_from().await.unwrap();
+    assert_eq!(value, ping);
+
+    let pong = IrValue::message("/pong", vec![IrValue::from(1)]);
+    server.send_to(&pong, from).await.unwrap();
+    assert_eq!(client.recv_from().await.unwrap().0, pong);
+}
+
+#[tokio::test]
+async fn undecodable_datagrams_do_not_end_the_stream() {
+    let (sender, receiver) = pair().await;
+    let to = receiver.local_addr().unwrap();
+    let mut incoming = receiver.incoming();
+
+    sender.socket().send_to(b"not OSC", to).await.unwrap();
+    match next(&mut incoming).await {
+        Err(Error::Decode { from, .. }) => assert_eq!(from, sender.local_addr().unwrap()),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let value = IrValue::message("/after", vec![]);
+    sender.send_to(&value, to).await.unwrap();
+    assert_eq!(next(&mut incoming).await.unwrap().0, value);
+}
+
+#[tokio::test]
+async fn unencodable_values_are_rejected_before_sending() {
+    let (sender, receiver) = pair().await;
+    let err = sender
+        .send_to(
+            &IrValue::from("not a message"),
+            receiver.local_addr().unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Encode(_)));
+}
+
+#[tokio::test]
+async fn clones_share_the_socket() {
+    let (sender, receiver) = pair().await;
+    let mut incoming = receiver.incoming();
+    let task = tokio::spawn({
+        let sender = sender.clone();
+        let to = receiver.local_addr().unwrap();
+        async move {
+            sender
+                .send_to(&IrValue::message("/spawned", vec![]), to)
+                .await
+        }
+    });
+    task.await.unwrap().unwrap();
+    let (value, from) = next(&mut incoming).await.unwrap();
+    assert_eq!(value, IrValue::message("/spawned", vec![]));
+    assert_eq!(from, sender.local_addr().unwrap());
+}
+
+#[tokio::test]
+async fn bind_with_applies_options() {
+    let options = SocketOptions::new()
+        .with_reuse_address(true)
+        .with_broadcast(true)
+        .with_multicast_loop(false)
+        .with_multicast_ttl(4);
+    let socket = OscSocket::bind_with("127.0.0.1:0".parse().unwrap(), &options)
+        .await
+        .unwrap();
+    assert!(socket.socket().broadcast().unwrap());
+    assert!(!socket.socket().multicast_loop_v4().unwrap());
+    assert_eq!(socket.socket().multicast_ttl_v4().unwrap(), 4);
+
+    socket.set_broadcast(false).unwrap();
+    assert!(!socket.socket().broadcast().unwrap());
+
+    // A second socket can share the port when both reuse the address
+    let port = socket.local_addr().unwrap().port();
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    OscSocket::bind_with(addr, &SocketOptions::new().with_reuse_address(true))
+        .await
+        .unwrap();
+}
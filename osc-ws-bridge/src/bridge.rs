@@ -0,0 +1,474 @@
+//! The bridge and its background tasks.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use osc_ir::IrValue;
+use osc_transport::OscSocket;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::protocol::{self, Request};
+use crate::{AddressPattern, Error, FrameFormat};
+
+/// Frames waiting to be written to one client; a client that falls further behind
+/// misses packets.
+const OUTGOING_CAPACITY: usize = 256;
+/// Events waiting to be taken by the application; further events are dropped.
+const EVENT_CAPACITY: usize = 256;
+/// Patterns one client may subscribe to; every packet is matched against each of them.
+pub const MAX_PATTERNS: usize = 64;
+
+/// Behaviour of a [`Bridge`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BridgeConfig {
+    /// Where values sent by clients are forwarded as OSC (default `None`: clients
+    /// cannot send)
+    pub osc_target: Option<SocketAddr>,
+    /// Encoding of the frames sent to clients (default JSON)
+    pub format: FrameFormat,
+    /// Whether floats in JSON frames are sent as OSC `f` rather than `d` arguments
+    /// (default `true`)
+    pub single_precision_floats: bool,
+}
+
+impl BridgeConfig {
+    /// Configuration with every setting at its default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the address values sent by clients are forwarded to.
+    pub fn with_osc_target(mut self, target: SocketAddr) -> Self {
+        self.osc_target = Some(target);
+        self
+    }
+
+    /// Sets the encoding of the frames sent to clients.
+    pub fn with_format(mut self, format: FrameFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets whether floats in JSON frames are sent as single precision.
+    pub fn with_single_precision_floats(mut self, single: bool) -> Self {
+        self.single_precision_floats = single;
+        self
+    }
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            osc_target: None,
+            format: FrameFormat::Json,
+            single_precision_floats: true,
+        }
+    }
+}
+
+/// Identifier of a WebSocket client, unique for the bridge's lifetime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClientId(u64);
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Something that happened on a [`Bridge`].
+#[derive(Debug)]
+pub enum BridgeEvent {
+    /// A WebSocket client completed its handshake
+    Connected {
+        /// The client
+        client: ClientId,
+        /// Address of the client
+        addr: SocketAddr,
+    },
+    /// A WebSocket client disconnected
+    Disconnected {
+        /// The client
+        client: ClientId,
+        /// The error that ended the connection, if any
+        error: Option<Error>,
+    },
+    /// Something failed; the bridge keeps running
+    Error {
+        /// The client concerned, or `None` for UDP and handshake errors
+        client: Option<ClientId>,
+        /// What went wrong
+        error: Error,
+    },
+}
+
+/// A connected client.
+struct Client {
+    addr: SocketAddr,
+    /// `None` until the client first subscribes
+    subscriptions: Option<Vec<AddressPattern>>,
+    outgoing: mpsc::Sender<Message>,
+}
+
+type Clients = Arc<Mutex<HashMap<ClientId, Client>>>;
+
+/// A running bridge between a UDP socket and a WebSocket listener.
+///
+/// The bridge works in background tasks and reports what happens as
+/// [`BridgeEvent`]s, through [`next_event`](Bridge::next_event) or as a `Stream`.
+/// Events are dropped rather than stalling the bridge when they are not taken.
+/// Dropping the bridge closes both sockets and every client connection.
+pub struct Bridge {
+    udp_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    config: BridgeConfig,
+    clients: Clients,
+    events: mpsc::Receiver<BridgeEvent>,
+    udp: JoinHandle<()>,
+    accept: JoinHandle<()>,
+}
+
+impl fmt::Debug for Bridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bridge")
+            .field("udp_addr", &self.udp_addr)
+            .field("ws_addr", &self.ws_addr)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Bridge {
+    /// Receives OSC on `udp_addr`, accepts WebSocket clients on `ws_addr` and starts
+    /// bridging.
+    pub async fn bind(
+        udp_addr: impl ToSocketAddrs,
+        ws_addr: impl ToSocketAddrs,
+        config: BridgeConfig,
+    ) -> Result<Self, Error> {
+        let socket = OscSocket::bind(udp_addr).await?;
+        let listener = TcpListener::bind(ws_addr).await?;
+        Self::from_parts(socket, listener, config)
+    }
+
+    /// Starts bridging an already bound UDP socket, e.g. one that joined a multicast
+    /// group, and WebSocket listener.
+    ///
+    /// Values sent by clients are forwarded from `socket`.
+    pub fn from_parts(
+        socket: OscSocket,
+        listener: TcpListener,
+        config: BridgeConfig,
+    ) -> Result<Self, Error> {
+        let udp_addr = socket.local_addr()?;
+        let ws_addr = listener.local_addr()?;
+        let clients = Clients::default();
+        let (events_tx, events) = mpsc::channel(EVENT_CAPACITY);
+        let udp = tokio::spawn(udp_loop(
+            socket.clone(),
+            config.format,
+            clients.clone(),
+            events_tx.clone(),
+        ));
+        let accept = tokio::spawn(accept_loop(
+            listener,
+            socket,
+            config.clone(),
+            clients.clone(),
+            events_tx,
+        ));
+        Ok(Self {
+            udp_addr,
+            ws_addr,
+            config,
+            clients,
+            events,
+            udp,
+            accept,
+        })
+    }
+
+    /// Returns the address OSC is received on.
+    pub fn udp_addr(&self) -> SocketAddr {
+        self.udp_addr
+    }
+
+    /// Returns the address WebSocket clients connect to.
+    pub fn ws_addr(&self) -> SocketAddr {
+        self.ws_addr
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &BridgeConfig {
+        &self.config
+    }
+
+    /// Returns the connected clients and their addresses.
+    pub fn clients(&self) -> Vec<(ClientId, SocketAddr)> {
+        let clients = self.clients.lock().unwrap();
+        let mut clients: Vec<_> = clients.iter().map(|(&id, c)| (id, c.addr)).collect();
+        clients.sort_by_key(|&(id, _)| id);
+        clients
+    }
+
+    /// Waits for the next event.
+    pub async fn next_event(&mut self) -> Option<BridgeEvent> {
+        self.events.recv().await
+    }
+}
+
+impl Stream for Bridge {
+    type Item = BridgeEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        self.udp.abort();
+        // The accept task owns the client tasks; aborting it aborts them too
+        self.accept.abort();
+    }
+}
+
+fn report(events: &mpsc::Sender<BridgeEvent>, event: BridgeEvent) {
+    let _ = events.try_send(event);
+}
+
+async fn udp_loop(
+    socket: OscSocket,
+    format: FrameFormat,
+    clients: Clients,
+    events: mpsc::Sender<BridgeEvent>,
+) {
+    let mut incoming = socket.incoming();
+    while let Some(received) = incoming.next().await {
+        match received {
+            Ok((value, _)) => dispatch(&value, format, &clients, &events),
+            Err(error) => report(
+                &events,
+                BridgeEvent::Error {
+                    client: None,
+                    error: error.into(),
+                },
+            ),
+        }
+    }
+}
+
+/// Queues `value` for every client subscribed to it.
+fn dispatch(
+    value: &IrValue,
+    format: FrameFormat,
+    clients: &Clients,
+    events: &mpsc::Sender<BridgeEvent>,
+) {
+    // Clients that have not subscribed share one encoding of the whole value
+    let mut whole: Option<Message> = None;
+    let clients = clients.lock().unwrap();
+    for (&id, client) in clients.iter() {
+        let frame = match &client.subscriptions {
+            None => match &whole {
+                Some(frame) => Ok(frame.clone()),
+                None => format
+                    .encode(value)
+                    .map(|frame| whole.insert(frame).clone()),
+            },
+            Some(patterns) => match protocol::filter(value, Some(patterns)) {
+                Some(filtered) => format.encode(&filtered),
+                None => continue,
+            },
+        };
+        match frame {
+            // A client whose queue is full misses this value
+            Ok(frame) => {
+                let _ = client.outgoing.try_send(frame);
+            }
+            Err(error) => report(
+                events,
+                BridgeEvent::Error {
+                    client: Some(id),
+                    error,
+                },
+            ),
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    socket: OscSocket,
+    config: BridgeConfig,
+    clients: Clients,
+    events: mpsc::Sender<BridgeEvent>,
+) {
+    let mut connections = JoinSet::new();
+    let mut next_id = 0;
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, addr) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        report(&events, BridgeEvent::Error { client: None, error: Error::Io(e) });
+                        // Errors such as running out of file descriptors persist
+                        // for a while; avoid spinning on them
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                next_id += 1;
+                let _ = stream.set_nodelay(true);
+                connections.spawn(serve(
+                    stream,
+                    ClientId(next_id),
+                    addr,
+                    socket.clone(),
+                    config.clone(),
+                    clients.clone(),
+                    events.clone(),
+                ));
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    id: ClientId,
+    addr: SocketAddr,
+    socket: OscSocket,
+    config: BridgeConfig,
+    clients: Clients,
+    events: mpsc::Sender<BridgeEvent>,
+) {
+    let mut ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            report(
+                &events,
+                BridgeEvent::Error {
+                    client: None,
+                    error: e.into(),
+                },
+            );
+            return;
+        }
+    };
+    let (outgoing, mut queued) = mpsc::channel(OUTGOING_CAPACITY);
+    let client = Client {
+        addr,
+        subscriptions: None,
+        outgoing,
+    };
+    clients.lock().unwrap().insert(id, client);
+    report(&events, BridgeEvent::Connected { client: id, addr });
+
+    let error = loop {
+        tokio::select! {
+            received = ws.next() => {
+                let message = match received {
+                    Some(Ok(message)) => message,
+                    None
+                    | Some(Err(tungstenite::Error::ConnectionClosed))
+                    | Some(Err(tungstenite::Error::AlreadyClosed)) => break None,
+                    Some(Err(e)) => break Some(e.into()),
+                };
+                let Some(request) = Request::decode(&message, config.single_precision_floats)
+                else {
+                    continue;
+                };
+                let reply = match request {
+                    Ok(Request::Forward(value)) => forward(&value, &socket, &config)
+                        .await
+                        .map(|()| None),
+                    Ok(request) => update_subscriptions(&clients, id, request).map(Some),
+                    Err(error) => Err(error),
+                };
+                let reply = match reply {
+                    Ok(None) => continue,
+                    Ok(Some(reply)) => reply,
+                    Err(error) => {
+                        let reply = protocol::error(&error);
+                        report(&events, BridgeEvent::Error { client: Some(id), error });
+                        reply
+                    }
+                };
+                match config.format.encode(&reply) {
+                    Ok(frame) => {
+                        if let Err(e) = ws.send(frame).await {
+                            break Some(e.into());
+                        }
+                    }
+                    Err(error) => report(&events, BridgeEvent::Error { client: Some(id), error }),
+                }
+            }
+            Some(frame) = queued.recv() => {
+                if let Err(e) = ws.send(frame).await {
+                    break Some(e.into());
+                }
+            }
+        }
+    };
+    clients.lock().unwrap().remove(&id);
+    report(&events, BridgeEvent::Disconnected { client: id, error });
+}
+
+/// Sends a value from a client to the OSC target.
+async fn forward(value: &IrValue, socket: &OscSocket, config: &BridgeConfig) -> Result<(), Error> {
+    let target = config.osc_target.ok_or(Error::NoTarget)?;
+    socket.send_to(value, target).await?;
+    Ok(())
+}
+
+/// Applies a subscription change and returns the answer listing the client's
+/// patterns.
+///
+/// A subscription that would leave the client with more than [`MAX_PATTERNS`] patterns
+/// is refused as a whole.
+fn update_subscriptions(
+    clients: &Clients,
+    id: ClientId,
+    request: Request,
+) -> Result<IrValue, Error> {
+    let mut clients = clients.lock().unwrap();
+    let Some(client) = clients.get_mut(&id) else {
+        return Ok(protocol::subscribed(&[]));
+    };
+    let mut subscriptions = client.subscriptions.clone().unwrap_or_default();
+    match request {
+        Request::Subscribe(patterns) => {
+            for pattern in patterns {
+                if !subscriptions.contains(&pattern) {
+                    subscriptions.push(pattern);
+                }
+            }
+            if subscriptions.len() > MAX_PATTERNS {
+                return Err(Error::InvalidCommand(format!(
+                    "more than {} patterns",
+                    MAX_PATTERNS
+                )));
+            }
+        }
+        Request::Unsubscribe(None) => subscriptions.clear(),
+        Request::Unsubscribe(Some(patterns)) => {
+            subscriptions.retain(|pattern| !patterns.iter().any(|p| p == pattern.as_str()))
+        }
+        Request::Forward(_) => {}
+    }
+    let reply = protocol::subscribed(&subscriptions);
+    client.subscriptions = Some(subscriptions);
+    Ok(reply)
+}
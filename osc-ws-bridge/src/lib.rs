@@ -0,0 +1,154 @@
+//! # osc-ws-bridge
+//!
+//! ⚠️ **EXPERIMENTAL** ⚠️  
+//! This crate is experimental and APIs may change significantly between versions.
+//!
+//! Bridge between OSC over UDP and WebSocket clients such as browser dashboards.
+//!
+//! Every OSC packet received over UDP is converted to JSON (with `osc-codec-json`) or
+//! MessagePack and sent to the connected WebSocket clients whose subscriptions match
+//! its address. Values sent by a client are encoded as OSC and forwarded over UDP to
+//! the configured target.
+//!
+//! ## Features
+//!
+//! - **UDP to WebSocket**: [`Bridge`] sends each received packet to its clients as a JSON
+//!   text frame or a MessagePack binary frame ([`FrameFormat`])
+//! - **WebSocket to UDP**: messages and bundles sent by clients are forwarded as OSC
+//!   packets to [`BridgeConfig::osc_target`]
+//! - **Subscriptions**: each client chooses the addresses it receives with OSC
+//!   [`AddressPattern`]s
+//! - **Command Line**: the `osc-ws-bridge` binary (feature `cli`, on by default)
+//!
+//! ## Protocol
+//!
+//! Messages are `{"$type": "osc.message", "address": "/fader/1", "args": [0.5]}` maps
+//! and bundles use the tagged `osc-codec-json` form, in both directions. Clients may
+//! send JSON text frames or MessagePack binary frames whatever the bridge's
+//! [`FrameFormat`].
+//!
+//! A client receives every packet until it subscribes. Afterwards it receives only
+//! messages whose address matches one of its patterns; bundles are pruned to their
+//! matching messages and dropped if none match.
+//!
+//! ```json
+//! {"$type": "bridge.subscribe", "patterns": ["/fader/*", "//level"]}
+//! {"$type": "bridge.unsubscribe", "patterns": ["/fader/*"]}
+//! ```
+//!
+//! `bridge.unsubscribe` without `patterns` removes every pattern. The bridge answers
+//! both with `{"$type": "bridge.subscribed", "patterns": [...]}` listing the current
+//! patterns, and reports a frame it cannot handle with
+//! `{"$type": "bridge.error", "message": "..."}`.
+//!
+//! JSON numbers do not distinguish single from double precision, so floats in JSON
+//! frames are forwarded as OSC `f` (32-bit) arguments unless
+//! [`BridgeConfig::single_precision_floats`] is turned off.
+//!
+//! ## Usage
+//!
+//! ```rust,no_run
+//! use osc_ws_bridge::{Bridge, BridgeConfig, BridgeEvent};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), osc_ws_bridge::Error> {
+//! let config = BridgeConfig::new().with_osc_target("127.0.0.1:8000".parse().unwrap());
+//! let mut bridge = Bridge::bind("0.0.0.0:9000", "0.0.0.0:8080", config).await?;
+//! while let Some(event) = bridge.next_event().await {
+//!     if let BridgeEvent::Error { client, error } = event {
+//!         eprintln!("{:?}: {}", client, error);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+
+use osc_ir::CodecError;
+use tokio_tungstenite::tungstenite;
+
+mod bridge;
+mod pattern;
+mod protocol;
+
+pub use bridge::{Bridge, BridgeConfig, BridgeEvent, ClientId, MAX_PATTERNS};
+pub use pattern::AddressPattern;
+pub use protocol::FrameFormat;
+
+/// Error produced by the bridge.
+#[derive(Debug)]
+pub enum Error {
+    /// Socket error
+    Io(io::Error),
+    /// Error of the UDP socket
+    Transport(osc_transport::Error),
+    /// WebSocket handshake or protocol error
+    WebSocket(Box<tungstenite::Error>),
+    /// A frame could not be decoded, or a value could not be encoded
+    Codec(CodecError),
+    /// An address pattern is malformed
+    Pattern {
+        /// The pattern
+        pattern: String,
+        /// What is wrong with it
+        reason: String,
+    },
+    /// A `bridge.*` command is malformed
+    InvalidCommand(String),
+    /// A client sent a value but the bridge has no OSC target to forward it to
+    NoTarget,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "socket error: {}", e),
+            Error::Transport(e) => write!(f, "{}", e),
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::Codec(e) => write!(f, "{}", e),
+            Error::Pattern { pattern, reason } => {
+                write!(f, "invalid address pattern {:?}: {}", pattern, reason)
+            }
+            Error::InvalidCommand(reason) => write!(f, "invalid command: {}", reason),
+            Error::NoTarget => f.write_str("no OSC target to forward to"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Transport(e) => Some(e),
+            Error::WebSocket(e) => Some(e.as_ref()),
+            Error::Codec(e) => Some(e),
+            Error::Pattern { .. } | Error::InvalidCommand(_) | Error::NoTarget => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<osc_transport::Error> for Error {
+    fn from(e: osc_transport::Error) -> Self {
+        Error::Transport(e)
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+impl From<CodecError> for Error {
+    fn from(e: CodecError) -> Self {
+        Error::Codec(e)
+    }
+}
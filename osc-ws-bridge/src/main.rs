@@ -0,0 +1,66 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use osc_ws_bridge::{Bridge, BridgeConfig, BridgeEvent, FrameFormat};
+
+#[derive(Parser)]
+#[command(name = "osc-ws-bridge")]
+#[command(about = "Bridge OSC over UDP to WebSocket clients speaking JSON or MessagePack", long_about = None)]
+struct Cli {
+    /// Address to receive OSC on
+    #[arg(long, default_value = "0.0.0.0:9000")]
+    udp: SocketAddr,
+    /// Address to accept WebSocket clients on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    ws: SocketAddr,
+    /// Where to forward values sent by WebSocket clients
+    #[arg(long)]
+    target: Option<SocketAddr>,
+    /// Encoding of the frames sent to clients
+    #[arg(long, default_value = "json", value_parser = ["json", "msgpack"])]
+    format: String,
+    /// Forward floats in JSON frames as OSC doubles (`d`) instead of floats (`f`)
+    #[arg(long)]
+    double_precision: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), osc_ws_bridge::Error> {
+    let cli = Cli::parse();
+    let mut config = BridgeConfig::new()
+        .with_format(match cli.format.as_str() {
+            "msgpack" => FrameFormat::Msgpack,
+            _ => FrameFormat::Json,
+        })
+        .with_single_precision_floats(!cli.double_precision);
+    if let Some(target) = cli.target {
+        config = config.with_osc_target(target);
+    }
+    let mut bridge = Bridge::bind(cli.udp, cli.ws, config).await?;
+    eprintln!(
+        "OSC on udp://{}, WebSocket clients on ws://{}",
+        bridge.udp_addr(),
+        bridge.ws_addr()
+    );
+    loop {
+        tokio::select! {
+            event = bridge.next_event() => match event {
+                Some(BridgeEvent::Connected { client, addr }) => {
+                    eprintln!("client {} connected from {}", client, addr)
+                }
+                Some(BridgeEvent::Disconnected { client, error: None }) => {
+                    eprintln!("client {} disconnected", client)
+                }
+                Some(BridgeEvent::Disconnected { client, error: Some(error) }) => {
+                    eprintln!("client {} disconnected: {}", client, error)
+                }
+                Some(BridgeEvent::Error { client: Some(client), error }) => {
+                    eprintln!("client {}: {}", client, error)
+                }
+                Some(BridgeEvent::Error { client: None, error }) => eprintln!("{}", error),
+                None => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
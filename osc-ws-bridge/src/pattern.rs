@@ -0,0 +1,290 @@
+//! OSC address patterns.
+
+use std::fmt;
+
+use crate::Error;
+
+/// One element of a compiled pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    /// A literal character
+    Char(char),
+    /// `?`: any single character except `/`
+    AnyChar,
+    /// `*`: any run of characters except `/`
+    AnyRun,
+    /// `[...]`: one character in (or, if negated, not in) the ranges
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+    /// `{a,b}`: one of the literal alternatives
+    Alternatives(Vec<String>),
+    /// `//`: any number of whole address parts, including none (OSC 1.1)
+    AnyParts,
+}
+
+/// A compiled OSC address pattern.
+///
+/// Supports the OSC 1.0 wildcards `?`, `*`, `[abc]`, `[a-z]`, `[!abc]` and
+/// `{foo,bar}`, none of which match across a `/`, and the OSC 1.1 `//` that
+/// matches any number of address parts.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AddressPattern {
+    source: String,
+    tokens: Vec<Token>,
+}
+
+impl AddressPattern {
+    /// Longest pattern accepted, in characters.
+    pub const MAX_LEN: usize = 256;
+
+    /// Compiles `pattern`.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::Pattern {
+            pattern: pattern.to_string(),
+            reason: reason.to_string(),
+        };
+        if !pattern.starts_with('/') {
+            return Err(invalid("must start with '/'"));
+        }
+        if pattern.chars().count() > Self::MAX_LEN {
+            return Err(invalid(&format!(
+                "longer than {} characters",
+                Self::MAX_LEN
+            )));
+        }
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '/' if chars.peek() == Some(&'/') => {
+                    chars.next();
+                    tokens.push(Token::AnyParts);
+                    Token::Char('/')
+                }
+                '?' => Token::AnyChar,
+                '*' => {
+                    while chars.peek() == Some(&'*') {
+                        chars.next();
+                    }
+                    Token::AnyRun
+                }
+                '[' => {
+                    let negated = chars.peek() == Some(&'!');
+                    if negated {
+                        chars.next();
+                    }
+                    let mut ranges = Vec::new();
+                    loop {
+                        match chars.next() {
+                            None => return Err(invalid("unclosed '['")),
+                            Some(']') => break,
+                            Some(start) => {
+                                let mut ahead = chars.clone();
+                                match (ahead.next(), ahead.next()) {
+                                    (Some('-'), Some(end)) if end != ']' => {
+                                        chars.next();
+                                        chars.next();
+                                        ranges.push((start.min(end), start.max(end)));
+                                    }
+                                    _ => ranges.push((start, start)),
+                                }
+                            }
+                        }
+                    }
+                    Token::Class { negated, ranges }
+                }
+                '{' => {
+                    let mut alternatives = vec![String::new()];
+                    loop {
+                        match chars.next() {
+                            None => return Err(invalid("unclosed '{'")),
+                            Some('}') => break,
+                            Some(',') => alternatives.push(String::new()),
+                            Some('/') => return Err(invalid("'/' inside '{}'")),
+                            Some(c) => alternatives.last_mut().unwrap().push(c),
+                        }
+                    }
+                    Token::Alternatives(alternatives)
+                }
+                ']' | '}' => return Err(invalid("unmatched closing bracket")),
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+        }
+        Ok(Self {
+            source: pattern.to_string(),
+            tokens,
+        })
+    }
+
+    /// Returns the pattern as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns `true` if `address` matches the pattern.
+    pub fn matches(&self, address: &str) -> bool {
+        let address: Vec<char> = address.chars().collect();
+        match_tokens(&self.tokens, &address)
+    }
+}
+
+impl fmt::Debug for AddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AddressPattern").field(&self.source).finish()
+    }
+}
+
+impl fmt::Display for AddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Runs the tokens over `address` as a set of reachable positions, one token at a
+/// time. Each token is a single sweep over the address, so matching takes
+/// O(tokens × address) time whatever the pattern.
+fn match_tokens(tokens: &[Token], address: &[char]) -> bool {
+    // reached[i]: the tokens so far can match `address[..i]`
+    let mut reached = vec![false; address.len() + 1];
+    reached[0] = true;
+    for token in tokens {
+        let mut next = vec![false; address.len() + 1];
+        match token {
+            // Reachable at `i` if some start since the last `/` was reached
+            Token::AnyRun => {
+                let mut since_slash = false;
+                for i in 0..=address.len() {
+                    if i > 0 && address[i - 1] == '/' {
+                        since_slash = false;
+                    }
+                    since_slash |= reached[i];
+                    next[i] = since_slash;
+                }
+            }
+            // Followed by the `/` that starts the next part: resume at every `/`
+            // at or after any reached start
+            Token::AnyParts => {
+                let mut any_earlier = false;
+                for i in 0..address.len() {
+                    any_earlier |= reached[i];
+                    next[i] = any_earlier && address[i] == '/';
+                }
+            }
+            Token::Alternatives(alternatives) => {
+                for start in (0..=address.len()).filter(|&i| reached[i]) {
+                    let rest = &address[start..];
+                    for alternative in alternatives {
+                        let len = alternative.chars().count();
+                        if rest.len() >= len && alternative.chars().eq(rest[..len].iter().copied())
+                        {
+                            next[start + len] = true;
+                        }
+                    }
+                }
+            }
+            single => {
+                for (start, &c) in address.iter().enumerate() {
+                    if reached[start] && matches_one(single, c) {
+                        next[start + 1] = true;
+                    }
+                }
+            }
+        }
+        if !next.contains(&true) {
+            return false;
+        }
+        reached = next;
+    }
+    reached[address.len()]
+}
+
+/// Returns `true` if the single-character `token` matches `c`.
+fn matches_one(token: &Token, c: char) -> bool {
+    match token {
+        Token::Char(expected) => c == *expected,
+        Token::AnyChar => c != '/',
+        Token::Class { negated, ranges } => {
+            c != '/' && ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+        }
+        Token::AnyRun | Token::Alternatives(_) | Token::AnyParts => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, address: &str) -> bool {
+        AddressPattern::new(pattern).unwrap().matches(address)
+    }
+
+    #[test]
+    fn literal_and_single_part_wildcards() {
+        assert!(matches("/fader/1", "/fader/1"));
+        assert!(!matches("/fader/1", "/fader/10"));
+        assert!(matches("/fader/?", "/fader/1"));
+        assert!(!matches("/fader/?", "/fader/10"));
+        assert!(matches("/fader/*", "/fader/10"));
+        assert!(matches("/fader/*", "/fader/"));
+        assert!(!matches("/fader/*", "/fader/1/x"));
+        assert!(matches("/*/1", "/knob/1"));
+        assert!(matches("/f*r*/1", "/fader/1"));
+    }
+
+    #[test]
+    fn brackets_and_braces() {
+        assert!(matches("/fader/[1-3]", "/fader/2"));
+        assert!(!matches("/fader/[1-3]", "/fader/4"));
+        assert!(matches("/fader/[!1-3]", "/fader/4"));
+        assert!(matches("/fader/[a-]", "/fader/-"));
+        assert!(matches("/{fader,knob}/1", "/knob/1"));
+        assert!(!matches("/{fader,knob}/1", "/button/1"));
+        assert!(matches("/mix/{a,ab}c", "/mix/abc"));
+    }
+
+    #[test]
+    fn double_slash_spans_parts() {
+        assert!(matches("//level", "/level"));
+        assert!(matches("//level", "/mixer/ch/3/level"));
+        assert!(!matches("//level", "/mixer/levels"));
+        assert!(matches("/mixer//*", "/mixer/ch/3"));
+    }
+
+    #[test]
+    fn pathological_patterns_match_quickly() {
+        // Exponential for a backtracking matcher
+        let pattern = format!("/{}b", "*a".repeat(30));
+        let address = format!("/{}", "a".repeat(200));
+        let started = std::time::Instant::now();
+        assert!(!matches(&pattern, &address));
+        assert!(matches(&pattern, &format!("{}b", address)));
+        assert!(!matches("//a//a//a//a//a//b", &"/a".repeat(100)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn long_addresses_match_in_linear_time() {
+        // Every `*` and `//` is reachable from every part of a 64 KiB address
+        let address = format!("/{}", "a/".repeat(32 * 1024 - 1));
+        let pattern = format!("/{}b", "*//".repeat(20));
+        let started = std::time::Instant::now();
+        assert!(!matches(&pattern, &address));
+        assert!(matches(&pattern, &format!("{}b", address)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let long = format!("/{}", "a".repeat(AddressPattern::MAX_LEN));
+        for pattern in ["fader", "/[1-3", "/{a,b", "/a]", "/{a/b}", &long] {
+            assert!(
+                matches!(AddressPattern::new(pattern), Err(Error::Pattern { .. })),
+                "{}",
+                pattern
+            );
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Frames exchanged with WebSocket clients.
+
+use osc_codec_json::JsonCodec;
+use osc_codec_msgpack::MsgpackCodec;
+use osc_ir::{Codec, IrBundle, IrBundleElement, IrValue};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{AddressPattern, Error};
+
+const MESSAGE_TYPE: &str = "osc.message";
+const SUBSCRIBE: &str = "bridge.subscribe";
+const UNSUBSCRIBE: &str = "bridge.unsubscribe";
+const SUBSCRIBED: &str = "bridge.subscribed";
+const ERROR: &str = "bridge.error";
+
+/// Encoding of the frames sent to WebSocket clients.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameFormat {
+    /// JSON text frames (`osc-codec-json`)
+    #[default]
+    Json,
+    /// MessagePack binary frames (`osc-codec-msgpack`)
+    Msgpack,
+}
+
+impl FrameFormat {
+    /// Encodes `value` as one frame.
+    pub(crate) fn encode(self, value: &IrValue) -> Result<Message, Error> {
+        Ok(match self {
+            FrameFormat::Json => {
+                let bytes = JsonCodec.encode(value)?;
+                Message::Text(String::from_utf8(bytes).expect("JSON is UTF-8"))
+            }
+            FrameFormat::Msgpack => Message::Binary(MsgpackCodec.encode(value)?),
+        })
+    }
+}
+
+/// A frame received from a client.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Request {
+    /// Add patterns to the client's subscriptions
+    Subscribe(Vec<AddressPattern>),
+    /// Remove patterns, or all of them if `None`
+    Unsubscribe(Option<Vec<String>>),
+    /// Forward a value as OSC
+    Forward(IrValue),
+}
+
+impl Request {
+    /// Decodes a text (JSON) or binary (MessagePack) frame; other frames yield `None`.
+    pub(crate) fn decode(
+        message: &Message,
+        single_precision_floats: bool,
+    ) -> Option<Result<Self, Error>> {
+        let value = match message {
+            Message::Text(text) => JsonCodec.decode(text.as_bytes()).map(|value| {
+                if single_precision_floats {
+                    to_single_precision(value)
+                } else {
+                    value
+                }
+            }),
+            Message::Binary(bytes) => MsgpackCodec.decode(bytes),
+            _ => return None,
+        };
+        Some(value.map_err(Error::Codec).and_then(Self::from_value))
+    }
+
+    fn from_value(value: IrValue) -> Result<Self, Error> {
+        let command = match type_tag(&value) {
+            Some(SUBSCRIBE) => SUBSCRIBE,
+            Some(UNSUBSCRIBE) => UNSUBSCRIBE,
+            _ => return Ok(Request::Forward(value)),
+        };
+        let patterns = match field(&value, "patterns") {
+            None => None,
+            Some(IrValue::Array(patterns)) => Some(
+                patterns
+                    .iter()
+                    .map(|p| {
+                        p.as_str().ok_or_else(|| {
+                            Error::InvalidCommand("patterns must be strings".to_string())
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Some(_) => {
+                return Err(Error::InvalidCommand(
+                    "patterns must be an array".to_string(),
+                ))
+            }
+        };
+        if command == UNSUBSCRIBE {
+            let patterns = patterns.map(|p| p.into_iter().map(String::from).collect());
+            return Ok(Request::Unsubscribe(patterns));
+        }
+        let patterns = patterns
+            .ok_or_else(|| Error::InvalidCommand("subscribe needs patterns".to_string()))?;
+        Ok(Request::Subscribe(
+            patterns
+                .into_iter()
+                .map(AddressPattern::new)
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+/// The answer to a subscription change.
+pub(crate) fn subscribed(patterns: &[AddressPattern]) -> IrValue {
+    let patterns = patterns.iter().map(|p| IrValue::from(p.as_str())).collect();
+    IrValue::Map(vec![
+        ("$type".into(), IrValue::from(SUBSCRIBED)),
+        ("patterns".into(), IrValue::Array(patterns)),
+    ])
+}
+
+/// The report of a frame the bridge could not handle.
+pub(crate) fn error(error: &Error) -> IrValue {
+    IrValue::Map(vec![
+        ("$type".into(), IrValue::from(ERROR)),
+        ("message".into(), IrValue::from(error.to_string())),
+    ])
+}
+
+/// Returns the part of `value` a client with `patterns` receives: the value itself if
+/// `patterns` is `None`, otherwise matching messages only.
+pub(crate) fn filter(value: &IrValue, patterns: Option<&[AddressPattern]>) -> Option<IrValue> {
+    let Some(patterns) = patterns else {
+        return Some(value.clone());
+    };
+    match value {
+        IrValue::Bundle(bundle) => filter_bundle(bundle, patterns).map(IrValue::Bundle),
+        _ => {
+            let address = message_address(value)?;
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches(address))
+                .then(|| value.clone())
+        }
+    }
+}
+
+fn filter_bundle(bundle: &IrBundle, patterns: &[AddressPattern]) -> Option<IrBundle> {
+    let mut filtered = IrBundle::new(bundle.timetag);
+    for element in &bundle.elements {
+        match element {
+            IrBundleElement::Message(message) => {
+                if let Some(message) = filter(message, Some(patterns)) {
+                    filtered.add_message(message);
+                }
+            }
+            IrBundleElement::Bundle(nested) => {
+                if let Some(nested) = filter_bundle(nested, patterns) {
+                    filtered.add_bundle(nested);
+                }
+            }
+        }
+    }
+    (!filtered.is_empty()).then_some(filtered)
+}
+
+fn field<'a>(value: &'a IrValue, key: &str) -> Option<&'a IrValue> {
+    value
+        .as_map()?
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v)
+}
+
+fn type_tag(value: &IrValue) -> Option<&str> {
+    field(value, "$type")?.as_str()
+}
+
+fn message_address(value: &IrValue) -> Option<&str> {
+    if type_tag(value) != Some(MESSAGE_TYPE) {
+        return None;
+    }
+    field(value, "address")?.as_str()
+}
+
+/// Turns the `Float` arguments of messages into `Float32`, recursing into bundles and
+/// array arguments.
+fn to_single_precision(value: IrValue) -> IrValue {
+    match value {
+        IrValue::Bundle(bundle) => IrValue::Bundle(bundle_to_single_precision(bundle)),
+        IrValue::Map(entries) => {
+            let is_message = entries
+                .iter()
+                .any(|(key, tag)| key == "$type" && tag.as_str() == Some(MESSAGE_TYPE));
+            IrValue::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| match key.as_str() {
+                        "args" if is_message => (key, args_to_single_precision(value)),
+                        _ => (key, value),
+                    })
+                    .collect(),
+            )
+        }
+        value => value,
+    }
+}
+
+fn bundle_to_single_precision(bundle: IrBundle) -> IrBundle {
+    IrBundle {
+        timetag: bundle.timetag,
+        elements: bundle
+            .elements
+            .into_iter()
+            .map(|element| match element {
+                IrBundleElement::Message(message) => {
+                    IrBundleElement::Message(to_single_precision(message))
+                }
+                IrBundleElement::Bundle(nested) => {
+                    IrBundleElement::Bundle(bundle_to_single_precision(nested))
+                }
+            })
+            .collect(),
+    }
+}
+
+fn args_to_single_precision(value: IrValue) -> IrValue {
+    match value {
+        IrValue::Float(x) => IrValue::Float32(x as f32),
+        IrValue::Array(items) => {
+            IrValue::Array(items.into_iter().map(args_to_single_precision).collect())
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osc_ir::IrTimetag;
+
+    fn patterns(patterns: &[&str]) -> Vec<AddressPattern> {
+        patterns
+            .iter()
+            .map(|p| AddressPattern::new(p).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn bundles_are_pruned_to_matching_messages() {
+        let mut inner = IrBundle::new(IrTimetag::from_ntp(2));
+        inner.add_message(IrValue::message("/knob/1", vec![]));
+        inner.add_message(IrValue::message("/fader/2", vec![]));
+        let mut outer = IrBundle::new(IrTimetag::from_ntp(1));
+        outer.add_message(IrValue::message("/fader/1", vec![]));
+        outer.add_bundle(inner);
+        outer.add_bundle(IrBundle::immediate());
+        let value = IrValue::Bundle(outer);
+
+        let mut expected_inner = IrBundle::new(IrTimetag::from_ntp(2));
+        expected_inner.add_message(IrValue::message("/fader/2", vec![]));
+        let mut expected = IrBundle::new(IrTimetag::from_ntp(1));
+        expected.add_message(IrValue::message("/fader/1", vec![]));
+        expected.add_bundle(expected_inner);
+
+        let faders = patterns(&["/fader/*"]);
+        assert_eq!(
+            filter(&value, Some(&faders)),
+            Some(IrValue::Bundle(expected))
+        );
+        assert_eq!(filter(&value, Some(&patterns(&["/button"]))), None);
+        assert_eq!(filter(&value, None), Some(value.clone()));
+        assert_eq!(filter(&value, Some(&[])), None);
+    }
+
+    #[test]
+    fn commands_are_recognised() {
+        let request = |json: &str| Request::decode(&Message::Text(json.into()), true).unwrap();
+        assert_eq!(
+            request(r#"{"$type": "bridge.subscribe", "patterns": ["/a/*"]}"#).unwrap(),
+            Request::Subscribe(patterns(&["/a/*"]))
+        );
+        assert_eq!(
+            request(r#"{"$type": "bridge.unsubscribe"}"#).unwrap(),
+            Request::Unsubscribe(None)
+        );
+        assert!(matches!(
+            request(r#"{"$type": "bridge.subscribe"}"#),
+            Err(Error::InvalidCommand(_))
+        ));
+        assert!(matches!(
+            request(r#"{"$type": "bridge.subscribe", "patterns": ["a"]}"#),
+            Err(Error::Pattern { .. })
+        ));
+        assert!(matches!(request("{"), Err(Error::Codec(_))));
+    }
+
+    #[test]
+    fn json_floats_become_single_precision() {
+        let json = r#"{"$type": "osc.message", "address": "/a", "args": [0.5, 1, [0.25]]}"#;
+        let expected = IrValue::message(
+            "/a",
+            vec![
+                IrValue::Float32(0.5),
+                IrValue::from(1),
+                IrValue::Array(vec![IrValue::Float32(0.25)]),
+            ],
+        );
+        let decoded = Request::decode(&Message::Text(json.into()), true).unwrap();
+        assert_eq!(decoded.unwrap(), Request::Forward(expected));
+        let decoded = Request::decode(&Message::Text(json.into()), false).unwrap();
+        assert!(matches!(
+            decoded.unwrap(),
+            Request::Forward(value) if field(&value, "args").unwrap().as_array().unwrap()[0]
+                == IrValue::Float(0.5)
+        ));
+    }
+}
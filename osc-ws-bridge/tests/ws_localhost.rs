@@ -0,0 +1,297 @@
+use std::future::Future;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use osc_codec_json::JsonCodec;
+use osc_codec_msgpack::MsgpackCodec;
+use osc_ir::{Codec, IrBundle, IrTimetag, IrValue};
+use osc_transport::OscSocket;
+use osc_ws_bridge::{
+    Bridge, BridgeConfig, BridgeEvent, ClientId, Error, FrameFormat, MAX_PATTERNS,
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn timeout<T>(future: impl Future<Output = T>) -> T {
+    tokio::time::timeout(Duration::from_secs(5), future)
+        .await
+        .expect("timed out")
+}
+
+async fn bridge(config: BridgeConfig) -> Bridge {
+    Bridge::bind("127.0.0.1:0", "127.0.0.1:0", config)
+        .await
+        .unwrap()
+}
+
+/// Connects a client and waits until the bridge has registered it.
+async fn connect(bridge: &mut Bridge) -> (Client, ClientId) {
+    let url = format!("ws://{}", bridge.ws_addr());
+    let (client, _) = timeout(tokio_tungstenite::connect_async(url))
+        .await
+        .unwrap();
+    match timeout(bridge.next_event()).await {
+        Some(BridgeEvent::Connected { client: id, .. }) => (client, id),
+        other => panic!("expected a connection, got {:?}", other),
+    }
+}
+
+async fn receive(client: &mut Client) -> IrValue {
+    match timeout(client.next()).await {
+        Some(Ok(Message::Text(text))) => JsonCodec.decode(text.as_bytes()).unwrap(),
+        Some(Ok(Message::Binary(bytes))) => MsgpackCodec.decode(&bytes).unwrap(),
+        other => panic!("expected a frame, got {:?}", other),
+    }
+}
+
+async fn send_json(client: &mut Client, json: &str) {
+    client.send(Message::Text(json.into())).await.unwrap();
+}
+
+fn subscribed(patterns: &[&str]) -> IrValue {
+    IrValue::Map(vec![
+        ("$type".into(), IrValue::from("bridge.subscribed")),
+        (
+            "patterns".into(),
+            IrValue::Array(patterns.iter().map(|&p| IrValue::from(p)).collect()),
+        ),
+    ])
+}
+
+fn is_error_reply(value: &IrValue) -> bool {
+    value.as_map().is_some_and(|entries| {
+        entries
+            .iter()
+            .any(|(k, v)| k == "$type" && v.as_str() == Some("bridge.error"))
+    })
+}
+
+#[tokio::test]
+async fn udp_packets_reach_clients_as_json() {
+    let mut bridge = bridge(BridgeConfig::new()).await;
+    let (mut client, _) = connect(&mut bridge).await;
+    let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+
+    // JSON has no single-precision floats
+    let value = IrValue::message("/fader/1", vec![IrValue::Float(0.25), IrValue::from("x")]);
+    sender.send_to(&value, bridge.udp_addr()).await.unwrap();
+    assert_eq!(receive(&mut client).await, value);
+
+    let mut bundle = IrBundle::new(IrTimetag::from_ntp(1 << 32));
+    bundle.add_message(IrValue::message("/a", vec![IrValue::from(1)]));
+    let bundle = IrValue::Bundle(bundle);
+    sender.send_to(&bundle, bridge.udp_addr()).await.unwrap();
+    assert_eq!(receive(&mut client).await, bundle);
+}
+
+#[tokio::test]
+async fn udp_packets_reach_clients_as_msgpack() {
+    let mut bridge = bridge(BridgeConfig::new().with_format(FrameFormat::Msgpack)).await;
+    let (mut client, _) = connect(&mut bridge).await;
+    let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let value = IrValue::message("/fader/1", vec![IrValue::Float32(0.5)]);
+    sender.send_to(&value, bridge.udp_addr()).await.unwrap();
+    match timeout(client.next()).await {
+        Some(Ok(Message::Binary(bytes))) => {
+            assert_eq!(MsgpackCodec.decode(&bytes).unwrap(), value)
+        }
+        other => panic!("expected a binary frame, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn client_values_are_forwarded_as_osc() {
+    let receiver = OscSocket::bind("127.0.0.1:0").await.unwrap();
+    let config = BridgeConfig::new().with_osc_target(receiver.local_addr().unwrap());
+    let mut bridge = bridge(config).await;
+    let (mut client, _) = connect(&mut bridge).await;
+
+    send_json(
+        &mut client,
+        r#"{"$type": "osc.message", "address": "/light/1", "args": [0.5, 3]}"#,
+    )
+    .await;
+    let (value, from) = timeout(receiver.recv_from()).await.unwrap();
+    assert_eq!(
+        value,
+        IrValue::message("/light/1", vec![IrValue::Float32(0.5), IrValue::from(3)])
+    );
+    assert_eq!(from, bridge.udp_addr());
+
+    // MessagePack frames keep their precision
+    let value = IrValue::message("/light/2", vec![IrValue::Float(0.5)]);
+    let frame = Message::Binary(MsgpackCodec.encode(&value).unwrap());
+    client.send(frame).await.unwrap();
+    assert_eq!(timeout(receiver.recv_from()).await.unwrap().0, value);
+}
+
+#[tokio::test]
+async fn double_precision_can_be_kept() {
+    let receiver = OscSocket::bind("127.0.0.1:0").await.unwrap();
+    let config = BridgeConfig::new()
+        .with_osc_target(receiver.local_addr().unwrap())
+        .with_single_precision_floats(false);
+    let mut bridge = bridge(config).await;
+    let (mut client, _) = connect(&mut bridge).await;
+
+    send_json(
+        &mut client,
+        r#"{"$type": "osc.message", "address": "/a", "args": [0.5]}"#,
+    )
+    .await;
+    assert_eq!(
+        timeout(receiver.recv_from()).await.unwrap().0,
+        IrValue::message("/a", vec![IrValue::Float(0.5)])
+    );
+}
+
+#[tokio::test]
+async fn subscriptions_filter_per_client() {
+    let mut bridge = bridge(BridgeConfig::new()).await;
+    let (mut faders, _) = connect(&mut bridge).await;
+    let (mut everything, _) = connect(&mut bridge).await;
+    let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+
+    send_json(
+        &mut faders,
+        r#"{"$type": "bridge.subscribe", "patterns": ["/fader/*", "//level"]}"#,
+    )
+    .await;
+    assert_eq!(
+        receive(&mut faders).await,
+        subscribed(&["/fader/*", "//level"])
+    );
+
+    let knob = IrValue::message("/knob/1", vec![IrValue::from(1)]);
+    let fader = IrValue::message("/fader/1", vec![IrValue::from(2)]);
+    let level = IrValue::message("/mixer/3/level", vec![IrValue::from(3)]);
+    let mut bundle = IrBundle::new(IrTimetag::from_ntp(7));
+    bundle.add_message(knob.clone());
+    bundle.add_message(level.clone());
+    let bundle = IrValue::Bundle(bundle);
+    for value in [&knob, &fader, &bundle] {
+        sender.send_to(value, bridge.udp_addr()).await.unwrap();
+    }
+
+    let mut pruned = IrBundle::new(IrTimetag::from_ntp(7));
+    pruned.add_message(level);
+    assert_eq!(receive(&mut faders).await, fader);
+    assert_eq!(receive(&mut faders).await, IrValue::Bundle(pruned));
+    for value in [knob, fader, bundle] {
+        assert_eq!(receive(&mut everything).await, value);
+    }
+
+    send_json(
+        &mut faders,
+        r#"{"$type": "bridge.unsubscribe", "patterns": ["/fader/*"]}"#,
+    )
+    .await;
+    assert_eq!(receive(&mut faders).await, subscribed(&["//level"]));
+    send_json(&mut faders, r#"{"$type": "bridge.unsubscribe"}"#).await;
+    assert_eq!(receive(&mut faders).await, subscribed(&[]));
+}
+
+#[tokio::test]
+async fn bad_requests_are_answered_with_errors() {
+    let mut bridge = bridge(BridgeConfig::new()).await;
+    let (mut client, id) = connect(&mut bridge).await;
+
+    // No OSC target configured
+    send_json(
+        &mut client,
+        r#"{"$type": "osc.message", "address": "/a", "args": []}"#,
+    )
+    .await;
+    assert!(is_error_reply(&receive(&mut client).await));
+    match timeout(bridge.next_event()).await {
+        Some(BridgeEvent::Error {
+            client: Some(from),
+            error: Error::NoTarget,
+        }) => assert_eq!(from, id),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    send_json(
+        &mut client,
+        r#"{"$type": "bridge.subscribe", "patterns": ["/[a-"]}"#,
+    )
+    .await;
+    assert!(is_error_reply(&receive(&mut client).await));
+    send_json(&mut client, "not json").await;
+    assert!(is_error_reply(&receive(&mut client).await));
+
+    // The connection is still usable
+    send_json(
+        &mut client,
+        r#"{"$type": "bridge.subscribe", "patterns": ["/a"]}"#,
+    )
+    .await;
+    assert_eq!(receive(&mut client).await, subscribed(&["/a"]));
+}
+
+#[tokio::test]
+async fn disconnected_clients_are_forgotten() {
+    let mut bridge = bridge(BridgeConfig::new()).await;
+    let (mut client, id) = connect(&mut bridge).await;
+    assert_eq!(bridge.clients().len(), 1);
+    assert_eq!(bridge.clients()[0].0, id);
+
+    client.close(None).await.unwrap();
+    loop {
+        match timeout(bridge.next_event()).await {
+            Some(BridgeEvent::Disconnected {
+                client,
+                error: None,
+            }) => {
+                assert_eq!(client, id);
+                break;
+            }
+            Some(BridgeEvent::Error { .. }) => continue,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(bridge.clients().is_empty());
+}
+
+#[tokio::test]
+async fn subscriptions_are_bounded() {
+    let mut bridge = bridge(BridgeConfig::new()).await;
+    let (mut client, _) = connect(&mut bridge).await;
+    let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+
+    // Exponential for a backtracking matcher
+    let pathological = format!("/{}b", "*a".repeat(30));
+    let subscribe = format!(
+        r#"{{"$type": "bridge.subscribe", "patterns": ["{}"]}}"#,
+        pathological
+    );
+    send_json(&mut client, &subscribe).await;
+    assert_eq!(receive(&mut client).await, subscribed(&[&pathological]));
+    let address = format!("/{}", "a".repeat(40));
+    sender
+        .send_to(&IrValue::message(&address, vec![]), bridge.udp_addr())
+        .await
+        .unwrap();
+    let wanted = IrValue::message(format!("{}b", address), vec![]);
+    sender.send_to(&wanted, bridge.udp_addr()).await.unwrap();
+    assert_eq!(receive(&mut client).await, wanted);
+
+    let patterns: Vec<String> = (0..MAX_PATTERNS).map(|i| format!("\"/{}\"", i)).collect();
+    let subscribe = format!(
+        r#"{{"$type": "bridge.subscribe", "patterns": [{}]}}"#,
+        patterns.join(",")
+    );
+    send_json(&mut client, &subscribe).await;
+    assert!(is_error_reply(&receive(&mut client).await));
+    // The refused subscription left the existing one alone
+    send_json(
+        &mut client,
+        r#"{"$type": "bridge.subscribe", "patterns": []}"#,
+    )
+    .await;
+    assert_eq!(receive(&mut client).await, subscribed(&[&pathological]));
+}
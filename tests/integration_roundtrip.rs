@@ -11,8 +11,8 @@ fn json_roundtrip() {
 fn msgpack_roundtrip() {
     use osc_ir::IrValue;
     let v = IrValue::String("ping".into());
-    let mp = osc_codec_msgpack::to_msgpack(&v);
-    let v2 = osc_codec_msgpack::from_msgpack(&mp);
+    let mp = osc_codec_msgpack::try_to_msgpack(&v).expect("encode");
+    let v2 = osc_codec_msgpack::try_from_msgpack(&mp).expect("decode");
     assert_eq!(v, v2);
 }
 
@@ -49,8 +49,8 @@ fn bundle_msgpack_roundtrip() {
     bundle.add_bundle(nested);
     
     let v = IrValue::Bundle(bundle);
-    let mp = osc_codec_msgpack::to_msgpack(&v);
-    let v2 = osc_codec_msgpack::from_msgpack(&mp);
+    let mp = osc_codec_msgpack::try_to_msgpack(&v).expect("encode");
+    let v2 = osc_codec_msgpack::try_from_msgpack(&mp).expect("decode");
     assert_eq!(v, v2);
 }
 
@@ -80,8 +80,8 @@ fn cross_codec_bundle_compatibility() {
     assert_eq!(original, from_json);
     
     // Test MessagePack roundtrip
-    let msgpack = osc_codec_msgpack::to_msgpack(&original);
-    let from_msgpack = osc_codec_msgpack::from_msgpack(&msgpack);
+    let msgpack = osc_codec_msgpack::try_to_msgpack(&original).expect("encode");
+    let from_msgpack = osc_codec_msgpack::try_from_msgpack(&msgpack).expect("decode");
     assert_eq!(original, from_msgpack);
     
     // Cross-codec compatibility: both should produce the same result